@@ -1,6 +1,22 @@
 use zed_text_editor::gui::GuiApp;
+#[cfg(not(target_arch = "wasm32"))]
+use zed_text_editor::io::read_stdin;
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
+    // `-`: open text piped in on stdin as an untitled buffer
+    let stdin_rope = if std::env::args().nth(1).as_deref() == Some("-") {
+        match read_stdin() {
+            Ok(rope) => Some(rope),
+            Err(e) => {
+                eprintln!("Failed to read stdin: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([800.0, 600.0])
@@ -14,7 +30,39 @@ fn main() -> eframe::Result<()> {
         Box::new(|cc| {
             // Setup custom theme
             zed_text_editor::gui::theme::setup_theme(&cc.egui_ctx);
-            Ok(Box::new(GuiApp::new(cc)))
+            let mut app = GuiApp::new(cc);
+            if let Some(rope) = stdin_rope {
+                app.open_untitled(rope);
+            }
+            Ok(Box::new(app))
         }),
     )
 }
+
+// In the browser: `trunk serve` builds this and mounts it on index.html's canvas
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use wasm_bindgen::JsCast;
+
+    wasm_bindgen_futures::spawn_local(async {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("the_canvas_id"))
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .expect("index.html has no <canvas id=\"the_canvas_id\">");
+
+        let started = eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(|cc| {
+                    zed_text_editor::gui::theme::setup_theme(&cc.egui_ctx);
+                    Ok(Box::new(GuiApp::new(cc)))
+                }),
+            )
+            .await;
+        if let Err(e) = started {
+            web_sys::console::error_1(&e);
+        }
+    });
+}
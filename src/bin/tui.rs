@@ -1,13 +1,46 @@
-use zed_text_editor::ui::{App, init, restore, render};
+use zed_text_editor::io::{load_rope, read_stdin, write_range, CatArgs};
+use zed_text_editor::ui::{App, LogView, init, restore, render};
 use std::io;
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // --cat <file> [--range a:b | --bytes a:b]: print through the Rope, no UI
+    match CatArgs::parse(&args) {
+        Ok(Some(cat)) => return run_cat(&cat),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: tui --cat <file> [--range start:end | --bytes start:end]");
+            std::process::exit(2);
+        }
+    }
+
+    // --tail <file>: read-only log viewer
+    if args.first().map(String::as_str) == Some("--tail") {
+        let Some(path) = args.get(1) else {
+            eprintln!("Usage: tui --tail <file>");
+            std::process::exit(2);
+        };
+        let mut view = LogView::new(path);
+        view.refresh()?; // Fail before touching the terminal if the file is unreadable
+
+        let mut terminal = init()?;
+        let result = run_tail(&mut terminal, &mut view);
+        restore()?;
+        return result;
+    }
+
+    // `-`: edit text piped in on stdin as an untitled buffer
+    let mut app = if args.first().map(String::as_str) == Some("-") {
+        App::untitled(read_stdin()?)
+    } else {
+        App::new()
+    };
+
     // Initialize terminal
     let mut terminal = init()?;
     
-    // Create app
-    let mut app = App::new();
-    
     // Main loop
     let result = run_app(&mut terminal, &mut app);
     
@@ -32,3 +65,29 @@ fn run_app(terminal: &mut zed_text_editor::ui::Tui, app: &mut App) -> io::Result
     }
     Ok(())
 }
+
+fn run_cat(cat: &CatArgs) -> io::Result<()> {
+    let rope = load_rope(&cat.path)?;
+    let stdout = io::stdout();
+    match write_range(&rope, cat.range, &mut stdout.lock()) {
+        // `| head` closing the pipe early isn't an error
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn run_tail(terminal: &mut zed_text_editor::ui::Tui, view: &mut LogView) -> io::Result<()> {
+    loop {
+        // Input polling doubles as the refresh interval (100ms)
+        if let Err(e) = view.refresh() {
+            view.status_message = format!("Error reading file: {}", e);
+        }
+        terminal.draw(|frame| view.render(frame))?;
+        view.handle_input()?;
+
+        if view.should_quit {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use super::offset::Offset;
+use std::ops::Range;
+
+/// Which side of text inserted exactly at an anchor it ends up on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bias {
+    /// Stay before the inserted text
+    Left,
+    /// Move past the inserted text
+    Right,
+}
+
+/// A position that follows its text as the buffer is edited: edits before
+/// it shift it, and an edit replacing text around it leaves it at the start
+/// (`Bias::Left`) or end (`Bias::Right`) of the new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Anchor {
+    pub offset: Offset,
+    pub bias: Bias,
+}
+
+impl Anchor {
+    pub fn new(offset: Offset, bias: Bias) -> Self {
+        Self { offset, bias }
+    }
+
+    /// Anchor that stays before text inserted at `offset`
+    pub fn before(offset: Offset) -> Self {
+        Self::new(offset, Bias::Left)
+    }
+
+    /// Anchor that moves past text inserted at `offset`
+    pub fn after(offset: Offset) -> Self {
+        Self::new(offset, Bias::Right)
+    }
+
+    /// Follow an edit that replaced `old_range` (byte offsets before the
+    /// edit) with `new_len` bytes
+    pub fn apply_edit(&mut self, old_range: &Range<usize>, new_len: usize) {
+        let offset = self.offset.value();
+        let new_end = old_range.start + new_len;
+        let moved = if offset < old_range.start {
+            offset
+        } else if offset > old_range.end || (offset == old_range.end && offset > old_range.start) {
+            offset - old_range.end + new_end
+        } else {
+            // Inside the replaced text, or at a pure insertion point
+            match self.bias {
+                Bias::Left => old_range.start,
+                Bias::Right => new_end,
+            }
+        };
+        self.offset = Offset(moved);
+    }
+}
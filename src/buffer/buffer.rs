@@ -1,8 +1,40 @@
+use super::anchor::{Anchor, Bias};
 use super::line_cache::{LineOffsetCache, PredictiveCache, ReusableBuffer};
 use super::offset::Offset;
 use super::point::Point;
+use super::snapshot::BufferSnapshot;
 use crate::rope::Rope;
+use crate::util::{brackets, tabs};
+use std::borrow::Cow;
+use std::ops::Range;
 use std::sync::Arc;
+use unicode_segmentation::GraphemeCursor;
+
+/// Whether `c` belongs to words: alphanumerics, and any of `word_chars`
+/// (the editor's setting, `_` by default)
+pub fn is_word_char(c: char, word_chars: &str) -> bool {
+    c.is_alphanumeric() || word_chars.contains(c)
+}
+
+/// What a char counts as when finding words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Word,
+    Space,
+    Punctuation,
+}
+
+impl CharClass {
+    fn of(c: char, word_chars: &str) -> Self {
+        if is_word_char(c, word_chars) {
+            CharClass::Word
+        } else if c.is_whitespace() {
+            CharClass::Space
+        } else {
+            CharClass::Punctuation
+        }
+    }
+}
 
 /// Buffer with advanced line offset caching for performance
 /// Uses Arc for cheap cloning (copy-on-write)
@@ -36,6 +68,29 @@ impl Buffer {
         }
     }
 
+    pub fn from_rope(rope: Rope) -> Self {
+        let line_count = rope.line_count();
+
+        Self {
+            rope: Arc::new(rope),
+            line_cache: LineOffsetCache::new(line_count),
+            reusable_buffer: ReusableBuffer::new(),
+            predictive_cache: PredictiveCache::new(),
+        }
+    }
+
+    /// The same text sharing this buffer's rope, with empty caches: what
+    /// history keeps, since the caches are rebuilt as soon as a snapshot is
+    /// current again
+    pub fn without_caches(&self) -> Self {
+        Self {
+            rope: Arc::clone(&self.rope),
+            line_cache: LineOffsetCache::new(self.rope.line_count()),
+            reusable_buffer: ReusableBuffer::new(),
+            predictive_cache: PredictiveCache::new(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.rope.len()
     }
@@ -106,6 +161,56 @@ impl Buffer {
         self.line_cache.update_line_count(new_line_count);
     }
 
+    /// Apply several edits in one pass. Ranges are into the text before any
+    /// of them and may come in any order but must not overlap; insertions at
+    /// the same offset keep their order. The line cache is invalidated once
+    pub fn edit(&mut self, edits: &[(Range<Offset>, &str)]) {
+        let mut edits: Vec<&(Range<Offset>, &str)> = edits
+            .iter()
+            .filter(|(range, text)| !range.is_empty() || !text.is_empty())
+            .collect();
+        if edits.is_empty() {
+            return;
+        }
+        edits.sort_by_key(|(range, _)| range.start);
+        for pair in edits.windows(2) {
+            assert!(
+                pair[0].0.end <= pair[1].0.start,
+                "overlapping edits {:?} and {:?}",
+                pair[0].0,
+                pair[1].0
+            );
+        }
+
+        // Edits on a single line only touch that line; the first one that
+        // adds or removes lines shifts every line after it
+        let mut affected_lines = Vec::new();
+        for (range, text) in &edits {
+            let (start_line, _) = self.rope.byte_to_line_col(range.start.value());
+            let (end_line, _) = self.rope.byte_to_line_col(range.end.value());
+            if start_line != end_line || text.contains('\n') {
+                affected_lines.extend(start_line..self.line_count());
+                break;
+            }
+            affected_lines.push(start_line);
+        }
+        self.line_cache.invalidate_lines(&affected_lines);
+
+        // Back to front so the offsets of the edits still to come stay valid
+        let rope = Arc::make_mut(&mut self.rope);
+        for (range, text) in edits.iter().rev() {
+            if !range.is_empty() {
+                rope.delete(range.start.value(), range.end.value());
+            }
+            if !text.is_empty() {
+                rope.insert(range.start.value(), text);
+            }
+        }
+
+        let new_line_count = rope.line_count();
+        self.line_cache.update_line_count(new_line_count);
+    }
+
     /// Get line by index - uses rope directly (already optimized)
     pub fn line(&self, line_idx: usize) -> Option<String> {
         self.rope.line(line_idx)
@@ -147,36 +252,222 @@ impl Buffer {
 
     /// Point to offset conversion
     pub fn point_to_offset(&self, point: Point) -> Offset {
-        let line_start = self.rope.line_to_byte(point.row);
+        // Columns are chars: count them from the line's start, stopping at its end
+        Offset(self.rope.line_col_to_byte(point.row, point.column))
+    }
 
-        // Get column offset within line
-        if let Some(line_text) = self.line(point.row) {
-            let column_bytes: usize = line_text
-                .chars()
-                .take(point.column)
-                .map(|c| c.len_utf8())
-                .sum();
+    /// Byte offset where a line starts
+    pub fn line_start_offset(&self, row: usize) -> Offset {
+        Offset(self.rope.line_to_byte(row))
+    }
 
-            Offset(line_start + column_bytes)
+    /// Byte offset where a line ends (before its newline, if any)
+    pub fn line_end_offset(&self, row: usize) -> Offset {
+        if row + 1 < self.line_count() {
+            Offset(self.rope.line_to_byte(row + 1) - 1)
         } else {
-            Offset(line_start)
+            Offset(self.len())
         }
     }
 
+    /// Column (in chars) of the first non-whitespace character of a line
+    /// (the line length for blank lines, 0 if the row is out of range)
+    pub fn line_indent_len(&self, row: usize) -> usize {
+        self.line(row)
+            .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+            .unwrap_or(0)
+    }
+
     /// Offset to point conversion
     pub fn offset_to_point(&self, offset: Offset) -> Point {
         let (line, col) = self.rope.byte_to_line_col(offset.value());
         Point::new(line, col)
     }
 
-    pub fn lines(&self) -> Vec<String> {
-        let mut result = Vec::new();
-        for i in 0..self.line_count() {
-            if let Some(line) = self.line(i) {
-                result.push(line);
+    /// Screen column of a point: tabs advance to the next multiple of
+    /// `tab_width` and wide characters (CJK, emoji) take two columns
+    pub fn display_column(&self, point: Point, tab_width: usize) -> usize {
+        let line = self.line(point.row).unwrap_or_default();
+        tabs::display_column(&line, point.column, tab_width)
+    }
+
+    /// Point in `row` at screen column `display`, or the last one before it
+    /// when it falls inside a tab or wide character (the line's end past it)
+    pub fn point_at_display_column(&self, row: usize, display: usize, tab_width: usize) -> Point {
+        let line = self.line(row).unwrap_or_default();
+        Point::new(row, tabs::column_at_display(&line, display, tab_width))
+    }
+
+    /// Anchor at a point that follows its text through later edits
+    pub fn anchor_at(&self, point: Point, bias: Bias) -> Anchor {
+        Anchor::new(self.point_to_offset(point), bias)
+    }
+
+    /// Current position of an anchor (clamped to the end of the buffer)
+    pub fn anchor_to_point(&self, anchor: Anchor) -> Point {
+        self.offset_to_point(Offset(anchor.offset.value().min(self.len())))
+    }
+
+    /// Start of the grapheme cluster (user-perceived character: an emoji,
+    /// a letter with its combining marks, `\r\n`) ending at `offset`
+    pub fn prev_grapheme_boundary(&self, offset: Offset) -> Offset {
+        let (start, window) = self.grapheme_window(offset);
+        let at = offset.value().min(self.len()) - start;
+        let mut cursor = GraphemeCursor::new(at, window.len(), true);
+        match cursor.prev_boundary(&window, 0) {
+            Ok(Some(boundary)) => Offset(start + boundary),
+            _ => Offset(start),
+        }
+    }
+
+    /// End of the grapheme cluster starting at `offset`
+    pub fn next_grapheme_boundary(&self, offset: Offset) -> Offset {
+        let (start, window) = self.grapheme_window(offset);
+        let at = offset.value().min(self.len()) - start;
+        let mut cursor = GraphemeCursor::new(at, window.len(), true);
+        match cursor.next_boundary(&window, 0) {
+            Ok(Some(boundary)) => Offset(start + boundary),
+            _ => Offset(start + window.len()),
+        }
+    }
+
+    /// The lines around `offset` (clusters never span more than a line
+    /// break) and where they start
+    fn grapheme_window(&self, offset: Offset) -> (usize, String) {
+        let offset = offset.value().min(self.len());
+        let row = self.offset_to_point(Offset(offset)).row;
+        let start = self.rope.line_to_byte(row.saturating_sub(1));
+        let end = self.rope.line_to_byte(row + 2);
+        (start, self.rope.slice_bytes(start, end))
+    }
+
+    /// Run of word characters, whitespace or punctuation at a point. Word
+    /// characters just before the point win, so a point at the end of a word
+    /// still selects it. Empty on an empty line
+    pub fn word_range_at(&self, point: Point, word_chars: &str) -> Range<Offset> {
+        let line: Vec<char> = self.line(point.row).unwrap_or_default().chars().collect();
+        let column = point.column.min(line.len());
+        let class = |c: char| CharClass::of(c, word_chars);
+
+        let before = column.checked_sub(1).map(|i| class(line[i]));
+        let target = match (line.get(column).map(|c| class(*c)), before) {
+            (_, Some(CharClass::Word)) => CharClass::Word,
+            (Some(at), _) => at,
+            (None, Some(before)) => before,
+            (None, None) => {
+                let offset = self.point_to_offset(Point::new(point.row, column));
+                return offset..offset;
+            }
+        };
+
+        let start = column
+            - line[..column]
+                .iter()
+                .rev()
+                .take_while(|c| class(**c) == target)
+                .count();
+        let end = column
+            + line[column..]
+                .iter()
+                .take_while(|c| class(**c) == target)
+                .count();
+        self.point_to_offset(Point::new(point.row, start))
+            ..self.point_to_offset(Point::new(point.row, end))
+    }
+
+    /// Word touching a point: alphanumerics and `word_chars` (None between
+    /// whitespace or punctuation)
+    pub fn word_at(&self, point: Point, word_chars: &str) -> Option<Range<Offset>> {
+        let range = self.word_range_at(point, word_chars);
+        let word = self
+            .rope
+            .slice_bytes(range.start.value(), range.end.value());
+        word.starts_with(|c| is_word_char(c, word_chars))
+            .then_some(range)
+    }
+
+    /// End of the word (or punctuation run) after `offset`, skipping
+    /// whitespace first. Stops at the line's end, and from there steps onto
+    /// the next line
+    pub fn next_word_boundary(&self, offset: Offset, word_chars: &str) -> Offset {
+        let point = self.offset_to_point(offset);
+        let line = self.line_chars(point.row);
+        if point.column >= line.len() {
+            return self.next_grapheme_boundary(offset);
+        }
+        let class = |column: usize| CharClass::of(line[column], word_chars);
+
+        let mut column = point.column;
+        while column < line.len() && class(column) == CharClass::Space {
+            column += 1;
+        }
+        if let Some(target) = (column < line.len()).then(|| class(column)) {
+            while column < line.len() && class(column) == target {
+                column += 1;
             }
         }
-        result
+        self.point_to_offset(Point::new(point.row, column))
+    }
+
+    /// Start of the word (or punctuation run) before `offset`, skipping
+    /// whitespace first; from a line's start, steps back to the previous
+    /// line's end
+    pub fn prev_word_boundary(&self, offset: Offset, word_chars: &str) -> Offset {
+        let point = self.offset_to_point(offset);
+        if point.column == 0 {
+            return self.prev_grapheme_boundary(offset);
+        }
+        let line = self.line_chars(point.row);
+        let class = |column: usize| CharClass::of(line[column - 1], word_chars);
+
+        let mut column = point.column.min(line.len());
+        while column > 0 && class(column) == CharClass::Space {
+            column -= 1;
+        }
+        if let Some(target) = (column > 0).then(|| class(column)) {
+            while column > 0 && class(column) == target {
+                column -= 1;
+            }
+        }
+        self.point_to_offset(Point::new(point.row, column))
+    }
+
+    /// A line's chars without its `\r`, if it ends in `\r\n`
+    fn line_chars(&self, row: usize) -> Vec<char> {
+        let line = self.line(row).unwrap_or_default();
+        line.strip_suffix('\r').unwrap_or(&line).chars().collect()
+    }
+
+    /// Paragraph at a point: the non-blank lines around it (or the run of
+    /// blank lines, on a blank line), without the last line's newline
+    pub fn paragraph_range_at(&self, point: Point) -> Range<Offset> {
+        let is_blank = |row: usize| self.line(row).is_none_or(|line| line.trim().is_empty());
+        let row = point.row.min(self.line_count() - 1);
+        let blank = is_blank(row);
+
+        let mut first = row;
+        while first > 0 && is_blank(first - 1) == blank {
+            first -= 1;
+        }
+        let mut last = row;
+        while last + 1 < self.line_count() && is_blank(last + 1) == blank {
+            last += 1;
+        }
+        self.line_start_offset(first)..self.line_end_offset(last)
+    }
+
+    /// Nearest `()`, `[]` or `{}` pair around a point, brackets included.
+    /// Scans the rope's chunks outwards from the point, stopping at the pair
+    pub fn bracket_pair_range_at(&self, point: Point) -> Option<Range<Offset>> {
+        let offset = self.point_to_offset(point).value();
+        let range = brackets::enclosing_pair(offset, self.rope.bytes_before(offset), |at| {
+            self.rope.chunks_in(at, self.len()).flat_map(str::bytes)
+        })?;
+        Some(Offset(range.start)..Offset(range.end))
+    }
+
+    pub fn lines(&self) -> Vec<Cow<'_, str>> {
+        self.rope.lines().collect()
     }
 
     /// 🚀 Get byte range for a line (efficient - for syntax highlighting)
@@ -194,6 +485,16 @@ impl Buffer {
         &self.rope
     }
 
+    /// Read-only view of the current text for another thread; later edits
+    /// to this buffer don't show up in it
+    pub fn snapshot(&self) -> BufferSnapshot {
+        BufferSnapshot::new(self, 0)
+    }
+
+    pub(crate) fn shared_rope(&self) -> Arc<Rope> {
+        Arc::clone(&self.rope)
+    }
+
     /// 🚀 Get cache statistics for performance monitoring
     pub fn cache_stats(&self) -> String {
         let stats = self.line_cache.cache_stats();
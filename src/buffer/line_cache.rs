@@ -11,7 +11,7 @@ pub struct LineOffsetCache {
     cached_offsets: Vec<Option<usize>>, // line_index -> byte_offset
     cached_range: Range<usize>,         // What lines we have cached
     current_version: u64,               // For cache invalidation
-    last_access: std::time::Instant,
+    last_access: web_time::Instant,
     total_lines: usize, // File size for bounds checking
     cache_hits: u64,    // Performance metrics
     cache_misses: u64,
@@ -29,7 +29,7 @@ pub struct PredictiveCache {
     pub visible_range: std::ops::Range<usize>, // Currently visible lines
     pub precalc_range: std::ops::Range<usize>, // Lines to pre-calculate (±200)
     pub scroll_direction: ScrollDirection,     // Predict where user will scroll
-    pub last_scroll_time: std::time::Instant,  // For scroll prediction
+    pub last_scroll_time: web_time::Instant,  // For scroll prediction
     pub scroll_velocity: f32,                  // Pixels/frame for prediction
 }
 
@@ -37,7 +37,7 @@ pub struct PredictiveCache {
 #[derive(Debug, Clone)]
 pub struct ReusableBuffer {
     pub buffer: Vec<usize>,
-    last_used: std::time::Instant,
+    last_used: web_time::Instant,
 }
 
 impl PredictiveCache {
@@ -46,7 +46,7 @@ impl PredictiveCache {
             visible_range: 0..0,
             precalc_range: 0..0,
             scroll_direction: ScrollDirection::Stationary,
-            last_scroll_time: std::time::Instant::now(),
+            last_scroll_time: web_time::Instant::now(),
             scroll_velocity: 0.0,
         }
     }
@@ -79,7 +79,7 @@ impl PredictiveCache {
         // 🚀 PREDICT where user will scroll next
         self.precalc_range = self.calculate_precalc_range(new_visible_range);
 
-        self.last_scroll_time = std::time::Instant::now();
+        self.last_scroll_time = web_time::Instant::now();
     }
 
     /// 🚀 CALCULATE which lines to pre-cache based on scroll behavior
@@ -137,7 +137,7 @@ impl LineOffsetCache {
             cached_offsets: Vec::new(),
             cached_range: 0..0,
             current_version: 0,
-            last_access: std::time::Instant::now(),
+            last_access: web_time::Instant::now(),
             total_lines,
             cache_hits: 0,
             cache_misses: 0,
@@ -150,7 +150,7 @@ impl LineOffsetCache {
         rope: &Rope, // 🚀 CHANGE: Take Rope reference instead of Editor
         reusable_buffer: &'a mut ReusableBuffer,
     ) -> &'a [usize] {
-        self.last_access = std::time::Instant::now();
+        self.last_access = web_time::Instant::now();
 
         // 🚀 REUSE EXISTING BUFFER (no allocation)
         reusable_buffer.buffer.clear();
@@ -395,17 +395,12 @@ impl LineOffsetCache {
     // ==================== PERFORMANCE METRICS ====================
 
     pub fn cache_stats(&self) -> CacheStats {
-        CacheStats {
-            hits: self.cache_hits,
-            misses: self.cache_misses,
-            hit_rate: if self.cache_hits + self.cache_misses > 0 {
-                self.cache_hits as f64 / (self.cache_hits + self.cache_misses) as f64
-            } else {
-                0.0
-            },
-            cached_lines: self.cached_offsets.iter().filter(|o| o.is_some()).count(),
-            total_capacity: self.cached_offsets.len(),
-        }
+        CacheStats::new(
+            self.cache_hits,
+            self.cache_misses,
+            self.cached_offsets.iter().filter(|o| o.is_some()).count(),
+            self.cached_offsets.len(),
+        )
     }
 
     pub fn memory_usage(&self) -> usize {
@@ -418,12 +413,12 @@ impl ReusableBuffer {
     pub fn new() -> Self {
         Self {
             buffer: Vec::with_capacity(1000), // Pre-allocate for typical viewport
-            last_used: std::time::Instant::now(),
+            last_used: web_time::Instant::now(),
         }
     }
 
     pub fn take(&mut self) -> Vec<usize> {
-        self.last_used = std::time::Instant::now();
+        self.last_used = web_time::Instant::now();
         std::mem::replace(&mut self.buffer, Vec::new())
     }
 
@@ -434,7 +429,7 @@ impl ReusableBuffer {
 }
 
 /// 🚀 PERFORMANCE METRICS
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
@@ -443,6 +438,24 @@ pub struct CacheStats {
     pub total_capacity: usize,
 }
 
+impl CacheStats {
+    /// Build stats from raw counters (hit rate is 0.0 before any lookup)
+    pub fn new(hits: u64, misses: u64, cached_lines: usize, total_capacity: usize) -> Self {
+        let lookups = hits + misses;
+        Self {
+            hits,
+            misses,
+            hit_rate: if lookups > 0 {
+                hits as f64 / lookups as f64
+            } else {
+                0.0
+            },
+            cached_lines,
+            total_capacity,
+        }
+    }
+}
+
 impl Default for LineOffsetCache {
     fn default() -> Self {
         Self::new(0)
@@ -1,12 +1,16 @@
+pub mod anchor;
 pub mod buffer;
 pub mod line_cache;
 
 pub mod line_cache_simple;
 pub mod offset;
 pub mod point; // NEW
+pub mod snapshot;
 
-pub use buffer::Buffer;
-pub use line_cache::{LineOffsetCache, PredictiveCache, ReusableBuffer};
+pub use anchor::{Anchor, Bias};
+pub use buffer::{is_word_char, Buffer};
+pub use line_cache::{CacheStats, LineOffsetCache, PredictiveCache, ReusableBuffer};
 
 pub use offset::Offset;
 pub use point::Point;
+pub use snapshot::BufferSnapshot;
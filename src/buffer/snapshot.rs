@@ -0,0 +1,78 @@
+use super::buffer::Buffer;
+use crate::rope::Rope;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A buffer's contents at an editor version. Cloning shares the rope rather
+/// than copying it, and nothing in it can change, so background work
+/// (highlighting, search, formatting, LSP sync) can read it on another
+/// thread while the buffer keeps being edited
+///
+/// With the `serde` feature it also serializes, for hot exit, IPC and golden
+/// fixtures (caches are rebuilt on load, not serialized)
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BufferSnapshot {
+    version: u64,
+    rope: Arc<Rope>,
+}
+
+impl BufferSnapshot {
+    pub fn new(buffer: &Buffer, version: u64) -> Self {
+        Self {
+            version,
+            rope: buffer.shared_rope(),
+        }
+    }
+
+    /// Editor version the text is from (0 for `Buffer::snapshot`, since a
+    /// buffer doesn't count its edits)
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    pub fn len(&self) -> usize {
+        self.rope.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rope.is_empty()
+    }
+
+    /// Lines, counting the empty one after a trailing newline (like
+    /// `Buffer::line_count`)
+    pub fn line_count(&self) -> usize {
+        if self.rope.is_empty() {
+            1
+        } else {
+            self.rope.line_count() + 1
+        }
+    }
+
+    pub fn line(&self, line_idx: usize) -> Option<String> {
+        self.rope.line(line_idx)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.rope.lines()
+    }
+
+    pub fn slice_bytes(&self, start: usize, end: usize) -> String {
+        self.rope.slice_bytes(start, end)
+    }
+
+    /// The whole text (copies it; prefer the methods above for parts)
+    pub fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    pub fn to_buffer(&self) -> Buffer {
+        Buffer::from_rope(Rope::clone(&self.rope))
+    }
+}
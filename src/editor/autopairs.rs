@@ -0,0 +1,77 @@
+/// Bracket and quote pairs closed automatically when the language doesn't
+/// say otherwise (quotes open and close with the same char)
+pub const PAIRS: [(char, char); 5] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
+/// The pairs auto-closed in one buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoPairs {
+    pairs: Vec<(char, char)>,
+}
+
+impl AutoPairs {
+    pub fn new(pairs: &[(char, char)]) -> Self {
+        Self {
+            pairs: pairs.to_vec(),
+        }
+    }
+
+    /// No auto-closing at all
+    pub fn none() -> Self {
+        Self { pairs: Vec::new() }
+    }
+
+    /// Parse a settings value: opener/closer characters back to back, e.g.
+    /// `()[]{}""` (whitespace between pairs is ignored)
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let chars: Vec<char> = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        if !chars.len().is_multiple_of(2) {
+            return Err(format!("unpaired character in auto-pairs {:?}", spec));
+        }
+        let pairs = chars.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        Ok(Self { pairs })
+    }
+
+    pub fn pairs(&self) -> &[(char, char)] {
+        &self.pairs
+    }
+
+    /// Closing character for an opening bracket or quote
+    pub fn closer_for(&self, open: char) -> Option<char> {
+        self.pairs.iter().find(|(o, _)| *o == open).map(|(_, c)| *c)
+    }
+
+    /// Whether `c` closes a pair (quotes count as closers)
+    pub fn is_closer(&self, c: char) -> bool {
+        self.pairs.iter().any(|(_, close)| *close == c)
+    }
+
+    /// Whether `before` + `after` (the chars around the cursor) form an empty pair
+    pub fn is_empty_pair(&self, before: char, after: char) -> bool {
+        self.closer_for(before) == Some(after)
+    }
+
+    /// Whether typing `open` should also insert its closer
+    ///
+    /// Only auto-close in front of whitespace, a closer, or the line end, so
+    /// typing `(` right before a word doesn't produce `()word`. Quotes also stay
+    /// single after a word character (`don't`, `a"`).
+    pub fn should_autoclose(&self, open: char, before: Option<char>, after: Option<char>) -> bool {
+        let Some(close) = self.closer_for(open) else {
+            return false;
+        };
+
+        let free_after = after.is_none_or(|c| c.is_whitespace() || self.is_closer(c));
+        if open == close {
+            let after_word = before.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            free_after && !after_word
+        } else {
+            free_after
+        }
+    }
+}
+
+impl Default for AutoPairs {
+    fn default() -> Self {
+        Self::new(&PAIRS)
+    }
+}
@@ -0,0 +1,218 @@
+use super::diagnostics::Diagnostic;
+use super::line_endings::{self, TextFormat};
+#[cfg(feature = "syntax")]
+use crate::syntax::imports;
+use crate::syntax::LanguageId;
+use std::ops::Range;
+#[cfg(feature = "syntax")]
+use tree_sitter::Tree;
+
+/// Replace a byte range of the current text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, new_text: impl Into<String>) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeActionKind {
+    /// Fixes a problem (usually a diagnostic)
+    QuickFix,
+    /// Whole-file cleanups like organizing imports
+    Source,
+}
+
+/// A named set of non-overlapping edits, applied as one undo step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: CodeActionKind,
+    pub edits: Vec<TextEdit>,
+    /// The diagnostic this action fixes, if any
+    pub diagnostic: Option<Diagnostic>,
+}
+
+impl CodeAction {
+    pub fn new(title: impl Into<String>, kind: CodeActionKind, edits: Vec<TextEdit>) -> Self {
+        Self {
+            title: title.into(),
+            kind,
+            edits,
+            diagnostic: None,
+        }
+    }
+
+    /// True if any edit touches `range` (e.g. the cursor line)
+    pub fn touches(&self, range: &Range<usize>) -> bool {
+        self.edits
+            .iter()
+            .any(|edit| edit.range.start <= range.end && range.start <= edit.range.end)
+    }
+}
+
+/// What providers see when asked for actions
+pub struct CodeActionContext {
+    pub text: String,
+    pub language: LanguageId,
+    /// Parse tree of `text` (None without a grammar; only with the `syntax`
+    /// feature)
+    #[cfg(feature = "syntax")]
+    pub tree: Option<Tree>,
+    /// Cursor byte offset and the byte range of its line
+    pub cursor: usize,
+    pub line: Range<usize>,
+    /// Diagnostics starting on the cursor line
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Source of code actions; built-in fixes today, a language server later
+pub trait CodeActionProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn actions(&self, context: &CodeActionContext) -> Vec<CodeAction>;
+}
+
+/// Fixes that need no external tools
+pub struct BuiltinCodeActions;
+
+impl BuiltinCodeActions {
+    fn remove_trailing_whitespace(text: &str) -> Option<CodeAction> {
+        let mut edits = Vec::new();
+        let mut line_start = 0;
+        for line in text.split_inclusive('\n') {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let content = content.strip_suffix('\r').unwrap_or(content);
+            let trimmed = content.trim_end_matches([' ', '\t']);
+            if trimmed.len() < content.len() {
+                let start = line_start + trimmed.len();
+                edits.push(TextEdit::new(start..line_start + content.len(), ""));
+            }
+            line_start += line.len();
+        }
+
+        (!edits.is_empty())
+            .then(|| CodeAction::new("Remove trailing whitespace", CodeActionKind::Source, edits))
+    }
+
+    fn add_missing_newline(text: &str) -> Option<CodeAction> {
+        (!text.is_empty() && !text.ends_with('\n')).then(|| {
+            CodeAction::new(
+                "Add missing newline at end of file",
+                CodeActionKind::Source,
+                vec![TextEdit::new(text.len()..text.len(), "\n")],
+            )
+        })
+    }
+
+    fn remove_bom(context: &CodeActionContext) -> Option<CodeAction> {
+        let edit = line_endings::strip_bom(&context.text)?;
+        let diagnostic = Self::diagnostic_at(context, &edit.range);
+        let mut action =
+            CodeAction::new("Remove byte order mark", CodeActionKind::Source, vec![edit]);
+        action.diagnostic = diagnostic;
+        Some(action)
+    }
+
+    fn unify_line_endings(context: &CodeActionContext) -> Option<CodeAction> {
+        let format = TextFormat::detect(&context.text);
+        if !format.is_mixed() {
+            return None;
+        }
+        let ending = format.line_ending();
+        let edits = line_endings::unify_line_endings(&context.text, ending);
+        let diagnostic = Self::diagnostic_at(context, &edits.first()?.range);
+        let mut action = CodeAction::new(
+            format!("Convert line endings to {}", ending.label()),
+            CodeActionKind::Source,
+            edits,
+        );
+        action.diagnostic = diagnostic;
+        Some(action)
+    }
+
+    /// The cursor line's diagnostic for exactly `range`, which an action
+    /// editing that range fixes
+    fn diagnostic_at(context: &CodeActionContext, range: &Range<usize>) -> Option<Diagnostic> {
+        context
+            .diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.range == *range)
+            .cloned()
+    }
+
+    #[cfg(feature = "syntax")]
+    fn organize_imports(context: &CodeActionContext) -> Option<CodeAction> {
+        let tree = context.tree.as_ref()?;
+        let edits: Vec<TextEdit> = imports::organize_imports(tree, &context.text, context.language)
+            .into_iter()
+            .map(|(range, new_text)| TextEdit::new(range, new_text))
+            .collect();
+
+        (!edits.is_empty())
+            .then(|| CodeAction::new("Organize imports", CodeActionKind::Source, edits))
+    }
+}
+
+impl CodeActionProvider for BuiltinCodeActions {
+    fn name(&self) -> &str {
+        "built-in"
+    }
+
+    fn actions(&self, context: &CodeActionContext) -> Vec<CodeAction> {
+        [
+            Self::remove_trailing_whitespace(&context.text),
+            Self::add_missing_newline(&context.text),
+            Self::remove_bom(context),
+            Self::unify_line_endings(context),
+            #[cfg(feature = "syntax")]
+            Self::organize_imports(context),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// All registered providers, asked in registration order
+pub struct CodeActions {
+    providers: Vec<Box<dyn CodeActionProvider>>,
+}
+
+impl CodeActions {
+    /// Starts with the built-in fixes
+    pub fn new() -> Self {
+        Self {
+            providers: vec![Box::new(BuiltinCodeActions)],
+        }
+    }
+
+    pub fn register(&mut self, provider: Box<dyn CodeActionProvider>) {
+        self.providers.push(provider);
+    }
+
+    /// Actions for the context; fixes for diagnostics on the cursor line come first
+    pub fn actions(&self, context: &CodeActionContext) -> Vec<CodeAction> {
+        let mut actions: Vec<CodeAction> = self
+            .providers
+            .iter()
+            .flat_map(|provider| provider.actions(context))
+            .collect();
+        actions.sort_by_key(|action| action.diagnostic.is_none());
+        actions
+    }
+}
+
+impl Default for CodeActions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
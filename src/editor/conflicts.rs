@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+/// Which side of a merge conflict to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+impl ConflictResolution {
+    pub const ALL: [ConflictResolution; 3] = [
+        ConflictResolution::Ours,
+        ConflictResolution::Theirs,
+        ConflictResolution::Both,
+    ];
+
+    /// Button label
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictResolution::Ours => "Accept Ours",
+            ConflictResolution::Theirs => "Accept Theirs",
+            ConflictResolution::Both => "Accept Both",
+        }
+    }
+}
+
+/// A `<<<<<<<` … `=======` … `>>>>>>>` region, as buffer rows (a diff3
+/// `|||||||` base section counts as neither side)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The `<<<<<<<` row
+    pub start: usize,
+    /// The `|||||||` row, if the conflict shows the common base
+    pub base: Option<usize>,
+    /// The `=======` row
+    pub separator: usize,
+    /// The `>>>>>>>` row
+    pub end: usize,
+}
+
+impl Conflict {
+    /// Rows of our side
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    /// Rows of their side
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+
+    /// Rows kept by a resolution, in order
+    pub fn kept_rows(&self, resolution: ConflictResolution) -> Vec<usize> {
+        match resolution {
+            ConflictResolution::Ours => self.ours().collect(),
+            ConflictResolution::Theirs => self.theirs().collect(),
+            ConflictResolution::Both => self.ours().chain(self.theirs()).collect(),
+        }
+    }
+
+    pub fn contains_row(&self, row: usize) -> bool {
+        (self.start..=self.end).contains(&row)
+    }
+}
+
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r']))
+}
+
+/// Complete conflict regions in `lines`, in order (an unfinished one is ignored)
+pub fn find_conflicts<S: AsRef<str>>(lines: &[S]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+    for (row, line) in lines.iter().enumerate() {
+        let line = line.as_ref();
+        if is_marker(line, "<<<<<<<") {
+            start = Some(row);
+            base = None;
+            separator = None;
+        } else if start.is_some() && separator.is_none() && is_marker(line, "|||||||") {
+            base = Some(row);
+        } else if start.is_some() && separator.is_none() && is_marker(line, "=======") {
+            separator = Some(row);
+        } else if is_marker(line, ">>>>>>>") {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                conflicts.push(Conflict {
+                    start,
+                    base,
+                    separator,
+                    end: row,
+                });
+            }
+            start = None;
+            separator = None;
+        }
+    }
+    conflicts
+}
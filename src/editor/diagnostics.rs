@@ -0,0 +1,148 @@
+use super::events::EditEvent;
+use crate::buffer::{Anchor, Offset};
+use std::ops::Range;
+#[cfg(feature = "syntax")]
+use tree_sitter::{Node, Tree};
+
+/// How serious a diagnostic is (most serious first, so sorting puts errors first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+}
+
+/// A problem reported for a byte range of the buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(range: Range<usize>, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// Diagnostics for one buffer, kept sorted by position (then severity)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticSet {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticSet {
+    pub fn new(mut diagnostics: Vec<Diagnostic>) -> Self {
+        diagnostics.sort_by_key(|d| (d.range.start, d.severity, d.range.end));
+        Self { diagnostics }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    /// First diagnostic starting inside `range` (e.g. one line's bytes)
+    pub fn first_in(&self, range: Range<usize>) -> Option<&Diagnostic> {
+        let index = self
+            .diagnostics
+            .partition_point(|d| d.range.start < range.start);
+        self.diagnostics
+            .get(index)
+            .filter(|d| d.range.start < range.end)
+    }
+
+    /// First diagnostic starting after `offset`, wrapping to the first one
+    pub fn next_after(&self, offset: usize) -> Option<&Diagnostic> {
+        self.diagnostics
+            .iter()
+            .find(|d| d.range.start > offset)
+            .or_else(|| self.diagnostics.first())
+    }
+
+    /// Last diagnostic starting before `offset`, wrapping to the last one
+    pub fn prev_before(&self, offset: usize) -> Option<&Diagnostic> {
+        self.diagnostics
+            .iter()
+            .rev()
+            .find(|d| d.range.start < offset)
+            .or_else(|| self.diagnostics.last())
+    }
+
+    /// Keep ranges pointing at the same text after an edit. Each range is
+    /// anchored to grow with text typed at either end; one inside replaced
+    /// text stretches over the new text
+    pub fn apply_edit(&mut self, event: &EditEvent) {
+        let new_len = event.new_text.len();
+        for diagnostic in &mut self.diagnostics {
+            let mut start = Anchor::before(Offset(diagnostic.range.start));
+            let mut end = Anchor::after(Offset(diagnostic.range.end));
+            start.apply_edit(&event.old_range, new_len);
+            end.apply_edit(&event.old_range, new_len);
+            diagnostic.range = start.offset.value()..end.offset.value();
+        }
+        self.diagnostics
+            .sort_by_key(|d| (d.range.start, d.severity, d.range.end));
+    }
+}
+
+/// Syntax errors in a parse tree: `ERROR` nodes and nodes the parser had to
+/// insert (reported as "missing ...")
+#[cfg(feature = "syntax")]
+pub fn syntax_diagnostics(tree: &Tree) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect_syntax_errors(tree.root_node(), &mut diagnostics);
+    diagnostics
+}
+
+#[cfg(feature = "syntax")]
+fn collect_syntax_errors(node: Node, diagnostics: &mut Vec<Diagnostic>) {
+    if !node.has_error() {
+        return;
+    }
+    if node.is_missing() {
+        diagnostics.push(Diagnostic::new(
+            node.byte_range(),
+            Severity::Error,
+            format!("missing `{}`", node.kind()),
+        ));
+        return;
+    }
+    if node.is_error() {
+        diagnostics.push(Diagnostic::new(
+            node.byte_range(),
+            Severity::Error,
+            "syntax error",
+        ));
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(child, diagnostics);
+    }
+}
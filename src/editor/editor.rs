@@ -1,22 +1,86 @@
+use super::autopairs::AutoPairs;
+use super::code_actions::{CodeAction, CodeActionContext, TextEdit};
+use super::conflicts::{self, Conflict, ConflictResolution};
+#[cfg(feature = "syntax")]
+use super::diagnostics;
+use super::diagnostics::{Diagnostic, DiagnosticSet};
+use super::events::{changed_range, EditEvent, EditorEvent, Observer};
+use super::line_endings::{self, LineEnding, TextFormat, BOM};
+use super::multi_cursor::MultiCursor;
+use super::search;
 use super::selection::Selection;
-use crate::buffer::{Buffer, Offset, Point};
-use crate::history::{History, Transaction};
-use crate::syntax::IndentCalculator;
+use super::text_objects::{self, TextObject, TextObjectRange};
+use super::transform::{self, SortOptions, Transform};
+use crate::buffer::{is_word_char, Anchor, Bias, Buffer, BufferSnapshot, Offset, Point};
+use crate::formatter::Formatter;
+use crate::history::{History, Hunk, Transaction};
+use crate::render::{DisplayPoint, WrapMap};
+use crate::rope::Rope;
+use crate::settings::modeline::{Modeline, MODELINE_LINES};
+use crate::settings::PasteMode;
+#[cfg(feature = "syntax")]
+use crate::syntax::imports;
+use crate::syntax::{
+    FileAssociations, IndentCalculator, LanguageConfig, LanguageId, LanguageRegistry,
+};
+use crate::util::diff::{diff_lines, diff_text, LineHunk};
+use crate::util::tabs::{column_at_display, display_column, indent_string};
+use std::collections::BTreeMap;
+use std::ops::Range;
 use std::path::Path;
-use std::time::Instant;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+use web_time::Instant;
+#[cfg(feature = "syntax")]
+use tree_sitter::Tree;
+
+/// Largest paste (in bytes) `PasteMode::Format` sends to the formatter
+const FORMAT_PASTE_LIMIT: usize = 4 * 1024;
+/// How long a paste waits for the formatter before going in re-indented
+const FORMAT_PASTE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Maximum number of remembered edit locations per buffer
+const MAX_EDIT_LOCATIONS: usize = 50;
+
+/// Default for `set_undo_hunk_limit`
+const DEFAULT_UNDO_HUNK_LIMIT: usize = 1024 * 1024;
+
+/// Where `from_template` puts the cursor
+const CURSOR_MARKER: &str = "$0";
 
 /// Editor state - buffer + cursor + history
 #[derive(Clone)]
 pub struct Editor {
     history: History,
     selection: Selection,
+    cursors: MultiCursor, // Extra cursors beside `selection` (the primary one)
     version: u64,
     indent_calculator: IndentCalculator,
+    language_registry: LanguageRegistry,
+    /// Language name forced for this buffer (see `set_language_override`)
+    language_override: Option<String>,
+    auto_close: bool,
+    autopair_overrides: BTreeMap<String, AutoPairs>, // Keyed by lowercase language name
+    paste_mode: PasteMode,
+    paste_mode_overrides: BTreeMap<String, PasteMode>, // Keyed by lowercase language name
+    word_chars: String,                                // Besides alphanumerics
+    word_chars_overrides: BTreeMap<String, String>,    // Keyed by lowercase language name
+    observers: Vec<Observer>,
+    observed_selection: Selection, // Last selection sent to observers
+    diagnostics: DiagnosticSet,
+    wrap_map: Option<WrapMap>, // Some while soft wrap is on
     file_path: Option<std::path::PathBuf>,
+    saved_version: Option<u64>, // None = content never matched what's on disk
+    edit_locations: Vec<Anchor>, // Oldest first, following later edits
+    edit_location_index: Option<usize>, // Position while cycling with go_to_last_edit_location
+    goal_column: Option<(usize, Point)>, // (goal, cursor it applies to) for up/down movement
+    selection_expansions: Vec<(Selection, Selection)>, // (before, after) for each expand_selection
+    undo_hunk_limit: usize,              // Bytes a replace-all may keep as hunks in its transaction
+    text_format: TextFormat,             // Detected on load, then kept up by notify_edit
 
     // ✅ Batching for word-by-word undo
     pending_insert: String,
-    pending_start_cursor: Option<Point>,
+    pending_start_selections: Option<Vec<Selection>>,
     pending_start_buffer: Option<Box<Buffer>>,  // ✅ Save the buffer state BEFORE pending edits
     last_edit_time: Instant,
 }
@@ -24,34 +88,81 @@ pub struct Editor {
 impl Editor {
     /// Create empty editor
     pub fn new() -> Self {
-        Self {
-            history: History::new(Buffer::new()),
-            selection: Selection::cursor(Point::zero()),
-            version: 0,
-            indent_calculator: IndentCalculator::new(),
-            file_path: None,
-            pending_insert: String::new(),
-            pending_start_cursor: None,
-            pending_start_buffer: None,
-            last_edit_time: Instant::now(),
-        }
+        Self::from_rope(Rope::new())
     }
 
     /// Create editor from text
     pub fn from_text(text: &str) -> Self {
+        Self::from_rope(Rope::from_text(text))
+    }
+
+    /// Editor for a new file starting from a template, its lines ending in
+    /// `ending`; the cursor goes where the template has `$0`, else at the start
+    pub fn from_template(template: &str, ending: LineEnding) -> Self {
+        let text = template
+            .replace("\r\n", "\n")
+            .replace('\n', ending.as_str());
+        let cursor = text.find(CURSOR_MARKER);
+        let mut editor = Self::from_text(&text.replacen(CURSOR_MARKER, "", 1));
+        if let Some(offset) = cursor {
+            let point = editor.buffer().offset_to_point(Offset(offset));
+            editor.set_cursor(point);
+        }
+        editor
+    }
+
+    /// Create editor over an already-built rope (e.g. one streamed from disk)
+    pub fn from_rope(rope: Rope) -> Self {
+        let buffer = Buffer::from_rope(rope);
         Self {
-            history: History::new(Buffer::from_text(text)),
+            text_format: Self::detect_text_format(&buffer),
+            history: History::new(buffer),
             selection: Selection::cursor(Point::zero()),
+            cursors: MultiCursor::new(),
             version: 0,
             indent_calculator: IndentCalculator::new(),
+            language_registry: LanguageRegistry::new(),
+            language_override: None,
+            auto_close: true,
+            autopair_overrides: BTreeMap::new(),
+            paste_mode: PasteMode::Reindent,
+            paste_mode_overrides: BTreeMap::new(),
+            word_chars: "_".to_string(),
+            word_chars_overrides: BTreeMap::new(),
+            observers: Vec::new(),
+            observed_selection: Selection::cursor(Point::zero()),
+            diagnostics: DiagnosticSet::default(),
+            wrap_map: None,
             file_path: None,
+            saved_version: Some(0),
+            edit_locations: Vec::new(),
+            edit_location_index: None,
+            goal_column: None,
+            selection_expansions: Vec::new(),
+            undo_hunk_limit: DEFAULT_UNDO_HUNK_LIMIT,
             pending_insert: String::new(),
-            pending_start_cursor: None,
+            pending_start_selections: None,
             pending_start_buffer: None,
             last_edit_time: Instant::now(),
         }
     }
 
+    /// Restore an editor from a snapshot (history starts fresh, contents unsaved)
+    pub fn from_snapshot(snapshot: &BufferSnapshot) -> Self {
+        let mut editor = Self::new();
+        editor.history = History::new(snapshot.to_buffer());
+        editor.text_format = Self::detect_text_format(editor.buffer());
+        editor.version = snapshot.version();
+        editor.saved_version = None;
+        editor
+    }
+
+    /// Snapshot of the current contents and version, to hand to background
+    /// work or serialize
+    pub fn snapshot(&self) -> BufferSnapshot {
+        BufferSnapshot::new(self.buffer(), self.version)
+    }
+
     /// Set the file path (needed for language detection)
     pub fn set_file_path(&mut self, path: Option<std::path::PathBuf>) {
         self.file_path = path;
@@ -62,6 +173,166 @@ impl Editor {
         self.file_path.as_deref()
     }
 
+    /// Columns per indent level (also the tab stop width)
+    pub fn indent_width(&self) -> usize {
+        self.indent_calculator.indent_width()
+    }
+
+    /// Most bytes of changed text a replace-all keeps in its undo step;
+    /// larger ones are recorded as a bare snapshot of the buffer
+    pub fn set_undo_hunk_limit(&mut self, bytes: usize) {
+        self.undo_hunk_limit = bytes;
+    }
+
+    /// Indent with `width`-column levels, using tab characters if `hard_tabs`
+    pub fn set_indent(&mut self, width: usize, hard_tabs: bool) {
+        self.indent_calculator.set_indent(width, hard_tabs);
+        if let Some(map) = &self.wrap_map {
+            self.set_soft_wrap(Some(map.width()));
+        }
+    }
+
+    /// Wrap lines at `width` screen columns, or turn soft wrap off with None
+    pub fn set_soft_wrap(&mut self, width: Option<usize>) {
+        let tab_width = self.indent_width();
+        let previous = self.wrap_map.as_ref().map(WrapMap::width);
+        self.wrap_map = match (width, self.wrap_map.take()) {
+            (Some(width), Some(map)) if map.width() == width && map.tab_width() == tab_width => {
+                Some(map)
+            }
+            (Some(width), _) => Some(WrapMap::new(self.buffer(), width, tab_width)),
+            (None, _) => None,
+        };
+        // Goal columns are measured on display rows, which just changed
+        if self.wrap_map.as_ref().map(WrapMap::width) != previous {
+            self.goal_column = None;
+        }
+    }
+
+    /// Display rows for soft wrap (None when lines don't wrap)
+    pub fn wrap_map(&self) -> Option<&WrapMap> {
+        self.wrap_map.as_ref()
+    }
+
+    /// Whitespace Tab inserts: one tab, or one indent level of spaces
+    pub fn indent_unit(&self) -> String {
+        self.indent_calculator.indent_unit()
+    }
+
+    /// Apply user file associations to language detection (and auto-indent)
+    pub fn set_file_associations(&mut self, associations: FileAssociations) {
+        self.indent_calculator
+            .set_file_associations(associations.clone());
+        self.language_registry.set_associations(associations);
+    }
+
+    /// Turn auto-closing on or off and replace the per-language pairs
+    /// (`overrides` maps language names, case-insensitive, to their pairs)
+    pub fn set_autopairs(&mut self, enabled: bool, overrides: BTreeMap<String, AutoPairs>) {
+        self.auto_close = enabled;
+        self.autopair_overrides = overrides
+            .into_iter()
+            .map(|(language, pairs)| (language.to_lowercase(), pairs))
+            .collect();
+    }
+
+    /// Pairs auto-closed in this buffer: the user's override for the
+    /// language, else the language's own, else the defaults
+    pub fn autopairs(&self) -> AutoPairs {
+        if !self.auto_close {
+            return AutoPairs::none();
+        }
+        let language = self.language();
+        if let Some(pairs) =
+            language.and_then(|language| self.autopair_overrides.get(&language.name.to_lowercase()))
+        {
+            return pairs.clone();
+        }
+        language
+            .map(|language| AutoPairs::new(language.autopairs))
+            .unwrap_or_default()
+    }
+
+    /// How pasting works by default and per language (`overrides` maps
+    /// language names, case-insensitive, to their mode)
+    pub fn set_paste_modes(&mut self, default: PasteMode, overrides: BTreeMap<String, PasteMode>) {
+        self.paste_mode = default;
+        self.paste_mode_overrides = overrides
+            .into_iter()
+            .map(|(language, mode)| (language.to_lowercase(), mode))
+            .collect();
+    }
+
+    /// How pasting works in this buffer: the user's mode for the language,
+    /// else the default
+    pub fn paste_mode(&self) -> PasteMode {
+        self.language()
+            .and_then(|language| self.paste_mode_overrides.get(&language.name.to_lowercase()))
+            .copied()
+            .unwrap_or(self.paste_mode)
+    }
+
+    /// Characters that count as part of words besides letters and digits, by
+    /// default and per language (`overrides` maps language names,
+    /// case-insensitive, to their characters)
+    pub fn set_word_chars(&mut self, default: String, overrides: BTreeMap<String, String>) {
+        self.word_chars = default;
+        self.word_chars_overrides = overrides
+            .into_iter()
+            .map(|(language, chars)| (language.to_lowercase(), chars))
+            .collect();
+    }
+
+    /// Word characters besides alphanumerics in this buffer: the user's for
+    /// the language, else the default
+    pub fn word_chars(&self) -> &str {
+        self.language()
+            .and_then(|language| self.word_chars_overrides.get(&language.name.to_lowercase()))
+            .unwrap_or(&self.word_chars)
+    }
+
+    /// Use a language by name or extension ("python", "js") regardless of the
+    /// file path, e.g. from a modeline; None goes back to detection
+    pub fn set_language_override(&mut self, language: Option<String>) {
+        self.language_override = language;
+    }
+
+    /// Vim/emacs modeline in the first or last lines of the buffer
+    pub fn modeline(&self) -> Option<Modeline> {
+        let count = self.buffer().line_count();
+        let head = count.min(MODELINE_LINES);
+        let tail = count.saturating_sub(MODELINE_LINES).max(head);
+        let lines: Vec<String> = (0..head)
+            .chain(tail..count)
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+        Modeline::find(&lines)
+    }
+
+    /// Language from the override, then the file path, falling back to a
+    /// shebang on the first line
+    pub fn language(&self) -> Option<&LanguageConfig> {
+        if let Some(name) = self.language_override.as_deref() {
+            let language = self.language_registry.language_by_name(name).or_else(|| {
+                self.language_registry
+                    .languages()
+                    .iter()
+                    .find(|language| language.extensions.contains(&name))
+            });
+            if language.is_some() {
+                return language;
+            }
+        }
+        if let Some(path) = self.file_path.as_deref() {
+            if let Some(language) = self.language_registry.detect_language(path) {
+                return Some(language);
+            }
+        }
+
+        let first_line = self.buffer().line(0)?;
+        self.language_registry.detect_from_shebang(&first_line)
+    }
+
     /// Get buffer reference
     pub fn buffer(&self) -> &Buffer {
         self.history.current()
@@ -72,9 +343,17 @@ impl Editor {
         self.selection.end
     }
 
-    /// Set cursor position
+    /// Set cursor position (dropping any extra cursors)
     pub fn set_cursor(&mut self, point: Point) {
         self.selection = Selection::cursor(point);
+        self.cursors.clear();
+    }
+
+    /// Set selection (start is the anchor, end is the cursor), dropping any
+    /// extra cursors
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = selection;
+        self.cursors.clear();
     }
 
     /// Get selection
@@ -82,24 +361,623 @@ impl Editor {
         self.selection
     }
 
+    /// Every selection, the primary one included, sorted by position
+    pub fn selections(&self) -> Vec<Selection> {
+        let mut selections = self.cursors.selections().to_vec();
+        selections.push(self.selection);
+        selections.sort_by_key(|selection| selection.range());
+        selections
+    }
+
+    /// Screen column of the cursor in its line, tabs expanded to the indent
+    /// width (the cursor's `column` counts characters)
+    pub fn cursor_visual_column(&self) -> usize {
+        self.buffer()
+            .display_column(self.cursor(), self.indent_width())
+    }
+
+    /// Status bar position: "Col" is the visual column, and "Char" the
+    /// character column when tabs make them differ (both 1-based)
+    pub fn cursor_position_label(&self) -> String {
+        let cursor = self.cursor();
+        let visual = self.cursor_visual_column();
+        if visual == cursor.column {
+            format!("Line {}, Col {}", cursor.row + 1, visual + 1)
+        } else {
+            format!(
+                "Line {}, Col {} (Char {})",
+                cursor.row + 1,
+                visual + 1,
+                cursor.column + 1
+            )
+        }
+    }
+
+    /// Status bar size of the selection, counted from its offsets rather
+    /// than its text ("3 lines, 57 bytes selected"; None without one). A
+    /// selection ending at the start of a line doesn't count that line
+    pub fn selection_label(&self) -> Option<String> {
+        let selections: Vec<Selection> = self
+            .selections()
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .collect();
+        if selections.is_empty() {
+            return None;
+        }
+        let (mut lines, mut bytes) = (0, 0);
+        for selection in &selections {
+            let (start, end) = selection.range();
+            lines += end.row - start.row + usize::from(end.column > 0 || end.row == start.row);
+            bytes += self.buffer().point_to_offset(end).value()
+                - self.buffer().point_to_offset(start).value();
+        }
+        let plural = |count: usize, noun: &str| {
+            format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+        };
+        let size = format!("{}, {}", plural(lines, "line"), plural(bytes, "byte"));
+        Some(match selections.len() {
+            1 => format!("{} selected", size),
+            count => format!("{} ({}) selected", plural(count, "selection"), size),
+        })
+    }
+
+    /// The selected text, a piece of the buffer at a time: each non-empty
+    /// selection, one per line. Copying or saving a large selection streams
+    /// this instead of building the text first
+    pub fn selected_chunks(&self) -> impl Iterator<Item = &str> + '_ {
+        let rope = self.buffer().rope();
+        let ranges: Vec<Range<usize>> = self
+            .selections()
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| {
+                let (start, end) = selection.range();
+                self.buffer().point_to_offset(start).value()
+                    ..self.buffer().point_to_offset(end).value()
+            })
+            .collect();
+        ranges.into_iter().enumerate().flat_map(move |(i, range)| {
+            let separator = (i > 0).then_some("\n");
+            separator
+                .into_iter()
+                .chain(rope.chunks_in(range.start, range.end))
+        })
+    }
+
+    /// `selected_chunks` as one string (for the clipboard), allocated once
+    pub fn selected_text(&self) -> String {
+        let len = self.selected_chunks().map(str::len).sum();
+        let mut text = String::with_capacity(len);
+        text.extend(self.selected_chunks());
+        text
+    }
+
+    /// Delete every selection as one undo step and return their text (as
+    /// `selected_text`); None, changing nothing, without a selection
+    pub fn cut(&mut self) -> Option<String> {
+        let text = self.selected_text();
+        if text.is_empty() {
+            return None;
+        }
+        let edits = self
+            .selections()
+            .into_iter()
+            .map(|selection| {
+                let (start, end) = selection.range();
+                let range = self.buffer().point_to_offset(start).value()
+                    ..self.buffer().point_to_offset(end).value();
+                (range, String::new())
+            })
+            .collect();
+        self.edit_every_selection(edits);
+        Some(text)
+    }
+
+    /// Number of cursors (1 without extra cursors)
+    pub fn cursor_count(&self) -> usize {
+        self.cursors.len() + 1
+    }
+
+    /// Add a cursor at `point`, keeping the current ones
+    pub fn add_cursor(&mut self, point: Point) {
+        self.flush_pending_insert();
+        let mut extras = self.cursors.take();
+        extras.push(Selection::cursor(point));
+        self.set_extra_selections(extras);
+    }
+
+    /// Add a cursor on the line above the topmost cursor (or below the
+    /// bottommost one), at the primary cursor's visual column (tabs
+    /// expanded) or that line's end
+    pub fn add_cursor_vertically(&mut self, above: bool) -> bool {
+        let selections = self.selections();
+        let row = if above {
+            selections
+                .first()
+                .map(|s| s.end.row)
+                .and_then(|row| row.checked_sub(1))
+        } else {
+            selections
+                .last()
+                .map(|s| s.end.row + 1)
+                .filter(|row| *row < self.buffer().line_count())
+        };
+        let Some(row) = row else {
+            return false;
+        };
+        let point = self.buffer().point_at_display_column(
+            row,
+            self.cursor_visual_column(),
+            self.indent_width(),
+        );
+        self.add_cursor(point);
+        true
+    }
+
+    /// Drop every cursor but the primary one. Returns false if there were none
+    pub fn clear_extra_cursors(&mut self) -> bool {
+        let had_extras = !self.cursors.is_empty();
+        self.cursors.clear();
+        had_extras
+    }
+
+    /// Text to look for when selecting occurrences: the primary selection,
+    /// or (selecting it first) the word at the cursor
+    fn occurrence_query(&mut self) -> Option<String> {
+        if self.selection.is_empty() {
+            let (start, end) = self.word_range_at_cursor()?;
+            self.selection = Selection::new(start, end);
+        }
+        let (start, end) = self.selection.range();
+        let start = self.buffer().point_to_offset(start).value();
+        let end = self.buffer().point_to_offset(end).value();
+        Some(self.buffer().slice_bytes(start, end))
+    }
+
+    fn selection_from_range(&self, range: Range<usize>) -> Selection {
+        Selection::new(
+            self.buffer().offset_to_point(Offset(range.start)),
+            self.buffer().offset_to_point(Offset(range.end)),
+        )
+    }
+
+    /// Select the word at the cursor, or with a selection, add the next
+    /// occurrence of the selected text (wrapping around) as a new selection
+    /// that becomes the primary one. Returns false if there is nothing new
+    pub fn select_next_occurrence(&mut self) -> bool {
+        self.flush_pending_insert();
+        if self.selection.is_empty() && self.cursors.is_empty() {
+            return self.occurrence_query().is_some();
+        }
+        let Some(query) = self.occurrence_query() else {
+            return false;
+        };
+
+        let taken: Vec<Range<usize>> = self
+            .selections()
+            .iter()
+            .map(|selection| {
+                let (start, end) = selection.range();
+                self.buffer().point_to_offset(start).value()
+                    ..self.buffer().point_to_offset(end).value()
+            })
+            .collect();
+        let from = self
+            .buffer()
+            .point_to_offset(self.selection.range().1)
+            .value();
+        let Some(found) = search::find_next(self.buffer().rope(), &query, from, |range| {
+            taken
+                .iter()
+                .any(|taken| range.start < taken.end && taken.start < range.end)
+        }) else {
+            return false;
+        };
+
+        let mut extras = self.cursors.take();
+        extras.push(self.selection);
+        self.selection = self.selection_from_range(found);
+        self.set_extra_selections(extras);
+        true
+    }
+
+    /// Select every occurrence of the selected text (or the word at the
+    /// cursor) at once; returns how many there are
+    pub fn select_all_occurrences(&mut self) -> usize {
+        self.flush_pending_insert();
+        let Some(query) = self.occurrence_query() else {
+            return 0;
+        };
+
+        let primary_start = self
+            .buffer()
+            .point_to_offset(self.selection.range().0)
+            .value();
+        let mut selections: Vec<Selection> = Vec::new();
+        for range in search::find_all(self.buffer().rope(), &query) {
+            let selection = self.selection_from_range(range.clone());
+            if range.start == primary_start {
+                self.selection = selection;
+            } else {
+                selections.push(selection);
+            }
+        }
+        self.set_extra_selections(selections);
+        self.cursor_count()
+    }
+
+    /// Rectangular selection between two corners whose columns are screen
+    /// columns (tabs expanded): one selection per row, with the primary one on
+    /// `head`'s row. Rows too short to reach the block are skipped, unless the
+    /// block is zero-width and they get a cursor at their end
+    pub fn select_block(&mut self, anchor: Point, head: Point) {
+        self.flush_pending_insert();
+        let tab_width = self.indent_width();
+        let last_row = self.buffer().line_count().saturating_sub(1);
+        let (anchor_row, head_row) = (anchor.row.min(last_row), head.row.min(last_row));
+        let left = anchor.column.min(head.column);
+        let right = anchor.column.max(head.column);
+
+        let mut primary = None;
+        let mut extras = Vec::new();
+        for row in anchor_row.min(head_row)..=anchor_row.max(head_row) {
+            let line = self.buffer().line(row).unwrap_or_default();
+            let line_width = display_column(&line, line.chars().count(), tab_width);
+            if line_width < left && left < right {
+                continue;
+            }
+            let to_point = |column| Point::new(row, column_at_display(&line, column, tab_width));
+            let selection = Selection::new(to_point(anchor.column), to_point(head.column));
+            if row == head_row {
+                primary = Some(selection);
+            } else {
+                extras.push(selection);
+            }
+        }
+
+        // The head row was too short: its nearest row in the block leads
+        let primary = primary
+            .or_else(|| {
+                if head_row < anchor_row && !extras.is_empty() {
+                    Some(extras.remove(0))
+                } else {
+                    extras.pop()
+                }
+            })
+            .unwrap_or_else(|| {
+                Selection::cursor(Point::new(head_row, self.line_char_len(head_row)))
+            });
+        self.selection = primary;
+        self.set_extra_selections(extras);
+    }
+
+    /// Turn every multi-line selection into one cursor per line it spans: at
+    /// the line's end, or with `select_lines` selecting the part of the line
+    /// it covered. The last line gets the primary cursor. Returns false if no
+    /// selection spans more than one line
+    pub fn split_selection_into_lines(&mut self, select_lines: bool) -> bool {
+        self.flush_pending_insert();
+        let selections = self.selections();
+        if selections.iter().all(|s| s.start.row == s.end.row) {
+            return false;
+        }
+
+        let mut split: Vec<Selection> = Vec::new();
+        for selection in selections {
+            let (start, end) = selection.range();
+            if start.row == end.row {
+                split.push(selection);
+                continue;
+            }
+            // A selection ending at column 0 doesn't cover that line
+            let last_row = if end.column == 0 {
+                end.row - 1
+            } else {
+                end.row
+            };
+            for row in start.row..=last_row {
+                let line_end = Point::new(row, self.line_char_len(row));
+                let row_end = if row == end.row { end } else { line_end };
+                split.push(if !select_lines {
+                    Selection::cursor(row_end)
+                } else if row == start.row {
+                    Selection::new(start, row_end)
+                } else {
+                    Selection::new(Point::new(row, 0), row_end)
+                });
+            }
+        }
+
+        self.selection = split.pop().unwrap_or(self.selection);
+        self.set_extra_selections(split);
+        true
+    }
+
+    /// Pad with spaces before each cursor so all of them reach the same
+    /// visual column, as one undo step. With several cursors on a line the
+    /// first ones on every line are aligned, then the second ones, and so on.
+    /// Returns false if there is only one cursor or they are already aligned
+    pub fn align_cursors(&mut self) -> bool {
+        self.flush_pending_insert();
+        let selections = self.selections();
+        if selections.len() < 2 {
+            return false;
+        }
+
+        // Visual column of each cursor, grouped by row in order
+        let mut rows: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+        for (index, selection) in selections.iter().enumerate() {
+            let head = selection.end;
+            let line = self.buffer().line(head.row).unwrap_or_default();
+            let column = display_column(&line, head.column, self.indent_width());
+            rows.entry(head.row).or_default().push((index, column));
+        }
+
+        let mut padding = vec![0; selections.len()];
+        let mut shifts: BTreeMap<usize, usize> = BTreeMap::new();
+        let most_per_row = rows.values().map(Vec::len).max().unwrap_or(0);
+        for nth in 0..most_per_row {
+            let column_of = |row: &usize, cursors: &Vec<(usize, usize)>| {
+                cursors
+                    .get(nth)
+                    .map(|&(index, column)| (index, column + shifts.get(row).unwrap_or(&0)))
+            };
+            let target = rows
+                .iter()
+                .filter_map(|(row, cursors)| column_of(row, cursors))
+                .map(|(_, column)| column)
+                .max()
+                .unwrap_or(0);
+            let pads: Vec<(usize, usize, usize)> = rows
+                .iter()
+                .filter_map(|(row, cursors)| {
+                    column_of(row, cursors).map(|(index, column)| (*row, index, target - column))
+                })
+                .collect();
+            for (row, index, pad) in pads {
+                padding[index] = pad;
+                *shifts.entry(row).or_default() += pad;
+            }
+        }
+        if padding.iter().all(|&pad| pad == 0) {
+            return false;
+        }
+
+        let edits = selections
+            .iter()
+            .zip(padding)
+            .map(|(selection, pad)| {
+                let offset = self.buffer().point_to_offset(selection.end).value();
+                (offset..offset, " ".repeat(pad))
+            })
+            .collect();
+        self.edit_every_selection(edits);
+        true
+    }
+
+    /// The primary selection followed by the extra ones, as undo history
+    /// records them
+    fn selection_state(&self) -> Vec<Selection> {
+        std::iter::once(self.selection)
+            .chain(self.cursors.selections().iter().copied())
+            .collect()
+    }
+
+    /// Put back selections recorded by `selection_state`
+    fn restore_selections(&mut self, selections: &[Selection]) {
+        let Some((primary, extras)) = selections.split_first() else {
+            return;
+        };
+        self.selection = Selection::new(
+            self.clamp_point(primary.start),
+            self.clamp_point(primary.end),
+        );
+        let extras = extras
+            .iter()
+            .map(|s| Selection::new(self.clamp_point(s.start), self.clamp_point(s.end)))
+            .collect();
+        self.set_extra_selections(extras);
+    }
+
+    /// Replace the extra cursors; any that overlap the primary selection are
+    /// merged into it
+    fn set_extra_selections(&mut self, selections: Vec<Selection>) {
+        let primary = self.selection;
+        let extras = MultiCursor::merge(selections)
+            .into_iter()
+            .filter(|selection| !MultiCursor::overlaps(*selection, primary))
+            .collect();
+        self.cursors.set(extras);
+    }
+
+    /// Run a movement on every cursor. Returns false (doing nothing) if there
+    /// are no extra cursors
+    fn move_every_cursor(&mut self, movement: fn(&mut Self)) -> bool {
+        if self.cursors.is_empty() {
+            return false;
+        }
+        self.flush_pending_insert();
+
+        let primary = self.selection;
+        let mut moved = Vec::with_capacity(self.cursors.len());
+        for selection in self.cursors.take() {
+            self.selection = selection;
+            movement(self);
+            moved.push(self.selection);
+        }
+        self.selection = primary;
+        movement(self);
+        self.set_extra_selections(moved);
+        true
+    }
+
+    /// Replace each selection's range with its text (one entry per
+    /// `selections()`, byte ranges) as a single undo step, leaving a cursor
+    /// after each replacement
+    fn edit_every_selection(&mut self, edits: Vec<(Range<usize>, String)>) {
+        let primary_index = self
+            .selections()
+            .iter()
+            .position(|selection| *selection == self.selection)
+            .unwrap_or(0);
+
+        // Edits from cursors that ran into each other collapse into one
+        let mut kept: Vec<(usize, Range<usize>, String)> = Vec::new();
+        for (index, (range, text)) in edits.into_iter().enumerate() {
+            if kept
+                .last()
+                .is_some_and(|(_, last, _)| range.start < last.end)
+            {
+                continue;
+            }
+            kept.push((index, range, text));
+        }
+        let (Some(first), Some(last)) = (kept.first(), kept.last()) else {
+            return;
+        };
+        let (start, end) = (first.1.start, last.1.end);
+
+        // Cursor offsets relative to `start` once every edit is applied
+        let old_text = self.buffer().slice_bytes(start, end);
+        let mut replacement = String::new();
+        let mut cursors = Vec::with_capacity(kept.len());
+        let mut copied = start;
+        for (index, range, new_text) in &kept {
+            replacement.push_str(&old_text[copied - start..range.start - start]);
+            replacement.push_str(new_text);
+            cursors.push((*index, replacement.len()));
+            copied = range.end;
+        }
+        replacement.push_str(&old_text[copied - start..]);
+        if replacement == old_text {
+            return;
+        }
+
+        // The primary cursor (or the one it merged into) leads the transaction
+        let primary = cursors
+            .iter()
+            .rev()
+            .find(|(index, _)| *index <= primary_index)
+            .map_or(cursors[0].1, |(_, offset)| *offset);
+        let start_point = self.buffer().offset_to_point(Offset(start));
+        let primary_point = Self::point_after_text(start_point, &replacement[..primary]);
+        let edits: Vec<(Range<Offset>, &str)> = kept
+            .iter()
+            .map(|(_, range, text)| (Offset(range.start)..Offset(range.end), text.as_str()))
+            .collect();
+        self.edit_ranges(&edits, Selection::cursor(primary_point));
+
+        let extras = cursors
+            .iter()
+            .map(|(_, offset)| {
+                Selection::cursor(self.buffer().offset_to_point(Offset(start + offset)))
+            })
+            .collect();
+        self.set_extra_selections(extras);
+        let selections_after = self.selection_state();
+        if let Some(transaction) = self.history.last_transaction_mut() {
+            transaction.selections_after = selections_after;
+        }
+    }
+
     /// Get current version (incremented on each edit)
     pub fn version(&self) -> u64 {
         self.version
     }
 
+    /// Check if the buffer has changes that aren't saved
+    pub fn is_dirty(&self) -> bool {
+        self.saved_version != Some(self.version)
+    }
+
+    /// Record that the current content matches what's on disk
+    pub fn mark_saved(&mut self) {
+        self.flush_pending_insert();
+        self.saved_version = Some(self.version);
+        self.emit(EditorEvent::Saved {
+            path: self.file_path.clone(),
+            version: self.version,
+        });
+    }
+
+    /// Flag the buffer as unsaved (e.g. content restored after a hot exit)
+    pub fn mark_dirty(&mut self) {
+        self.saved_version = None;
+    }
+
+    /// Remember the cursor as an edit location (edits on adjacent rows coalesce)
+    fn record_edit_location(&mut self) {
+        let point = self.cursor();
+        let anchor = self.buffer().anchor_at(point, Bias::Right);
+        self.edit_location_index = None;
+
+        let last_row = self
+            .edit_locations
+            .last()
+            .map(|&last| self.buffer().anchor_to_point(last).row);
+        if last_row.is_some_and(|row| row.abs_diff(point.row) <= 1) {
+            self.edit_locations.pop();
+        }
+
+        let buffer = self.history.current();
+        self.edit_locations
+            .retain(|&location| buffer.anchor_to_point(location).row != point.row);
+        self.edit_locations.push(anchor);
+        if self.edit_locations.len() > MAX_EDIT_LOCATIONS {
+            self.edit_locations.remove(0);
+        }
+    }
+
+    /// Recent edit locations in this buffer (oldest first), moved along by
+    /// the edits since
+    pub fn edit_locations(&self) -> Vec<Point> {
+        self.edit_locations
+            .iter()
+            .map(|&anchor| self.buffer().anchor_to_point(anchor))
+            .collect()
+    }
+
+    /// Jump to the previous edit location; repeated calls cycle further back
+    /// and wrap around to the most recent one. Returns false if nothing was edited
+    pub fn go_to_last_edit_location(&mut self) -> bool {
+        if self.edit_locations.is_empty() {
+            return false;
+        }
+        self.flush_pending_insert();
+
+        let newest = self.edit_locations.len() - 1;
+        let newest_point = self.buffer().anchor_to_point(self.edit_locations[newest]);
+        let index = match self.edit_location_index {
+            Some(0) => newest,
+            Some(index) => index - 1,
+            // Already sitting on the latest edit - start one further back
+            None if newest > 0 && newest_point == self.cursor() => newest - 1,
+            None => newest,
+        };
+        self.edit_location_index = Some(index);
+
+        let point = self.buffer().anchor_to_point(self.edit_locations[index]);
+        self.set_cursor(point);
+        true
+    }
+
     /// ✅ Flush pending inserts to history
     fn flush_pending_insert(&mut self) {
         if self.pending_insert.is_empty() {
             return;
         }
 
-        if let Some(start_cursor) = self.pending_start_cursor {
+        if let Some(start_selections) = self.pending_start_selections.take() {
+            let start_cursor = start_selections[0].end;
             let transaction =
-                Transaction::insert(self.pending_insert.clone(), start_cursor, self.cursor());
+                Transaction::insert(self.pending_insert.clone(), start_cursor, self.cursor())
+                    .with_selections(start_selections, self.selection_state());
             
             // Use the saved buffer state (BEFORE pending edits) and current buffer (AFTER pending edits)
             if let Some(before_buffer) = self.pending_start_buffer.take() {
                 let after_buffer = self.buffer().clone();
+                let transaction = transaction.at(before_buffer.point_to_offset(start_cursor).value());
                 self.history.push(*before_buffer, after_buffer, transaction);
             } else {
                 // Fallback: shouldn't happen if logic is right
@@ -108,18 +986,23 @@ impl Editor {
         }
 
         self.pending_insert.clear();
-        self.pending_start_cursor = None;
     }
 
     /// ✅ SIMPLE WORD-BY-WORD: Only batch non-whitespace characters
     /// Whitespace (spaces, tabs, newlines) are applied directly without batching
     pub fn insert(&mut self, text: &str) {
+        if !self.cursors.is_empty() || !self.selection.is_empty() {
+            self.insert_at_every_cursor(text);
+            return;
+        }
+
         let cursor_before = self.cursor();
         let is_whitespace = text.chars().all(|c| c.is_whitespace());
 
         // If it's whitespace, flush pending word and insert directly (no batching for whitespace)
         if is_whitespace {
             self.flush_pending_insert();
+            let selections_before = self.selection_state();
             
             let offset = self.buffer().point_to_offset(cursor_before);
             
@@ -136,29 +1019,52 @@ impl Editor {
                 text.to_string()
             };
 
-            let mut new_buffer = self.buffer().clone();
+            let old_buffer = self.buffer().clone();
+            let mut new_buffer = old_buffer.clone();
             new_buffer.insert(offset, &text_to_insert);
 
             let new_offset = offset.value() + text_to_insert.len();
             let cursor_after = new_buffer.offset_to_point(Offset(new_offset));
 
-            self.history.update_current(new_buffer);
+            // Whitespace right after the last edit joins its undo step;
+            // anywhere else it is a step of its own
+            let joined = !self.history.can_redo()
+                && self.history.last_transaction_mut().is_some_and(|transaction| {
+                    transaction.extend_insert(offset.value(), &text_to_insert)
+                });
+            if joined {
+                self.history.update_current(new_buffer);
+            } else {
+                let transaction =
+                    Transaction::insert(text_to_insert.clone(), cursor_before, cursor_after)
+                        .at(offset.value())
+                        .with_selections(selections_before, Vec::new());
+                self.history.push(old_buffer.clone(), new_buffer, transaction);
+            }
             self.set_cursor(cursor_after);
+            // Redo has to land after the whitespace
+            let selections_after = self.selection_state();
+            if let Some(transaction) = self.history.last_transaction_mut() {
+                transaction.selections_after = selections_after;
+            }
             self.version += 1;
+            self.notify_edit(&old_buffer, offset.value(), "", &text_to_insert);
             self.last_edit_time = Instant::now();
+            self.record_edit_location();
             return;  // Don't batch whitespace
         }
 
         // Non-whitespace: add to pending word batch
         
         // Start new pending batch if needed and save the buffer state BEFORE editing
-        if self.pending_start_cursor.is_none() {
-            self.pending_start_cursor = Some(cursor_before);
+        if self.pending_start_selections.is_none() {
+            self.pending_start_selections = Some(self.selection_state());
             self.pending_start_buffer = Some(Box::new(self.buffer().clone()));  // ✅ SAVE BEFORE STATE
         }
 
         let offset = self.buffer().point_to_offset(cursor_before);
-        let mut new_buffer = self.buffer().clone();
+        let old_buffer = self.buffer().clone();
+        let mut new_buffer = old_buffer.clone();
         new_buffer.insert(offset, text);
 
         let new_offset = offset.value() + text.len();
@@ -167,14 +1073,233 @@ impl Editor {
         self.history.update_current(new_buffer);
         self.set_cursor(cursor_after);
         self.version += 1;
+        self.notify_edit(&old_buffer, offset.value(), "", text);
         self.last_edit_time = Instant::now();
+        self.record_edit_location();
 
         // Add to pending batch (only non-whitespace)
         self.pending_insert.push_str(text);
     }
 
+    /// Type at every cursor (or over a single selection), replacing the
+    /// selections (one undo step per keystroke); newlines take each line's
+    /// auto-indent
+    fn insert_at_every_cursor(&mut self, text: &str) {
+        self.flush_pending_insert();
+        let edits = self
+            .selections()
+            .iter()
+            .map(|selection| {
+                let (start, end) = selection.range();
+                let new_text = if text == "\n" {
+                    let indent = self.indent_calculator.calculate_indent_with_rope(
+                        self.buffer().rope(),
+                        start.row,
+                        self.file_path.as_deref(),
+                    );
+                    format!("\n{}", indent)
+                } else {
+                    text.to_string()
+                };
+                let start = self.buffer().point_to_offset(start).value();
+                let end = self.buffer().point_to_offset(end).value();
+                (start..end, new_text)
+            })
+            .collect();
+        self.edit_every_selection(edits);
+    }
+
+    /// Byte range backspace (`forward` = false) or delete removes at a
+    /// selection: the selected text, or the character beside the cursor
+    fn deletion_range(&self, selection: Selection, forward: bool) -> Range<usize> {
+        let (start, end) = selection.range();
+        let start = self.buffer().point_to_offset(start).value();
+        let end = self.buffer().point_to_offset(end).value();
+        if start < end {
+            return start..end;
+        }
+
+        if forward {
+            start..self.buffer().next_grapheme_boundary(Offset(start)).value()
+        } else {
+            self.buffer().prev_grapheme_boundary(Offset(start)).value()..start
+        }
+    }
+
+    /// Paste over the selection, re-indenting multi-line text so its first
+    /// line sits at the cursor line's indentation and the rest keep their
+    /// indentation relative to it. One undo step.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n");
+        if !text.contains('\n') {
+            self.paste_verbatim(&text);
+            return;
+        }
+
+        let (start, end) = self.selection.range();
+        let line = self.buffer().line(start.row).unwrap_or_default();
+        let prefix: String = line.chars().take(start.column).collect();
+        let tab_width = self.indent_width();
+        let hard_tabs = self.indent_calculator.hard_tabs();
+
+        // Pasting at the start of a line replaces its leading whitespace with
+        // the indentation the line should have; mid-line the first pasted
+        // line goes in as-is and the rest follow the current line
+        let at_line_start = prefix.trim().is_empty();
+        let base = if at_line_start && line.trim().is_empty() && start.row > 0 {
+            self.indent_calculator.calculate_indent_with_rope(
+                self.buffer().rope(),
+                start.row - 1,
+                self.file_path.as_deref(),
+            )
+        } else {
+            line.chars().take_while(|c| c.is_whitespace()).collect()
+        };
+
+        let lines: Vec<&str> = text.split('\n').collect();
+        let indent_cols = |line: &str| {
+            let leading = line.chars().take_while(|c| c.is_whitespace()).count();
+            display_column(line, leading, tab_width)
+        };
+        // The first line is often copied from its first non-blank character,
+        // so it only counts towards the common indent if it has any
+        let reference = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| {
+                !line.trim().is_empty() && (*i > 0 || line.starts_with(char::is_whitespace))
+            })
+            .map(|(_, line)| indent_cols(line))
+            .min()
+            .unwrap_or(0);
+
+        let last = lines.len() - 1;
+        let reindented: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let content = line.trim_start();
+                if i == 0 && !at_line_start {
+                    line.to_string()
+                } else if content.is_empty() && i == last && at_line_start {
+                    // Whole lines pasted: the rest of the line keeps its indent
+                    prefix.clone()
+                } else if content.is_empty() {
+                    String::new()
+                } else {
+                    let extra = indent_cols(line).saturating_sub(reference);
+                    format!(
+                        "{}{}{}",
+                        base,
+                        indent_string(extra, tab_width, hard_tabs),
+                        content
+                    )
+                }
+            })
+            .collect();
+        let new_text = reindented.join("\n");
+
+        let start = if at_line_start {
+            Point::new(start.row, 0)
+        } else {
+            start
+        };
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        let cursor_after = Self::point_after_text(start, &new_text);
+        self.edit_range(
+            start_offset,
+            end_offset,
+            &new_text,
+            Selection::cursor(cursor_after),
+        );
+    }
+
+    /// Paste the way `paste_mode` says. In `Format` mode multi-line pastes of
+    /// up to `FORMAT_PASTE_LIMIT` bytes go through the file's formatter
+    /// (without their shared indentation) first; pastes it can't format, and
+    /// every paste without a `formatter`, are only re-indented
+    pub fn paste_with_mode(&mut self, text: &str, formatter: Option<&Formatter>) {
+        match self.paste_mode() {
+            PasteMode::Verbatim => self.paste_verbatim(text),
+            PasteMode::Reindent => self.paste(text),
+            PasteMode::Format => {
+                let text = text.replace("\r\n", "\n");
+                let formatted = formatter
+                    .zip(self.file_path.as_deref())
+                    .filter(|_| text.contains('\n') && text.len() <= FORMAT_PASTE_LIMIT)
+                    .and_then(|(formatter, path)| {
+                        let snippet = Self::dedent(&text);
+                        formatter
+                            .format_snippet(&snippet, path, FORMAT_PASTE_TIMEOUT)
+                            .ok()
+                    });
+                match formatted {
+                    Some(mut formatted) => {
+                        // Formatters end the text with a newline; keep the paste's
+                        if !text.ends_with('\n') && formatted.ends_with('\n') {
+                            formatted.pop();
+                        }
+                        self.paste(&formatted)
+                    }
+                    None => self.paste(&text),
+                }
+            }
+        }
+    }
+
+    /// `text` without the indentation its non-blank lines share; the first
+    /// line only counts if it's indented, since it's often copied from its
+    /// first non-blank character
+    fn dedent(text: &str) -> String {
+        let shared = text
+            .split('\n')
+            .enumerate()
+            .filter(|(i, line)| {
+                !line.trim().is_empty() && (*i > 0 || line.starts_with(char::is_whitespace))
+            })
+            .map(|(_, line)| &line[..line.len() - line.trim_start().len()])
+            .reduce(|shared, indent| {
+                let len = shared
+                    .char_indices()
+                    .zip(indent.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(shared.len().min(indent.len()), |((i, _), _)| i);
+                &shared[..len]
+            })
+            .unwrap_or("");
+        text.split('\n')
+            .map(|line| line.strip_prefix(shared).unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Paste over the selection exactly as given (one undo step)
+    pub fn paste_verbatim(&mut self, text: &str) {
+        let (start, end) = self.selection.range();
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        let cursor_after = Self::point_after_text(start, text);
+        self.edit_range(
+            start_offset,
+            end_offset,
+            text,
+            Selection::cursor(cursor_after),
+        );
+    }
+
     /// Backspace with immediate history save
     pub fn backspace(&mut self) {
+        if !self.cursors.is_empty() || !self.selection.is_empty() {
+            let edits = self
+                .selections()
+                .into_iter()
+                .map(|selection| (self.deletion_range(selection, false), String::new()))
+                .collect();
+            self.edit_every_selection(edits);
+            return;
+        }
+
         self.flush_pending_insert(); // Flush any pending text inserts
         self.pending_start_buffer = None;  // Clear the saved buffer state
 
@@ -187,7 +1312,7 @@ impl Editor {
         let cursor_offset = self.buffer().point_to_offset(cursor);
 
         if cursor_offset.value() > 0 {
-            let start = Offset(cursor_offset.value() - 1);
+            let start = self.buffer().prev_grapheme_boundary(cursor_offset);
 
             let deleted_text = self
                 .buffer()
@@ -200,24 +1325,38 @@ impl Editor {
 
             let cursor_after = new_buffer.offset_to_point(start);
 
-            let transaction = Transaction::delete(deleted_text, cursor, cursor_after);
-            self.history.push(old_buffer, new_buffer, transaction);
+            let transaction =
+                Transaction::delete(deleted_text.clone(), cursor, cursor_after).at(start.value());
+            self.history
+                .push(old_buffer.clone(), new_buffer, transaction);
 
             self.set_cursor(cursor_after);
             self.version += 1;
+            self.notify_edit(&old_buffer, start.value(), &deleted_text, "");
             self.last_edit_time = Instant::now();
+            self.record_edit_location();
         }
     }
 
     /// Delete with immediate history save
     pub fn delete(&mut self) {
+        if !self.cursors.is_empty() || !self.selection.is_empty() {
+            let edits = self
+                .selections()
+                .into_iter()
+                .map(|selection| (self.deletion_range(selection, true), String::new()))
+                .collect();
+            self.edit_every_selection(edits);
+            return;
+        }
+
         self.flush_pending_insert(); // Flush any pending text inserts
 
         let cursor = self.cursor();
         let cursor_offset = self.buffer().point_to_offset(cursor);
 
         if cursor_offset.value() < self.buffer().len() {
-            let end = Offset(cursor_offset.value() + 1);
+            let end = self.buffer().next_grapheme_boundary(cursor_offset);
 
             let deleted_text = self
                 .buffer()
@@ -228,35 +1367,1271 @@ impl Editor {
             let mut new_buffer = old_buffer.clone();
             new_buffer.delete(cursor_offset, end);
 
-            let transaction = Transaction::delete(deleted_text, cursor, cursor);
-            self.history.push(old_buffer, new_buffer, transaction);
+            let transaction =
+                Transaction::delete(deleted_text.clone(), cursor, cursor).at(cursor_offset.value());
+            self.history
+                .push(old_buffer.clone(), new_buffer, transaction);
 
             self.version += 1;
+            self.notify_edit(&old_buffer, cursor_offset.value(), &deleted_text, "");
             self.last_edit_time = Instant::now();
+            self.record_edit_location();
         }
     }
 
-    /// ✅ Undo - properly handles pending text
-    pub fn undo(&mut self) {
-        // Check if we have pending text (incomplete word without space)
-        if !self.pending_insert.is_empty() {
-            // ✅ FIX: If user has pending text, discard it (don't flush it)
-            // Restore buffer to state BEFORE pending text started
-            if let Some(before_buffer) = self.pending_start_buffer.take() {
-                self.history.update_current((*before_buffer).clone());
-                // Move cursor back to where pending text started
-                if let Some(start_cursor) = self.pending_start_cursor {
-                    self.set_cursor(start_cursor);
-                }
+    /// Characters immediately before and after the cursor on its line
+    fn chars_around_cursor(&self) -> (Option<char>, Option<char>) {
+        let cursor = self.cursor();
+        let line = self.buffer().line(cursor.row).unwrap_or_default();
+        let mut chars = line.chars().skip(cursor.column.saturating_sub(1));
+        let before = if cursor.column > 0 {
+            chars.next()
+        } else {
+            None
+        };
+        (before, chars.next())
+    }
+
+    /// Typed-text entry point with auto-closing pairs:
+    /// - an opener inserts its closer (see `AutoPairs::should_autoclose`)
+    /// - typing the closer that's already next to the cursor steps over it
+    /// - a bracket or quote typed with a selection wraps the selection
+    pub fn insert_with_autopairs(&mut self, text: &str) {
+        if !self.cursors.is_empty() {
+            self.insert(text);
+            return;
+        }
+
+        let mut chars = text.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            self.insert(text);
+            return;
+        };
+
+        let autopairs = self.autopairs();
+        if !self.selection.is_empty() {
+            if let Some(close) = autopairs.closer_for(c) {
+                self.wrap_selection(c, close);
+                return;
+            }
+        }
+
+        let (before, after) = self.chars_around_cursor();
+        if autopairs.is_closer(c) && after == Some(c) {
+            self.move_right();
+        } else if autopairs.should_autoclose(c, before, after) {
+            let close = autopairs.closer_for(c).unwrap_or(c);
+            self.insert(&format!("{}{}", c, close));
+            self.move_left();
+        } else {
+            self.insert(text);
+        }
+    }
+
+    /// Several keystrokes' worth of typed text at once (fast typing or key
+    /// repeat within one frame): runs without pair characters go in as a
+    /// single edit, pair characters one at a time through autopairs
+    pub fn type_text(&mut self, text: &str) {
+        let autopairs = self.autopairs();
+        if !self.cursors.is_empty() || autopairs.pairs().is_empty() {
+            self.insert(text);
+            return;
+        }
+
+        let is_pair_char = |c: char| autopairs.closer_for(c).is_some() || autopairs.is_closer(c);
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (at, c) = rest
+                .char_indices()
+                .find(|(_, c)| is_pair_char(*c))
+                .unwrap_or((rest.len(), ' '));
+            if at > 0 {
+                self.insert(&rest[..at]);
+                rest = &rest[at..];
+            } else {
+                self.insert_with_autopairs(&rest[..c.len_utf8()]);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    /// Surround the selection with a pair, keeping the wrapped text selected
+    fn wrap_selection(&mut self, open: char, close: char) {
+        let (start, end) = self.selection.range();
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        let selected = self
+            .buffer()
+            .slice_bytes(start_offset.value(), end_offset.value());
+
+        // Points on the first row move right past the opener
+        let shift = |p: Point| {
+            if p.row == start.row {
+                Point::new(p.row, p.column + 1)
+            } else {
+                p
+            }
+        };
+        let selection_after =
+            Selection::new(shift(self.selection.start), shift(self.selection.end));
+        self.edit_range(
+            start_offset,
+            end_offset,
+            &format!("{}{}{}", open, selected, close),
+            selection_after,
+        );
+    }
+
+    /// Backspace that removes both halves of an empty pair (`(|)` → `|`)
+    pub fn backspace_with_autopairs(&mut self) {
+        if self.selection.is_empty() && self.cursors.is_empty() {
+            if let (Some(before), Some(after)) = self.chars_around_cursor() {
+                if self.autopairs().is_empty_pair(before, after) {
+                    let cursor = self.cursor();
+                    let offset = self.buffer().point_to_offset(cursor).value();
+                    let start = offset - before.len_utf8();
+                    let end = offset + after.len_utf8();
+                    let cursor_after = Point::new(cursor.row, cursor.column - 1);
+                    self.edit_range(
+                        Offset(start),
+                        Offset(end),
+                        "",
+                        Selection::cursor(cursor_after),
+                    );
+                    return;
+                }
+            }
+        }
+
+        self.backspace();
+    }
+
+    /// Replace a byte range as a single undo transaction, then apply the new selection
+    fn edit_range(
+        &mut self,
+        start: Offset,
+        end: Offset,
+        new_text: &str,
+        selection_after: Selection,
+    ) {
+        self.edit_ranges(&[(start..end, new_text)], selection_after);
+    }
+
+    /// Replace non-overlapping byte ranges (sorted by start) with
+    /// `Buffer::edit` as a single undo transaction, then apply the new
+    /// selection. History and subscribers see one edit spanning them all
+    fn edit_ranges(&mut self, edits: &[(Range<Offset>, &str)], selection_after: Selection) {
+        self.flush_pending_insert();
+        self.pending_start_buffer = None;
+
+        let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+            return;
+        };
+        let (start, end) = (first.0.start, last.0.end);
+        let old_text = self.buffer().slice_bytes(start.value(), end.value());
+        let mut new_text = String::with_capacity(old_text.len());
+        let mut copied = 0;
+        for (range, text) in edits {
+            new_text.push_str(&old_text[copied..range.start.value() - start.value()]);
+            new_text.push_str(text);
+            copied = range.end.value() - start.value();
+        }
+        new_text.push_str(&old_text[copied..]);
+        let new_text = new_text.as_str();
+
+        let old_buffer = self.buffer().clone();
+        let mut new_buffer = old_buffer.clone();
+        new_buffer.edit(edits);
+
+        let selections_before = self.selection_state();
+        let (cursor_before, cursor_after) = (self.cursor(), selection_after.end);
+        let transaction = if new_text.is_empty() {
+            Transaction::delete(old_text.clone(), cursor_before, cursor_after)
+        } else if old_text.is_empty() {
+            Transaction::insert(new_text.to_string(), cursor_before, cursor_after)
+        } else {
+            Transaction::replace(
+                old_text.clone(),
+                new_text.to_string(),
+                cursor_before,
+                cursor_after,
+            )
+        }
+        .at(start.value())
+        .with_selections(selections_before, vec![selection_after]);
+        self.history
+            .push(old_buffer.clone(), new_buffer, transaction);
+
+        self.selection = selection_after;
+        self.cursors.clear();
+        self.version += 1;
+        self.notify_edit(&old_buffer, start.value(), &old_text, new_text);
+        self.last_edit_time = Instant::now();
+        self.record_edit_location();
+    }
+
+    /// Point reached after inserting `text` at `start`
+    fn point_after_text(start: Point, text: &str) -> Point {
+        match text.rfind('\n') {
+            Some(last_newline) => Point::new(
+                start.row + text.matches('\n').count(),
+                text[last_newline + 1..].chars().count(),
+            ),
+            None => Point::new(start.row, start.column + text.chars().count()),
+        }
+    }
+
+    /// Range of the word (see `word_chars`) touching the cursor
+    fn word_range_at_cursor(&self) -> Option<(Point, Point)> {
+        let range = self.buffer().word_at(self.cursor(), self.word_chars())?;
+        Some((
+            self.buffer().offset_to_point(range.start),
+            self.buffer().offset_to_point(range.end),
+        ))
+    }
+
+    /// Change the case of the selection (or the word at the cursor) as one
+    /// undo step, leaving the converted text selected
+    pub fn transform_selection(&mut self, transform: Transform) {
+        self.flush_pending_insert();
+
+        let (start, end) = if self.selection.is_empty() {
+            match self.word_range_at_cursor() {
+                Some(range) => range,
+                None => return,
+            }
+        } else {
+            self.selection.range()
+        };
+
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        let old_text = self
+            .buffer()
+            .slice_bytes(start_offset.value(), end_offset.value());
+        let new_text = transform.apply(&old_text);
+        if new_text == old_text {
+            return;
+        }
+
+        let selection_after = Selection::new(start, Self::point_after_text(start, &new_text));
+        self.edit_range(start_offset, end_offset, &new_text, selection_after);
+    }
+
+    /// Sort the selected lines (the whole buffer if nothing is selected) as
+    /// one undo step, leaving the sorted lines selected
+    pub fn sort_lines(&mut self, options: SortOptions) {
+        self.rewrite_lines(|lines| options.apply(lines));
+    }
+
+    /// Reverse the order of the selected lines (the whole buffer if nothing is selected)
+    pub fn reverse_lines(&mut self) {
+        self.rewrite_lines(|lines| lines.iter().rev().cloned().collect());
+    }
+
+    /// Re-fill the paragraph at the cursor (or the selected lines) to
+    /// `width` columns as one undo step, keeping comment prefixes and list
+    /// indentation. Without a selection the paragraph is the run of
+    /// non-blank lines that are all comments, or all code, like the cursor's.
+    /// Returns the first rewritten row, or None if nothing changed
+    pub fn reflow_paragraph(&mut self, width: usize) -> Option<usize> {
+        self.flush_pending_insert();
+
+        let comment = self.language().and_then(|language| language.line_comment);
+        let line = |row: usize| self.buffer().line(row).unwrap_or_default();
+        let kind = |row: usize| {
+            let line = line(row);
+            let prefix = transform::reflow_prefix(&line, comment);
+            let is_blank = line[prefix.len()..].trim().is_empty();
+            (!is_blank).then(|| prefix.trim().is_empty())
+        };
+
+        let (first, last) = if self.selection.is_empty() {
+            let row = self.selection.end.row;
+            let cursor_kind = kind(row)?;
+            let mut first = row;
+            while first > 0 && kind(first - 1) == Some(cursor_kind) {
+                first -= 1;
+            }
+            let mut last = row;
+            while last + 1 < self.buffer().line_count() && kind(last + 1) == Some(cursor_kind) {
+                last += 1;
+            }
+            (first, last)
+        } else {
+            self.selected_rows()
+        };
+
+        let lines: Vec<String> = (first..=last).map(line).collect();
+        let new_lines = transform::reflow(&lines, width, comment);
+        if new_lines == lines {
+            return None;
+        }
+
+        let last_after = first + new_lines.len().saturating_sub(1);
+        let end_column = new_lines.last().map_or(0, |line| line.chars().count());
+        let selection_after = Selection::cursor(Point::new(last_after, end_column));
+        self.replace_rows(first, last, &new_lines, selection_after);
+        Some(first)
+    }
+
+    /// Replace the selected rows (or every row) with `f(rows)`
+    fn rewrite_lines(&mut self, f: impl FnOnce(&[String]) -> Vec<String>) {
+        self.flush_pending_insert();
+
+        let (first, last) = if self.selection.is_empty() {
+            // Leave the empty line after a trailing newline where it is
+            let last = self.buffer().line_count().saturating_sub(1);
+            if last > 0 && self.line_char_len(last) == 0 {
+                (0, last - 1)
+            } else {
+                (0, last)
+            }
+        } else {
+            self.selected_rows()
+        };
+        let lines: Vec<String> = (first..=last)
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+
+        let new_lines = f(&lines);
+        if new_lines == lines {
+            return;
+        }
+
+        let last_after = first + new_lines.len().saturating_sub(1);
+        let end_column = new_lines.last().map_or(0, |line| line.chars().count());
+        let selection_after =
+            Selection::new(Point::new(first, 0), Point::new(last_after, end_column));
+        self.replace_rows(first, last, &new_lines, selection_after);
+    }
+
+    /// Rows covered by the selection (a selection ending at column 0 excludes that row)
+    fn selected_rows(&self) -> (usize, usize) {
+        let (start, end) = self.selection.range();
+        let end_row = if end.row > start.row && end.column == 0 {
+            end.row - 1
+        } else {
+            end.row
+        };
+        (start.row, end_row)
+    }
+
+    /// Selection moved by a number of rows along with its lines (columns
+    /// unchanged); a point that would land past the last of `line_count_after`
+    /// rows goes to the end of the moved block instead
+    fn selection_shifted(&self, rows: isize, line_count_after: usize) -> Selection {
+        let (_, last) = self.selected_rows();
+        let block_end = Point::new(last.saturating_add_signed(rows), self.line_char_len(last));
+        let shift = |p: Point| {
+            let row = p.row.saturating_add_signed(rows);
+            if row < line_count_after {
+                Point::new(row, p.column)
+            } else {
+                block_end
+            }
+        };
+        Selection::new(shift(self.selection.start), shift(self.selection.end))
+    }
+
+    /// Character length of a line (0 if out of range)
+    fn line_char_len(&self, row: usize) -> usize {
+        self.buffer()
+            .line(row)
+            .map(|line| line.chars().count())
+            .unwrap_or(0)
+    }
+
+    /// Duplicate the current line (or every selected line) below itself
+    pub fn duplicate_line(&mut self) {
+        let (first, last) = self.selected_rows();
+        let buffer = self.buffer();
+        let block_start = buffer.line_start_offset(first);
+        let block_end = buffer.line_end_offset(last);
+        let block = buffer.slice_bytes(block_start.value(), block_end.value());
+
+        let line_count = last - first + 1;
+        let selection_after =
+            self.selection_shifted(line_count as isize, buffer.line_count() + line_count);
+        self.edit_range(
+            block_end,
+            block_end,
+            &format!("\n{}", block),
+            selection_after,
+        );
+    }
+
+    /// Delete the current line (or every selected line)
+    pub fn delete_line(&mut self) {
+        let (first, last) = self.selected_rows();
+        let buffer = self.buffer();
+        let line_count = buffer.line_count();
+        let column = self.cursor().column;
+
+        let (start, end, row_after, row_len_after) = if last + 1 < line_count {
+            // Remove the lines together with their trailing newline
+            (
+                buffer.line_start_offset(first),
+                buffer.line_start_offset(last + 1),
+                first,
+                self.line_char_len(last + 1),
+            )
+        } else if first > 0 {
+            // Last line of the buffer - remove the newline before it instead
+            (
+                buffer.line_end_offset(first - 1),
+                Offset(buffer.len()),
+                first - 1,
+                self.line_char_len(first - 1),
+            )
+        } else {
+            (Offset::zero(), Offset(buffer.len()), 0, 0)
+        };
+
+        let cursor_after = Point::new(row_after, column.min(row_len_after));
+        self.edit_range(start, end, "", Selection::cursor(cursor_after));
+    }
+
+    /// Delete from the cursor to the end of the line (Emacs `kill-line`);
+    /// at the end of a line, join it with the next one
+    pub fn delete_to_line_end(&mut self) {
+        let cursor = self.cursor();
+        let line_len = self.line_char_len(cursor.row);
+        let end = if cursor.column < line_len {
+            Point::new(cursor.row, line_len)
+        } else if cursor.row + 1 < self.buffer().line_count() {
+            Point::new(cursor.row + 1, 0)
+        } else {
+            return;
+        };
+
+        let start_offset = self.buffer().point_to_offset(cursor);
+        let end_offset = self.buffer().point_to_offset(end);
+        self.edit_range(start_offset, end_offset, "", Selection::cursor(cursor));
+    }
+
+    /// Delete from the start of the line to the cursor; at column 0, join
+    /// with the previous line
+    pub fn delete_to_line_start(&mut self) {
+        let cursor = self.cursor();
+        let start = if cursor.column > 0 {
+            Point::new(cursor.row, 0)
+        } else if cursor.row > 0 {
+            Point::new(cursor.row - 1, self.line_char_len(cursor.row - 1))
+        } else {
+            return;
+        };
+
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(cursor);
+        self.edit_range(start_offset, end_offset, "", Selection::cursor(start));
+    }
+
+    /// Swap the characters around the cursor and move past them; at the end
+    /// of a line, swap the last two characters instead
+    pub fn transpose_chars(&mut self) {
+        let cursor = self.cursor();
+        let Some(line) = self.buffer().line(cursor.row) else {
+            return;
+        };
+        let chars: Vec<char> = line.chars().collect();
+        if chars.len() < 2 || cursor.column == 0 {
+            return;
+        }
+
+        let column = cursor.column.min(chars.len() - 1);
+        let (first, second) = (chars[column - 1], chars[column]);
+        let start = Point::new(cursor.row, column - 1);
+        let end = Point::new(cursor.row, column + 1);
+
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        self.edit_range(
+            start_offset,
+            end_offset,
+            &format!("{}{}", second, first),
+            Selection::cursor(end),
+        );
+    }
+
+    /// Swap the word at (or before) the cursor with the next word on the line,
+    /// leaving the cursor after both
+    pub fn transpose_words(&mut self) {
+        let cursor = self.cursor();
+        let Some(line) = self.buffer().line(cursor.row) else {
+            return;
+        };
+        let chars: Vec<char> = line.chars().collect();
+        let is_word = |c: char| is_word_char(c, self.word_chars());
+
+        // Word spans as char ranges
+        let mut words = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if is_word(chars[i]) {
+                let start = i;
+                while i < chars.len() && is_word(chars[i]) {
+                    i += 1;
+                }
+                words.push((start, i));
+            } else {
+                i += 1;
+            }
+        }
+
+        // The first word of the pair is the last one starting at or before the
+        // cursor; at the end of the line, swap the last two words
+        let column = cursor.column.min(chars.len());
+        let Some(mut first) = words.iter().rposition(|(start, _)| *start < column.max(1)) else {
+            return;
+        };
+        if first + 1 >= words.len() {
+            if first == 0 {
+                return;
+            }
+            first -= 1;
+        }
+        let (a, b) = (words[first], words[first + 1]);
+
+        let word = |(start, end): (usize, usize)| chars[start..end].iter().collect::<String>();
+        let between: String = chars[a.1..b.0].iter().collect();
+        let new_text = format!("{}{}{}", word(b), between, word(a));
+
+        let start = Point::new(cursor.row, a.0);
+        let end = Point::new(cursor.row, b.1);
+        let start_offset = self.buffer().point_to_offset(start);
+        let end_offset = self.buffer().point_to_offset(end);
+        self.edit_range(start_offset, end_offset, &new_text, Selection::cursor(end));
+    }
+
+    /// Swap the current line (or selected lines) with the line above
+    pub fn move_line_up(&mut self) {
+        let (first, last) = self.selected_rows();
+        if first == 0 {
+            return;
+        }
+
+        let buffer = self.buffer();
+        let start = buffer.line_start_offset(first - 1);
+        let end = buffer.line_end_offset(last);
+        let above = buffer.slice_bytes(start.value(), buffer.line_end_offset(first - 1).value());
+        let block = buffer.slice_bytes(buffer.line_start_offset(first).value(), end.value());
+
+        let selection_after = self.selection_shifted(-1, buffer.line_count());
+        self.edit_range(
+            start,
+            end,
+            &format!("{}\n{}", block, above),
+            selection_after,
+        );
+    }
+
+    /// Swap the current line (or selected lines) with the line below
+    pub fn move_line_down(&mut self) {
+        let (first, last) = self.selected_rows();
+        if last + 1 >= self.buffer().line_count() {
+            return;
+        }
+
+        let buffer = self.buffer();
+        let start = buffer.line_start_offset(first);
+        let end = buffer.line_end_offset(last + 1);
+        let block = buffer.slice_bytes(start.value(), buffer.line_end_offset(last).value());
+        let below = buffer.slice_bytes(buffer.line_start_offset(last + 1).value(), end.value());
+
+        let selection_after = self.selection_shifted(1, buffer.line_count());
+        self.edit_range(
+            start,
+            end,
+            &format!("{}\n{}", below, block),
+            selection_after,
+        );
+    }
+
+    /// Replace whole rows with new line contents as one transaction
+    fn replace_rows(
+        &mut self,
+        first: usize,
+        last: usize,
+        lines: &[String],
+        selection_after: Selection,
+    ) {
+        let start = self.buffer().line_start_offset(first);
+        let end = self.buffer().line_end_offset(last);
+        self.edit_range(start, end, &lines.join("\n"), selection_after);
+    }
+
+    /// Toggle comments on the current line or all selected lines
+    /// Uses the language's line comment token, falling back to wrapping the lines
+    /// in a block comment for languages that only have block comments
+    pub fn toggle_comment(&mut self) {
+        let Some(language) = self.language() else {
+            return;
+        };
+        let line_comment = language.line_comment;
+        let block_comment = language.block_comment;
+
+        let (first, last) = self.selected_rows();
+        let lines: Vec<String> = (first..=last)
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+
+        if let Some(token) = line_comment {
+            self.toggle_line_comment(first, &lines, token);
+        } else if let Some((open, close)) = block_comment {
+            self.toggle_block_comment(first, &lines, open, close);
+        }
+    }
+
+    fn toggle_line_comment(&mut self, first: usize, lines: &[String], token: &str) {
+        let is_blank = |line: &String| line.trim().is_empty();
+        let all_blank = lines.iter().all(is_blank);
+        let commented = !all_blank
+            && lines
+                .iter()
+                .filter(|line| !is_blank(line))
+                .all(|line| line.trim_start().starts_with(token));
+
+        let indent_of = |line: &String| line.chars().take_while(|c| c.is_whitespace()).count();
+        let min_indent = lines
+            .iter()
+            .filter(|line| all_blank || !is_blank(line))
+            .map(indent_of)
+            .min()
+            .unwrap_or(0);
+
+        // Per-row (column, char delta) used to remap the selection
+        let mut row_edits = Vec::with_capacity(lines.len());
+        let new_lines: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                if !all_blank && is_blank(line) {
+                    row_edits.push(None);
+                    return line.clone();
+                }
+
+                if commented {
+                    let indent = indent_of(line);
+                    let byte_indent = line.len() - line.trim_start().len();
+                    let rest = &line[byte_indent + token.len()..];
+                    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+                    let removed = line.chars().count() - indent - rest.chars().count();
+                    row_edits.push(Some((indent, -(removed as isize))));
+                    format!("{}{}", &line[..byte_indent], rest)
+                } else {
+                    let byte_indent: usize =
+                        line.chars().take(min_indent).map(|c| c.len_utf8()).sum();
+                    let prefix = format!("{} ", token);
+                    row_edits.push(Some((min_indent, prefix.chars().count() as isize)));
+                    format!("{}{}{}", &line[..byte_indent], prefix, &line[byte_indent..])
+                }
+            })
+            .collect();
+
+        let map_point = |point: Point| {
+            let Some(Some((column, delta))) = row_edits.get(point.row.wrapping_sub(first)) else {
+                return point;
+            };
+            let mapped = if *delta >= 0 {
+                if point.column >= *column {
+                    point.column + *delta as usize
+                } else {
+                    point.column
+                }
+            } else {
+                let removed = delta.unsigned_abs();
+                if point.column > *column {
+                    (point.column.saturating_sub(removed)).max(*column)
+                } else {
+                    point.column
+                }
+            };
+            Point::new(point.row, mapped)
+        };
+
+        let selection_after = Selection::new(
+            map_point(self.selection.start),
+            map_point(self.selection.end),
+        );
+        let last = first + lines.len() - 1;
+        self.replace_rows(first, last, &new_lines, selection_after);
+    }
+
+    fn toggle_block_comment(&mut self, first: usize, lines: &[String], open: &str, close: &str) {
+        let text = lines.join("\n");
+        let trimmed = text.trim();
+        let last = first + lines.len() - 1;
+
+        let new_text = if trimmed.len() >= open.len() + close.len()
+            && trimmed.starts_with(open)
+            && trimmed.ends_with(close)
+        {
+            let leading = &text[..text.len() - text.trim_start().len()];
+            let inner = &trimmed[open.len()..trimmed.len() - close.len()];
+            let inner = inner.strip_prefix(' ').unwrap_or(inner);
+            let inner = inner.strip_suffix(' ').unwrap_or(inner);
+            format!("{}{}", leading, inner)
+        } else {
+            let leading = &text[..text.len() - text.trim_start().len()];
+            format!("{}{} {} {}", leading, open, text.trim_start(), close)
+        };
+
+        let new_lines: Vec<String> = new_text.split('\n').map(str::to_string).collect();
+        let clamp = |point: Point| {
+            let len = new_lines
+                .get(point.row.wrapping_sub(first))
+                .map(|line| line.chars().count())
+                .unwrap_or(point.column);
+            Point::new(point.row, point.column.min(len))
+        };
+        let selection_after =
+            Selection::new(clamp(self.selection.start), clamp(self.selection.end));
+        self.replace_rows(first, last, &new_lines, selection_after);
+    }
+
+    /// Tab key at the cursor: a tab character with hard tabs, otherwise spaces
+    /// up to the next tab stop
+    pub fn insert_indent(&mut self) {
+        if self.indent_calculator.hard_tabs() {
+            self.insert("\t");
+            return;
+        }
+
+        let cursor = self.cursor();
+        let line = self.buffer().line(cursor.row).unwrap_or_default();
+        let width = self.indent_width();
+        let column = display_column(&line, cursor.column, width);
+        self.insert(&" ".repeat(width - column % width));
+    }
+
+    /// Indent every selected line (or the current line) by one indent level
+    /// Blank lines are left alone; points at column 0 stay put so whole-line
+    /// selections remain whole-line
+    pub fn indent_selection(&mut self) {
+        let (first, last) = self.selected_rows();
+        let indent = self.indent_calculator.indent_unit();
+        let width = indent.chars().count();
+
+        let lines: Vec<String> = (first..=last)
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            return;
+        }
+
+        let new_lines: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.clone()
+                } else {
+                    format!("{}{}", indent, line)
+                }
+            })
+            .collect();
+
+        let shift = |point: Point| {
+            let indented = point.row >= first
+                && point.row <= last
+                && !lines[point.row - first].trim().is_empty();
+            if indented && point.column > 0 {
+                Point::new(point.row, point.column + width)
+            } else {
+                point
+            }
+        };
+        let selection_after =
+            Selection::new(shift(self.selection.start), shift(self.selection.end));
+        self.replace_rows(first, last, &new_lines, selection_after);
+    }
+
+    /// Remove one indent level (a tab or up to indent-width spaces) from every
+    /// selected line (or the current line)
+    pub fn outdent_selection(&mut self) {
+        let (first, last) = self.selected_rows();
+        let width = self.indent_calculator.indent_width();
+
+        let lines: Vec<String> = (first..=last)
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+
+        let removed: Vec<usize> = lines
+            .iter()
+            .map(|line| {
+                if line.starts_with('\t') {
+                    1
+                } else {
+                    line.chars().take(width).take_while(|c| *c == ' ').count()
+                }
+            })
+            .collect();
+        if removed.iter().all(|n| *n == 0) {
+            return;
+        }
+
+        let new_lines: Vec<String> = lines
+            .iter()
+            .zip(&removed)
+            .map(|(line, n)| line[*n..].to_string())
+            .collect();
+
+        let shift = |point: Point| {
+            if point.row >= first && point.row <= last {
+                Point::new(
+                    point.row,
+                    point.column.saturating_sub(removed[point.row - first]),
+                )
+            } else {
+                point
+            }
+        };
+        let selection_after =
+            Selection::new(shift(self.selection.start), shift(self.selection.end));
+        self.replace_rows(first, last, &new_lines, selection_after);
+    }
+
+    /// Move the cursor to the bracket matching the one under (or just before) it
+    pub fn jump_to_matching_bracket(&mut self) -> bool {
+        self.flush_pending_insert();
+
+        let text = self.text();
+        let offset = self.buffer().point_to_offset(self.cursor()).value();
+        let target = text_objects::matching_bracket(&text, offset).or_else(|| {
+            offset
+                .checked_sub(1)
+                .and_then(|before| text_objects::matching_bracket(&text, before))
+        });
+
+        match target {
+            Some(target) => {
+                let point = self.buffer().offset_to_point(Offset(target));
+                self.set_cursor(point);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Grow the selection to the smallest syntax node that encloses more
+    /// than it. Returns false without a grammar or at the whole file
+    pub fn expand_selection(&mut self) -> bool {
+        self.flush_pending_insert();
+        // A selection changed since the last expansion starts a new stack
+        if self
+            .selection_expansions
+            .last()
+            .is_some_and(|(_, after)| *after != self.selection)
+        {
+            self.selection_expansions.clear();
+        }
+
+        let (start, end) = self.selection.range();
+        let start = self.buffer().point_to_offset(start).value();
+        let end = self.buffer().point_to_offset(end).value();
+        let Some(node) = self.enclosing_syntax_node(start..end) else {
+            return false;
+        };
+
+        let before = self.selection;
+        let after = Selection::new(
+            self.buffer().offset_to_point(Offset(node.start)),
+            self.buffer().offset_to_point(Offset(node.end)),
+        );
+        self.set_selection(after);
+        self.selection_expansions.push((before, after));
+        true
+    }
+
+    /// Undo the last `expand_selection`. Returns false if the selection
+    /// wasn't expanded (or has changed since)
+    pub fn shrink_selection(&mut self) -> bool {
+        match self.selection_expansions.pop() {
+            Some((before, after)) if after == self.selection => {
+                self.set_selection(before);
+                true
+            }
+            _ => {
+                self.selection_expansions.clear();
+                false
+            }
+        }
+    }
+
+    /// Byte range of the smallest syntax node enclosing more than `range`
+    #[cfg(feature = "syntax")]
+    fn enclosing_syntax_node(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let tree = self.syntax_tree(&self.text())?;
+        let mut node = tree
+            .root_node()
+            .descendant_for_byte_range(range.start, range.end);
+        while let Some(current) = node {
+            if current.start_byte() < range.start || current.end_byte() > range.end {
+                break;
+            }
+            node = current.parent();
+        }
+        node.map(|node| node.byte_range())
+    }
+
+    /// Without the `syntax` feature there are no syntax nodes
+    #[cfg(not(feature = "syntax"))]
+    fn enclosing_syntax_node(&self, _range: Range<usize>) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Parse tree of `text` when the buffer has a grammar
+    #[cfg(feature = "syntax")]
+    fn syntax_tree(&self, text: &str) -> Option<Tree> {
+        let language = self.language()?;
+        let mut parser = self.language_registry.create_parser(language);
+        parser.parse(text, None)
+    }
+
+    /// Bracket the "to bracket" commands run to: the match of the bracket
+    /// under (or just before) the cursor, otherwise the closer of the
+    /// enclosing pair. Brackets in strings and comments are skipped when
+    /// there's a grammar
+    fn bracket_target(&self, text: &str, offset: usize) -> Option<usize> {
+        #[cfg(feature = "syntax")]
+        let tree = self.syntax_tree(text);
+        let matching = |at: usize| {
+            #[cfg(feature = "syntax")]
+            if let Some(tree) = &tree {
+                return text_objects::matching_bracket_in_tree(tree, at);
+            }
+            text_objects::matching_bracket(text, at)
+        };
+        let enclosing = || {
+            #[cfg(feature = "syntax")]
+            if let Some(tree) = &tree {
+                return text_objects::enclosing_brackets_in_tree(tree, offset);
+            }
+            text_objects::enclosing_brackets(text, offset)
+        };
+        matching(offset)
+            .or_else(|| offset.checked_sub(1).and_then(matching))
+            .or_else(|| Some(enclosing()?.outer.end - 1))
+    }
+
+    /// Byte range from the cursor to its bracket target, with or without
+    /// the bracket itself
+    fn to_bracket_range(&self, inclusive: bool) -> Option<Range<usize>> {
+        let text = self.text();
+        let offset = self.buffer().point_to_offset(self.cursor()).value();
+        let target = self.bracket_target(&text, offset)?;
+        Some(match (target >= offset, inclusive) {
+            (true, true) => offset..target + 1,
+            (true, false) => offset..target,
+            (false, true) => target..offset,
+            (false, false) => target + 1..offset,
+        })
+    }
+
+    /// Select from the cursor to the matching (or enclosing closing) bracket;
+    /// `inclusive` takes the bracket too. Returns false if there's none
+    pub fn select_to_bracket(&mut self, inclusive: bool) -> bool {
+        self.flush_pending_insert();
+        let Some(range) = self.to_bracket_range(inclusive) else {
+            return false;
+        };
+
+        // Keep the anchor at the cursor so the selection grows towards the bracket
+        let cursor = self.buffer().point_to_offset(self.cursor()).value();
+        let far = if range.start == cursor {
+            range.end
+        } else {
+            range.start
+        };
+        let far = self.buffer().offset_to_point(Offset(far));
+        self.selection = Selection::new(self.cursor(), far);
+        true
+    }
+
+    /// Delete from the cursor to the matching (or enclosing closing) bracket
+    /// as one undo step; `inclusive` deletes the bracket too
+    pub fn delete_to_bracket(&mut self, inclusive: bool) -> bool {
+        let Some(range) = self.to_bracket_range(inclusive) else {
+            return false;
+        };
+        if range.is_empty() {
+            return false;
+        }
+        let start = self.buffer().offset_to_point(Offset(range.start));
+        self.edit_range(
+            Offset(range.start),
+            Offset(range.end),
+            "",
+            Selection::cursor(start),
+        );
+        true
+    }
+
+    /// Remove the innermost brackets or quotes around the cursor, keeping
+    /// what's between them (one undo step). Returns false if there are none
+    pub fn unwrap_surrounding(&mut self) -> bool {
+        self.flush_pending_insert();
+        let text = self.text();
+        let offset = self.buffer().point_to_offset(self.cursor()).value();
+        #[cfg(feature = "syntax")]
+        let brackets = match self.syntax_tree(&text) {
+            Some(tree) => text_objects::enclosing_brackets_in_tree(&tree, offset),
+            None => text_objects::enclosing_brackets(&text, offset),
+        };
+        #[cfg(not(feature = "syntax"))]
+        let brackets = text_objects::enclosing_brackets(&text, offset);
+        let quotes = self.text_object_range(TextObject::Quotes);
+        let Some(range) = [brackets, quotes]
+            .into_iter()
+            .flatten()
+            .min_by_key(|range| range.outer.len())
+        else {
+            return false;
+        };
+
+        // The cursor stays on the same character of the kept text
+        let inner = text[range.inner.clone()].to_string();
+        let cursor = offset
+            .clamp(range.inner.start, range.inner.end)
+            .saturating_sub(range.inner.start - range.outer.start);
+        let start = self.buffer().offset_to_point(Offset(range.outer.start));
+        let cursor_after = Self::point_after_text(start, &inner[..cursor - range.outer.start]);
+        self.edit_range(
+            Offset(range.outer.start),
+            Offset(range.outer.end),
+            &inner,
+            Selection::cursor(cursor_after),
+        );
+        true
+    }
+
+    /// Put the cursor at a clicked point (clamped to the text), or with
+    /// `extend` (Shift+click) move the selection's head there instead
+    pub fn click_at(&mut self, point: Point, extend: bool) {
+        if extend {
+            self.select_to(point);
+        } else {
+            self.flush_pending_insert();
+            let point = self.clamp_point(point);
+            self.set_cursor(point);
+        }
+    }
+
+    /// Move the selection's head to `point` (clamped), keeping its anchor -
+    /// what dragging with the mouse does
+    pub fn select_to(&mut self, point: Point) {
+        self.flush_pending_insert();
+        let point = self.clamp_point(point);
+        self.set_selection(Selection::new(self.selection.start, point));
+    }
+
+    /// Select whole lines `anchor_row` through `head_row` (either order),
+    /// the last one's newline included, with the head on `head_row`'s side -
+    /// what clicking and dragging over line numbers does
+    pub fn select_lines(&mut self, anchor_row: usize, head_row: usize) {
+        self.flush_pending_insert();
+        let last_row = self.line_count().saturating_sub(1);
+        let (anchor_row, head_row) = (anchor_row.min(last_row), head_row.min(last_row));
+        let line_end = |row: usize| {
+            if row < last_row {
+                Point::new(row + 1, 0)
+            } else {
+                Point::new(row, self.line_char_len(row))
+            }
+        };
+        let selection = if head_row >= anchor_row {
+            Selection::new(Point::new(anchor_row, 0), line_end(head_row))
+        } else {
+            Selection::new(line_end(anchor_row), Point::new(head_row, 0))
+        };
+        self.set_selection(selection);
+    }
+
+    /// Move the selected text to `target` (drag-and-drop) as one undo step,
+    /// leaving it selected there. Returns false if nothing is selected or the
+    /// target is inside the selection
+    pub fn move_selection_to(&mut self, target: Point) -> bool {
+        self.flush_pending_insert();
+        let (start, end) = self.selection.range();
+        let target = self.clamp_point(target);
+        if start == end || (start..=end).contains(&target) {
+            return false;
+        }
+
+        let buffer = self.buffer();
+        let [start_offset, end_offset, target_offset] =
+            [start, end, target].map(|point| buffer.point_to_offset(point));
+        let text = buffer.slice_bytes(start_offset.value(), end_offset.value());
+        // Rewrite the span from the selection to the target in one edit, so
+        // the delete and insert undo together
+        let (span_start, span_end, new_text, moved_start) = if target < start {
+            let between = buffer.slice_bytes(target_offset.value(), start_offset.value());
+            (target_offset, end_offset, text.clone() + &between, target)
+        } else {
+            let between = buffer.slice_bytes(end_offset.value(), target_offset.value());
+            let moved_start = Self::point_after_text(start, &between);
+            (start_offset, target_offset, between + &text, moved_start)
+        };
+
+        let moved_end = Self::point_after_text(moved_start, &text);
+        self.edit_range(
+            span_start,
+            span_end,
+            &new_text,
+            Selection::new(moved_start, moved_end),
+        );
+        true
+    }
+
+    /// Nearest point that exists in the buffer
+    fn clamp_point(&self, point: Point) -> Point {
+        let row = point.row.min(self.buffer().line_count().saturating_sub(1));
+        Point::new(row, point.column.min(self.line_char_len(row)))
+    }
+
+    /// Select the word (or whitespace / punctuation run) at a point, as a
+    /// double-click does; on an empty line this just moves the cursor there
+    pub fn select_word_at(&mut self, point: Point) {
+        self.flush_pending_insert();
+
+        let range = self.buffer().word_range_at(point, self.word_chars());
+        let start = self.buffer().offset_to_point(range.start);
+        let end = self.buffer().offset_to_point(range.end);
+        self.selection = Selection::new(start, end);
+    }
+
+    /// Select the inside (or, with `around`, the whole) of the text object at
+    /// the cursor. Returns false if the cursor isn't in one
+    pub fn select_text_object(&mut self, object: TextObject, around: bool) -> bool {
+        self.flush_pending_insert();
+
+        let Some(range) = self.text_object_range(object) else {
+            return false;
+        };
+        let range = if around { range.outer } else { range.inner };
+
+        let start = self.buffer().offset_to_point(Offset(range.start));
+        let end = self.buffer().offset_to_point(Offset(range.end));
+        self.selection = Selection::new(start, end);
+        true
+    }
+
+    /// Byte ranges of a text object around the cursor
+    pub fn text_object_range(&self, object: TextObject) -> Option<TextObjectRange> {
+        let cursor = self.cursor();
+        let offset = self.buffer().point_to_offset(cursor).value();
+
+        match object {
+            TextObject::Word => {
+                let inner = self.buffer().word_range_at(cursor, self.word_chars());
+                if inner.is_empty() {
+                    return None;
+                }
+                let (start, end) = (inner.start.value(), inner.end.value());
+                let line_start = self.buffer().line_start_offset(cursor.row).value();
+                let line_end = self.buffer().line_end_offset(cursor.row).value();
+                let line = self.buffer().slice_bytes(line_start, line_end);
+                let is_blank = |c: char| c == ' ' || c == '\t';
+
+                // Around a word takes the spaces after it, or before it at line end
+                let after = line[end - line_start..].len()
+                    - line[end - line_start..].trim_start_matches(is_blank).len();
+                let before = line[..start - line_start].len()
+                    - line[..start - line_start].trim_end_matches(is_blank).len();
+                let outer =
+                    if after > 0 || line[start - line_start..end - line_start].trim().is_empty() {
+                        start..end + after
+                    } else {
+                        start - before..end
+                    };
+                Some(TextObjectRange {
+                    outer,
+                    inner: start..end,
+                })
+            }
+            TextObject::Paragraph => {
+                let inner = self.buffer().paragraph_range_at(cursor);
+                let last_row = self.buffer().offset_to_point(inner.end).row;
+
+                // Around a paragraph takes the blank lines after it
+                let outer_end = if last_row + 1 < self.buffer().line_count() {
+                    let gap = self
+                        .buffer()
+                        .paragraph_range_at(Point::new(last_row + 1, 0));
+                    let gap_end_row = self.buffer().offset_to_point(gap.end).row;
+                    if gap_end_row + 1 < self.buffer().line_count() {
+                        self.buffer().line_start_offset(gap_end_row + 1)
+                    } else {
+                        gap.end
+                    }
+                } else {
+                    inner.end
+                };
+                Some(TextObjectRange {
+                    outer: inner.start.value()..outer_end.value(),
+                    inner: inner.start.value()..inner.end.value(),
+                })
+            }
+            TextObject::Brackets => {
+                let outer = self.buffer().bracket_pair_range_at(cursor)?;
+                let (start, end) = (outer.start.value(), outer.end.value());
+                Some(TextObjectRange {
+                    outer: start..end,
+                    inner: start + 1..end - 1,
+                })
+            }
+            TextObject::Quotes => {
+                #[cfg(feature = "syntax")]
+                if let Some(language) = self.language() {
+                    let text = self.text();
+                    let mut parser = self.language_registry.create_parser(language);
+                    if let Some(tree) = parser.parse(&text, None) {
+                        return text_objects::enclosing_string_node(&tree, &text, offset);
+                    }
+                }
+
+                // No grammar - pair quotes on the cursor line
+                let line_start = self.buffer().line_start_offset(cursor.row).value();
+                let line = self.buffer().line(cursor.row)?;
+                let range = text_objects::enclosing_quotes_in_line(&line, offset - line_start)?;
+                Some(TextObjectRange {
+                    outer: range.outer.start + line_start..range.outer.end + line_start,
+                    inner: range.inner.start + line_start..range.inner.end + line_start,
+                })
+            }
+        }
+    }
+
+    /// ✅ Undo - properly handles pending text
+    pub fn undo(&mut self) {
+        // Check if we have pending text (incomplete word without space)
+        if !self.pending_insert.is_empty() {
+            // ✅ FIX: If user has pending text, discard it (don't flush it)
+            // Restore buffer to state BEFORE pending text started
+            let old_buffer = self.buffer().clone();
+            let mut span = None;
+            if let Some(before_buffer) = self.pending_start_buffer.take() {
+                self.history.update_current((*before_buffer).clone());
+                // Put back the selections from before the pending text
+                if let Some(start_selections) = self.pending_start_selections.take() {
+                    let start = before_buffer.point_to_offset(start_selections[0].end).value();
+                    span = Some((start, start + self.pending_insert.len(), start));
+                    self.restore_selections(&start_selections);
+                }
             }
             self.pending_insert.clear();
-            self.pending_start_cursor = None;
+            self.pending_start_selections = None;
             self.version += 1;
+            self.notify_span(&old_buffer, span);
         } else {
             // No pending text - normal undo: pop from history
+            let old_buffer = self.buffer().clone();
             if let Some(transaction) = self.history.undo() {
-                self.set_cursor(transaction.cursor_before);
+                self.restore_selections(&transaction.selections_before);
                 self.version += 1;
+                let span = transaction
+                    .changed_range()
+                    .map(|(start, old_end, new_end)| (start, new_end, old_end));
+                self.notify_span(&old_buffer, span);
             }
         }
     }
@@ -265,14 +2640,441 @@ impl Editor {
     pub fn redo(&mut self) {
         // Clear any pending insert before redo
         self.pending_insert.clear();
-        self.pending_start_cursor = None;
+        self.pending_start_selections = None;
         self.pending_start_buffer = None;
 
+        let old_buffer = self.buffer().clone();
         if let Some(transaction) = self.history.redo() {
-            // Restore cursor to the state AFTER the redone transaction
-            self.set_cursor(transaction.cursor_after);
+            // Restore the selections AFTER the redone transaction
+            self.restore_selections(&transaction.selections_after);
             self.version += 1;
+            self.notify_span(&old_buffer, transaction.changed_range());
+        }
+    }
+
+    /// Receive the `Edited` events of the `observe` stream, unwrapped: an
+    /// `EditEvent` for every change to the text (typing, edits, undo/redo).
+    /// A receiver that is dropped unsubscribes itself
+    pub fn subscribe(&mut self) -> Receiver<EditEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.observers.push(Observer::Edits(sender));
+        receiver
+    }
+
+    /// Receive an `EditorEvent` for every file opened into this editor, edit,
+    /// cursor move and save; a receiver that is dropped stops observing
+    pub fn observe(&mut self) -> Receiver<EditorEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.observers.push(Observer::Events(sender));
+        receiver
+    }
+
+    /// Swap in `editor` (a file just opened, or new untitled text), which
+    /// takes over this editor's observers and subscribers and tells
+    /// observers with `Opened`
+    pub fn replace(&mut self, mut editor: Editor) {
+        editor.observers = std::mem::take(&mut self.observers);
+        editor.observed_selection = editor.selection;
+        *self = editor;
+        self.emit(EditorEvent::Opened {
+            path: self.file_path.clone(),
+            version: self.version,
+        });
+    }
+
+    /// Tell observers where the primary selection is if it moved since the
+    /// last call. Front-ends call this once per input they handle, so a
+    /// burst of movement (or the cursor-by-cursor steps of a multi-cursor
+    /// edit) is one event
+    pub fn flush_events(&mut self) {
+        if self.selection == self.observed_selection {
+            return;
+        }
+        self.observed_selection = self.selection;
+        self.emit(EditorEvent::CursorMoved {
+            selection: self.selection,
+            version: self.version,
+        });
+    }
+
+    fn emit(&mut self, event: EditorEvent) {
+        self.observers.retain(|observer| observer.send(&event));
+    }
+
+    /// Whether anything needs `EditEvent`s: observers (subscribers
+    /// included), diagnostics to shift or a wrap map to update
+    fn tracks_edits(&self) -> bool {
+        !self.observers.is_empty()
+            || !self.diagnostics.is_empty()
+            || !self.edit_locations.is_empty()
+            || self.wrap_map.is_some()
+    }
+
+    /// Tell subscribers that `old_text` at byte `offset` of `old_buffer` became
+    /// `new_text` (the current buffer); diagnostics, edit locations and
+    /// wrapping follow it
+    fn notify_edit(&mut self, old_buffer: &Buffer, offset: usize, old_text: &str, new_text: &str) {
+        // Only line breaks in or around the edit (or a BOM at the start) can
+        // change the format
+        let rope = self.history.current().rope();
+        let before = offset.checked_sub(1).and_then(|at| rope.byte_at(at));
+        let after = rope.byte_at(offset + new_text.len());
+        self.text_format.apply_edit(before, old_text, new_text, after);
+        if offset < BOM.len_utf8() {
+            self.text_format.bom = rope
+                .chunks()
+                .find(|chunk| !chunk.as_str().is_empty())
+                .is_some_and(|chunk| chunk.as_str().starts_with(BOM));
+        }
+
+        if !self.tracks_edits() {
+            return;
+        }
+
+        let old_end = offset + old_text.len();
+        let event = EditEvent {
+            old_range: offset..old_end,
+            old_text: old_text.to_string(),
+            new_text: new_text.to_string(),
+            start: old_buffer.offset_to_point(Offset(offset)),
+            old_end: old_buffer.offset_to_point(Offset(old_end)),
+            new_end: self
+                .buffer()
+                .offset_to_point(Offset(offset + new_text.len())),
+            version: self.version,
+        };
+        self.diagnostics.apply_edit(&event);
+        for location in &mut self.edit_locations {
+            location.apply_edit(&event.old_range, new_text.len());
+        }
+        if let Some(mut map) = self.wrap_map.take() {
+            map.apply_edit(&event, self.buffer());
+            self.wrap_map = Some(map);
+        }
+        self.emit(EditorEvent::Edited(event));
+    }
+
+    /// Notify subscribers of the byte span `(start, old_end, new_end)` a
+    /// transaction changed in `old_buffer`; one without a span (a snapshot)
+    /// falls back to diffing the buffers
+    fn notify_span(&mut self, old_buffer: &Buffer, span: Option<(usize, usize, usize)>) {
+        let Some((start, old_end, new_end)) = span else {
+            self.notify_replaced(old_buffer);
+            return;
+        };
+        if start == old_end && start == new_end {
+            return;
+        }
+        let old_text = old_buffer.slice_bytes(start, old_end);
+        let new_text = self.buffer().slice_bytes(start, new_end);
+        self.notify_edit(old_buffer, start, &old_text, &new_text);
+    }
+
+    /// Notify subscribers of a whole-buffer swap nothing recorded the span
+    /// of, as the smallest edit that explains it
+    fn notify_replaced(&mut self, old_buffer: &Buffer) {
+        self.text_format = Self::detect_text_format(self.buffer());
+        if !self.tracks_edits() {
+            return;
+        }
+
+        let (old, new) = (old_buffer.to_string(), self.text());
+        let (start, old_end, new_end) = changed_range(&old, &new);
+        if start == old_end && start == new_end {
+            return;
+        }
+        self.notify_edit(
+            old_buffer,
+            start,
+            &old[start..old_end],
+            &new[start..new_end],
+        );
+    }
+
+    /// Replace the diagnostics shown for this buffer (byte ranges into the
+    /// current text)
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = DiagnosticSet::new(diagnostics);
+    }
+
+    pub fn diagnostics(&self) -> &DiagnosticSet {
+        &self.diagnostics
+    }
+
+    /// First diagnostic starting on `row` - the one shown at the end of the line
+    pub fn diagnostic_on_row(&self, row: usize) -> Option<&Diagnostic> {
+        if self.diagnostics.is_empty() || row >= self.line_count() {
+            return None;
+        }
+        let start = self.buffer().line_start_offset(row).value();
+        let end = if row + 1 < self.line_count() {
+            self.buffer().line_start_offset(row + 1).value()
+        } else {
+            self.buffer().len() + 1
+        };
+        self.diagnostics.first_in(start..end)
+    }
+
+    /// Replace the diagnostics with the syntax errors tree-sitter finds
+    /// (none without a grammar) and warnings about a byte order mark or
+    /// mixed line endings; returns how many there are
+    pub fn check_syntax(&mut self) -> usize {
+        let text = self.text();
+        #[cfg(feature = "syntax")]
+        let mut found = self
+            .language()
+            .and_then(|language| {
+                let mut parser = self.language_registry.create_parser(language);
+                parser.parse(&text, None)
+            })
+            .map(|tree| diagnostics::syntax_diagnostics(&tree))
+            .unwrap_or_default();
+        #[cfg(not(feature = "syntax"))]
+        let mut found = Vec::new();
+        found.extend(line_endings::format_diagnostics(&text));
+        self.set_diagnostics(found);
+        self.diagnostics.len()
+    }
+
+    /// Move the cursor to the next diagnostic (wrapping around) and return it
+    pub fn go_to_next_problem(&mut self) -> Option<Diagnostic> {
+        self.flush_pending_insert();
+        let offset = self.buffer().point_to_offset(self.cursor()).value();
+        let diagnostic = self.diagnostics.next_after(offset).cloned()?;
+        self.go_to_diagnostic(&diagnostic);
+        Some(diagnostic)
+    }
+
+    /// Move the cursor to the previous diagnostic (wrapping around) and return it
+    pub fn go_to_prev_problem(&mut self) -> Option<Diagnostic> {
+        self.flush_pending_insert();
+        let offset = self.buffer().point_to_offset(self.cursor()).value();
+        let diagnostic = self.diagnostics.prev_before(offset).cloned()?;
+        self.go_to_diagnostic(&diagnostic);
+        Some(diagnostic)
+    }
+
+    fn go_to_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        let offset = diagnostic.range.start.min(self.buffer().len());
+        let point = self.buffer().offset_to_point(Offset(offset));
+        self.set_cursor(point);
+    }
+
+    /// Merge conflict regions in the buffer, in order. Lines are only
+    /// collected and parsed once the rope's chunks turn up a marker
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        if self.buffer().rope().find("<<<<<<<", 0).is_none() {
+            return Vec::new();
+        }
+        conflicts::find_conflicts(&self.buffer().lines())
+    }
+
+    /// Move the cursor to the next (or previous) conflict's `<<<<<<<` line,
+    /// wrapping around, and return it
+    pub fn go_to_conflict(&mut self, forward: bool) -> Option<Conflict> {
+        self.flush_pending_insert();
+        let conflicts = self.conflicts();
+        let row = self.cursor().row;
+        let conflict = if forward {
+            conflicts
+                .iter()
+                .find(|conflict| conflict.start > row)
+                .or(conflicts.first())
+        } else {
+            conflicts
+                .iter()
+                .rev()
+                .find(|conflict| conflict.start < row)
+                .or(conflicts.last())
+        }
+        .cloned()?;
+        self.set_cursor(Point::new(conflict.start, 0));
+        Some(conflict)
+    }
+
+    /// Replace the conflict around `row` with the side(s) `resolution` keeps,
+    /// as one undo step. Returns false if `row` isn't in a conflict
+    pub fn resolve_conflict(&mut self, row: usize, resolution: ConflictResolution) -> bool {
+        self.flush_pending_insert();
+        let Some(conflict) = self
+            .conflicts()
+            .into_iter()
+            .find(|conflict| conflict.contains_row(row))
+        else {
+            return false;
+        };
+
+        let kept: Vec<String> = conflict
+            .kept_rows(resolution)
+            .into_iter()
+            .map(|row| self.buffer().line(row).unwrap_or_default())
+            .collect();
+        let is_last_line = conflict.end + 1 >= self.buffer().line_count();
+        let (start, end, text) = if !is_last_line {
+            let text: String = kept.iter().map(|line| format!("{}\n", line)).collect();
+            let end = self.buffer().line_start_offset(conflict.end + 1);
+            (self.buffer().line_start_offset(conflict.start), end, text)
+        } else if kept.is_empty() && conflict.start > 0 {
+            // Take the newline before the conflict with it
+            let start = self.buffer().line_end_offset(conflict.start - 1);
+            (
+                start,
+                self.buffer().line_end_offset(conflict.end),
+                String::new(),
+            )
+        } else {
+            let start = self.buffer().line_start_offset(conflict.start);
+            (
+                start,
+                self.buffer().line_end_offset(conflict.end),
+                kept.join("\n"),
+            )
+        };
+
+        let cursor_row = if kept.is_empty() && is_last_line {
+            conflict.start.saturating_sub(1)
+        } else {
+            conflict.start
+        };
+        let selection_after = Selection::cursor(Point::new(cursor_row, 0));
+        self.edit_range(start, end, &text, selection_after);
+        true
+    }
+
+    /// Text, parse tree and cursor surroundings for code action providers
+    pub fn code_action_context(&self) -> CodeActionContext {
+        let text = self.text();
+        let cursor = self.cursor();
+        let language = self
+            .language()
+            .map_or(LanguageId::Unknown, |config| config.id);
+        let line_start = self.buffer().line_start_offset(cursor.row).value();
+        let line_end = line_start + self.buffer().line(cursor.row).unwrap_or_default().len();
+
+        CodeActionContext {
+            language,
+            #[cfg(feature = "syntax")]
+            tree: self.syntax_tree(&text),
+            cursor: self.buffer().point_to_offset(cursor).value(),
+            line: line_start..line_end,
+            diagnostics: self
+                .diagnostic_on_row(cursor.row)
+                .cloned()
+                .into_iter()
+                .collect(),
+            text,
+        }
+    }
+
+    /// Apply a code action's edits as one undo step, keeping the cursor on
+    /// the same text. Returns false if the action has no edits
+    pub fn apply_code_action(&mut self, action: &CodeAction) -> bool {
+        self.apply_edits(&action.edits)
+    }
+
+    /// Sort, group and dedupe the file's import blocks in one undo step;
+    /// returns false if there is no grammar or nothing to change
+    #[cfg(feature = "syntax")]
+    pub fn organize_imports(&mut self) -> bool {
+        let Some(language) = self.language() else {
+            return false;
+        };
+        let id = language.id;
+        let text = self.text();
+        let mut parser = self.language_registry.create_parser(language);
+        let Some(tree) = parser.parse(&text, None) else {
+            return false;
+        };
+
+        let edits: Vec<TextEdit> = imports::organize_imports(&tree, &text, id)
+            .into_iter()
+            .map(|(range, new_text)| TextEdit::new(range, new_text))
+            .collect();
+        self.apply_edits(&edits)
+    }
+
+    /// Without the `syntax` feature there is no grammar to find imports with
+    #[cfg(not(feature = "syntax"))]
+    pub fn organize_imports(&mut self) -> bool {
+        false
+    }
+
+    /// Whether the text starts with a byte order mark and how its lines end
+    pub fn text_format(&self) -> TextFormat {
+        self.text_format
+    }
+
+    /// Scan every chunk of `buffer` for its `TextFormat`
+    fn detect_text_format(buffer: &Buffer) -> TextFormat {
+        let chunks: Vec<_> = buffer.rope().chunks().collect();
+        TextFormat::detect_chunks(chunks.iter().map(|chunk| chunk.as_str()))
+    }
+
+    /// Remove a leading byte order mark as one undo step; returns false if
+    /// there is none
+    pub fn remove_bom(&mut self) -> bool {
+        match line_endings::strip_bom(&self.buffer().slice_bytes(0, 3)) {
+            Some(edit) => self.apply_edits(&[edit]),
+            None => false,
+        }
+    }
+
+    /// Convert every line ending to the one most lines use, as one undo
+    /// step; returns false if they already agree
+    pub fn unify_line_endings(&mut self) -> bool {
+        let ending = self.text_format().line_ending();
+        self.convert_line_endings(ending)
+    }
+
+    /// Convert every line ending to `ending` (e.g. the project's, before a
+    /// save) as one undo step; returns false if there was nothing to convert
+    pub fn convert_line_endings(&mut self, ending: LineEnding) -> bool {
+        self.apply_edits(&line_endings::unify_line_endings(&self.text(), ending))
+    }
+
+    /// Apply non-overlapping edits (byte ranges into the current text) as one
+    /// undo step, keeping the cursor on the same text
+    pub fn apply_edits(&mut self, edits: &[TextEdit]) -> bool {
+        let mut edits: Vec<&TextEdit> = edits.iter().collect();
+        edits.sort_by_key(|edit| edit.range.start);
+        let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+            return false;
+        };
+        let (start, end) = (first.range.start, last.range.end);
+
+        let text = self.text();
+        let cursor = self.buffer().point_to_offset(self.cursor()).value();
+        let mut replacement = String::new();
+        let mut new_cursor = cursor;
+        let mut copied = start;
+        for edit in &edits {
+            replacement.push_str(&text[copied..edit.range.start]);
+            if cursor >= edit.range.end {
+                new_cursor = (new_cursor + edit.new_text.len()) - edit.range.len();
+            } else if cursor > edit.range.start {
+                new_cursor = start + replacement.len();
+            }
+            replacement.push_str(&edit.new_text);
+            copied = edit.range.end;
         }
+        replacement.push_str(&text[copied..end]);
+
+        let new_text = format!("{}{}{}", &text[..start], replacement, &text[end..]);
+        let point = Self::point_after_text(Point::zero(), &new_text[..new_cursor]);
+        let edits: Vec<(Range<Offset>, &str)> = edits
+            .iter()
+            .map(|edit| {
+                let range = Offset(edit.range.start)..Offset(edit.range.end);
+                (range, edit.new_text.as_str())
+            })
+            .collect();
+        self.edit_ranges(&edits, Selection::cursor(point));
+        true
+    }
+
+    /// The most recent undoable edit
+    pub fn last_transaction(&self) -> Option<&Transaction> {
+        self.history.last_transaction()
     }
 
     /// Check if can undo
@@ -288,86 +3090,258 @@ impl Editor {
 
     /// Move cursor left
     pub fn move_left(&mut self) {
+        if self.move_every_cursor(Self::move_left) {
+            return;
+        }
         self.flush_pending_insert(); // Flush on cursor movement
 
-        let cursor = self.cursor();
-
-        if cursor.column > 0 {
-            self.set_cursor(Point::new(cursor.row, cursor.column - 1));
-        } else if cursor.row > 0 {
-            if let Some(prev_line) = self.buffer().line(cursor.row - 1) {
-                self.set_cursor(Point::new(cursor.row - 1, prev_line.len()));
-            }
-        }
+        // A whole grapheme cluster (emoji, combining marks) at a time
+        let buffer = self.buffer();
+        let offset = buffer.prev_grapheme_boundary(buffer.point_to_offset(self.cursor()));
+        self.set_cursor(buffer.offset_to_point(offset));
     }
 
     /// Move cursor right
     pub fn move_right(&mut self) {
+        if self.move_every_cursor(Self::move_right) {
+            return;
+        }
+        self.flush_pending_insert(); // Flush on cursor movement
+
+        let buffer = self.buffer();
+        let offset = buffer.next_grapheme_boundary(buffer.point_to_offset(self.cursor()));
+        self.set_cursor(buffer.offset_to_point(offset));
+    }
+
+    /// Move to the start of the word before the cursor (see
+    /// `Buffer::prev_word_boundary`)
+    pub fn move_to_previous_word(&mut self) {
+        if self.move_every_cursor(Self::move_to_previous_word) {
+            return;
+        }
+        self.flush_pending_insert(); // Flush on cursor movement
+
+        let buffer = self.buffer();
+        let offset =
+            buffer.prev_word_boundary(buffer.point_to_offset(self.cursor()), self.word_chars());
+        self.set_cursor(buffer.offset_to_point(offset));
+    }
+
+    /// Move to the end of the word after the cursor
+    pub fn move_to_next_word(&mut self) {
+        if self.move_every_cursor(Self::move_to_next_word) {
+            return;
+        }
         self.flush_pending_insert(); // Flush on cursor movement
 
+        let buffer = self.buffer();
+        let offset =
+            buffer.next_word_boundary(buffer.point_to_offset(self.cursor()), self.word_chars());
+        self.set_cursor(buffer.offset_to_point(offset));
+    }
+
+    /// Screen column vertical movement aims for: the remembered goal while the
+    /// cursor stays where the last vertical move left it, otherwise the
+    /// current column (tabs expanded)
+    fn goal_column(&self) -> usize {
+        let cursor = self.cursor();
+        match self.goal_column {
+            Some((goal, at)) if at == cursor => goal,
+            _ => self.buffer().display_column(cursor, self.indent_width()),
+        }
+    }
+
+    /// Move to another row, landing on the goal screen column or that row's end
+    fn move_vertically_to(&mut self, row: usize) {
+        let goal = self.goal_column();
+        let point = self
+            .buffer()
+            .point_at_display_column(row, goal, self.indent_width());
+        self.set_cursor(point);
+        self.goal_column = Some((goal, point));
+    }
+
+    /// With soft wrap: move to another display row, keeping the screen column
+    /// within the wrapped row. Returns false without soft wrap
+    fn move_by_display_row(&mut self, up: bool) -> bool {
+        let Some(map) = &self.wrap_map else {
+            return false;
+        };
         let cursor = self.cursor();
+        let display = map.to_display_point(cursor);
+        let target = if up {
+            display.row.checked_sub(1)
+        } else {
+            Some(display.row + 1).filter(|&row| row < map.display_row_count())
+        };
+        let Some(target) = target else {
+            return true;
+        };
 
-        if let Some(current_line) = self.buffer().line(cursor.row) {
-            if cursor.column < current_line.len() {
-                self.set_cursor(Point::new(cursor.row, cursor.column + 1));
-            } else if cursor.row + 1 < self.buffer().line_count() {
-                self.set_cursor(Point::new(cursor.row + 1, 0));
+        let tab_width = self.indent_width();
+        let segment_text = |row: usize, range: Range<usize>| -> String {
+            let line = self.buffer().line(row).unwrap_or_default();
+            line.chars().skip(range.start).take(range.len()).collect()
+        };
+        let goal = match self.goal_column {
+            Some((goal, at)) if at == cursor => goal,
+            _ => {
+                let (row, range) = map.segment_at(display.row);
+                display_column(&segment_text(row, range), display.column, tab_width)
             }
-        }
+        };
+        let (row, range) = map.segment_at(target);
+        let column = column_at_display(&segment_text(row, range), goal, tab_width);
+        let point = map.to_point(DisplayPoint::new(target, column));
+        self.set_cursor(point);
+        self.goal_column = Some((goal, point));
+        true
     }
 
-    /// Move cursor up
+    /// Move cursor up (by display row when soft wrap is on)
     pub fn move_up(&mut self) {
+        if self.move_every_cursor(Self::move_up) {
+            return;
+        }
         self.flush_pending_insert(); // Flush on cursor movement
 
         let cursor = self.cursor();
 
+        if self.move_by_display_row(true) {
+            return;
+        }
         if cursor.row > 0 {
-            let new_row = cursor.row - 1;
-            let column = if let Some(line) = self.buffer().line(new_row) {
-                cursor.column.min(line.len())
-            } else {
-                0
-            };
-            self.set_cursor(Point::new(new_row, column));
+            self.move_vertically_to(cursor.row - 1);
         }
     }
 
-    /// Move cursor down
+    /// Move cursor down (by display row when soft wrap is on)
     pub fn move_down(&mut self) {
+        if self.move_every_cursor(Self::move_down) {
+            return;
+        }
         self.flush_pending_insert(); // Flush on cursor movement
 
         let cursor = self.cursor();
 
+        if self.move_by_display_row(false) {
+            return;
+        }
         if cursor.row + 1 < self.buffer().line_count() {
-            let new_row = cursor.row + 1;
-            let column = if let Some(line) = self.buffer().line(new_row) {
-                cursor.column.min(line.len())
-            } else {
-                0
-            };
-            self.set_cursor(Point::new(new_row, column));
+            self.move_vertically_to(cursor.row + 1);
         }
     }
 
     /// Move cursor to start of line
     pub fn move_to_line_start(&mut self) {
+        if self.move_every_cursor(Self::move_to_line_start) {
+            return;
+        }
         self.flush_pending_insert();
 
         let cursor = self.cursor();
         self.set_cursor(Point::new(cursor.row, 0));
     }
 
+    /// Smart Home: jump to the first non-whitespace character, or to column 0
+    /// when already there
+    pub fn move_to_line_indent_start(&mut self) {
+        if self.move_every_cursor(Self::move_to_line_indent_start) {
+            return;
+        }
+        self.flush_pending_insert();
+
+        let cursor = self.cursor();
+        let indent = self.buffer().line_indent_len(cursor.row);
+        let column = if cursor.column == indent { 0 } else { indent };
+        self.set_cursor(Point::new(cursor.row, column));
+    }
+
     /// Move cursor to end of line
     pub fn move_to_line_end(&mut self) {
+        if self.move_every_cursor(Self::move_to_line_end) {
+            return;
+        }
         self.flush_pending_insert();
 
         let cursor = self.cursor();
         if let Some(line) = self.buffer().line(cursor.row) {
-            self.set_cursor(Point::new(cursor.row, line.len()));
+            let point = Point::new(cursor.row, line.chars().count());
+            self.set_cursor(point);
+            // Keep hugging line ends on the following vertical moves
+            self.goal_column = Some((usize::MAX, point));
         }
     }
 
+    /// Move to the blank line before the paragraph (vim `{`), skipping any
+    /// blank lines the cursor is on; the first line if there is none
+    pub fn move_to_previous_paragraph(&mut self) {
+        if self.move_every_cursor(Self::move_to_previous_paragraph) {
+            return;
+        }
+        self.flush_pending_insert();
+
+        let mut row = self.cursor().row;
+        while row > 0 && self.is_blank_line(row) {
+            row -= 1;
+        }
+        while row > 0 && !self.is_blank_line(row) {
+            row -= 1;
+        }
+        self.set_cursor(Point::new(row, 0));
+    }
+
+    /// Move to the blank line after the paragraph (vim `}`), skipping any
+    /// blank lines the cursor is on; the end of the last line if there is none
+    pub fn move_to_next_paragraph(&mut self) {
+        if self.move_every_cursor(Self::move_to_next_paragraph) {
+            return;
+        }
+        self.flush_pending_insert();
+
+        let last = self.buffer().line_count().saturating_sub(1);
+        let mut row = self.cursor().row;
+        while row < last && self.is_blank_line(row) {
+            row += 1;
+        }
+        while row < last && !self.is_blank_line(row) {
+            row += 1;
+        }
+        let column = if self.is_blank_line(row) {
+            0
+        } else {
+            self.line_char_len(row)
+        };
+        self.set_cursor(Point::new(row, column));
+    }
+
+    fn is_blank_line(&self, row: usize) -> bool {
+        self.buffer()
+            .line(row)
+            .is_none_or(|line| line.trim().is_empty())
+    }
+
+    /// Move cursor to a line, keeping the column where the line allows it
+    pub fn move_to_line(&mut self, row: usize) {
+        self.flush_pending_insert();
+
+        let row = row.min(self.buffer().line_count().saturating_sub(1));
+        let column = if let Some(line) = self.buffer().line(row) {
+            self.cursor().column.min(line.chars().count())
+        } else {
+            0
+        };
+        self.set_cursor(Point::new(row, column));
+    }
+
+    /// Move to `row` like `move_to_line`, remembering the position jumped
+    /// from so `go_to_last_edit_location` leads back to it
+    pub fn jump_to_line(&mut self, row: usize) {
+        self.flush_pending_insert();
+        self.record_edit_location();
+        self.move_to_line(row);
+    }
+
     /// Get text content
     pub fn text(&self) -> String {
         self.buffer().to_string()
@@ -378,36 +3352,147 @@ impl Editor {
         self.buffer().line_count()
     }
 
-    /// Replace entire buffer content (used for formatting)
+    /// Replace entire buffer content as one undo step, recorded as the
+    /// changed hunks (see `set_undo_hunk_limit`)
     pub fn replace_all(&mut self, new_text: &str) {
         self.flush_pending_insert();
 
         let old_cursor = self.cursor();
         let old_buffer = self.buffer().clone();
-        let new_buffer = Buffer::from_text(new_text);
+        let old_text = self.text();
+        let hunks: Vec<Hunk> = diff_text(&old_text, new_text)
+            .into_iter()
+            .map(|(old, new)| Hunk {
+                offset: old.start,
+                old_text: old_text[old].to_string(),
+                new_text: new_text[new].to_string(),
+            })
+            .collect();
+
+        // Unchanged chunks stay shared with the old buffer kept for undo
+        let edits: Vec<(Range<Offset>, &str)> = hunks
+            .iter()
+            .map(|hunk| {
+                let range = Offset(hunk.offset)..Offset(hunk.offset + hunk.old_text.len());
+                (range, hunk.new_text.as_str())
+            })
+            .collect();
+        let mut new_buffer = old_buffer.clone();
+        new_buffer.edit(&edits);
 
         let new_cursor = if old_cursor.row < new_buffer.line_count() {
             if let Some(line) = new_buffer.line(old_cursor.row) {
-                Point::new(old_cursor.row, old_cursor.column.min(line.len()))
+                Point::new(old_cursor.row, old_cursor.column.min(line.chars().count()))
             } else {
                 Point::zero()
             }
         } else {
             let last_row = new_buffer.line_count().saturating_sub(1);
             if let Some(last_line) = new_buffer.line(last_row) {
-                Point::new(last_row, last_line.len())
+                Point::new(last_row, last_line.chars().count())
             } else {
                 Point::zero()
             }
         };
 
-        let old_text = self.text();
+        let span = Some(Hunk::span(&hunks));
         let transaction =
-            Transaction::replace(old_text, new_text.to_string(), old_cursor, new_cursor);
+            Transaction::replace_hunks(hunks, self.undo_hunk_limit, old_cursor, new_cursor);
 
-        self.history.push(old_buffer, new_buffer, transaction);
+        self.history
+            .push(old_buffer.clone(), new_buffer, transaction);
         self.set_cursor(new_cursor);
         self.version += 1;
+        self.notify_span(&old_buffer, span);
+    }
+
+    /// Replace the whole text as one undo step per changed hunk
+    ///
+    /// Hunks far from the cursor are applied first, so undo reverts the hunks
+    /// near the cursor first; the cursor is carried through every hunk like an
+    /// anchor instead of being clamped against a whole-file replace.
+    pub fn replace_all_by_hunks(&mut self, new_text: &str) {
+        self.flush_pending_insert();
+
+        let old_text = self.text();
+        let old_lines: Vec<&str> = old_text.split('\n').collect();
+        let new_lines: Vec<&str> = new_text.split('\n').collect();
+        let hunks = diff_lines(&old_lines, &new_lines);
+
+        let cursor_row = self.cursor().row;
+        let distance = |hunk: &LineHunk| {
+            if cursor_row < hunk.old.start {
+                hunk.old.start - cursor_row
+            } else {
+                cursor_row.saturating_sub(hunk.old.end.saturating_sub(1))
+            }
+        };
+        let mut order: Vec<usize> = (0..hunks.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(distance(&hunks[i])));
+
+        let mut applied = vec![false; hunks.len()];
+        for i in order {
+            // Rows move by the line delta of hunks already applied above this one
+            let shift: isize = hunks[..i]
+                .iter()
+                .zip(&applied)
+                .filter(|(_, applied)| **applied)
+                .map(|(hunk, _)| hunk.line_delta())
+                .sum();
+            let hunk = &hunks[i];
+            let row = hunk.old.start.saturating_add_signed(shift);
+            self.replace_line_block(row, hunk.old.len(), &new_lines[hunk.new.clone()]);
+            applied[i] = true;
+        }
+    }
+
+    /// Replace `count` rows starting at `row` with `lines` (either may be
+    /// empty) as one transaction, mapping the cursor through the change
+    fn replace_line_block(&mut self, row: usize, count: usize, lines: &[&str]) {
+        let buffer = self.buffer();
+        let line_count = buffer.line_count();
+        let joined = lines.join("\n");
+
+        let (start, end, text) = if count == 0 {
+            if row < line_count {
+                let at = buffer.line_start_offset(row);
+                (at, at, format!("{}\n", joined))
+            } else {
+                let at = Offset(buffer.len());
+                (at, at, format!("\n{}", joined))
+            }
+        } else if !lines.is_empty() {
+            let start = buffer.line_start_offset(row);
+            let end = buffer.line_end_offset(row + count - 1);
+            (start, end, joined)
+        } else if row + count < line_count {
+            let start = buffer.line_start_offset(row);
+            (start, buffer.line_start_offset(row + count), String::new())
+        } else if row > 0 {
+            let start = buffer.line_end_offset(row - 1);
+            (start, Offset(buffer.len()), String::new())
+        } else {
+            (Offset::zero(), Offset(buffer.len()), String::new())
+        };
+
+        let cursor = self.cursor();
+        let cursor_after = if cursor.row < row {
+            cursor
+        } else if cursor.row >= row + count {
+            let delta = lines.len() as isize - count as isize;
+            Point::new(cursor.row.saturating_add_signed(delta), cursor.column)
+        } else if let Some(last) = lines.len().checked_sub(1) {
+            let new_row = row + (cursor.row - row).min(last);
+            let line_len = lines[new_row - row].chars().count();
+            Point::new(new_row, cursor.column.min(line_len))
+        } else if row + count < line_count {
+            Point::new(row, 0)
+        } else {
+            let prev = row.saturating_sub(1);
+            Point::new(prev, self.line_char_len(prev))
+        };
+
+        self.edit_range(start, end, &text, Selection::cursor(cursor_after));
     }
 
     /// Format the buffer using provided formatter
@@ -416,24 +3501,30 @@ impl Editor {
         formatter: &crate::formatter::Formatter,
         file_path: Option<&Path>,
     ) -> Result<(), String> {
-        let current_text = self.text();
-        let had_trailing_newline = current_text.ends_with('\n');
-
-        match formatter.format_text(&current_text, file_path) {
-            Ok(mut formatted_text) => {
-                // ✅ FIX: If original didn't have trailing newline but formatter added one, remove it
-                if !had_trailing_newline && formatted_text.ends_with('\n') {
-                    formatted_text.pop();
-                }
-                
-                if formatted_text != current_text {
-                    self.replace_all(&formatted_text);
-                }
+        match formatter.format_text(&self.text(), file_path) {
+            Ok(formatted_text) => {
+                self.apply_formatted(formatted_text);
                 Ok(())
             }
             Err(e) => Err(format!("Format failed: {:?}", e)),
         }
     }
+
+    /// Replace the buffer with a formatter's output, as minimal hunks so
+    /// cursors and undo history survive
+    pub fn apply_formatted(&mut self, mut formatted_text: String) {
+        let current_text = self.text();
+        let had_trailing_newline = current_text.ends_with('\n');
+
+        // ✅ FIX: If original didn't have trailing newline but formatter added one, remove it
+        if !had_trailing_newline && formatted_text.ends_with('\n') {
+            formatted_text.pop();
+        }
+
+        if formatted_text != current_text {
+            self.replace_all_by_hunks(&formatted_text);
+        }
+    }
 }
 
 impl Default for Editor {
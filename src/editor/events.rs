@@ -0,0 +1,104 @@
+use super::selection::Selection;
+use crate::buffer::Point;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+
+/// One change to the buffer, sent to `Editor::observe` receivers as
+/// `EditorEvent::Edited` and to `Editor::subscribe` receivers as is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditEvent {
+    /// Byte range that was replaced, in the text before the edit
+    pub old_range: Range<usize>,
+    pub old_text: String,
+    pub new_text: String,
+    /// Where the edit starts, and where the old and new text end
+    pub start: Point,
+    pub old_end: Point,
+    pub new_end: Point,
+    /// Editor version after the edit
+    pub version: u64,
+}
+
+impl EditEvent {
+    /// Byte range of the inserted text, in the text after the edit
+    pub fn new_range(&self) -> Range<usize> {
+        self.old_range.start..self.old_range.start + self.new_text.len()
+    }
+
+    /// Rows added (positive) or removed (negative) by the edit
+    pub fn row_delta(&self) -> isize {
+        self.new_end.row as isize - self.old_end.row as isize
+    }
+
+    /// Bytes added (positive) or removed (negative) by the edit
+    pub fn byte_delta(&self) -> isize {
+        self.new_text.len() as isize - self.old_text.len() as isize
+    }
+}
+
+/// Something that happened in an editor, sent in order to `Editor::observe`
+/// receivers: one stream for plugins, language servers, the git gutter and
+/// autosave to follow instead of each polling `Editor::version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorEvent {
+    /// A file, or untitled text with no path, replaced the editor's contents
+    /// (see `Editor::replace`)
+    Opened { path: Option<PathBuf>, version: u64 },
+    /// The text changed
+    Edited(EditEvent),
+    /// The primary selection moved; reported once per `Editor::flush_events`
+    /// however many steps it took
+    CursorMoved { selection: Selection, version: u64 },
+    /// The text at `version` was written to `path`
+    Saved { path: Option<PathBuf>, version: u64 },
+}
+
+/// A receiver of an editor's `EditorEvent` stream: all of it
+/// (`Editor::observe`), or only its edits (`Editor::subscribe`)
+#[derive(Debug, Clone)]
+pub(crate) enum Observer {
+    Events(Sender<EditorEvent>),
+    Edits(Sender<EditEvent>),
+}
+
+impl Observer {
+    /// Pass `event` on if this observer wants it; false once its receiver
+    /// has been dropped
+    pub(crate) fn send(&self, event: &EditorEvent) -> bool {
+        match (self, event) {
+            (Observer::Events(sender), _) => sender.send(event.clone()).is_ok(),
+            (Observer::Edits(sender), EditorEvent::Edited(edit)) => {
+                sender.send(edit.clone()).is_ok()
+            }
+            (Observer::Edits(_), _) => true,
+        }
+    }
+}
+
+/// Smallest byte range that differs between `old` and `new`:
+/// `(start, old_end, new_end)`, on character boundaries
+pub fn changed_range(old: &str, new: &str) -> (usize, usize, usize) {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+
+    (prefix, old.len() - suffix, new.len() - suffix)
+}
@@ -0,0 +1,148 @@
+use super::code_actions::TextEdit;
+use super::diagnostics::{Diagnostic, Severity};
+
+/// UTF-8 byte order mark
+pub const BOM: char = '\u{feff}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Status bar label
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+}
+
+/// Whether a text starts with a byte order mark and how its lines end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextFormat {
+    pub bom: bool,
+    /// Lines ending in a bare `\n`
+    pub lf: usize,
+    /// Lines ending in `\r\n`
+    pub crlf: usize,
+}
+
+impl TextFormat {
+    pub fn detect(text: &str) -> Self {
+        Self::detect_chunks([text])
+    }
+
+    /// `detect` over text in pieces (a rope's chunks), which may split a
+    /// `\r\n` between them
+    pub fn detect_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut format = Self::default();
+        let mut at_start = true;
+        let mut after_cr = false;
+        for chunk in chunks.into_iter().filter(|chunk| !chunk.is_empty()) {
+            format.bom |= at_start && chunk.starts_with(BOM);
+            at_start = false;
+            let split_crlf = after_cr && chunk.starts_with('\n');
+            let crlf = chunk.matches("\r\n").count() + usize::from(split_crlf);
+            format.crlf += crlf;
+            format.lf += chunk.matches('\n').count() - crlf;
+            after_cr = chunk.ends_with('\r');
+        }
+        format
+    }
+
+    /// Update the line counts for `old_text` becoming `new_text` between the
+    /// bytes `before` and `after`, which a `\r\n` can pair up across
+    pub fn apply_edit(
+        &mut self,
+        before: Option<u8>,
+        old_text: &str,
+        new_text: &str,
+        after: Option<u8>,
+    ) {
+        let cr = if before == Some(b'\r') { "\r" } else { "" };
+        let lf = if after == Some(b'\n') { "\n" } else { "" };
+        let old = Self::detect_chunks([cr, old_text, lf]);
+        let new = Self::detect_chunks([cr, new_text, lf]);
+        self.lf = (self.lf + new.lf).saturating_sub(old.lf);
+        self.crlf = (self.crlf + new.crlf).saturating_sub(old.crlf);
+    }
+
+    /// Both kinds of line ending appear
+    pub fn is_mixed(&self) -> bool {
+        self.lf > 0 && self.crlf > 0
+    }
+
+    /// The ending most lines use (LF on a tie, or with no line breaks)
+    pub fn line_ending(&self) -> LineEnding {
+        if self.crlf > self.lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// A BOM or mixed line endings: what the warnings and fixes are about
+    pub fn has_problems(&self) -> bool {
+        self.bom || self.is_mixed()
+    }
+}
+
+/// Edit removing a leading byte order mark
+pub fn strip_bom(text: &str) -> Option<TextEdit> {
+    text.starts_with(BOM)
+        .then(|| TextEdit::new(0..BOM.len_utf8(), ""))
+}
+
+/// Edits turning every line ending that isn't `ending` into it
+pub fn unify_line_endings(text: &str, ending: LineEnding) -> Vec<TextEdit> {
+    text.match_indices('\n')
+        .filter_map(|(newline, _)| {
+            let crlf = text[..newline].ends_with('\r');
+            match (ending, crlf) {
+                (LineEnding::Lf, true) => Some(TextEdit::new(newline - 1..newline + 1, "\n")),
+                (LineEnding::CrLf, false) => Some(TextEdit::new(newline..newline + 1, "\r\n")),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Warnings for a byte order mark and for the first line ending that
+/// differs from the rest of the file
+pub fn format_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let format = TextFormat::detect(text);
+    let mut diagnostics = Vec::new();
+    if format.bom {
+        diagnostics.push(Diagnostic::new(
+            0..BOM.len_utf8(),
+            Severity::Warning,
+            "File starts with a byte order mark",
+        ));
+    }
+    if format.is_mixed() {
+        let ending = format.line_ending();
+        if let Some(edit) = unify_line_endings(text, ending).first() {
+            diagnostics.push(Diagnostic::new(
+                edit.range.clone(),
+                Severity::Warning,
+                format!(
+                    "Mixed line endings ({} LF, {} CRLF); most lines use {}",
+                    format.lf,
+                    format.crlf,
+                    ending.label()
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
@@ -1,7 +1,26 @@
+pub mod autopairs;
+pub mod code_actions;
+pub mod conflicts;
+pub mod diagnostics;
 pub mod editor;
+pub mod events;
+pub mod line_endings;
 pub mod multi_cursor;
+pub mod search;
 pub mod selection;
+pub mod text_objects;
+pub mod transform;
 
+pub use autopairs::AutoPairs;
+pub use code_actions::{
+    CodeAction, CodeActionContext, CodeActionKind, CodeActionProvider, CodeActions, TextEdit,
+};
+pub use conflicts::{Conflict, ConflictResolution};
+pub use diagnostics::{Diagnostic, DiagnosticSet, Severity};
 pub use editor::Editor;
+pub use events::{EditEvent, EditorEvent};
+pub use line_endings::{LineEnding, TextFormat};
 pub use multi_cursor::MultiCursor;
 pub use selection::Selection;
+pub use text_objects::{TextObject, TextObjectRange};
+pub use transform::{SortOptions, Transform};
@@ -1,10 +1,8 @@
-// Multi-cursor support - to be implemented later
-
 use super::selection::Selection;
 
-/// Multiple cursors/selections
-#[allow(dead_code)] // We'll use this later
-#[derive(Debug, Clone)]
+/// Extra cursors/selections beside the editor's primary one, kept sorted by
+/// position with duplicates and overlaps merged
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MultiCursor {
     selections: Vec<Selection>,
 }
@@ -15,10 +13,58 @@ impl MultiCursor {
             selections: Vec::new(),
         }
     }
-}
 
-impl Default for MultiCursor {
-    fn default() -> Self {
-        Self::new()
+    pub fn is_empty(&self) -> bool {
+        self.selections.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.selections.len()
+    }
+
+    /// Sorted by start position
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    pub fn add(&mut self, selection: Selection) {
+        self.selections.push(selection);
+        self.selections = Self::merge(std::mem::take(&mut self.selections));
+    }
+
+    pub fn set(&mut self, selections: Vec<Selection>) {
+        self.selections = Self::merge(selections);
+    }
+
+    /// Remove every extra cursor, returning them
+    pub fn take(&mut self) -> Vec<Selection> {
+        std::mem::take(&mut self.selections)
+    }
+
+    pub fn clear(&mut self) {
+        self.selections.clear();
+    }
+
+    /// Sort selections and merge the ones that overlap (or are the same cursor)
+    pub fn merge(mut selections: Vec<Selection>) -> Vec<Selection> {
+        selections.sort_by_key(|selection| selection.range());
+        let mut merged: Vec<Selection> = Vec::with_capacity(selections.len());
+        for selection in selections {
+            let (start, end) = selection.range();
+            match merged.last_mut() {
+                Some(last) if Self::overlaps(*last, selection) => {
+                    let (last_start, last_end) = last.range();
+                    *last = Selection::new(last_start.min(start), last_end.max(end));
+                }
+                _ => merged.push(selection),
+            }
+        }
+        merged
+    }
+
+    /// Two selections share text, or are the same cursor
+    pub fn overlaps(a: Selection, b: Selection) -> bool {
+        let ((a_start, a_end), (b_start, b_end)) = (a.range(), b.range());
+        a.range() == b.range() || (a_start < b_end && b_start < a_end)
     }
 }
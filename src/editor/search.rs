@@ -0,0 +1,23 @@
+use crate::rope::Rope;
+use std::ops::Range;
+
+/// Byte ranges of every occurrence of `query` in `rope`, left to right and
+/// non-overlapping (case-sensitive; empty queries match nothing)
+pub fn find_all(rope: &Rope, query: &str) -> Vec<Range<usize>> {
+    std::iter::successors(rope.find(query, 0), |found| rope.find(query, found.end)).collect()
+}
+
+/// First occurrence starting at or after `from`, wrapping around to the
+/// start of the text; occurrences `skip` rejects are passed over
+pub fn find_next(
+    rope: &Rope,
+    query: &str,
+    from: usize,
+    skip: impl Fn(&Range<usize>) -> bool,
+) -> Option<Range<usize>> {
+    let first_from = |from| {
+        std::iter::successors(rope.find(query, from), |found| rope.find(query, found.end))
+            .find(|found| !skip(found))
+    };
+    first_from(from).or_else(|| first_from(0))
+}
@@ -1,4 +1,5 @@
 use crate::buffer::Point; // Remove Offset
+use std::ops::Range;
 
 /// Text selection (range)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,4 +35,21 @@ impl Selection {
             (self.end, self.start)
         }
     }
+
+    /// Columns of `row` (a line `line_len` chars long) that are selected,
+    /// and whether the selection continues past the line's end (its newline
+    /// is selected too). None if the row has nothing selected
+    pub fn row_span(&self, row: usize, line_len: usize) -> Option<(Range<usize>, bool)> {
+        let (start, end) = self.range();
+        if self.is_empty() || row < start.row || row > end.row {
+            return None;
+        }
+        let from = if row == start.row { start.column } else { 0 }.min(line_len);
+        let (to, past_end) = if row == end.row {
+            (end.column.min(line_len), false)
+        } else {
+            (line_len, true)
+        };
+        (from < to || past_end).then_some((from..to, past_end))
+    }
 }
@@ -0,0 +1,203 @@
+use crate::util::brackets;
+#[cfg(feature = "syntax")]
+use crate::util::brackets::{closer_for, opener_for};
+use std::ops::Range;
+#[cfg(feature = "syntax")]
+use tree_sitter::{Node, Tree};
+
+/// Objects that can be selected inside/around the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextObject {
+    /// The word (or run of whitespace / punctuation) under the cursor
+    Word,
+    /// The block of non-blank lines around the cursor
+    Paragraph,
+    /// The nearest enclosing `()`, `[]` or `{}` pair
+    Brackets,
+    /// The string (or char literal) under the cursor
+    Quotes,
+}
+
+/// Byte ranges of a text object: `outer` includes the delimiters, `inner` excludes them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextObjectRange {
+    pub outer: Range<usize>,
+    pub inner: Range<usize>,
+}
+
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+/// Offset of the bracket matching the one at `offset`
+pub fn matching_bracket(text: &str, offset: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    brackets::find_closer(offset, bytes.get(offset..)?.iter().copied())
+        .or_else(|| brackets::find_opener(offset, bytes.get(..=offset)?.iter().rev().copied()))
+}
+
+/// Nearest bracket pair enclosing `offset`; a cursor on an opening bracket
+/// counts as inside that pair
+pub fn enclosing_brackets(text: &str, offset: usize) -> Option<TextObjectRange> {
+    let bytes = text.as_bytes();
+    let offset = offset.min(bytes.len());
+    let before = bytes[..offset].iter().rev().copied();
+    let outer = brackets::enclosing_pair(offset, before, |at| bytes[at..].iter().copied())?;
+    Some(TextObjectRange {
+        inner: outer.start + 1..outer.end - 1,
+        outer,
+    })
+}
+
+/// True for an anonymous `(`/`)`/`[`/`]`/`{`/`}` token. Brackets inside
+/// strings and comments are part of those nodes, so they never match
+#[cfg(feature = "syntax")]
+fn is_bracket_token(node: &Node) -> bool {
+    !node.is_named()
+        && node.end_byte() == node.start_byte() + 1
+        && matches!(node.kind(), "(" | ")" | "[" | "]" | "{" | "}")
+}
+
+/// Sibling token that closes `open` (the first matching closer after it)
+#[cfg(feature = "syntax")]
+fn closing_sibling<'tree>(open: &Node<'tree>) -> Option<Node<'tree>> {
+    let close = closer_for(open.kind().as_bytes()[0])?;
+    let mut sibling = open.next_sibling();
+    while let Some(node) = sibling {
+        if is_bracket_token(&node) && node.kind().as_bytes()[0] == close {
+            return Some(node);
+        }
+        sibling = node.next_sibling();
+    }
+    None
+}
+
+/// Like `matching_bracket`, but pairs only real bracket tokens of the parse
+/// tree, skipping brackets in strings and comments
+#[cfg(feature = "syntax")]
+pub fn matching_bracket_in_tree(tree: &Tree, offset: usize) -> Option<usize> {
+    let node = tree
+        .root_node()
+        .descendant_for_byte_range(offset, offset + 1)?;
+    if !is_bracket_token(&node) || node.start_byte() != offset {
+        return None;
+    }
+
+    if closer_for(node.kind().as_bytes()[0]).is_some() {
+        return closing_sibling(&node).map(|close| close.start_byte());
+    }
+    let open = opener_for(node.kind().as_bytes()[0])?;
+    let mut sibling = node.prev_sibling();
+    while let Some(candidate) = sibling {
+        if is_bracket_token(&candidate)
+            && candidate.kind().as_bytes()[0] == open
+            && closing_sibling(&candidate).is_some_and(|close| close.id() == node.id())
+        {
+            return Some(candidate.start_byte());
+        }
+        sibling = candidate.prev_sibling();
+    }
+    None
+}
+
+/// Like `enclosing_brackets`, using the parse tree's bracket tokens
+#[cfg(feature = "syntax")]
+pub fn enclosing_brackets_in_tree(tree: &Tree, offset: usize) -> Option<TextObjectRange> {
+    let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+    loop {
+        let mut cursor = node.walk();
+        let pair = node
+            .children(&mut cursor)
+            .filter(|child| is_bracket_token(child) && child.start_byte() <= offset)
+            .filter_map(|open| Some((open, closing_sibling(&open)?)))
+            .filter(|(_, close)| offset <= close.start_byte())
+            .last();
+        if let Some((open, close)) = pair {
+            return Some(TextObjectRange {
+                outer: open.start_byte()..close.end_byte(),
+                inner: open.end_byte()..close.start_byte(),
+            });
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Node kinds of complete string literals across the bundled grammars
+#[cfg(feature = "syntax")]
+const STRING_LITERAL_KINDS: &[&str] = &[
+    "string_literal",
+    "raw_string_literal",
+    "char_literal",
+    "string",
+    "template_string",
+];
+
+/// Smallest string-like tree-sitter node (string, raw string, char literal,
+/// template string) containing `offset`
+#[cfg(feature = "syntax")]
+pub fn enclosing_string_node(tree: &Tree, text: &str, offset: usize) -> Option<TextObjectRange> {
+    let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+
+    loop {
+        // Fragments inside a string ("string_content", "string_fragment", ...)
+        // are skipped; only whole literals carry their delimiters
+        if STRING_LITERAL_KINDS.contains(&node.kind()) {
+            return quoted_range(text, node.start_byte()..node.end_byte());
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Quote pair on a single line around `column_byte` (fallback when there is no
+/// syntax tree); quotes are paired left to right, skipping escaped ones
+pub fn enclosing_quotes_in_line(line: &str, column_byte: usize) -> Option<TextObjectRange> {
+    let mut open: Option<(usize, char)> = None;
+    let mut escaped = false;
+
+    for (i, ch) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if ch == '\\' {
+            escaped = true;
+            continue;
+        }
+
+        match open {
+            Some((start, quote)) if ch == quote => {
+                if (start..=i).contains(&column_byte) {
+                    return Some(TextObjectRange {
+                        outer: start..i + 1,
+                        inner: start + 1..i,
+                    });
+                }
+                open = None;
+            }
+            None if QUOTES.contains(&ch) => open = Some((i, ch)),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Split a string literal's range into delimiters and content, handling
+/// prefixes (`r#"`, `f"`) and triple quotes
+#[cfg(feature = "syntax")]
+fn quoted_range(text: &str, range: Range<usize>) -> Option<TextObjectRange> {
+    let literal = &text[range.clone()];
+    let quote_start = literal.find(QUOTES)?;
+    let quote = literal[quote_start..].chars().next()?;
+    let run = literal[quote_start..]
+        .chars()
+        .take_while(|c| *c == quote)
+        .count();
+    let delimiter = if run >= 3 { 3 } else { 1 };
+    let last_quote = literal.rfind(quote)?;
+
+    let inner_start = quote_start + delimiter;
+    let inner_end = (last_quote + 1).saturating_sub(delimiter).max(inner_start);
+    Some(TextObjectRange {
+        outer: range.clone(),
+        inner: range.start + inner_start..range.start + inner_end,
+    })
+}
@@ -0,0 +1,267 @@
+/// Case conversions applied to the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Uppercase,
+    Lowercase,
+    TitleCase,
+    SnakeCase,
+    CamelCase,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 5] = [
+        Transform::Uppercase,
+        Transform::Lowercase,
+        Transform::TitleCase,
+        Transform::SnakeCase,
+        Transform::CamelCase,
+    ];
+
+    /// Menu label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Transform::Uppercase => "UPPERCASE",
+            Transform::Lowercase => "lowercase",
+            Transform::TitleCase => "Title Case",
+            Transform::SnakeCase => "snake_case",
+            Transform::CamelCase => "camelCase",
+        }
+    }
+
+    /// Convert text; snake/camel case work line by line and keep each
+    /// line's surrounding whitespace
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Transform::Uppercase => text.to_uppercase(),
+            Transform::Lowercase => text.to_lowercase(),
+            Transform::TitleCase => title_case(text),
+            Transform::SnakeCase => map_lines(text, |words| words.join("_")),
+            Transform::CamelCase => map_lines(text, |words| {
+                words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| {
+                        if i == 0 {
+                            word.clone()
+                        } else {
+                            capitalize(word)
+                        }
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Options for sorting selected lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortOptions {
+    pub ascending: bool,
+    /// Drop repeated lines (compared with the same case rule as the sort)
+    pub unique: bool,
+    pub case_insensitive: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            ascending: true,
+            unique: false,
+            case_insensitive: false,
+        }
+    }
+}
+
+impl SortOptions {
+    /// Sort lines (stable, so equal lines keep their order and `unique`
+    /// keeps the first of each)
+    pub fn apply(&self, lines: &[String]) -> Vec<String> {
+        let key = |line: &String| {
+            if self.case_insensitive {
+                line.to_lowercase()
+            } else {
+                line.clone()
+            }
+        };
+
+        let mut keyed: Vec<(String, &String)> =
+            lines.iter().map(|line| (key(line), line)).collect();
+        keyed.sort_by(|a, b| {
+            if self.ascending {
+                a.0.cmp(&b.0)
+            } else {
+                b.0.cmp(&a.0)
+            }
+        });
+        if self.unique {
+            keyed.dedup_by(|a, b| a.0 == b.0);
+        }
+        keyed.into_iter().map(|(_, line)| line.clone()).collect()
+    }
+}
+
+/// Indentation, `comment` token (with doc-comment extras like `///` or `//!`)
+/// and the spaces after it that start a line of prose
+pub fn reflow_prefix<'a>(line: &'a str, comment: Option<&str>) -> &'a str {
+    let indent = line.len() - line.trim_start().len();
+    let mut end = indent;
+    if let Some(token) = comment.filter(|token| line[indent..].starts_with(*token)) {
+        end += token.len();
+        end += line[end..]
+            .find(|c: char| !token.contains(c) && c != '!')
+            .unwrap_or(line.len() - end);
+        end += line[end..]
+            .find(|c: char| c != ' ' && c != '\t')
+            .unwrap_or(line.len() - end);
+    }
+    &line[..end]
+}
+
+/// Length of a list marker (`- `, `* `, `+ `, `1. `, `2) `) at the start of `text`
+fn list_marker_len(text: &str) -> Option<usize> {
+    let word = text.split_whitespace().next()?;
+    let is_bullet = matches!(word, "-" | "*" | "+");
+    let is_number = word.len() > 1
+        && word.ends_with(['.', ')'])
+        && word[..word.len() - 1].chars().all(|c| c.is_ascii_digit());
+    (is_bullet || is_number).then_some(word.len() + 1)
+}
+
+/// Re-fill lines of prose to fit `width` columns. Each paragraph (lines
+/// between blank ones, or a list item) keeps its first line's indentation
+/// and comment prefix, and a list item's later lines hang under its text
+pub fn reflow(lines: &[String], width: usize, comment: Option<&str>) -> Vec<String> {
+    let mut result = Vec::with_capacity(lines.len());
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in lines {
+        let body = &line[reflow_prefix(line, comment).len()..];
+        if body.trim().is_empty() || list_marker_len(body).is_some() {
+            result.extend(reflow_paragraph(&paragraph, width, comment));
+            paragraph.clear();
+        }
+        if body.trim().is_empty() {
+            result.push(line.clone());
+        } else {
+            paragraph.push(line);
+        }
+    }
+    result.extend(reflow_paragraph(&paragraph, width, comment));
+    result
+}
+
+fn reflow_paragraph(lines: &[&str], width: usize, comment: Option<&str>) -> Vec<String> {
+    let Some(first) = lines.first() else {
+        return Vec::new();
+    };
+    let prefix = reflow_prefix(first, comment);
+    let hanging = match list_marker_len(&first[prefix.len()..]) {
+        Some(len) => format!("{}{}", prefix, " ".repeat(len)),
+        None => prefix.to_string(),
+    };
+    let words = lines
+        .iter()
+        .flat_map(|line| line[reflow_prefix(line, comment).len()..].split_whitespace());
+
+    let mut result = Vec::new();
+    let mut current = prefix.to_string();
+    let mut current_width = current.chars().count();
+    let mut empty = true;
+    for word in words {
+        let word_width = word.chars().count();
+        if !empty && current_width + 1 + word_width > width {
+            result.push(std::mem::replace(&mut current, hanging.clone()));
+            current_width = hanging.chars().count();
+            empty = true;
+        }
+        if !empty {
+            current.push(' ');
+            current_width += 1;
+        }
+        current.push_str(word);
+        current_width += word_width;
+        empty = false;
+    }
+    result.push(current);
+    result
+}
+
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            result.push(c);
+            at_word_start = true;
+        } else if at_word_start {
+            result.extend(c.to_uppercase());
+            at_word_start = false;
+        } else {
+            result.extend(c.to_lowercase());
+        }
+    }
+    result
+}
+
+/// Uppercase the first char, lowercase the rest
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Rejoin each line's lowercased words with `join`, keeping its indentation
+/// and trailing whitespace
+fn map_lines(text: &str, join: impl Fn(&[String]) -> String) -> String {
+    text.split('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                return line.to_string();
+            }
+            let leading = &line[..line.len() - line.trim_start().len()];
+            let trailing = &line[line.trim_end().len()..];
+            let words: Vec<String> = split_words(trimmed)
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect();
+            format!("{}{}{}", leading, join(&words), trailing)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split identifiers and phrases into words: separators (space, `_`, `-`, ...),
+/// lower→upper transitions (`fooBar`) and acronym ends (`HTTPServer`)
+fn split_words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
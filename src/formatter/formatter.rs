@@ -1,4 +1,10 @@
+use super::sandbox::{Invocation, Sandbox};
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Output log lines kept (oldest dropped first)
+const OUTPUT_LOG_LEN: usize = 200;
 
 #[derive(Debug, Clone)]
 pub enum FormatError {
@@ -6,6 +12,8 @@ pub enum FormatError {
     NotFound(String),
     InvalidOutput(String),
     UnsupportedLanguage(String),
+    TimedOut(String),
+    Cancelled,
 }
 
 pub type FormatResult = Result<String, FormatError>;
@@ -21,19 +29,51 @@ pub trait FormatterProvider: Send + Sync {
     /// Check if the formatter binary is available
     fn is_available(&self) -> bool;
 
-    /// Format the given text
-    fn format(&self, text: &str, file_path: Option<&Path>) -> FormatResult;
+    /// Program to run (the formatter's name by default)
+    fn program(&self) -> &str {
+        self.name()
+    }
+
+    /// Arguments for formatting text on stdin to stdout
+    fn args(&self, file_path: Option<&Path>) -> Vec<String>;
 }
 
 /// Main formatter manager
 pub struct Formatter {
     providers: Vec<Box<dyn FormatterProvider>>,
+    sandbox: Sandbox,
+    /// Each invocation and its failure, if any
+    output_log: Mutex<Vec<String>>,
 }
 
 impl Formatter {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            sandbox: Sandbox::default(),
+            output_log: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Environment, directory and limits formatters run with
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.sandbox = sandbox;
+    }
+
+    /// Formatter invocations (as shell command lines) and their errors, oldest first
+    pub fn output_log(&self) -> Vec<String> {
+        self.output_log
+            .lock()
+            .map(|log| log.clone())
+            .unwrap_or_default()
+    }
+
+    fn log(&self, line: String) {
+        if let Ok(mut log) = self.output_log.lock() {
+            if log.len() == OUTPUT_LOG_LEN {
+                log.remove(0);
+            }
+            log.push(line);
         }
     }
 
@@ -54,27 +94,57 @@ impl Formatter {
 
     /// Format text using the appropriate provider
     pub fn format_text(&self, text: &str, file_path: Option<&Path>) -> FormatResult {
-        if let Some(path) = file_path {
-            if let Some(provider) = self.find_provider(path) {
-                if !provider.is_available() {
-                    return Err(FormatError::NotFound(format!(
-                        "{} is not installed or not in PATH",
-                        provider.name()
-                    )));
-                }
-                return provider.format(text, Some(path));
-            }
+        let Some(path) = file_path else {
+            return Err(FormatError::UnsupportedLanguage(
+                "Cannot determine language without file path".to_string(),
+            ));
+        };
+        let invocation = self.invocation(path)?;
+        let result = invocation.run(text);
+        self.log_run(&invocation, &result);
+        result
+    }
+
+    /// Format a piece of `file_path`'s language (a paste) as `format_text`
+    /// does, giving up after `timeout` if the sandbox allows longer
+    pub fn format_snippet(&self, text: &str, file_path: &Path, timeout: Duration) -> FormatResult {
+        let mut invocation = self.invocation(file_path)?;
+        invocation.timeout = Some(invocation.timeout.map_or(timeout, |limit| limit.min(timeout)));
+        let result = invocation.run(text);
+        self.log_run(&invocation, &result);
+        result
+    }
+
+    /// The sandboxed command that formats `file_path`, for running it
+    /// elsewhere (e.g. on a background thread)
+    pub fn invocation(&self, file_path: &Path) -> Result<Invocation, FormatError> {
+        let Some(provider) = self.find_provider(file_path) else {
             return Err(FormatError::UnsupportedLanguage(format!(
                 "No formatter found for {:?}",
-                path.extension()
+                file_path.extension()
+            )));
+        };
+        if !provider.is_available() {
+            return Err(FormatError::NotFound(format!(
+                "{} is not installed or not in PATH",
+                provider.name()
             )));
         }
-
-        Err(FormatError::UnsupportedLanguage(
-            "Cannot determine language without file path".to_string(),
+        Ok(self.sandbox.invocation(
+            provider.program(),
+            &provider.args(Some(file_path)),
+            Some(file_path),
         ))
     }
 
+    /// Record an invocation and its failure, if any, in the output log
+    pub fn log_run(&self, invocation: &Invocation, result: &FormatResult) {
+        self.log(format!("$ {}", invocation));
+        if let Err(error) = result {
+            self.log(format!("{:?}", error));
+        }
+    }
+
     /// Get list of available formatters
     pub fn available_formatters(&self) -> Vec<&str> {
         self.providers
@@ -1,6 +1,8 @@
 pub mod config;
 pub mod formatter;
 pub mod providers;
+pub mod sandbox;
 
 pub use config::FormatterConfig;
 pub use formatter::{FormatError, FormatResult, Formatter, FormatterProvider}; // ADD FormatError here
+pub use sandbox::{Invocation, Sandbox};
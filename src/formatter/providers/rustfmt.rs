@@ -1,7 +1,6 @@
-use crate::formatter::{FormatError, FormatResult, FormatterProvider};
-use std::io::Write;
+use crate::formatter::FormatterProvider;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::Command;
 
 pub struct RustfmtProvider {
     additional_args: Vec<String>,
@@ -39,35 +38,7 @@ impl FormatterProvider for RustfmtProvider {
         Command::new("rustfmt").arg("--version").output().is_ok()
     }
 
-    fn format(&self, text: &str, _file_path: Option<&Path>) -> FormatResult {
-        let mut child = Command::new("rustfmt")
-            .args(&self.additional_args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| FormatError::ExecutionFailed(e.to_string()))?;
-
-        // Write input to stdin
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(text.as_bytes())
-                .map_err(|e| FormatError::ExecutionFailed(e.to_string()))?;
-        }
-
-        // Wait for process and collect output
-        let output = child
-            .wait_with_output()
-            .map_err(|e| FormatError::ExecutionFailed(e.to_string()))?;
-
-        if output.status.success() {
-            String::from_utf8(output.stdout).map_err(|e| FormatError::InvalidOutput(e.to_string()))
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(FormatError::ExecutionFailed(format!(
-                "rustfmt failed: {}",
-                stderr
-            )))
-        }
+    fn args(&self, _file_path: Option<&Path>) -> Vec<String> {
+        self.additional_args.clone()
     }
 }
@@ -0,0 +1,255 @@
+use crate::formatter::FormatError;
+use crate::progress::CancelHandle;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::{Command, Stdio};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// How external tools (formatters, tasks) run: with a minimal environment,
+/// in a chosen directory and under optional limits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sandbox {
+    /// Variables passed through from the editor's environment; every other
+    /// one is cleared
+    pub inherit_env: Vec<String>,
+    /// Variables set for every tool (overriding inherited ones)
+    pub env: BTreeMap<String, String>,
+    /// Directory tools run in; None runs them in the file's directory
+    pub working_dir: Option<PathBuf>,
+    /// Kill a tool still running after this many seconds (0 = no limit)
+    pub timeout_seconds: u64,
+    /// Run tools under `nice -n` with this adjustment (Unix only)
+    pub nice: Option<i32>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            inherit_env: [
+                "PATH",
+                "HOME",
+                "USER",
+                "LANG",
+                "LC_ALL",
+                "TMPDIR",
+                "CARGO_HOME",
+                "RUSTUP_HOME",
+                "RUSTUP_TOOLCHAIN",
+                "NODE_PATH",
+            ]
+            .map(String::from)
+            .to_vec(),
+            env: BTreeMap::new(),
+            working_dir: None,
+            timeout_seconds: 10,
+            nice: None,
+        }
+    }
+}
+
+impl Sandbox {
+    /// The exact command that running `program` on `file_path` would execute
+    pub fn invocation(
+        &self,
+        program: &str,
+        args: &[String],
+        file_path: Option<&Path>,
+    ) -> Invocation {
+        let mut env: BTreeMap<String, String> = self
+            .inherit_env
+            .iter()
+            .filter_map(|name| Some((name.clone(), std::env::var(name).ok()?)))
+            .collect();
+        env.extend(self.env.clone());
+
+        let working_dir = self.working_dir.clone().or_else(|| {
+            file_path
+                .and_then(Path::parent)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map(Path::to_path_buf)
+        });
+
+        Invocation {
+            program: program.to_string(),
+            args: args.to_vec(),
+            working_dir,
+            env: env.into_iter().collect(),
+            timeout: (self.timeout_seconds > 0).then(|| Duration::from_secs(self.timeout_seconds)),
+            nice: self.nice.filter(|_| cfg!(unix)),
+        }
+    }
+}
+
+/// A resolved tool command line; its `Display` is what gets logged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: Option<PathBuf>,
+    /// The tool's whole environment
+    pub env: Vec<(String, String)>,
+    pub timeout: Option<Duration>,
+    pub nice: Option<i32>,
+}
+
+impl Invocation {
+    /// Run the tool with `input` on stdin and return its stdout
+    pub fn run(&self, input: &str) -> Result<String, FormatError> {
+        self.run_cancellable(input, &CancelHandle::default())
+    }
+
+    /// Browsers can't start processes, so external tools never run there
+    #[cfg(target_arch = "wasm32")]
+    pub fn run_cancellable(
+        &self,
+        _input: &str,
+        _cancel: &CancelHandle,
+    ) -> Result<String, FormatError> {
+        Err(FormatError::ExecutionFailed(format!(
+            "{} can't run in the browser",
+            self.program
+        )))
+    }
+
+    /// `run`, killing the tool early once `cancel` is set
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_cancellable(
+        &self,
+        input: &str,
+        cancel: &CancelHandle,
+    ) -> Result<String, FormatError> {
+        let mut command = match self.nice {
+            Some(level) => {
+                let mut command = Command::new("nice");
+                command.arg("-n").arg(level.to_string()).arg(&self.program);
+                command
+            }
+            None => Command::new(&self.program),
+        };
+        command
+            .args(&self.args)
+            .env_clear()
+            .envs(self.env.iter().map(|(name, value)| (name, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command.spawn().map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                FormatError::NotFound(format!("{} is not installed or not in PATH", self.program))
+            }
+            _ => FormatError::ExecutionFailed(e.to_string()),
+        })?;
+
+        // Feed stdin and drain the output pipes on their own threads so a
+        // tool blocked on a full pipe can't outlive the timeout
+        let stdin = child.stdin.take().map(|mut stdin| {
+            let input = input.to_string();
+            thread::spawn(move || stdin.write_all(input.as_bytes()))
+        });
+        let read = |pipe: Option<Box<dyn Read + Send>>| {
+            thread::spawn(move || {
+                let mut bytes = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    pipe.read_to_end(&mut bytes).ok();
+                }
+                bytes
+            })
+        };
+        let stdout = read(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+        let stderr = read(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| FormatError::ExecutionFailed(e.to_string()))?
+            {
+                break status;
+            }
+            if cancel.is_cancelled() {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(FormatError::Cancelled);
+            }
+            if self
+                .timeout
+                .is_some_and(|timeout| started.elapsed() > timeout)
+            {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(FormatError::TimedOut(format!(
+                    "{} took longer than {}s",
+                    self.program,
+                    self.timeout.unwrap_or_default().as_secs()
+                )));
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        if let Some(Ok(Err(e))) = stdin.map(|writer| writer.join()) {
+            // A tool may exit without reading its input; only report that if it failed
+            if !status.success() {
+                return Err(FormatError::ExecutionFailed(e.to_string()));
+            }
+        }
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if status.success() {
+            String::from_utf8(stdout).map_err(|e| FormatError::InvalidOutput(e.to_string()))
+        } else {
+            Err(FormatError::ExecutionFailed(format!(
+                "{} failed: {}",
+                self.program,
+                String::from_utf8_lossy(&stderr)
+            )))
+        }
+    }
+}
+
+/// Single-quote a word for a shell unless it's plain
+fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+impl fmt::Display for Invocation {
+    /// As a shell command line: `cd DIR && env -i VAR=… [nice -n N] PROGRAM ARGS…`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(dir) = &self.working_dir {
+            write!(f, "cd {} && ", quote(&dir.to_string_lossy()))?;
+        }
+        write!(f, "env -i")?;
+        for (name, value) in &self.env {
+            write!(f, " {}={}", name, quote(value))?;
+        }
+        if let Some(level) = self.nice {
+            write!(f, " nice -n {}", level)?;
+        }
+        write!(f, " {}", quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        Ok(())
+    }
+}
@@ -1,31 +1,149 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::editor::LineEnding;
+use crate::editor::{
+    AutoPairs, CodeActions, ConflictResolution, EditorEvent, SortOptions, TextObject, Transform,
+};
 use crate::formatter::providers::{PrettierProvider, RustfmtProvider};
+use crate::formatter::{FormatError, FormatResult, Invocation};
+#[cfg(target_arch = "wasm32")]
+use crate::io::decode_text;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::io::read_file_decoded;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::io::write_file_from_chunks;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::io::write_file_from_rope; // 🚀 Import new efficient rope writer
-use crate::{read_file, Editor, Formatter, SyntaxHighlighter, SyntaxTheme};
-use std::path::PathBuf;
-use std::time::Instant;
-
-use super::viewport_renderer::ViewportRenderer;
-
-#[derive(Clone, Debug)]
-enum LoadingState {
-    Idle,
-    Loading { progress: f32, message: String },
-    Complete,
-    Error(String),
+use crate::io::{
+    load_rope, read_file_with_progress, ProjectMatch, RecoveryEntry, RecoveryJournal, TextEncoding,
+};
+use crate::keymap::{
+    Abandoned, Command, CommandHistory, EditAction, KeyAction, Keymap, PendingKeys,
+};
+use crate::notifications::{NotificationAction, Notifications};
+use crate::progress::Progress;
+use crate::rope::Rope;
+use crate::settings::{EditorConfig, Modeline};
+use crate::syntax::LanguageRegistry;
+use crate::{Editor, Formatter, Settings};
+#[cfg(feature = "syntax")]
+use crate::{SyntaxHighlighter, SyntaxTheme};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, SystemTime};
+use web_time::Instant;
+
+use super::code_action_menu::CodeActionMenu;
+use super::input::key_event_from_egui;
+use super::keybindings_panel::KeybindingsPanel;
+use super::notification_panel::NotificationPanel;
+use super::output_panel::OutputPanel;
+use super::profiler::ProfilerOverlay;
+use super::project_search_panel::ProjectSearchPanel;
+use super::settings_panel::SettingsPanel;
+use super::split_pane::SplitPane;
+use super::task;
+use super::viewport_renderer::{ViewportAction, ViewportRenderer};
+
+/// State of the "Change File Association" window
+struct AssociationDialog {
+    pattern: String,
+    language: String,
+}
+
+/// Language choice that turns language features off for matching files
+const PLAIN_TEXT: &str = "Plain Text";
+
+/// How often the open file's modification time is compared against disk
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Larger files skip the 💡 check (it re-parses the buffer when the cursor moves)
+const LIGHTBULB_MAX_LINES: usize = 10_000;
+
+/// Files at least this large are read on a background thread with progress
+const BACKGROUND_LOAD_BYTES: u64 = 4_000_000;
+
+/// Inserting a file larger than this asks first
+const INSERT_FILE_WARN_BYTES: u64 = 10_000_000;
+
+/// A file waiting on "Insert anyway": its path, or in the browser its name
+/// and contents
+#[cfg(not(target_arch = "wasm32"))]
+type PendingInsert = PathBuf;
+#[cfg(target_arch = "wasm32")]
+type PendingInsert = super::web::PickedFile;
+
+/// A file being read on a background thread
+struct PendingLoad {
+    path: PathBuf,
+    file_size: u64,
+    task: u64,
+    result: Receiver<std::io::Result<Rope>>,
+}
+
+/// A formatter running on a background thread against buffer `version`
+struct PendingFormat {
+    invocation: Invocation,
+    version: u64,
+    task: u64,
+    result: Receiver<FormatResult>,
 }
 
 pub struct GuiApp {
     editor: Editor,
     cursor_blink: bool,
     last_blink: Instant,
-    last_input_time: Instant,  // ✅ Track when user last typed
-    status_message: String,
+    last_input_time: Instant, // ✅ Track when user last typed
+    notifications: Notifications,
+    notification_panel: NotificationPanel,
+    /// Modification time of the open file when last loaded, saved or checked
+    disk_modified: Option<SystemTime>,
+    last_disk_check: Instant,
     auto_scroll: bool,
     current_file: Option<PathBuf>,
-    loading_state: LoadingState,
+    progress: Progress,
+    pending_load: Option<PendingLoad>,
+    /// A file being picked in the browser: its name and contents, or None
+    #[cfg(target_arch = "wasm32")]
+    pending_pick: Option<Receiver<Option<super::web::PickedFile>>>,
+    /// Like `pending_pick`, for Insert File
+    #[cfg(target_arch = "wasm32")]
+    pending_insert_pick: Option<Receiver<Option<super::web::PickedFile>>>,
+    pending_insert: Option<PendingInsert>,
+    pending_format: Option<PendingFormat>,
     renderer: ViewportRenderer,
+    /// Second view of the buffer, beside the main one
+    split: Option<SplitPane>,
     formatter: Formatter,
+    #[cfg(feature = "syntax")]
     highlighter: SyntaxHighlighter,
+    recovery: RecoveryJournal,
+    profiler: ProfilerOverlay,
+    output_panel: OutputPanel,
+    project_search: ProjectSearchPanel,
+    settings: Settings,
+    settings_path: PathBuf,
+    association_dialog: Option<AssociationDialog>,
+    settings_panel: SettingsPanel,
+    keymap: Keymap,
+    keymap_path: PathBuf,
+    keybindings_panel: KeybindingsPanel,
+    /// Keys typed so far toward a multi-key binding
+    pending_keys: PendingKeys,
+    /// Text those keys would have typed, in case the sequence is abandoned
+    pending_text: String,
+    command_history: CommandHistory,
+    /// What the editor did since the last frame (edits drive precise
+    /// renderer cache updates); observed once, it follows `Editor::replace`
+    editor_events: Receiver<EditorEvent>,
+    code_actions: CodeActions,
+    code_action_menu: CodeActionMenu,
+    /// (version, cursor row) the 💡 was last checked for, and whether it shows
+    lightbulb: Option<(u64, usize, bool)>,
+    /// Settings the open file's modeline overrides
+    modeline: Modeline,
+    /// Style the project's `.editorconfig` gives the open file
+    editorconfig: EditorConfig,
 }
 
 impl GuiApp {
@@ -34,55 +152,423 @@ impl GuiApp {
         formatter.register(Box::new(RustfmtProvider::new()));
         formatter.register(Box::new(PrettierProvider::new()));
 
+        #[cfg(feature = "syntax")]
         let highlighter = SyntaxHighlighter::new(SyntaxTheme::dark());
 
-        Self {
-            editor: Editor::new(),
+        let settings_path = Settings::default_path();
+        let (settings, settings_error) = match Settings::load(&settings_path) {
+            Ok(settings) => (settings, None),
+            Err(e) => (Settings::default(), Some(e)),
+        };
+        formatter.set_sandbox(settings.tools.clone());
+
+        let keymap_path = Keymap::default_path();
+        let (keymap, keymap_error) = match Keymap::load(&keymap_path) {
+            Ok(keymap) => (keymap, None),
+            Err(e) => (Keymap::defaults(), Some(e)),
+        };
+
+        let mut editor = Editor::new();
+        let editor_events = editor.observe();
+        let mut app = Self {
+            editor,
             cursor_blink: true,
             last_blink: Instant::now(),
             last_input_time: Instant::now(),
-            status_message: String::new(),
+            notifications: Notifications::new(),
+            notification_panel: NotificationPanel::new(),
+            disk_modified: None,
+            last_disk_check: Instant::now(),
             auto_scroll: true,
             current_file: None,
-            loading_state: LoadingState::Idle,
+            progress: Progress::new(),
+            pending_load: None,
+            #[cfg(target_arch = "wasm32")]
+            pending_pick: None,
+            #[cfg(target_arch = "wasm32")]
+            pending_insert_pick: None,
+            pending_insert: None,
+            pending_format: None,
             renderer: ViewportRenderer::new(),
+            split: None,
             formatter,
+            #[cfg(feature = "syntax")]
             highlighter,
+            recovery: RecoveryJournal::default_location(),
+            profiler: ProfilerOverlay::new(),
+            output_panel: OutputPanel::new(),
+            project_search: ProjectSearchPanel::new(),
+            settings,
+            settings_path,
+            association_dialog: None,
+            settings_panel: SettingsPanel::new(),
+            keymap,
+            keymap_path,
+            keybindings_panel: KeybindingsPanel::new(),
+            pending_keys: PendingKeys::new(),
+            pending_text: String::new(),
+            command_history: CommandHistory::new(),
+            editor_events,
+            code_actions: CodeActions::new(),
+            code_action_menu: CodeActionMenu::new(),
+            lightbulb: None,
+            modeline: Modeline::default(),
+            editorconfig: EditorConfig::default(),
+        };
+        app.configure_editor();
+        app.renderer.set_font_size(app.settings.editor.font_size);
+        if let Some(e) = settings_error {
+            app.notifications
+                .error(format!("Could not read settings: {}", e))
+                .action(NotificationAction::OpenSettings);
         }
+        if let Some(e) = keymap_error {
+            app.notifications
+                .error(format!("Could not read keymap: {}", e))
+                .action(NotificationAction::OpenKeybindings);
+        }
+        app.restore_hot_exit();
+        app
     }
 
-    fn handle_text_input(&mut self, text: &str) {
-        let cursor_line = self.editor.cursor().row;
+    /// Replace the buffer with unsaved, untitled text (saving prompts for a path)
+    pub fn open_untitled(&mut self, rope: Rope) {
+        self.editor.replace(Editor::from_rope(rope));
+        self.configure_editor();
+        self.editor.mark_dirty();
+        self.current_file = None;
+        self.renderer.invalidate_from_line(0);
+        self.notifications.info(format!(
+            "📥 Read {} lines from stdin",
+            self.editor.line_count()
+        ));
+    }
+
+    /// Apply user settings to a freshly created editor
+    fn configure_editor(&mut self) {
+        self.editor
+            .set_file_associations(self.settings.file_associations.clone());
+        self.modeline = if self.settings.editor.modelines {
+            self.editor.modeline().unwrap_or_default()
+        } else {
+            Modeline::default()
+        };
+        self.editorconfig = self
+            .editor
+            .file_path()
+            .map(EditorConfig::for_path)
+            .unwrap_or_default();
+        let editor_settings = &self.settings.editor;
+        let tab_size = self
+            .modeline
+            .tab_size
+            .or(self.editorconfig.tab_size)
+            .unwrap_or(editor_settings.tab_size);
+        let hard_tabs = self
+            .modeline
+            .hard_tabs
+            .or(self.editorconfig.hard_tabs)
+            .unwrap_or(editor_settings.hard_tabs);
+        self.editor.set_indent(tab_size, hard_tabs);
+        self.editor
+            .set_undo_hunk_limit(editor_settings.undo_hunk_limit_kb * 1024);
+        self.editor
+            .set_language_override(self.modeline.language.clone());
+
+        let mut overrides = BTreeMap::new();
+        for (language, spec) in &self.settings.editor.autopairs {
+            match AutoPairs::parse(spec) {
+                Ok(pairs) => {
+                    overrides.insert(language.clone(), pairs);
+                }
+                Err(e) => {
+                    self.notifications
+                        .warn(format!("Settings: {}", e))
+                        .action(NotificationAction::OpenSettings);
+                }
+            }
+        }
+        self.editor
+            .set_autopairs(self.settings.editor.auto_close_pairs, overrides);
+        self.editor.set_paste_modes(
+            self.settings.editor.paste_mode(),
+            self.settings.editor.paste_modes.clone(),
+        );
+        self.editor.set_word_chars(
+            self.settings.editor.word_chars.clone(),
+            self.settings.editor.language_word_chars.clone(),
+        );
+    }
+
+    /// Open the association window prefilled for the current file
+    fn open_association_dialog(&mut self) {
+        let Some(path) = self.current_file.as_ref() else {
+            self.notifications
+                .warn("Save file first to change its association");
+            return;
+        };
+
+        let pattern = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("*.{}", ext),
+            None => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let language = self
+            .editor
+            .language()
+            .map(|lang| lang.name.to_string())
+            .unwrap_or_else(|| PLAIN_TEXT.to_string());
+
+        self.association_dialog = Some(AssociationDialog { pattern, language });
+    }
 
-        // Auto-close brackets
-        let auto_close = match text {
-            "{" => Some("}"),
-            "[" => Some("]"),
-            "(" => Some(")"),
-            "\"" => Some("\""),
-            "'" => Some("'"),
-            _ => None,
+    fn show_association_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.association_dialog.as_mut() else {
+            return;
         };
 
-        if let Some(closing) = auto_close {
-            self.editor.insert(text);
-            self.editor.insert(closing);
-            self.editor.move_left();
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("🗂 Change File Association")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Pattern:");
+                    ui.text_edit_singleline(&mut dialog.pattern);
+                });
+                egui::ComboBox::from_label("Language")
+                    .selected_text(dialog.language.as_str())
+                    .show_ui(ui, |ui| {
+                        for lang in LanguageRegistry::new().languages() {
+                            ui.selectable_value(
+                                &mut dialog.language,
+                                lang.name.to_string(),
+                                lang.name,
+                            );
+                        }
+                        ui.selectable_value(
+                            &mut dialog.language,
+                            PLAIN_TEXT.to_string(),
+                            PLAIN_TEXT,
+                        );
+                    });
+                apply = ui.button("Apply").clicked();
+            });
+
+        if apply {
+            let dialog = self.association_dialog.take().unwrap();
+            self.set_file_association(dialog.pattern.trim(), &dialog.language);
+        } else if !open {
+            self.association_dialog = None;
+        }
+    }
+
+    /// Persist a pattern → language association and re-detect the current file
+    fn set_file_association(&mut self, pattern: &str, language: &str) {
+        if pattern.is_empty() {
+            self.notifications.warn("Association pattern is empty");
+            return;
+        }
+
+        self.settings.file_associations.set(pattern, language);
+        self.configure_editor();
+        self.renderer.invalidate_from_line(0);
+
+        match self.settings.save(&self.settings_path) {
+            Ok(_) => {
+                self.notifications
+                    .info(format!("🗂 {} files now open as {}", pattern, language));
+            }
+            Err(e) => {
+                self.notifications
+                    .warn(format!("Association applied but not saved: {}", e));
+            }
+        }
+    }
+
+    /// Apply changed settings and persist them
+    fn apply_settings(&mut self) {
+        self.renderer.set_font_size(self.settings.editor.font_size);
+        if let Some(split) = &mut self.split {
+            split.renderer.set_font_size(self.settings.editor.font_size);
+        }
+        self.formatter.set_sandbox(self.settings.tools.clone());
+        self.configure_editor();
+
+        if let Err(e) = self.settings.save(&self.settings_path) {
+            self.notifications
+                .error(format!("Could not save settings: {}", e))
+                .action(NotificationAction::OpenSettings);
+        }
+    }
+
+    /// "Edit in settings.json": open the settings file itself in the editor
+    fn open_settings_file(&mut self) {
+        if !self.settings_path.exists() {
+            if let Err(e) = self.settings.save(&self.settings_path) {
+                self.notifications
+                    .error(format!("Could not create settings file: {}", e));
+                return;
+            }
+        }
+
+        let path = self.settings_path.clone();
+        let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        self.load_file_simple(&path, file_size);
+    }
+
+    fn show_settings_panel(&mut self, ctx: &egui::Context) {
+        let response = self
+            .settings_panel
+            .show(ctx, &mut self.settings, &self.settings_path);
+        if response.changed {
+            self.apply_settings();
+        }
+        if response.edit_file {
+            self.open_settings_file();
+        }
+    }
+
+    /// Hot-apply keymap edits and write them to keymap.toml
+    fn show_keybindings_panel(&mut self, ctx: &egui::Context) {
+        let changed = self
+            .keybindings_panel
+            .show(ctx, &mut self.keymap, &self.keymap_path);
+        if changed {
+            if let Err(e) = self.keymap.save(&self.keymap_path) {
+                self.notifications
+                    .error(format!("Could not save keymap: {}", e));
+            }
+        }
+    }
+
+    /// Reopen the buffer left unsaved at the last exit, still marked dirty
+    fn restore_hot_exit(&mut self) {
+        if !self.settings.editor.hot_exit {
+            return;
+        }
+
+        let entries = match self.recovery.load_all() {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.notifications
+                    .warn(format!("Could not read recovery data: {}", e));
+                return;
+            }
+        };
+
+        if let Some(entry) = entries.into_iter().next() {
+            let mut editor = Editor::from_text(&entry.text);
+            editor.set_file_path(entry.path.clone());
+            self.editor.replace(editor);
+            self.configure_editor();
+            self.editor.mark_dirty();
+            self.current_file = entry.path;
+            self.notifications
+                .info("♻️ Restored unsaved changes from last session");
+        }
+    }
+
+    /// Keep unsaved content in the recovery journal instead of prompting on quit
+    fn write_hot_exit(&mut self) {
+        let result = if self.editor.is_dirty() && self.settings.editor.hot_exit {
+            self.recovery.save_all(&[RecoveryEntry {
+                path: self.current_file.clone(),
+                text: self.editor.text(),
+            }])
         } else {
-            self.editor.insert(text);
+            self.recovery.clear()
+        };
+
+        if let Err(e) = result {
+            eprintln!("Failed to write recovery data: {}", e);
         }
+    }
+
+    fn handle_text_input(&mut self, text: &str) {
+        let cursor_line = self.editor.cursor().row;
+
+        self.editor.type_text(text);
 
-        self.status_message.clear();
+        self.notifications.dismiss_info();
         self.auto_scroll = true;
-        self.last_input_time = Instant::now();  // ✅ Reset input time on typing
-        self.cursor_blink = true;  // ✅ Show cursor when typing
+        self.last_input_time = Instant::now(); // ✅ Reset input time on typing
+        self.cursor_blink = true; // ✅ Show cursor when typing
         self.renderer.invalidate_from_line(cursor_line);
     }
 
-    fn handle_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) {
+    fn handle_paste(&mut self, text: &str, verbatim: bool) {
+        let first_row = self.editor.selection().range().0.row;
+        if verbatim {
+            self.editor.paste_verbatim(text);
+        } else {
+            self.editor.paste_with_mode(text, Some(&self.formatter));
+        }
+        self.notifications.dismiss_info();
+        self.auto_scroll = true;
+        self.renderer.invalidate_from_line(first_row);
+    }
+
+    /// Ctrl+X: delete the selections, returning their text for the clipboard
+    fn handle_cut(&mut self) -> Option<String> {
+        let first_row = self.editor.selections().first()?.range().0.row;
+        let text = self.editor.cut()?;
+        self.notifications.dismiss_info();
+        self.auto_scroll = true;
+        self.renderer.invalidate_from_line(first_row);
+        Some(text)
+    }
+
+    /// Returns the key's action, so the text the press also types can be
+    /// held back while a sequence waits and dropped once it runs
+    fn handle_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) -> KeyAction {
         let cursor_before = self.editor.cursor();
 
+        let event = key_event_from_egui(key, modifiers);
+        let dispatch = self
+            .pending_keys
+            .press(&self.keymap, &event, Instant::now());
+        if let Some(abandoned) = dispatch.abandoned {
+            self.handle_abandoned_keys(abandoned);
+        }
+        match &dispatch.action {
+            KeyAction::Run(command) => self.run_command(*command),
+            KeyAction::Wait => {}
+            KeyAction::Unbound => self.handle_editing_key(key, modifiers),
+        }
+
+        let cursor_after = self.editor.cursor();
+        if cursor_before != cursor_after {
+            self.auto_scroll = true;
+        }
+        dispatch.action
+    }
+
+    /// A key sequence that stopped short: run the binding it spelled out, or
+    /// type what its keys would have typed
+    fn handle_abandoned_keys(&mut self, abandoned: Abandoned) {
+        let text = std::mem::take(&mut self.pending_text);
+        match abandoned {
+            Abandoned::Run(command) => self.run_command(command),
+            Abandoned::Replay(_) if !text.is_empty() => self.handle_text_input(&text),
+            Abandoned::Replay(_) => {}
+        }
+    }
+
+    /// Cursor movement and basic editing keys (not rebindable)
+    fn handle_editing_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) {
         match key {
+            egui::Key::ArrowLeft if modifiers.ctrl || modifiers.alt => {
+                self.editor.move_to_previous_word();
+            }
+            egui::Key::ArrowRight if modifiers.ctrl || modifiers.alt => {
+                self.editor.move_to_next_word();
+            }
             egui::Key::ArrowLeft => {
                 self.editor.move_left();
             }
@@ -96,78 +582,643 @@ impl GuiApp {
                 self.editor.move_down();
             }
             egui::Key::Home => {
-                self.editor.move_to_line_start();
+                self.editor.move_to_line_indent_start();
             }
             egui::Key::End => {
                 self.editor.move_to_line_end();
             }
+            egui::Key::Escape if self.editor.clear_extra_cursors() => {
+                self.notifications.dismiss_info();
+            }
             egui::Key::Backspace => {
                 let cursor_line = self.editor.cursor().row;
-                self.editor.backspace();
-                self.status_message.clear();
+                self.editor.backspace_with_autopairs();
+                self.notifications.dismiss_info();
                 self.renderer
                     .invalidate_from_line(cursor_line.saturating_sub(1));
             }
             egui::Key::Delete => {
                 let cursor_line = self.editor.cursor().row;
                 self.editor.delete();
-                self.status_message.clear();
+                self.notifications.dismiss_info();
                 self.renderer.invalidate_line(cursor_line);
             }
+            egui::Key::Tab => {
+                let (start, end) = self.editor.selection().range();
+                if modifiers.shift {
+                    self.editor.outdent_selection();
+                } else if start.row != end.row {
+                    self.editor.indent_selection();
+                } else {
+                    self.editor.insert_indent();
+                }
+                self.notifications.dismiss_info();
+                self.renderer.invalidate_from_line(start.row);
+            }
             egui::Key::Enter => {
                 let cursor_line = self.editor.cursor().row;
                 self.editor.insert("\n");
-                self.status_message.clear();
+                self.notifications.dismiss_info();
                 self.renderer.invalidate_from_line(cursor_line);
             }
-            egui::Key::Z if modifiers.ctrl => {
+            _ => {}
+        }
+    }
+
+    /// Execute a keymap command
+    fn run_command(&mut self, command: Command) {
+        let cursor_line = self.editor.cursor().row;
+        let first_row = self.editor.selection().range().0.row;
+        self.command_history.record(command);
+
+        match command {
+            Command::Undo => {
                 if self.editor.can_undo() {
                     self.editor.undo();
-                    self.status_message = "Undo".to_string();
-                    self.renderer.invalidate_from_line(0);
+                    self.notifications.info("Undo");
                 }
             }
-            egui::Key::Y if modifiers.ctrl => {
+            Command::Redo => {
                 if self.editor.can_redo() {
                     self.editor.redo();
-                    self.status_message = "Redo".to_string();
-                    self.renderer.invalidate_from_line(0);
+                    self.notifications.info("Redo");
+                }
+            }
+            Command::Save => self.save_file(),
+            Command::Open => self.open_file(),
+            Command::GoToLastEdit => {
+                if self.editor.go_to_last_edit_location() {
+                    self.notifications.info("Last edit location");
+                }
+            }
+            Command::FormatCode => self.format_code(),
+            Command::ToggleComment => {
+                self.editor.toggle_comment();
+                self.renderer.invalidate_from_line(first_row);
+            }
+            Command::DeleteLine => {
+                self.editor.delete_line();
+                self.renderer
+                    .invalidate_from_line(first_row.saturating_sub(1));
+            }
+            Command::DuplicateLine => {
+                self.editor.duplicate_line();
+                self.renderer.invalidate_from_line(first_row);
+            }
+            Command::MoveLineUp => {
+                self.editor.move_line_up();
+                self.renderer
+                    .invalidate_from_line(first_row.saturating_sub(1));
+            }
+            Command::MoveLineDown => {
+                self.editor.move_line_down();
+                self.renderer.invalidate_from_line(first_row);
+            }
+            Command::DeleteToLineEnd => {
+                self.editor.delete_to_line_end();
+                self.renderer.invalidate_from_line(cursor_line);
+            }
+            Command::DeleteToLineStart => {
+                self.editor.delete_to_line_start();
+                self.renderer
+                    .invalidate_from_line(cursor_line.saturating_sub(1));
+            }
+            Command::TransposeChars => {
+                self.editor.transpose_chars();
+                self.renderer.invalidate_line(cursor_line);
+            }
+            Command::TransposeWords => {
+                self.editor.transpose_words();
+                self.renderer.invalidate_line(cursor_line);
+            }
+            Command::JumpToMatchingBracket => {
+                self.editor.jump_to_matching_bracket();
+            }
+            Command::SelectToBracket | Command::SelectToBracketInclusive => {
+                let inclusive = command == Command::SelectToBracketInclusive;
+                if !self.editor.select_to_bracket(inclusive) {
+                    self.notifications.info("No bracket to select to");
+                }
+            }
+            Command::DeleteToBracket | Command::DeleteToBracketInclusive => {
+                let inclusive = command == Command::DeleteToBracketInclusive;
+                if self.editor.delete_to_bracket(inclusive) {
+                    self.renderer
+                        .invalidate_from_line(first_row.saturating_sub(1));
+                } else {
+                    self.notifications.info("No bracket to delete to");
+                }
+            }
+            Command::UnwrapSurrounding => {
+                if self.editor.unwrap_surrounding() {
+                    self.renderer
+                        .invalidate_from_line(first_row.saturating_sub(1));
+                } else {
+                    self.notifications.info("No enclosing brackets or quotes");
+                }
+            }
+            Command::SelectInsideBrackets => self.select_text_object(TextObject::Brackets, false),
+            Command::SelectAroundBrackets => self.select_text_object(TextObject::Brackets, true),
+            Command::SelectInsideQuotes => self.select_text_object(TextObject::Quotes, false),
+            Command::SelectAroundQuotes => self.select_text_object(TextObject::Quotes, true),
+            Command::SelectInsideWord => self.select_text_object(TextObject::Word, false),
+            Command::SelectAroundWord => self.select_text_object(TextObject::Word, true),
+            Command::SelectInsideParagraph => self.select_text_object(TextObject::Paragraph, false),
+            Command::SelectAroundParagraph => self.select_text_object(TextObject::Paragraph, true),
+            Command::ExpandSelection => {
+                if !self.editor.expand_selection() {
+                    self.notifications.info("No larger syntax node");
+                }
+            }
+            Command::ShrinkSelection => {
+                self.editor.shrink_selection();
+            }
+            Command::AddCursorAbove | Command::AddCursorBelow => {
+                let above = command == Command::AddCursorAbove;
+                if self.editor.add_cursor_vertically(above) {
+                    self.notifications
+                        .info(format!("{} cursors", self.editor.cursor_count()));
+                }
+            }
+            Command::MoveToPreviousParagraph => self.editor.move_to_previous_paragraph(),
+            Command::MoveToNextParagraph => self.editor.move_to_next_paragraph(),
+            Command::SelectNextOccurrence => {
+                if !self.editor.select_next_occurrence() {
+                    self.notifications.info("No more occurrences");
+                } else if self.editor.cursor_count() > 1 {
+                    self.notifications
+                        .info(format!("{} selections", self.editor.cursor_count()));
+                }
+            }
+            Command::SelectAllOccurrences => {
+                self.notifications
+                    .info(match self.editor.select_all_occurrences() {
+                        0 => "Nothing to select".to_string(),
+                        count => format!("{} occurrences selected", count),
+                    });
+            }
+            Command::AddCursorsToLineEnds | Command::SplitSelectionIntoLines => {
+                let select_lines = command == Command::SplitSelectionIntoLines;
+                if self.editor.split_selection_into_lines(select_lines) {
+                    self.notifications
+                        .info(format!("{} cursors", self.editor.cursor_count()));
+                } else {
+                    self.notifications.info("Select several lines first");
+                }
+            }
+            Command::AlignCursors => {
+                let top_row = self.editor.selections()[0].range().0.row;
+                if self.editor.align_cursors() {
+                    self.renderer.invalidate_from_line(top_row);
+                } else {
+                    self.notifications.info("Cursors already aligned");
+                }
+            }
+            Command::ReflowParagraph => {
+                let width = self.settings.editor.reflow_width;
+                match self.editor.reflow_paragraph(width) {
+                    Some(row) => self.renderer.invalidate_from_line(row),
+                    None => {
+                        self.notifications.info("Nothing to reflow");
+                    }
+                }
+            }
+            Command::ToggleProfiler => self.profiler.toggle(),
+            Command::ToggleOutput => self.output_panel.toggle(),
+            Command::ToggleNotifications => self.notification_panel.toggle(),
+            Command::ToggleSplit => self.toggle_split(),
+            Command::FindInFiles => self.toggle_project_search(),
+            Command::ToggleScrollLock => match &mut self.split {
+                Some(split) => split.toggle_scroll_lock(),
+                None => {
+                    self.notifications
+                        .info("Open a split view (Ctrl+\\) to lock its scrolling");
+                }
+            },
+            Command::OpenSettings => self.settings_panel.open(),
+            Command::OpenKeybindings => self.keybindings_panel.open(),
+            Command::RepeatLastCommand => self.repeat_last_edit(),
+            Command::NextProblem => self.go_to_problem(true),
+            Command::PreviousProblem => self.go_to_problem(false),
+            Command::ShowCodeActions => self.show_code_actions(),
+            Command::OrganizeImports => {
+                self.notifications.info(if self.editor.organize_imports() {
+                    "✨ Imports organized".to_string()
+                } else {
+                    "Imports are already organized".to_string()
+                });
+            }
+            Command::RemoveBom => {
+                if self.editor.remove_bom() {
+                    self.editor.check_syntax();
+                    self.notifications.info("Byte order mark removed");
+                } else {
+                    self.notifications.info("The file has no byte order mark");
+                }
+            }
+            Command::UnifyLineEndings => {
+                let ending = self.editor.text_format().line_ending();
+                if self.editor.unify_line_endings() {
+                    self.editor.check_syntax();
+                    self.notifications
+                        .info(format!("Line endings converted to {}", ending.label()));
+                } else {
+                    self.notifications
+                        .info("Line endings are already consistent");
+                }
+            }
+            Command::NextConflict => self.go_to_conflict(true),
+            Command::PreviousConflict => self.go_to_conflict(false),
+            Command::AcceptOurs => self.resolve_conflict(cursor_line, ConflictResolution::Ours),
+            Command::AcceptTheirs => self.resolve_conflict(cursor_line, ConflictResolution::Theirs),
+            Command::AcceptBoth => self.resolve_conflict(cursor_line, ConflictResolution::Both),
+        }
+    }
+
+    fn go_to_conflict(&mut self, forward: bool) {
+        self.notifications
+            .info(match self.editor.go_to_conflict(forward) {
+                Some(_) => {
+                    self.auto_scroll = true;
+                    format!("{} merge conflicts", self.editor.conflicts().len())
+                }
+                None => "No merge conflicts".to_string(),
+            });
+    }
+
+    fn resolve_conflict(&mut self, row: usize, resolution: ConflictResolution) {
+        if self.editor.resolve_conflict(row, resolution) {
+            self.renderer.invalidate_from_line(row.saturating_sub(1));
+            let remaining = self.editor.conflicts().len();
+            self.notifications.info(format!(
+                "{} - {} conflicts left",
+                resolution.label(),
+                remaining
+            ));
+        } else {
+            self.notifications.info("No merge conflict at the cursor");
+        }
+    }
+
+    /// Open the 💡 menu with the actions available at the cursor
+    fn show_code_actions(&mut self) {
+        let context = self.editor.code_action_context();
+        let actions = self.code_actions.actions(&context);
+        if actions.is_empty() {
+            self.notifications.info("No code actions available");
+        } else {
+            self.code_action_menu.open(actions);
+        }
+    }
+
+    /// Whether the cursor line gets a 💡: some action edits it or fixes one of
+    /// its diagnostics (cached until the text or cursor row changes)
+    fn update_lightbulb(&mut self) {
+        let (version, row) = (self.editor.version(), self.editor.cursor().row);
+        let show = match self.lightbulb {
+            Some((v, r, show)) if v == version && r == row => show,
+            _ if self.editor.line_count() > LIGHTBULB_MAX_LINES => false,
+            _ => {
+                let context = self.editor.code_action_context();
+                self.code_actions
+                    .actions(&context)
+                    .iter()
+                    .any(|action| action.diagnostic.is_some() || action.touches(&context.line))
+            }
+        };
+        self.lightbulb = Some((version, row, show));
+        self.renderer.set_lightbulb_row(show.then_some(row));
+    }
+
+    /// While a key sequence waits: the keys that can come next and what they run
+    fn show_key_hints(&self, ctx: &egui::Context) {
+        let hints = self.pending_keys.hints(&self.keymap);
+        if hints.is_empty() {
+            return;
+        }
+        let typed: Vec<String> = self
+            .pending_keys
+            .chords()
+            .iter()
+            .map(|chord| chord.to_string())
+            .collect();
+        egui::Area::new(egui::Id::new("key_hints"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -36.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.strong(format!("{} …", typed.join(" ")));
+                    ui.separator();
+                    egui::Grid::new("key_hints_grid").show(ui, |ui| {
+                        for (rest, command) in hints {
+                            ui.monospace(rest.to_string());
+                            ui.label(command.label());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+    }
+
+    fn show_code_action_menu(&mut self, ctx: &egui::Context) {
+        if let Some(action) = self.code_action_menu.show(ctx) {
+            if self.editor.apply_code_action(&action) {
+                self.notifications.info(format!("✨ {}", action.title));
+            }
+        }
+    }
+
+    /// F8 / Shift+F8: jump to the next or previous diagnostic
+    fn go_to_problem(&mut self, forward: bool) {
+        let diagnostic = if forward {
+            self.editor.go_to_next_problem()
+        } else {
+            self.editor.go_to_prev_problem()
+        };
+        self.notifications.info(match diagnostic {
+            Some(diagnostic) => {
+                self.auto_scroll = true;
+                format!("{}: {}", diagnostic.severity.label(), diagnostic.message)
+            }
+            None => "No problems".to_string(),
+        });
+    }
+
+    /// Perform an edit and remember it for "Repeat Last Command"
+    fn run_edit_action(&mut self, action: EditAction) {
+        let first_row = self.editor.selection().range().0.row;
+        match action {
+            EditAction::Command(command) => {
+                self.run_command(command);
+                return;
+            }
+            EditAction::Transform(transform) => {
+                self.editor.transform_selection(transform);
+                self.renderer.invalidate_from_line(first_row);
+            }
+            EditAction::SortLines(options) => {
+                self.editor.sort_lines(options);
+            }
+            EditAction::ReverseLines => {
+                self.editor.reverse_lines();
+            }
+        }
+        self.command_history.record_edit(action);
+    }
+
+    fn repeat_last_edit(&mut self) {
+        match self.command_history.last_edit() {
+            Some(action) => {
+                self.run_edit_action(action);
+                self.notifications.info(format!("🔁 {}", action.label()));
+            }
+            None => {
+                self.notifications.info("Nothing to repeat");
+            }
+        }
+    }
+
+    fn select_text_object(&mut self, object: TextObject, around: bool) {
+        if !self.editor.select_text_object(object, around) {
+            self.notifications.info(match object {
+                TextObject::Word => "No word at the cursor".to_string(),
+                TextObject::Paragraph => "No paragraph at the cursor".to_string(),
+                TextObject::Brackets => "No enclosing brackets".to_string(),
+                TextObject::Quotes => "No enclosing string".to_string(),
+            });
+        }
+    }
+
+    /// Open a second view of the buffer beside the main one, or close it
+    fn toggle_split(&mut self) {
+        self.split = match self.split.take() {
+            Some(_) => None,
+            None => Some(SplitPane::new(self.settings.editor.font_size)),
+        };
+    }
+
+    /// The open file's directory, or the working directory for untitled
+    /// buffers
+    fn project_dir(&self) -> Option<PathBuf> {
+        self.current_file
+            .as_deref()
+            .and_then(Path::parent)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok())
+    }
+
+    /// Open or close Find in Files, searching from `project_dir`
+    fn toggle_project_search(&mut self) {
+        if !self.project_search.is_visible() {
+            if let Some(root) = self.project_dir() {
+                self.project_search.set_root(root);
+            }
+        }
+        self.project_search.toggle();
+    }
+
+    /// Open the file a Find in Files match is in and jump to its line
+    fn open_project_match(&mut self, found: ProjectMatch) {
+        if self.current_file.as_ref() != Some(&found.path) {
+            match std::fs::metadata(&found.path) {
+                Ok(metadata) => self.load_file_simple(&found.path, metadata.len()),
+                Err(e) => {
+                    self.notifications.error(format!(
+                        "Could not open {}: {}",
+                        found.path.display(),
+                        e
+                    ));
+                    return;
+                }
+            }
+        }
+        if self.current_file.as_ref() == Some(&found.path) {
+            self.editor.move_to_line(found.line);
+            self.auto_scroll = true;
+        }
+    }
+
+    fn handle_viewport_action(&mut self, action: ViewportAction) {
+        match action {
+            ViewportAction::ShowCodeActions => self.show_code_actions(),
+            ViewportAction::PlaceCursor { point, extend } => self.editor.click_at(point, extend),
+            ViewportAction::SelectTo(point) => self.editor.select_to(point),
+            ViewportAction::MoveSelection(point) => {
+                let first_row = self.editor.selection().range().0.row.min(point.row);
+                if self.editor.move_selection_to(point) {
+                    self.renderer.invalidate_from_line(first_row);
+                }
+            }
+            ViewportAction::SelectWordAt(point) => self.editor.select_word_at(point),
+            ViewportAction::SelectLines { anchor, head } => self.editor.select_lines(anchor, head),
+            ViewportAction::ResolveConflict(row, resolution) => {
+                self.resolve_conflict(row, resolution)
+            }
+            ViewportAction::SelectBlock { anchor, head } => {
+                self.editor.select_block(anchor, head);
+                if self.editor.cursor_count() > 1 {
+                    self.notifications
+                        .info(format!("{} cursors", self.editor.cursor_count()));
+                }
+            }
+            ViewportAction::JumpToLine(row) => {
+                let from = self.editor.cursor().row;
+                self.editor.jump_to_line(row);
+                let distance = row as isize - from as isize;
+                self.notifications
+                    .info(format!("Jumped {:+} lines", distance));
+                self.auto_scroll = true;
+            }
+        }
+    }
+
+    /// Run the formatter on a background thread; the result applies only if
+    /// the buffer hasn't changed meanwhile
+    fn format_code(&mut self) {
+        let Some(path) = self.current_file.clone() else {
+            self.notifications
+                .warn("Save file first to enable formatting");
+            return;
+        };
+        let invocation = match self.formatter.invocation(&path) {
+            Ok(invocation) => invocation,
+            Err(e) => {
+                self.notifications
+                    .error(format!("Format failed: {:?}", e))
+                    .action(NotificationAction::ShowOutput);
+                return;
+            }
+        };
+        if let Some(pending) = self.pending_format.take() {
+            self.progress.cancel(pending.task);
+        }
+
+        let handle = self
+            .progress
+            .start(format!("Formatting {}", Self::display_name(&path)));
+        let task = handle.id();
+        let (sender, result) = mpsc::channel();
+        let snapshot = self.editor.snapshot();
+        let thread_invocation = invocation.clone();
+        task::spawn(move || {
+            let output =
+                thread_invocation.run_cancellable(&snapshot.text(), &handle.cancel_handle());
+            sender.send(output).ok();
+        });
+        self.pending_format = Some(PendingFormat {
+            invocation,
+            version: self.editor.version(),
+            task,
+            result,
+        });
+    }
+
+    fn finish_format(&mut self, pending: PendingFormat, result: FormatResult) {
+        self.formatter.log_run(&pending.invocation, &result);
+        match result {
+            Ok(_) if self.editor.version() != pending.version => {
+                self.notifications
+                    .warn("The file changed while formatting; nothing was applied")
+                    .action(NotificationAction::RetryFormat);
+            }
+            Ok(formatted) => {
+                self.editor.apply_formatted(formatted);
+                self.renderer.invalidate_from_line(0);
+                self.notifications.info("✨ Code formatted successfully");
+            }
+            Err(FormatError::Cancelled) => {
+                self.notifications.info("Formatting cancelled");
+            }
+            Err(e) => {
+                self.notifications
+                    .error(format!("Format failed: {:?}", e))
+                    .action(NotificationAction::RetryFormat)
+                    .action(NotificationAction::ShowOutput);
+            }
+        }
+    }
+
+    /// Apply the results of background loads and formatting that finished
+    fn poll_background_tasks(&mut self) {
+        if let Some(pending) = &self.pending_load {
+            match pending.result.try_recv() {
+                Ok(result) => {
+                    let pending = self.pending_load.take().unwrap();
+                    match result {
+                        Ok(rope) => self.finish_load(&pending.path, pending.file_size, rope),
+                        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                            self.notifications.info("Loading cancelled");
+                        }
+                        Err(e) => {
+                            self.notifications.error(format!(
+                                "Could not read {}: {}",
+                                pending.path.display(),
+                                e
+                            ));
+                        }
+                    }
                 }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.pending_load = None,
             }
-            egui::Key::S if modifiers.ctrl => {
-                self.save_file();
-            }
-            egui::Key::O if modifiers.ctrl => {
-                self.open_file();
-            }
-            egui::Key::F if modifiers.ctrl && modifiers.shift => {
-                self.format_code();
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(picked) = self
+            .pending_pick
+            .as_ref()
+            .and_then(|pick| pick.try_recv().ok())
+        {
+            self.pending_pick = None;
+            if let Some((name, bytes)) = picked {
+                let path = PathBuf::from(name);
+                match Rope::from_reader(bytes.as_slice()) {
+                    Ok(rope) => self.finish_load(&path, bytes.len() as u64, rope),
+                    Err(e) => {
+                        self.notifications.error(format!(
+                            "Could not read {}: {}",
+                            path.display(),
+                            e
+                        ));
+                    }
+                }
             }
-            _ => {}
         }
 
-        let cursor_after = self.editor.cursor();
-        if cursor_before != cursor_after {
-            self.auto_scroll = true;
+        #[cfg(target_arch = "wasm32")]
+        if let Some(picked) = self
+            .pending_insert_pick
+            .as_ref()
+            .and_then(|pick| pick.try_recv().ok())
+        {
+            self.pending_insert_pick = None;
+            if let Some(picked) = picked {
+                self.insert_picked_file(picked);
+            }
         }
-    }
 
-    fn format_code(&mut self) {
-        if let Some(ref file_path) = self.current_file {
-            match self.editor.format(&self.formatter, Some(file_path)) {
-                Ok(_) => {
-                    self.status_message = "✨ Code formatted successfully".to_string();
-                    self.renderer.invalidate_from_line(0);
-                }
-                Err(e) => {
-                    self.status_message = format!("⚠️ Format failed: {}", e);
+        if let Some(pending) = &self.pending_format {
+            match pending.result.try_recv() {
+                Ok(result) => {
+                    let pending = self.pending_format.take().unwrap();
+                    self.finish_format(pending, result);
                 }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.pending_format = None,
             }
-        } else {
-            self.status_message = "⚠️ Save file first to enable formatting".to_string();
         }
+
+        self.project_search.poll(&self.progress, Instant::now());
+    }
+
+    fn display_name(path: &Path) -> &str {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     fn open_file(&mut self) {
         if let Some(path) = rfd::FileDialog::new()
             .add_filter(
@@ -185,77 +1236,153 @@ impl GuiApp {
                     const MAX_SIZE: u64 = 100_000_000;
 
                     if file_size > MAX_SIZE {
-                        self.status_message = format!(
-                            "⚠️ File too large: {:.2} MB (max: 100 MB)",
+                        self.notifications.warn(format!(
+                            "File too large: {:.2} MB (max: 100 MB)",
                             file_size as f64 / 1_000_000.0
-                        );
+                        ));
                         return;
                     }
 
                     self.load_file_simple(&path, file_size);
                 }
                 Err(e) => {
-                    self.status_message = format!("❌ Error: {}", e);
+                    self.notifications
+                        .error(format!("Could not open {}: {}", path.display(), e));
                 }
             }
         }
     }
 
+    /// The browser's picker hands over the file's contents (picked up by
+    /// `poll_background_tasks`)
+    #[cfg(target_arch = "wasm32")]
+    fn open_file(&mut self) {
+        self.pending_pick = Some(super::web::pick_file());
+    }
+
     fn load_file_simple(&mut self, path: &PathBuf, file_size: u64) {
-        match read_file(path) {
-            Ok(contents) => {
-                let line_count = contents.lines().count();
-                self.editor = Editor::from_text(&contents);
-                self.editor.set_file_path(Some(path.clone()));
-                self.current_file = Some(path.clone());
-                self.renderer.invalidate_from_line(0);
+        if file_size >= BACKGROUND_LOAD_BYTES {
+            self.load_file_in_background(path, file_size);
+            return;
+        }
 
-                let filename = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unknown");
-                self.status_message = format!(
-                    "✅ Opened: {} ({:.1} KB, {} lines)",
-                    filename,
-                    file_size as f64 / 1000.0,
-                    line_count
-                );
-            }
+        match load_rope(path) {
+            Ok(rope) => self.finish_load(path, file_size, rope),
             Err(e) => {
-                self.status_message = format!("❌ Error: {}", e);
+                self.notifications
+                    .error(format!("Could not read {}: {}", path.display(), e));
             }
         }
     }
 
+    /// Read a large file on a background thread, with progress in the status bar
+    fn load_file_in_background(&mut self, path: &Path, file_size: u64) {
+        if let Some(pending) = self.pending_load.take() {
+            self.progress.cancel(pending.task);
+        }
+
+        let handle = self
+            .progress
+            .start(format!("Loading {}", Self::display_name(path)));
+        let task = handle.id();
+        let (sender, result) = mpsc::channel();
+        let thread_path = path.to_path_buf();
+        task::spawn(move || {
+            sender
+                .send(read_file_with_progress(&thread_path, &handle))
+                .ok();
+        });
+        self.pending_load = Some(PendingLoad {
+            path: path.to_path_buf(),
+            file_size,
+            task,
+            result,
+        });
+    }
+
+    fn finish_load(&mut self, path: &Path, file_size: u64, rope: Rope) {
+        let mut editor = Editor::from_rope(rope);
+        editor.set_file_path(Some(path.to_path_buf()));
+        self.editor.replace(editor);
+        self.configure_editor();
+        self.editor.check_syntax();
+        self.current_file = Some(path.to_path_buf());
+        self.disk_modified = Self::modified_time(path);
+        self.renderer.invalidate_from_line(0);
+
+        self.notifications.info(format!(
+            "✅ Opened: {} ({:.1} KB, {} lines)",
+            Self::display_name(path),
+            file_size as f64 / 1000.0,
+            self.editor.line_count()
+        ));
+        self.warn_about_text_format();
+    }
+
+    /// Offer to clean up a byte order mark or mixed line endings
+    fn warn_about_text_format(&mut self) {
+        let format = self.editor.text_format();
+        if format.bom {
+            self.notifications
+                .warn("This file starts with a byte order mark")
+                .action(NotificationAction::RemoveBom);
+        }
+        if format.is_mixed() {
+            self.notifications
+                .warn(format!(
+                    "This file mixes line endings ({} LF, {} CRLF)",
+                    format.lf, format.crlf
+                ))
+                .action(NotificationAction::UnifyLineEndings);
+        }
+    }
+
     /// 🚀 PERFORMANCE-FIXED: Save file using Rope directly (no string conversion!)
+    #[cfg(not(target_arch = "wasm32"))]
     fn save_file(&mut self) {
         if let Some(ref path) = self.current_file.clone() {
             // Format if formatter is available
-            if self.formatter.find_provider(&path).is_some() {
-                match self.editor.format(&self.formatter, Some(&path)) {
+            if self.settings.editor.format_on_save && self.formatter.find_provider(path).is_some()
+            {
+                match self.editor.format(&self.formatter, Some(path)) {
                     Ok(_) => {}
                     Err(e) => {
-                        self.status_message = format!("⚠️ Format failed: {}, saving anyway", e);
+                        self.notifications
+                            .warn(format!("Format failed: {}, saving anyway", e))
+                            .action(NotificationAction::ShowOutput);
                     }
                 }
             }
 
+            // A file's first save takes the project's line ending; files
+            // already on disk keep theirs
+            if !path.exists() {
+                if let Some(ending) = self.editorconfig.line_ending {
+                    self.editor.convert_line_endings(ending);
+                }
+            }
+
             // 🚀 CRITICAL FIX: Write directly from Rope without converting to String!
             // OLD CODE:
             // match crate::write_file(&path, &self.editor.text()) {  // ❌ Converts entire file!
 
             // NEW CODE: Use efficient rope writer
-            match write_file_from_rope(&path, self.editor.buffer().rope()) {
+            match write_file_from_rope(path, self.editor.buffer().rope()) {
                 Ok(_) => {
                     let filename = path
                         .file_name()
                         .and_then(|n| n.to_str())
                         .unwrap_or("Unknown");
-                    self.status_message = format!("💾 Saved: {}", filename);
+                    self.notifications.info(format!("💾 Saved: {}", filename));
+                    self.disk_modified = Self::modified_time(path);
+                    self.editor.mark_saved();
+                    self.editor.check_syntax();
                     self.renderer.invalidate_from_line(0);
                 }
                 Err(e) => {
-                    self.status_message = format!("❌ Error: {}", e);
+                    self.notifications
+                        .error(format!("Could not save {}: {}", path.display(), e))
+                        .action(NotificationAction::RetrySave);
                 }
             }
         } else {
@@ -264,6 +1391,7 @@ impl GuiApp {
     }
 
     /// 🚀 PERFORMANCE-FIXED: Save as using Rope directly
+    #[cfg(not(target_arch = "wasm32"))]
     fn save_file_as(&mut self) {
         if let Some(path) = rfd::FileDialog::new()
             .add_filter("Text Files", &["txt"])
@@ -273,6 +1401,13 @@ impl GuiApp {
             .add_filter("All Files", &["*"])
             .save_file()
         {
+            self.editorconfig = EditorConfig::for_path(&path);
+            if !path.exists() {
+                if let Some(ending) = self.editorconfig.line_ending {
+                    self.editor.convert_line_endings(ending);
+                }
+            }
+
             // 🚀 CRITICAL FIX: Write directly from Rope
             // OLD CODE: match crate::write_file(&path, &self.editor.text()) {
             // NEW CODE:
@@ -284,55 +1419,426 @@ impl GuiApp {
                         .file_name()
                         .and_then(|n| n.to_str())
                         .unwrap_or("Unknown");
-                    self.status_message = format!("💾 Saved as: {}", filename);
+                    self.notifications
+                        .info(format!("💾 Saved as: {}", filename));
+                    self.disk_modified = Self::modified_time(&path);
+                    self.editor.mark_saved();
                 }
                 Err(e) => {
-                    self.status_message = format!("❌ Error: {}", e);
+                    self.notifications
+                        .error(format!("Could not save {}: {}", path.display(), e))
+                        .action(NotificationAction::RetrySave);
                 }
             }
         }
     }
 
+    /// The browser can't write files: offer the buffer as a download instead
+    #[cfg(target_arch = "wasm32")]
+    fn save_file(&mut self) {
+        let name = self
+            .current_file
+            .as_deref()
+            .map_or("untitled.txt", Self::display_name)
+            .to_string();
+        match super::web::download(&name, &self.editor.text()) {
+            Ok(()) => {
+                self.notifications.info(format!("💾 Downloaded: {}", name));
+                self.editor.mark_saved();
+            }
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not save {}: {:?}", name, e))
+                    .action(NotificationAction::RetrySave);
+            }
+        }
+    }
+
+    /// The browser asks where downloads go, so this is just `save_file`
+    #[cfg(target_arch = "wasm32")]
+    fn save_file_as(&mut self) {
+        self.save_file();
+    }
+
+    /// Write the selected text to a new file, streamed from the buffer (the
+    /// buffer keeps its own file)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_selection_as(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("All Files", &["*"])
+            .save_file()
+        else {
+            return;
+        };
+        match write_file_from_chunks(&path, self.editor.selected_chunks()) {
+            Ok(()) => {
+                self.notifications.info(format!(
+                    "💾 Saved selection as: {}",
+                    Self::display_name(&path)
+                ));
+            }
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not save {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// The selected text as a download
+    #[cfg(target_arch = "wasm32")]
+    fn save_selection_as(&mut self) {
+        let name = "selection.txt";
+        match super::web::download(name, &self.editor.selected_text()) {
+            Ok(()) => {
+                self.notifications.info(format!("💾 Downloaded: {}", name));
+            }
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not save {}: {:?}", name, e));
+            }
+        }
+    }
+
+    /// Pick a file and insert its text at the cursor, asking first when it's large
+    #[cfg(not(target_arch = "wasm32"))]
+    fn insert_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("All Files", &["*"])
+            .pick_file()
+        else {
+            return;
+        };
+        match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.len() > INSERT_FILE_WARN_BYTES => {
+                self.warn_about_large_insert(Self::display_name(&path), metadata.len());
+                self.pending_insert = Some(path);
+            }
+            Ok(_) => self.insert_file_at_cursor(&path),
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not open {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn insert_pending_file(&mut self) {
+        if let Some(path) = self.pending_insert.take() {
+            self.insert_file_at_cursor(&path);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn insert_file_at_cursor(&mut self, path: &Path) {
+        match read_file_decoded(path) {
+            Ok((text, encoding)) => self.insert_text(Self::display_name(path), &text, encoding),
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not read {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// The browser's picker hands over the file's contents (picked up by
+    /// `poll_background_tasks`)
+    #[cfg(target_arch = "wasm32")]
+    fn insert_file(&mut self) {
+        self.pending_insert_pick = Some(super::web::pick_file());
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn insert_picked_file(&mut self, (name, bytes): super::web::PickedFile) {
+        if bytes.len() as u64 > INSERT_FILE_WARN_BYTES {
+            self.warn_about_large_insert(&name, bytes.len() as u64);
+            self.pending_insert = Some((name, bytes));
+            return;
+        }
+        let (text, encoding) = decode_text(bytes);
+        self.insert_text(&name, &text, encoding);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn insert_pending_file(&mut self) {
+        if let Some((name, bytes)) = self.pending_insert.take() {
+            let (text, encoding) = decode_text(bytes);
+            self.insert_text(&name, &text, encoding);
+        }
+    }
+
+    fn warn_about_large_insert(&mut self, name: &str, file_size: u64) {
+        self.notifications
+            .warn(format!(
+                "{} is {:.1} MB; inserting it may make editing slow",
+                name,
+                file_size as f64 / 1_000_000.0
+            ))
+            .action(NotificationAction::InsertAnyway);
+    }
+
+    /// Paste a file's decoded text as is, saying how it was decoded unless
+    /// it was plain UTF-8
+    fn insert_text(&mut self, name: &str, text: &str, encoding: TextEncoding) {
+        self.handle_paste(text, true);
+        let lines = text.lines().count();
+        match encoding {
+            TextEncoding::Utf8 => {
+                self.notifications
+                    .info(format!("📥 Inserted {} ({} lines)", name, lines));
+            }
+            TextEncoding::Latin1 => {
+                self.notifications.warn(format!(
+                    "📥 Inserted {} ({} lines); it isn't valid UTF-8, so it was read as {}",
+                    name,
+                    lines,
+                    encoding.label()
+                ));
+            }
+            _ => {
+                self.notifications.info(format!(
+                    "📥 Inserted {} ({} lines, converted from {})",
+                    name,
+                    lines,
+                    encoding.label()
+                ));
+            }
+        }
+    }
+
     fn new_file(&mut self) {
-        self.editor = Editor::new();
+        self.editor.replace(Editor::new());
+        self.configure_editor();
         self.current_file = None;
+        self.disk_modified = None;
         self.renderer.invalidate_from_line(0);
-        self.status_message = "📄 New file".to_string();
+        self.notifications.info("📄 New file");
+    }
+
+    /// Pick where a new file goes (starting in `project_dir`) and start it
+    /// in the project's style, so its first save changes nothing but the
+    /// text typed
+    #[cfg(not(target_arch = "wasm32"))]
+    fn new_file_in_project(&mut self) {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(dir) = self.project_dir() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(path) = dialog.save_file() {
+            self.start_new_file(&path);
+        }
+    }
+
+    /// A buffer for a file that isn't on disk yet: the language's template
+    /// (from the settings) with the line ending `.editorconfig` asks for, and
+    /// its indentation applied by `configure_editor`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_new_file(&mut self, path: &Path) {
+        let mut probe = Editor::new();
+        probe.set_file_associations(self.settings.file_associations.clone());
+        probe.set_file_path(Some(path.to_path_buf()));
+        let template = probe
+            .language()
+            .and_then(|language| self.settings.editor.file_template(language.name));
+        let ending = EditorConfig::for_path(path)
+            .line_ending
+            .unwrap_or(LineEnding::Lf);
+        let mut editor = match template {
+            Some(template) => Editor::from_template(template, ending),
+            None => Editor::new(),
+        };
+        editor.set_file_path(Some(path.to_path_buf()));
+        editor.mark_dirty();
+
+        self.editor.replace(editor);
+        self.configure_editor();
+        self.current_file = Some(path.to_path_buf());
+        self.disk_modified = None;
+        self.auto_scroll = true;
+        self.renderer.invalidate_from_line(0);
+        self.notifications
+            .info(format!("📄 New file: {}", Self::display_name(path)));
+    }
+
+    /// Running tasks, right to left: a progress bar (or spinner while the
+    /// amount of work is unknown) and a button to cancel
+    fn show_progress(&mut self, ui: &mut egui::Ui) {
+        for task in self.progress.tasks() {
+            ui.separator();
+            if ui
+                .add_enabled(!task.cancelled, egui::Button::new("✕").small())
+                .on_hover_text("Cancel")
+                .clicked()
+            {
+                self.progress.cancel(task.id);
+            }
+            let name = if task.cancelled {
+                format!("{} (cancelling)", task.name)
+            } else {
+                task.name
+            };
+            match task.fraction {
+                Some(fraction) => {
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(180.0)
+                            .text(name),
+                    );
+                }
+                None => {
+                    ui.spinner();
+                    ui.label(name);
+                }
+            }
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Notice the open file changing on disk (checked once a second): reload
+    /// it if there are no unsaved edits, otherwise offer to
+    fn check_disk_changes(&mut self) {
+        if self.last_disk_check.elapsed() < DISK_CHECK_INTERVAL {
+            return;
+        }
+        self.last_disk_check = Instant::now();
+
+        let (Some(path), Some(known)) = (self.current_file.clone(), self.disk_modified) else {
+            return;
+        };
+        match Self::modified_time(&path) {
+            Some(modified) if modified != known => {
+                self.disk_modified = Some(modified);
+                if self.editor.is_dirty() {
+                    self.notifications
+                        .warn(format!(
+                            "{} changed on disk; you have unsaved edits",
+                            path.display()
+                        ))
+                        .action(NotificationAction::ReloadFile);
+                } else {
+                    self.reload_file();
+                }
+            }
+            Some(_) => {}
+            None => {
+                self.disk_modified = None;
+                self.notifications
+                    .warn(format!("{} was deleted or moved", path.display()));
+            }
+        }
+    }
+
+    /// Replace the buffer with the file's contents on disk
+    fn reload_file(&mut self) {
+        let Some(path) = self.current_file.clone() else {
+            return;
+        };
+        let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        self.load_file_simple(&path, file_size);
+        self.notifications
+            .info(format!("🔄 Reloaded {} from disk", path.display()));
+    }
+
+    fn run_notification_action(&mut self, action: NotificationAction) {
+        match action {
+            NotificationAction::RetrySave => self.save_file(),
+            NotificationAction::RetryFormat => self.format_code(),
+            NotificationAction::ReloadFile => self.reload_file(),
+            NotificationAction::ShowOutput => self.output_panel.open(),
+            NotificationAction::OpenSettings => self.settings_panel.open(),
+            NotificationAction::OpenKeybindings => self.keybindings_panel.open(),
+            NotificationAction::RemoveBom => self.run_command(Command::RemoveBom),
+            NotificationAction::UnifyLineEndings => self.run_command(Command::UnifyLineEndings),
+            NotificationAction::InsertAnyway => self.insert_pending_file(),
+        }
     }
 }
 
 impl eframe::App for GuiApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_start = Instant::now();
+
         // ✅ Only blink cursor if user hasn't typed for 800ms (500ms grace + 300ms delay before blink)
         let is_typing = self.last_input_time.elapsed().as_millis() < 800;
-        
-        if !is_typing && self.last_blink.elapsed().as_millis() > 500 {
+
+        if !is_typing
+            && self.settings.editor.cursor_blink
+            && self.last_blink.elapsed().as_millis() > 500
+        {
             self.cursor_blink = !self.cursor_blink;
             self.last_blink = Instant::now();
-        } else if is_typing {
-            // ✅ Keep cursor visible while typing
+        } else if is_typing || !self.settings.editor.cursor_blink {
+            // ✅ Keep cursor visible while typing (or always, with blinking off)
             self.cursor_blink = true;
         }
         ctx.request_repaint();
+        self.check_disk_changes();
+        self.poll_background_tasks();
+
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.write_hot_exit();
+        }
+
+        // Typing into a dialog's text field or recording a shortcut must not
+        // also edit the buffer
+        let dialog_has_focus = ctx.wants_keyboard_input()
+            || self.keybindings_panel.is_recording()
+            || self.code_action_menu.is_open();
+
+        if let Some(abandoned) = self.pending_keys.expire(&self.keymap, Instant::now()) {
+            self.handle_abandoned_keys(abandoned);
+        }
 
+        // Text events arriving in one frame (fast typing, key repeat) are
+        // applied as one edit; anything else flushes the text typed before it.
+        // The text of a key that went to a binding follows its key event
+        let mut typed = String::new();
+        let mut typed_events = 0;
+        let mut bound_key = None;
+        let mut copied = None;
         ctx.input(|i| {
-            for event in &i.events {
+            for event in i.events.iter().filter(|_| !dialog_has_focus) {
+                if let egui::Event::Text(text) = event {
+                    match bound_key.take() {
+                        Some(KeyAction::Wait) => self.pending_text.push_str(text),
+                        Some(KeyAction::Run(_)) => {}
+                        _ => {
+                            typed.push_str(text);
+                            typed_events += 1;
+                        }
+                    }
+                    continue;
+                }
+                if !typed.is_empty() {
+                    self.handle_text_input(&std::mem::take(&mut typed));
+                }
                 match event {
-                    egui::Event::Text(text) => {
-                        self.handle_text_input(text);
+                    egui::Event::Paste(text) => {
+                        // Ctrl+Shift+V pastes without re-indenting
+                        self.handle_paste(text, i.modifiers.shift);
                     }
+                    egui::Event::Copy => copied = Some(self.editor.selected_text()),
+                    egui::Event::Cut => copied = self.handle_cut(),
                     egui::Event::Key {
                         key,
                         pressed: true,
                         modifiers,
                         ..
                     } => {
-                        self.handle_key(*key, *modifiers);
+                        bound_key = Some(self.handle_key(*key, *modifiers));
                     }
                     _ => {}
                 }
             }
         });
+        if !typed.is_empty() {
+            self.handle_text_input(&typed);
+        }
+        // Nothing selected copies nothing (the clipboard keeps its text)
+        if let Some(text) = copied.filter(|text| !text.is_empty()) {
+            ctx.copy_text(text);
+        }
 
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -341,10 +1847,19 @@ impl eframe::App for GuiApp {
                         self.new_file();
                         ui.close_menu();
                     }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("📄 New File...").clicked() {
+                        self.new_file_in_project();
+                        ui.close_menu();
+                    }
                     if ui.button("📂 Open (Ctrl+O)").clicked() {
                         self.open_file();
                         ui.close_menu();
                     }
+                    if ui.button("📥 Insert File...").clicked() {
+                        self.insert_file();
+                        ui.close_menu();
+                    }
                     if ui.button("💾 Save (Ctrl+S)").clicked() {
                         self.save_file();
                         ui.close_menu();
@@ -353,6 +1868,14 @@ impl eframe::App for GuiApp {
                         self.save_file_as();
                         ui.close_menu();
                     }
+                    let has_selection = self.editor.selection_label().is_some();
+                    if ui
+                        .add_enabled(has_selection, egui::Button::new("💾 Save Selection As..."))
+                        .clicked()
+                    {
+                        self.save_selection_as();
+                        ui.close_menu();
+                    }
                 });
 
                 ui.menu_button("Edit", |ui| {
@@ -361,7 +1884,6 @@ impl eframe::App for GuiApp {
                         .clicked()
                     {
                         self.editor.undo();
-                        self.renderer.invalidate_from_line(0);
                         ui.close_menu();
                     }
                     if ui
@@ -369,7 +1891,101 @@ impl eframe::App for GuiApp {
                         .clicked()
                     {
                         self.editor.redo();
-                        self.renderer.invalidate_from_line(0);
+                        ui.close_menu();
+                    }
+                    let last_edit = self.command_history.last_edit();
+                    let repeat_label = match last_edit {
+                        Some(action) => format!("🔁 Repeat {} (Ctrl+Shift+.)", action.label()),
+                        None => "🔁 Repeat Last Command (Ctrl+Shift+.)".to_string(),
+                    };
+                    if ui
+                        .add_enabled(last_edit.is_some(), egui::Button::new(repeat_label))
+                        .clicked()
+                    {
+                        self.repeat_last_edit();
+                        ui.close_menu();
+                    }
+                    if ui.button("💡 Code Actions (Ctrl+.)").clicked() {
+                        self.show_code_actions();
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    for (label, object, around) in [
+                        (
+                            "Select Inside Brackets (Alt+[)",
+                            TextObject::Brackets,
+                            false,
+                        ),
+                        (
+                            "Select Around Brackets (Alt+Shift+[)",
+                            TextObject::Brackets,
+                            true,
+                        ),
+                        ("Select Inside Quotes (Alt+')", TextObject::Quotes, false),
+                        (
+                            "Select Around Quotes (Alt+Shift+')",
+                            TextObject::Quotes,
+                            true,
+                        ),
+                        ("Select Word (Alt+W)", TextObject::Word, false),
+                        ("Select Paragraph (Alt+P)", TextObject::Paragraph, false),
+                    ] {
+                        if ui.button(label).clicked() {
+                            self.select_text_object(object, around);
+                            ui.close_menu();
+                        }
+                    }
+                    ui.menu_button("Transform Case", |ui| {
+                        for transform in Transform::ALL {
+                            if ui.button(transform.label()).clicked() {
+                                self.run_edit_action(EditAction::Transform(transform));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Sort Lines", |ui| {
+                        let sorts = [
+                            ("Sort Ascending", SortOptions::default()),
+                            (
+                                "Sort Descending",
+                                SortOptions {
+                                    ascending: false,
+                                    ..SortOptions::default()
+                                },
+                            ),
+                            (
+                                "Sort Ascending (Case Insensitive)",
+                                SortOptions {
+                                    case_insensitive: true,
+                                    ..SortOptions::default()
+                                },
+                            ),
+                            (
+                                "Sort Unique (Remove Duplicates)",
+                                SortOptions {
+                                    unique: true,
+                                    ..SortOptions::default()
+                                },
+                            ),
+                        ];
+                        for (label, options) in sorts {
+                            if ui.button(label).clicked() {
+                                self.run_edit_action(EditAction::SortLines(options));
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.button("Reverse Lines").clicked() {
+                            self.run_edit_action(EditAction::ReverseLines);
+                            ui.close_menu();
+                        }
+                    });
+                    if ui
+                        .button("Go to Matching Bracket (Ctrl+Shift+\\)")
+                        .clicked()
+                    {
+                        self.editor.jump_to_matching_bracket();
                         ui.close_menu();
                     }
 
@@ -386,6 +2002,75 @@ impl eframe::App for GuiApp {
                         self.format_code();
                         ui.close_menu();
                     }
+                    if ui
+                        .add_enabled(
+                            self.editor.language().is_some(),
+                            egui::Button::new("📦 Organize Imports (Alt+Shift+O)"),
+                        )
+                        .clicked()
+                    {
+                        self.run_edit_action(EditAction::Command(Command::OrganizeImports));
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("View", |ui| {
+                    let label = if self.profiler.is_visible() {
+                        "⏱ Hide Profiler (F12)"
+                    } else {
+                        "⏱ Show Profiler (F12)"
+                    };
+                    if ui.button(label).clicked() {
+                        self.profiler.toggle();
+                        ui.close_menu();
+                    }
+                    let label = if self.output_panel.is_visible() {
+                        "📜 Hide Output (Ctrl+Shift+U)"
+                    } else {
+                        "📜 Show Output (Ctrl+Shift+U)"
+                    };
+                    if ui.button(label).clicked() {
+                        self.output_panel.toggle();
+                        ui.close_menu();
+                    }
+                    let label = if self.notification_panel.is_visible() {
+                        "🔔 Hide Notifications (Ctrl+Shift+N)"
+                    } else {
+                        "🔔 Show Notifications (Ctrl+Shift+N)"
+                    };
+                    if ui.button(label).clicked() {
+                        self.notification_panel.toggle();
+                        ui.close_menu();
+                    }
+                    let label = if self.split.is_some() {
+                        "📑 Close Split View (Ctrl+\\)"
+                    } else {
+                        "📑 Split View (Ctrl+\\)"
+                    };
+                    if ui.button(label).clicked() {
+                        self.toggle_split();
+                        ui.close_menu();
+                    }
+                    if ui.button("🔎 Find in Files (Ctrl+Alt+F)").clicked() {
+                        self.toggle_project_search();
+                        ui.close_menu();
+                    }
+                    if ui.button("🗂 Change File Association...").clicked() {
+                        self.open_association_dialog();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("⚙ Settings... (Ctrl+,)").clicked() {
+                        self.settings_panel.open();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("⌨ Keyboard Shortcuts... (Ctrl+Shift+,)")
+                        .clicked()
+                    {
+                        self.keybindings_panel.open();
+                        ui.close_menu();
+                    }
                 });
 
                 ui.separator();
@@ -395,33 +2080,175 @@ impl eframe::App for GuiApp {
                     .and_then(|p| p.file_name())
                     .and_then(|n| n.to_str())
                     .unwrap_or("Untitled");
-                ui.label(format!("📝 {}", filename));
+                let dirty_marker = if self.editor.is_dirty() { " ●" } else { "" };
+                ui.label(format!("📝 {}{}", filename, dirty_marker));
             });
         });
 
+        let text_format = self.editor.text_format();
+        let mut format_fix = None;
         egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
-            let cursor = self.editor.cursor();
-            let status = if !self.status_message.is_empty() {
-                self.status_message.clone()
-            } else {
-                format!(
-                    "Line {}, Col {} | {} lines",
-                    cursor.row + 1,
-                    cursor.column + 1,
+            ui.horizontal(|ui| {
+                let mut status = format!(
+                    "{} | {} lines",
+                    self.editor.cursor_position_label(),
                     self.editor.line_count()
-                )
-            };
-            ui.label(status);
+                );
+                if let Some(selection) = self.editor.selection_label() {
+                    status.push_str(&format!(" | {}", selection));
+                }
+                if self.editor.cursor_count() > 1 {
+                    status.push_str(&format!(" | {} cursors", self.editor.cursor_count()));
+                }
+                ui.label(status);
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let count = self.notifications.history().len();
+                    if ui
+                        .selectable_label(
+                            self.notification_panel.is_visible(),
+                            format!("🔔 {}", count),
+                        )
+                        .on_hover_text("Notifications (Ctrl+Shift+N)")
+                        .clicked()
+                    {
+                        self.notification_panel.toggle();
+                    }
+
+                    let ending = text_format.line_ending();
+                    if text_format.is_mixed() {
+                        let hover = format!(
+                            "{} LF, {} CRLF lines - click to convert all to {}",
+                            text_format.lf,
+                            text_format.crlf,
+                            ending.label()
+                        );
+                        if ui.button("⚠ Mixed EOL").on_hover_text(hover).clicked() {
+                            format_fix = Some(Command::UnifyLineEndings);
+                        }
+                    } else {
+                        ui.label(ending.label());
+                    }
+                    if text_format.bom
+                        && ui
+                            .button("⚠ BOM")
+                            .on_hover_text("Starts with a byte order mark - click to remove it")
+                            .clicked()
+                    {
+                        format_fix = Some(Command::RemoveBom);
+                    }
+                    self.show_progress(ui);
+                });
+            });
         });
+        if let Some(command) = format_fix {
+            self.run_command(command);
+        }
+
+        self.editor.flush_events();
+        for event in self.editor_events.try_iter() {
+            match event {
+                EditorEvent::Edited(edit) => self.renderer.apply_edit(&edit),
+                EditorEvent::Opened { .. } => self.renderer.invalidate_from_line(0),
+                EditorEvent::CursorMoved { .. } | EditorEvent::Saved { .. } => {}
+            }
+        }
+
+        let soft_wrap = self
+            .modeline
+            .soft_wrap
+            .unwrap_or(self.settings.editor.soft_wrap);
+        let wrap_width = if soft_wrap {
+            self.renderer.wrap_columns()
+        } else {
+            None
+        };
+        self.editor.set_soft_wrap(wrap_width);
+        self.update_lightbulb();
+        let mut split_action = None;
+        let mut close_split = false;
+        if let Some(split) = &mut self.split {
+            egui::SidePanel::right("split_pane")
+                .resizable(true)
+                .default_width(ctx.screen_rect().width() / 2.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let locked = split.is_scroll_locked();
+                        if ui
+                            .selectable_label(locked, if locked { "🔒" } else { "🔓" })
+                            .on_hover_text("Scroll with the main pane (Ctrl+Alt+S)")
+                            .clicked()
+                        {
+                            split.toggle_scroll_lock();
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            close_split =
+                                ui.small_button("✕").on_hover_text("Close split").clicked();
+                        });
+                    });
+                    split_action = split.renderer.render_with_highlighting(
+                        ui,
+                        &self.editor,
+                        self.cursor_blink,
+                        false,
+                    );
+                });
+        }
+        if close_split {
+            self.split = None;
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.renderer.render_with_highlighting(
+            let action = self.renderer.render_with_highlighting(
                 ui,
                 &self.editor,
                 self.cursor_blink,
                 self.auto_scroll,
             );
             self.auto_scroll = false;
+
+            if let Some(action) = action {
+                self.handle_viewport_action(action);
+            }
         });
+        if let Some(action) = split_action {
+            self.handle_viewport_action(action);
+        }
+        if let Some(split) = &mut self.split {
+            let (main, other) = split.sync_scroll(
+                self.renderer.scroll_offset(),
+                split.renderer.scroll_offset(),
+            );
+            if let Some(offset) = main {
+                self.renderer.set_scroll_offset(offset);
+            }
+            if let Some(offset) = other {
+                split.renderer.set_scroll_offset(offset);
+            }
+        }
+
+        self.profiler
+            .record(frame_start.elapsed(), self.renderer.stats());
+        if typed_events > 0 {
+            self.profiler
+                .record_typing(frame_start.elapsed(), typed_events);
+        }
+        self.profiler.show(ctx);
+        if self.output_panel.is_visible() {
+            self.output_panel.show(ctx, &self.formatter.output_log());
+        }
+        if self.project_search.is_visible() {
+            if let Some(found) = self.project_search.show(ctx, &self.progress) {
+                self.open_project_match(found);
+            }
+        }
+        self.show_association_dialog(ctx);
+        self.show_settings_panel(ctx);
+        self.show_keybindings_panel(ctx);
+        self.show_code_action_menu(ctx);
+        self.show_key_hints(ctx);
+        if let Some(action) = self.notification_panel.show(ctx, &mut self.notifications) {
+            self.run_notification_action(action);
+        }
     }
 }
@@ -0,0 +1,105 @@
+use crate::editor::{CodeAction, CodeActionKind};
+use egui::Color32;
+
+/// 💡 Code actions popup: pick with the mouse, or Up/Down + Enter (Esc closes)
+pub struct CodeActionMenu {
+    actions: Vec<CodeAction>,
+    selected: usize,
+}
+
+impl CodeActionMenu {
+    pub fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.actions.is_empty()
+    }
+
+    pub fn open(&mut self, actions: Vec<CodeAction>) {
+        self.actions = actions;
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.actions.clear();
+    }
+
+    /// Draw the menu; returns the action the user picked (closing the menu)
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<CodeAction> {
+        if !self.is_open() {
+            return None;
+        }
+
+        let mut chosen = self.handle_keys(ctx);
+        if !self.is_open() {
+            return None;
+        }
+        let mut open = true;
+        egui::Window::new("💡 Code Actions")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                for (index, action) in self.actions.iter().enumerate() {
+                    let icon = match action.kind {
+                        CodeActionKind::QuickFix => "🔧",
+                        CodeActionKind::Source => "✨",
+                    };
+                    let label = format!("{} {}", icon, action.title);
+                    let response = ui.selectable_label(index == self.selected, label);
+                    let response = match &action.diagnostic {
+                        Some(diagnostic) => response.on_hover_text(&diagnostic.message),
+                        None => response,
+                    };
+                    if response.clicked() {
+                        chosen = Some(index);
+                    }
+                }
+                ui.separator();
+                ui.colored_label(Color32::GRAY, "↑↓ select · Enter apply · Esc close");
+            });
+
+        if !open {
+            self.close();
+        }
+        let action = chosen.and_then(|index| self.actions.get(index).cloned());
+        if action.is_some() {
+            self.close();
+        }
+        action
+    }
+
+    /// Up/Down move the selection; Enter picks it, Escape closes the menu
+    fn handle_keys(&mut self, ctx: &egui::Context) -> Option<usize> {
+        let last = self.actions.len() - 1;
+        let mut chosen = None;
+        ctx.input(|i| {
+            for event in &i.events {
+                if let egui::Event::Key {
+                    key, pressed: true, ..
+                } = event
+                {
+                    match key {
+                        egui::Key::ArrowUp => self.selected = self.selected.saturating_sub(1),
+                        egui::Key::ArrowDown => self.selected = (self.selected + 1).min(last),
+                        egui::Key::Enter => chosen = Some(self.selected),
+                        egui::Key::Escape => self.actions.clear(),
+                        _ => {}
+                    }
+                }
+            }
+        });
+        chosen
+    }
+}
+
+impl Default for CodeActionMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
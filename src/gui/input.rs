@@ -0,0 +1,18 @@
+use crate::keymap::{KeyEvent, Modifiers};
+
+/// Translate an egui key press into the shared key model
+///
+/// egui reports Cmd as `mac_cmd` on macOS; it's kept separate here and folded
+/// into Ctrl when the keymap is consulted.
+pub fn key_event_from_egui(key: egui::Key, modifiers: egui::Modifiers) -> KeyEvent {
+    KeyEvent::new(
+        key.name(),
+        Modifiers {
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            cmd: modifiers.mac_cmd,
+        },
+        None,
+    )
+}
@@ -0,0 +1,175 @@
+use super::input::key_event_from_egui;
+use super::settings_panel::matches_query;
+use crate::keymap::{Command, Keymap};
+use egui::Color32;
+use std::path::Path;
+
+/// Keyboard shortcuts window: every command with its chords and conflicts,
+/// plus "record new shortcut" capture
+pub struct KeybindingsPanel {
+    open: bool,
+    query: String,
+    /// Command waiting for its new chord
+    recording: Option<Command>,
+}
+
+impl KeybindingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            recording: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// While recording, key presses belong to the panel, not the editor
+    pub fn is_recording(&self) -> bool {
+        self.open && self.recording.is_some()
+    }
+
+    /// Draw the window; returns true if the keymap changed
+    pub fn show(&mut self, ctx: &egui::Context, keymap: &mut Keymap, keymap_path: &Path) -> bool {
+        if !self.open {
+            self.recording = None;
+            return false;
+        }
+
+        let mut changed = self.capture_recording(ctx, keymap);
+
+        let query = &mut self.query;
+        let recording = &mut self.recording;
+        egui::Window::new("⌨ Keyboard Shortcuts")
+            .open(&mut self.open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(query).hint_text("🔍 Search commands"));
+
+                let conflicts = keymap.conflicts();
+                if !conflicts.is_empty() {
+                    ui.colored_label(
+                        Color32::from_rgb(230, 160, 60),
+                        format!("⚠ {} conflicting shortcut(s)", conflicts.len()),
+                    );
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("keybindings")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                for command in Command::ALL {
+                                    let chords: Vec<String> = keymap
+                                        .sequences_for(command)
+                                        .iter()
+                                        .map(|sequence| sequence.to_string())
+                                        .collect();
+                                    if !matches_query(query, command.label(), &chords.join(" ")) {
+                                        continue;
+                                    }
+
+                                    ui.label(command.label());
+
+                                    if *recording == Some(command) {
+                                        ui.colored_label(
+                                            Color32::LIGHT_BLUE,
+                                            "Press a shortcut (Esc to cancel)...",
+                                        );
+                                    } else if chords.is_empty() {
+                                        ui.weak("—");
+                                    } else {
+                                        let others = keymap.conflicts_with(command);
+                                        let text =
+                                            egui::RichText::new(chords.join(", ")).monospace();
+                                        if others.is_empty() {
+                                            ui.label(text);
+                                        } else {
+                                            let names: Vec<&str> =
+                                                others.iter().map(|other| other.label()).collect();
+                                            ui.label(text.color(Color32::from_rgb(230, 90, 90)))
+                                                .on_hover_text(format!(
+                                                    "Also bound to: {}",
+                                                    names.join(", ")
+                                                ));
+                                        }
+                                    }
+
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("⏺ Record").clicked() {
+                                            *recording = Some(command);
+                                        }
+                                        if ui.small_button("Reset").clicked() {
+                                            keymap.reset(command);
+                                            changed = true;
+                                        }
+                                        if ui.small_button("Clear").clicked() {
+                                            keymap.unbind(command);
+                                            changed = true;
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Reset All").clicked() {
+                        *keymap = Keymap::defaults();
+                        changed = true;
+                    }
+                    ui.weak(keymap_path.display().to_string());
+                });
+            });
+
+        changed
+    }
+
+    /// Bind the first key pressed while recording (Escape cancels)
+    fn capture_recording(&mut self, ctx: &egui::Context, keymap: &mut Keymap) -> bool {
+        let Some(command) = self.recording else {
+            return false;
+        };
+
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+
+        match pressed {
+            Some((egui::Key::Escape, _)) => {
+                self.recording = None;
+                false
+            }
+            Some((key, modifiers)) => match key_event_from_egui(key, modifiers).chord() {
+                Some(chord) => {
+                    keymap.bind(command, chord);
+                    self.recording = None;
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+}
+
+impl Default for KeybindingsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
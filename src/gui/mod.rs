@@ -1,6 +1,27 @@
 pub mod app;
+pub mod code_action_menu;
+pub mod input;
+pub mod keybindings_panel;
+pub mod notification_panel;
+pub mod output_panel;
+pub mod profiler;
+pub mod project_search_panel;
+pub mod settings_panel;
+pub mod split_pane;
+pub(crate) mod task;
 pub mod theme;
 pub mod viewport_renderer;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
 
 pub use app::GuiApp;
-pub use viewport_renderer::ViewportRenderer;
+pub use code_action_menu::CodeActionMenu;
+pub use input::key_event_from_egui;
+pub use keybindings_panel::KeybindingsPanel;
+pub use notification_panel::NotificationPanel;
+pub use output_panel::OutputPanel;
+pub use profiler::ProfilerOverlay;
+pub use project_search_panel::ProjectSearchPanel;
+pub use settings_panel::{SettingsPanel, SettingsResponse};
+pub use split_pane::SplitPane;
+pub use viewport_renderer::{RenderStats, ViewportAction, ViewportRenderer};
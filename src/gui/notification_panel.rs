@@ -0,0 +1,140 @@
+use crate::notifications::{Level, Notification, NotificationAction, Notifications};
+use egui::{Color32, Pos2};
+use web_time::Instant;
+
+const TOAST_WIDTH: f32 = 340.0;
+
+fn level_color(level: Level) -> Color32 {
+    match level {
+        Level::Info => Color32::from_rgb(120, 170, 230),
+        Level::Warning => Color32::from_rgb(230, 190, 90),
+        Level::Error => Color32::from_rgb(230, 100, 100),
+    }
+}
+
+/// Toasts stacked in the bottom-right corner, plus the 🔔 history window
+pub struct NotificationPanel {
+    history_visible: bool,
+}
+
+impl NotificationPanel {
+    pub fn new() -> Self {
+        Self {
+            history_visible: false,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.history_visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.history_visible = !self.history_visible;
+    }
+
+    /// Draw the toasts and (while open) the history; returns the action
+    /// button clicked this frame
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        notifications: &mut Notifications,
+    ) -> Option<NotificationAction> {
+        let mut clicked = None;
+        let mut dismissed = Vec::new();
+
+        let toasts: Vec<&Notification> = notifications.toasts(Instant::now()).collect();
+        if !toasts.is_empty() {
+            egui::Area::new(egui::Id::new("notification_toasts"))
+                .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -36.0])
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    ui.set_width(TOAST_WIDTH);
+                    for notification in toasts.iter().rev() {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.set_width(TOAST_WIDTH);
+                            ui.horizontal(|ui| {
+                                ui.weak(notification.level.label());
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.small_button("✕").on_hover_text("Dismiss").clicked()
+                                        {
+                                            dismissed.push(notification.id);
+                                        }
+                                    },
+                                );
+                            });
+                            if let Some(action) = Self::show_notification(ui, notification) {
+                                clicked = Some(action);
+                                dismissed.push(notification.id);
+                            }
+                        });
+                        ui.add_space(4.0);
+                    }
+                });
+        }
+        for id in dismissed {
+            notifications.dismiss(id);
+        }
+
+        let mut open = self.history_visible;
+        let mut clear = false;
+        egui::Window::new("🔔 Notifications")
+            .open(&mut open)
+            .default_pos(Pos2::new(520.0, 120.0))
+            .default_size([420.0, 320.0])
+            .show(ctx, |ui| {
+                if notifications.history().is_empty() {
+                    ui.weak("No notifications");
+                    return;
+                }
+                clear = ui.button("Clear All").clicked();
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for notification in notifications.history().iter().rev() {
+                            ui.weak(format!("{}s ago", notification.created.elapsed().as_secs()));
+                            if let Some(action) = Self::show_notification(ui, notification) {
+                                clicked = Some(action);
+                            }
+                            ui.separator();
+                        }
+                    });
+            });
+        if clear {
+            notifications.clear();
+        }
+        self.history_visible = open;
+
+        clicked
+    }
+
+    /// Icon, message and action buttons
+    fn show_notification(
+        ui: &mut egui::Ui,
+        notification: &Notification,
+    ) -> Option<NotificationAction> {
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(level_color(notification.level), notification.level.icon());
+            ui.label(&notification.message);
+        });
+        if !notification.actions.is_empty() {
+            ui.horizontal(|ui| {
+                for action in &notification.actions {
+                    if ui.button(action.label()).clicked() {
+                        clicked = Some(*action);
+                    }
+                }
+            });
+        }
+        clicked
+    }
+}
+
+impl Default for NotificationPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
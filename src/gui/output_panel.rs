@@ -0,0 +1,57 @@
+use egui::Pos2;
+
+/// Window listing external tool output: each formatter invocation as the
+/// exact command line it ran, and any error it reported
+pub struct OutputPanel {
+    visible: bool,
+}
+
+impl OutputPanel {
+    pub fn new() -> Self {
+        Self { visible: false }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn open(&mut self) {
+        self.visible = true;
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Draw the window (no-op while hidden)
+    pub fn show(&mut self, ctx: &egui::Context, lines: &[String]) {
+        let mut open = self.visible;
+
+        egui::Window::new("📜 Output")
+            .open(&mut open)
+            .default_pos(Pos2::new(80.0, 420.0))
+            .default_size([640.0, 220.0])
+            .show(ctx, |ui| {
+                if lines.is_empty() {
+                    ui.weak("No tools have run yet");
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for line in lines {
+                            ui.add(egui::Label::new(egui::RichText::new(line).monospace()).wrap());
+                        }
+                    });
+            });
+
+        self.visible = open;
+    }
+}
+
+impl Default for OutputPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,199 @@
+use super::viewport_renderer::RenderStats;
+use crate::buffer::CacheStats;
+use egui::{Color32, Pos2, Rect, Vec2};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frames kept for the frame-time chart
+const HISTORY_LEN: usize = 120;
+
+/// 60fps frame budget in milliseconds
+const FRAME_BUDGET_MS: f32 = 1000.0 / 60.0;
+
+/// Debug overlay charting frame time, highlight time, rope queries and cache hit rates
+pub struct ProfilerOverlay {
+    visible: bool,
+    frame_times: VecDeque<f32>,
+    highlight_times: VecDeque<f32>,
+    /// Milliseconds from reading typed text to finishing the frame showing it
+    typing_latencies: VecDeque<f32>,
+    /// Text events applied together in the last typing frame
+    last_typing_batch: usize,
+    last_stats: RenderStats,
+}
+
+impl ProfilerOverlay {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(HISTORY_LEN),
+            highlight_times: VecDeque::with_capacity(HISTORY_LEN),
+            typing_latencies: VecDeque::with_capacity(HISTORY_LEN),
+            last_typing_batch: 0,
+            last_stats: RenderStats::default(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Record one frame's timings and renderer counters
+    pub fn record(&mut self, frame_time: Duration, stats: &RenderStats) {
+        Self::push(&mut self.frame_times, frame_time.as_secs_f32() * 1000.0);
+        Self::push(
+            &mut self.highlight_times,
+            stats.highlight_time.as_secs_f32() * 1000.0,
+        );
+        self.last_stats = stats.clone();
+    }
+
+    /// Record a frame that applied `events` coalesced text events, and how
+    /// long it took from reading them to the end of the frame
+    pub fn record_typing(&mut self, latency: Duration, events: usize) {
+        Self::push(&mut self.typing_latencies, latency.as_secs_f32() * 1000.0);
+        self.last_typing_batch = events;
+    }
+
+    fn push(history: &mut VecDeque<f32>, value: f32) {
+        if history.len() == HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(value);
+    }
+
+    fn average(history: &VecDeque<f32>) -> f32 {
+        if history.is_empty() {
+            0.0
+        } else {
+            history.iter().sum::<f32>() / history.len() as f32
+        }
+    }
+
+    /// Draw the overlay window (no-op while hidden)
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.visible;
+
+        egui::Window::new("⏱ Profiler")
+            .open(&mut open)
+            .resizable(false)
+            .default_pos(Pos2::new(520.0, 40.0))
+            .show(ctx, |ui| {
+                let frame_max = self.frame_times.iter().cloned().fold(0.0, f32::max);
+                ui.monospace(format!(
+                    "Frame      avg {:6.2} ms  max {:6.2} ms",
+                    Self::average(&self.frame_times),
+                    frame_max
+                ));
+                ui.monospace(format!(
+                    "Highlight  avg {:6.2} ms  last {:5.2} ms",
+                    Self::average(&self.highlight_times),
+                    self.highlight_times.back().cloned().unwrap_or(0.0)
+                ));
+                ui.monospace(format!(
+                    "Typing     avg {:6.2} ms  max {:6.2} ms  ({} keys/frame last)",
+                    Self::average(&self.typing_latencies),
+                    self.typing_latencies.iter().cloned().fold(0.0, f32::max),
+                    self.last_typing_batch
+                ));
+                ui.monospace(format!(
+                    "Rope queries   {:6} / frame",
+                    self.last_stats.rope_queries
+                ));
+                if self.last_stats.deferred_lines > 0 {
+                    ui.monospace(format!(
+                        "Deferred       {:6} lines (highlight budget spent)",
+                        self.last_stats.deferred_lines
+                    ));
+                }
+
+                self.draw_chart(ui);
+
+                ui.separator();
+                Self::cache_row(ui, "Line cache  ", &self.last_stats.line_cache);
+                Self::cache_row(ui, "Offset cache", &self.last_stats.offset_cache);
+            });
+
+        self.visible = open;
+    }
+
+    fn cache_row(ui: &mut egui::Ui, label: &str, stats: &CacheStats) {
+        ui.monospace(format!(
+            "{} {:5.1}% hit  ({} hits / {} misses, {}/{} entries)",
+            label,
+            stats.hit_rate * 100.0,
+            stats.hits,
+            stats.misses,
+            stats.cached_lines,
+            stats.total_capacity
+        ));
+    }
+
+    /// Bar chart of recent frame times, highlight share drawn on top,
+    /// with a line marking the 60fps budget
+    fn draw_chart(&self, ui: &mut egui::Ui) {
+        let size = Vec2::new(HISTORY_LEN as f32 * 2.5, 60.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, Color32::from_rgb(20, 20, 20));
+
+        let scale_ms = self
+            .frame_times
+            .iter()
+            .cloned()
+            .fold(FRAME_BUDGET_MS * 2.0, f32::max);
+        let bar_width = rect.width() / HISTORY_LEN as f32;
+        let height_for = |ms: f32| (ms / scale_ms).min(1.0) * rect.height();
+
+        for (i, (frame_ms, highlight_ms)) in self
+            .frame_times
+            .iter()
+            .zip(self.highlight_times.iter())
+            .enumerate()
+        {
+            let x = rect.min.x + i as f32 * bar_width;
+            let frame_color = if *frame_ms > FRAME_BUDGET_MS {
+                Color32::from_rgb(220, 90, 80)
+            } else {
+                Color32::from_rgb(90, 170, 110)
+            };
+
+            let frame_h = height_for(*frame_ms);
+            painter.rect_filled(
+                Rect::from_min_max(
+                    Pos2::new(x, rect.max.y - frame_h),
+                    Pos2::new(x + bar_width - 0.5, rect.max.y),
+                ),
+                0.0,
+                frame_color,
+            );
+
+            let highlight_h = height_for(*highlight_ms);
+            painter.rect_filled(
+                Rect::from_min_max(
+                    Pos2::new(x, rect.max.y - highlight_h),
+                    Pos2::new(x + bar_width - 0.5, rect.max.y),
+                ),
+                0.0,
+                Color32::from_rgb(100, 150, 230),
+            );
+        }
+
+        let budget_y = rect.max.y - height_for(FRAME_BUDGET_MS);
+        painter.hline(
+            rect.x_range(),
+            budget_y,
+            egui::Stroke::new(1.0, Color32::from_rgb(200, 200, 100)),
+        );
+    }
+}
+
+impl Default for ProfilerOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
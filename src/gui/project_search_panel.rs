@@ -0,0 +1,217 @@
+use crate::gui::task;
+use crate::io::{search_project, ProjectMatch, MAX_PROJECT_MATCHES};
+use crate::progress::Progress;
+use egui::Pos2;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+use web_time::Instant;
+
+/// How long the query has to stay unchanged before a search starts
+pub const SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// A search running on a background thread
+struct RunningSearch {
+    query: String,
+    task: u64,
+    result: Receiver<io::Result<Vec<ProjectMatch>>>,
+}
+
+/// 🔎 Find in files under a root directory. Editing the query cancels the
+/// search in flight, and a new one starts once typing pauses
+pub struct ProjectSearchPanel {
+    visible: bool,
+    root: PathBuf,
+    query: String,
+    /// When the query last changed, until a search for it starts
+    edited: Option<Instant>,
+    running: Option<RunningSearch>,
+    results: Vec<ProjectMatch>,
+    /// Query the results are for
+    searched: Option<String>,
+}
+
+impl ProjectSearchPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            root: PathBuf::from("."),
+            query: String::new(),
+            edited: None,
+            running: None,
+            results: Vec::new(),
+            searched: None,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn set_root(&mut self, root: PathBuf) {
+        self.root = root;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replace the query, cancelling the running search; the next one
+    /// starts from `poll` once `SEARCH_DEBOUNCE` has passed
+    pub fn set_query(&mut self, query: &str, progress: &Progress, now: Instant) {
+        if query == self.query {
+            return;
+        }
+        self.query = query.to_string();
+        self.cancel(progress);
+        self.edited = Some(now);
+    }
+
+    fn cancel(&mut self, progress: &Progress) {
+        if let Some(running) = self.running.take() {
+            progress.cancel(running.task);
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// A search is running or waiting for the debounce
+    pub fn is_busy(&self) -> bool {
+        self.edited.is_some() || self.running.is_some()
+    }
+
+    pub fn results(&self) -> &[ProjectMatch] {
+        &self.results
+    }
+
+    /// Progress task of the search in flight
+    pub fn search_task(&self) -> Option<u64> {
+        self.running.as_ref().map(|running| running.task)
+    }
+
+    /// Collect a finished search and start one whose debounce has passed; a
+    /// search started here is only collected by a later poll
+    pub fn poll(&mut self, progress: &Progress, now: Instant) {
+        if let Some(running) = &self.running {
+            match running.result.try_recv() {
+                Ok(result) => {
+                    let running = self.running.take().unwrap();
+                    // A cancelled search has nothing to show
+                    if let Ok(results) = result {
+                        self.results = results;
+                        self.searched = Some(running.query);
+                    }
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.running = None,
+            }
+        }
+
+        if self
+            .edited
+            .is_some_and(|edited| now.duration_since(edited) >= SEARCH_DEBOUNCE)
+        {
+            self.edited = None;
+            self.start(progress);
+        }
+    }
+
+    fn start(&mut self, progress: &Progress) {
+        self.cancel(progress);
+        if self.query.is_empty() {
+            self.results.clear();
+            self.searched = None;
+            return;
+        }
+
+        let handle = progress.start(format!("Searching for \"{}\"", self.query));
+        let task = handle.id();
+        let (sender, result) = mpsc::channel();
+        let root = self.root.clone();
+        let query = self.query.clone();
+        task::spawn(move || {
+            sender.send(search_project(&root, &query, &handle)).ok();
+        });
+        self.running = Some(RunningSearch {
+            query: self.query.clone(),
+            task,
+            result,
+        });
+    }
+
+    /// Draw the window (no-op while hidden); returns the match clicked
+    pub fn show(&mut self, ctx: &egui::Context, progress: &Progress) -> Option<ProjectMatch> {
+        let mut open = self.visible;
+        let mut clicked = None;
+
+        egui::Window::new("🔎 Find in Files")
+            .open(&mut open)
+            .default_pos(Pos2::new(120.0, 100.0))
+            .default_size([560.0, 360.0])
+            .show(ctx, |ui| {
+                ui.weak(self.root.display().to_string());
+                let mut query = self.query.clone();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut query)
+                        .hint_text("Search")
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    self.set_query(&query, progress, Instant::now());
+                }
+
+                if self.is_busy() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.weak("Searching…");
+                    });
+                } else if let Some(searched) = &self.searched {
+                    let more = if self.results.len() >= MAX_PROJECT_MATCHES {
+                        "+"
+                    } else {
+                        ""
+                    };
+                    ui.weak(format!(
+                        "{}{} matches for \"{}\"",
+                        self.results.len(),
+                        more,
+                        searched
+                    ));
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for found in &self.results {
+                            let path = found.path.strip_prefix(&self.root).unwrap_or(&found.path);
+                            let label =
+                                format!("{}:{}  {}", path.display(), found.line + 1, found.text);
+                            if ui.selectable_label(false, label).clicked() {
+                                clicked = Some(found.clone());
+                            }
+                        }
+                    });
+            });
+        self.visible = open;
+
+        clicked
+    }
+}
+
+impl Default for ProjectSearchPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
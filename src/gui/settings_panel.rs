@@ -0,0 +1,297 @@
+use crate::settings::{EditorSettings, Settings};
+use std::path::Path;
+
+/// What the app should do after the settings window was drawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SettingsResponse {
+    /// A value changed - apply and save
+    pub changed: bool,
+    /// "Edit in settings.json" was clicked
+    pub edit_file: bool,
+}
+
+/// Controls shown in the settings window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    FontSize,
+    CursorBlink,
+    TabSize,
+    HardTabs,
+    ReindentOnPaste,
+    AutoClosePairs,
+    WordChars,
+    SoftWrap,
+    ReflowWidth,
+    FormatOnSave,
+    HotExit,
+    Modelines,
+}
+
+struct SettingItem {
+    group: &'static str,
+    label: &'static str,
+    /// Extra words the search box matches
+    keywords: &'static str,
+    control: Control,
+}
+
+const ITEMS: [SettingItem; 12] = [
+    SettingItem {
+        group: "Editor",
+        label: "Font size",
+        keywords: "text zoom",
+        control: Control::FontSize,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Blink cursor",
+        keywords: "caret animation",
+        control: Control::CursorBlink,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Tab size",
+        keywords: "indent width spaces",
+        control: Control::TabSize,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Indent with tabs",
+        keywords: "hard tabs spaces indentation",
+        control: Control::HardTabs,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Re-indent pasted text",
+        keywords: "paste indentation clipboard",
+        control: Control::ReindentOnPaste,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Auto-close brackets and quotes",
+        keywords: "autopairs pairs parentheses",
+        control: Control::AutoClosePairs,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Word characters besides letters and digits",
+        keywords: "word boundaries movement double-click select identifier",
+        control: Control::WordChars,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Soft wrap long lines",
+        keywords: "word wrap line wrapping",
+        control: Control::SoftWrap,
+    },
+    SettingItem {
+        group: "Editor",
+        label: "Reflow paragraph width",
+        keywords: "fill wrap column prose comments",
+        control: Control::ReflowWidth,
+    },
+    SettingItem {
+        group: "Files",
+        label: "Format on save",
+        keywords: "rustfmt prettier formatter",
+        control: Control::FormatOnSave,
+    },
+    SettingItem {
+        group: "Files",
+        label: "Restore unsaved changes on restart",
+        keywords: "hot exit recovery session",
+        control: Control::HotExit,
+    },
+    SettingItem {
+        group: "Files",
+        label: "Read vim/emacs modelines",
+        keywords: "modeline tab width filetype security",
+        control: Control::Modelines,
+    },
+];
+
+const FILE_ASSOCIATIONS_GROUP: &str = "File Associations";
+
+/// True if every word of the query appears in the label or keywords
+/// (case-insensitive); an empty query matches everything
+pub fn matches_query(query: &str, label: &str, keywords: &str) -> bool {
+    let haystack = format!("{} {}", label, keywords).to_lowercase();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| haystack.contains(word))
+}
+
+/// Settings window: a search box over grouped controls bound to `Settings`
+pub struct SettingsPanel {
+    open: bool,
+    query: String,
+}
+
+impl SettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &mut Settings,
+        settings_path: &Path,
+    ) -> SettingsResponse {
+        let mut response = SettingsResponse::default();
+        if !self.open {
+            return response;
+        }
+
+        let query = &mut self.query;
+        egui::Window::new("⚙ Settings")
+            .open(&mut self.open)
+            .collapsible(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(query).hint_text("🔍 Search settings"));
+                ui.separator();
+
+                let mut shown_any = false;
+                let mut last_group = "";
+                for item in ITEMS
+                    .iter()
+                    .filter(|item| matches_query(query, item.label, item.keywords))
+                {
+                    if item.group != last_group {
+                        ui.add_space(4.0);
+                        ui.strong(item.group);
+                        last_group = item.group;
+                    }
+                    response.changed |= Self::show_control(ui, item, &mut settings.editor);
+                    shown_any = true;
+                }
+
+                shown_any |= Self::show_associations(ui, query, settings, &mut response);
+
+                if !shown_any {
+                    ui.label("No settings match your search");
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("📝 Edit in settings.json").clicked() {
+                        response.edit_file = true;
+                    }
+                    ui.weak(settings_path.display().to_string());
+                });
+            });
+
+        response
+    }
+
+    fn show_control(ui: &mut egui::Ui, item: &SettingItem, editor: &mut EditorSettings) -> bool {
+        match item.control {
+            Control::FontSize => ui
+                .add(
+                    egui::Slider::new(
+                        &mut editor.font_size,
+                        EditorSettings::MIN_FONT_SIZE..=EditorSettings::MAX_FONT_SIZE,
+                    )
+                    .step_by(1.0)
+                    .text(item.label),
+                )
+                .changed(),
+            Control::CursorBlink => ui.checkbox(&mut editor.cursor_blink, item.label).changed(),
+            Control::TabSize => ui
+                .add(
+                    egui::Slider::new(
+                        &mut editor.tab_size,
+                        EditorSettings::MIN_TAB_SIZE..=EditorSettings::MAX_TAB_SIZE,
+                    )
+                    .text(item.label),
+                )
+                .changed(),
+            Control::HardTabs => ui.checkbox(&mut editor.hard_tabs, item.label).changed(),
+            Control::ReindentOnPaste => ui
+                .checkbox(&mut editor.reindent_on_paste, item.label)
+                .changed(),
+            Control::AutoClosePairs => ui
+                .checkbox(&mut editor.auto_close_pairs, item.label)
+                .changed(),
+            Control::WordChars => {
+                ui.horizontal(|ui| {
+                    ui.label(item.label);
+                    ui.add(egui::TextEdit::singleline(&mut editor.word_chars).desired_width(80.0))
+                        .changed()
+                })
+                .inner
+            }
+            Control::SoftWrap => ui.checkbox(&mut editor.soft_wrap, item.label).changed(),
+            Control::ReflowWidth => ui
+                .add(
+                    egui::Slider::new(
+                        &mut editor.reflow_width,
+                        EditorSettings::MIN_REFLOW_WIDTH..=EditorSettings::MAX_REFLOW_WIDTH,
+                    )
+                    .text(item.label),
+                )
+                .changed(),
+            Control::FormatOnSave => ui
+                .checkbox(&mut editor.format_on_save, item.label)
+                .changed(),
+            Control::HotExit => ui.checkbox(&mut editor.hot_exit, item.label).changed(),
+            Control::Modelines => ui.checkbox(&mut editor.modelines, item.label).changed(),
+        }
+    }
+
+    /// Matching file associations, each with a remove button
+    fn show_associations(
+        ui: &mut egui::Ui,
+        query: &str,
+        settings: &mut Settings,
+        response: &mut SettingsResponse,
+    ) -> bool {
+        let group_matches = matches_query(query, FILE_ASSOCIATIONS_GROUP, "language glob");
+        let matching: Vec<(String, String)> = settings
+            .file_associations
+            .entries()
+            .iter()
+            .filter(|entry| group_matches || matches_query(query, &entry.pattern, &entry.language))
+            .map(|entry| (entry.pattern.clone(), entry.language.clone()))
+            .collect();
+        if matching.is_empty() && !group_matches {
+            return false;
+        }
+
+        ui.add_space(4.0);
+        ui.strong(FILE_ASSOCIATIONS_GROUP);
+        if matching.is_empty() {
+            ui.weak("None - use View → Change File Association...");
+        }
+        for (pattern, language) in matching {
+            ui.horizontal(|ui| {
+                ui.monospace(&pattern);
+                ui.label(format!("→ {}", language));
+                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                    settings.file_associations.remove(&pattern);
+                    response.changed = true;
+                }
+            });
+        }
+        true
+    }
+}
+
+impl Default for SettingsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,57 @@
+use super::viewport_renderer::ViewportRenderer;
+
+/// A second view of the open buffer beside the main one, with its own
+/// scroll position
+pub struct SplitPane {
+    pub renderer: ViewportRenderer,
+    /// Scroll together with the main pane, keeping the distance between them
+    scroll_locked: bool,
+    /// (main, split) offsets as of the last sync
+    synced: Option<(f32, f32)>,
+    /// A synced scroll is being applied; the next offsets are its result
+    settling: bool,
+}
+
+impl SplitPane {
+    pub fn new(font_size: f32) -> Self {
+        let mut renderer = ViewportRenderer::new();
+        renderer.set_font_size(font_size);
+        Self {
+            renderer,
+            scroll_locked: false,
+            synced: None,
+            settling: false,
+        }
+    }
+
+    pub fn is_scroll_locked(&self) -> bool {
+        self.scroll_locked
+    }
+
+    pub fn toggle_scroll_lock(&mut self) {
+        self.scroll_locked = !self.scroll_locked;
+    }
+
+    /// Given both panes' scroll offsets this frame, the offsets to move
+    /// (main, split) to: while locked, whichever pane moved drags the other
+    /// by the same amount
+    pub fn sync_scroll(&mut self, main: f32, split: f32) -> (Option<f32>, Option<f32>) {
+        let last = self.synced.replace((main, split));
+        if !self.scroll_locked || std::mem::take(&mut self.settling) {
+            return (None, None);
+        }
+        let Some((last_main, last_split)) = last else {
+            return (None, None);
+        };
+
+        let target = if main != last_main {
+            (None, Some((split + main - last_main).max(0.0)))
+        } else if split != last_split {
+            (Some((main + split - last_split).max(0.0)), None)
+        } else {
+            return (None, None);
+        };
+        self.settling = true;
+        target
+    }
+}
@@ -0,0 +1,9 @@
+/// Run `work` off the UI thread. Browsers give wasm no threads, so there it
+/// runs to completion before returning; either way its result should go
+/// through a channel the UI polls
+pub(crate) fn spawn<F: FnOnce() + Send + 'static>(work: F) {
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::spawn(work);
+    #[cfg(target_arch = "wasm32")]
+    work();
+}
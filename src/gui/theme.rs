@@ -17,6 +17,7 @@ pub const LINE_NUMBER_BG: Color32 = Color32::from_rgb(40, 40, 40);
 pub const LINE_NUMBER_FG: Color32 = Color32::from_rgb(100, 100, 100);
 pub const TEXT_COLOR: Color32 = Color32::from_rgb(200, 200, 200);
 pub const CURSOR_COLOR: Color32 = Color32::from_rgb(255, 255, 255);
-// Fixed: Use const-friendly function
-pub const SELECTION_COLOR: Color32 = Color32::from_rgba_premultiplied(80, 120, 204, 80);
+// rgb(80, 120, 204) at 80/255 alpha, premultiplied (channels can't exceed
+// alpha, or the selection blends brighter than the text on top of it)
+pub const SELECTION_COLOR: Color32 = Color32::from_rgba_premultiplied(25, 38, 64, 80);
 pub const STATUS_BAR_BG: Color32 = Color32::from_rgb(40, 40, 40);
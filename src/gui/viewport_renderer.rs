@@ -1,17 +1,73 @@
+use crate::buffer::{CacheStats, Point};
+use crate::editor::{Conflict, ConflictResolution, Diagnostic, EditEvent, Selection, Severity};
+use crate::gui::{task, theme};
 use crate::syntax::{HighlightedRange, InstantHighlighter};
+use crate::util::tabs::char_widths;
 use egui::{Color32, FontId, Pos2, Rect, Vec2};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+use web_time::Instant;
+
+/// Gutter width left of the text (line numbers, 💡)
+const LINE_NUMBER_WIDTH: f32 = 60.0;
+
+/// Lines shown by the scrollbar hover preview
+const PREVIEW_LINES: usize = 9;
+/// Longest line prefix the preview draws (in characters)
+const PREVIEW_COLUMNS: usize = 80;
+
+/// Lines highlighted (and cached) together; the unit of highlight work
+const HIGHLIGHT_BLOCK_LINES: usize = 32;
+/// Highlighting time a frame may spend before leaving blocks to later frames
+const HIGHLIGHT_BUDGET: Duration = Duration::from_millis(4);
+/// Lines longer than this (in bytes) are never highlighted
+const MAX_HIGHLIGHT_LINE_LEN: usize = 10_000;
+
+/// A display row's text (the `columns` of `line`) with each tab expanded to
+/// the spaces reaching its tab stop, since egui draws every tab the same
+/// width; and where each of the row's characters, and its end, lands in it
+fn expand_tabs(
+    line: &str,
+    columns: &std::ops::Range<usize>,
+    tab_width: usize,
+) -> (String, Vec<usize>) {
+    let widths = char_widths(line, tab_width);
+    let mut text = String::new();
+    let mut shown_columns = Vec::with_capacity(columns.len() + 1);
+    let mut shown = 0;
+    let chars = line
+        .chars()
+        .enumerate()
+        .skip(columns.start)
+        .take(columns.len());
+    for (column, c) in chars {
+        shown_columns.push(shown);
+        if c == '\t' {
+            text.extend(std::iter::repeat_n(' ', widths[column]));
+            shown += widths[column];
+        } else {
+            text.push(c);
+            shown += 1;
+        }
+    }
+    shown_columns.push(shown);
+    (text, shown_columns)
+}
 
-/// Cached line with version tracking
+/// Cached line with version tracking; the text is shared by every user of
+/// the line this frame (drawing, highlight filtering) without copying
 #[derive(Clone)]
 struct CachedLine {
-    content: String,
+    content: Arc<str>,
     version: u64,
     width: f32,
 }
 
 impl CachedLine {
-    fn new(content: String, version: u64) -> Self {
+    fn new(content: Arc<str>, version: u64) -> Self {
         Self {
             content,
             version,
@@ -31,6 +87,73 @@ struct CachedLineOffset {
     version: u64,
 }
 
+/// Highlights of one block of lines, relative to its start, for the text
+/// and language they were computed from
+struct CachedHighlights {
+    text_hash: u64,
+    language: &'static str,
+    ranges: Vec<HighlightedRange>,
+}
+
+/// Blocks being re-highlighted off the UI thread after the language
+/// changed; until they all arrive the old highlights stay on screen, then
+/// they replace them in one frame
+struct Restyle {
+    language: &'static str,
+    result: Receiver<Vec<(usize, CachedHighlights)>>,
+}
+
+/// Interaction produced by the viewport that the app applies to the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportAction {
+    /// Alt+click on the gutter - jump the cursor to this line
+    JumpToLine(usize),
+    /// The 💡 next to the cursor line was clicked
+    ShowCodeActions,
+    /// Click on the text - move the cursor there (Shift extends the selection)
+    PlaceCursor { point: Point, extend: bool },
+    /// Dragging over the text - move the selection's head here
+    SelectTo(Point),
+    /// Double-click on the text - select the word under the pointer
+    SelectWordAt(Point),
+    /// Alt+drag on the text - block selection between two corners (columns
+    /// are screen columns, tabs expanded)
+    SelectBlock { anchor: Point, head: Point },
+    /// An "Accept …" button above a merge conflict (its `<<<<<<<` row)
+    ResolveConflict(usize, ConflictResolution),
+    /// The selection was dragged and dropped here - move its text
+    MoveSelection(Point),
+    /// Click or drag on the line numbers - select whole lines from the
+    /// pressed row to the row under the pointer
+    SelectLines { anchor: usize, head: usize },
+}
+
+/// Dragging the selected text to move it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextDrag {
+    /// Pressed inside the selection, not moved yet (releasing places the cursor)
+    Pressed,
+    /// Would drop the text here
+    Over(Point),
+    /// Escape was pressed; the rest of this drag does nothing
+    Cancelled,
+}
+
+/// Renderer counters for the last frame (shown by the profiling overlay)
+#[derive(Debug, Clone, Default)]
+pub struct RenderStats {
+    /// Time spent computing syntax highlights
+    pub highlight_time: Duration,
+    /// Rope/buffer lookups that missed the renderer caches
+    pub rope_queries: u64,
+    /// Line content cache (cumulative)
+    pub line_cache: CacheStats,
+    /// Line offset cache (cumulative)
+    pub offset_cache: CacheStats,
+    /// Visible lines drawn plain because the highlight budget ran out
+    pub deferred_lines: usize,
+}
+
 /// Fast viewport renderer with caching
 pub struct ViewportRenderer {
     line_cache: HashMap<usize, CachedLine>,
@@ -39,10 +162,36 @@ pub struct ViewportRenderer {
     line_offset_cache: HashMap<usize, CachedLineOffset>,
     last_version: u64,
     frame_count: u64,
-    highlighter: InstantHighlighter,
+    highlighter: Arc<InstantHighlighter>,
+    /// Highlights by block index (line / `HIGHLIGHT_BLOCK_LINES`)
+    highlight_blocks: HashMap<usize, CachedHighlights>,
+    restyle: Option<Restyle>,
+    /// When this frame started spending the highlight budget
+    highlight_started: Instant,
     predictive_cache: crate::buffer::PredictiveCache,
     last_viewport: (usize, usize),
     last_cursor_line: usize,
+    stats: RenderStats,
+    line_hits: u64,
+    line_misses: u64,
+    offset_hits: u64,
+    offset_misses: u64,
+    font_size: f32,
+    lightbulb_row: Option<usize>,
+    /// Text columns that fit in the last frame's viewport
+    wrap_columns: Option<usize>,
+    /// Where the current Alt+drag started (row, screen column)
+    block_anchor: Option<Point>,
+    text_drag: Option<TextDrag>,
+    /// Row where the current press on the line numbers started
+    line_drag: Option<usize>,
+    /// Vertical scroll offset shown last frame
+    scroll_offset: f32,
+    /// Vertical offset to jump to next frame
+    pending_scroll: Option<f32>,
+    scroll_id: Option<egui::Id>,
+    /// Merge conflicts of the buffer version they were found in
+    conflicts: (u64, Vec<Conflict>),
 }
 
 impl ViewportRenderer {
@@ -53,13 +202,66 @@ impl ViewportRenderer {
             line_offset_cache: HashMap::new(),
             last_version: 0,
             frame_count: 0,
-            highlighter: InstantHighlighter::new(),
+            highlighter: Arc::new(InstantHighlighter::new()),
+            highlight_blocks: HashMap::new(),
+            restyle: None,
+            highlight_started: Instant::now(),
             predictive_cache: crate::buffer::PredictiveCache::new(),
             last_viewport: (0, 0),
             last_cursor_line: 0,
+            stats: RenderStats::default(),
+            line_hits: 0,
+            line_misses: 0,
+            offset_hits: 0,
+            offset_misses: 0,
+            font_size: 14.0,
+            lightbulb_row: None,
+            wrap_columns: None,
+            block_anchor: None,
+            text_drag: None,
+            line_drag: None,
+            scroll_offset: 0.0,
+            pending_scroll: None,
+            scroll_id: None,
+            conflicts: (u64::MAX, Vec::new()),
         }
     }
 
+    /// Text columns that fit beside the gutter (known after the first frame);
+    /// the width soft wrap should use
+    pub fn wrap_columns(&self) -> Option<usize> {
+        self.wrap_columns
+    }
+
+    /// Vertical scroll offset of the last frame, in points
+    pub fn scroll_offset(&self) -> f32 {
+        self.scroll_offset
+    }
+
+    /// Scroll to a vertical offset on the next frame
+    pub fn set_scroll_offset(&mut self, offset: f32) {
+        self.pending_scroll = Some(offset);
+    }
+
+    /// Show the 💡 code actions marker in the gutter of `row`
+    pub fn set_lightbulb_row(&mut self, row: Option<usize>) {
+        self.lightbulb_row = row;
+    }
+
+    /// Editor font size in points (clears cached text widths)
+    pub fn set_font_size(&mut self, font_size: f32) {
+        if self.font_size != font_size {
+            self.font_size = font_size;
+            self.width_cache.clear();
+            self.invalidate_from_line(0);
+        }
+    }
+
+    /// Counters from the most recent frame
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
     /// 🚀 NEW: Get line offset with caching (avoids expensive rope scans!)
     fn get_line_offset_cached(
         &mut self,
@@ -70,11 +272,14 @@ impl ViewportRenderer {
         // Check cache first
         if let Some(cached) = self.line_offset_cache.get(&line_idx) {
             if cached.version == current_version {
+                self.offset_hits += 1;
                 return cached.offset;
             }
         }
 
         // Cache miss - calculate and store
+        self.offset_misses += 1;
+        self.stats.rope_queries += 1;
         let offset = rope.line_to_byte(line_idx);
 
         // Only cache if we have room (prevent unbounded growth)
@@ -97,14 +302,17 @@ impl ViewportRenderer {
         editor: &crate::Editor,
         line_idx: usize,
         current_version: u64,
-    ) -> String {
+    ) -> Arc<str> {
         if let Some(cached) = self.line_cache.get(&line_idx) {
             if cached.is_valid(current_version) {
+                self.line_hits += 1;
                 return cached.content.clone();
             }
         }
 
-        let content = editor.buffer().line(line_idx).unwrap_or_default();
+        self.line_misses += 1;
+        self.stats.rope_queries += 1;
+        let content: Arc<str> = editor.buffer().line(line_idx).unwrap_or_default().into();
 
         if self.line_cache.len() < 500 {
             self.line_cache
@@ -144,6 +352,45 @@ impl ViewportRenderer {
         self.width_cache.clear();
     }
 
+    /// Carry the caches across one edit instead of dropping them: lines
+    /// outside the edit keep their content (shifted by the rows it added or
+    /// removed) and line offsets after it shift by the bytes it changed
+    pub fn apply_edit(&mut self, event: &EditEvent) {
+        let (first, last) = (event.start.row, event.old_end.row);
+        let (row_delta, byte_delta) = (event.row_delta(), event.byte_delta());
+        // Only entries from the version just before this edit are still accurate
+        let previous = event.version.wrapping_sub(1);
+
+        self.line_cache = self
+            .line_cache
+            .drain()
+            .filter(|(row, line)| line.version == previous && (*row < first || *row > last))
+            .filter_map(|(row, mut line)| {
+                line.version = event.version;
+                let row = if row > last {
+                    row.checked_add_signed(row_delta)?
+                } else {
+                    row
+                };
+                Some((row, line))
+            })
+            .collect();
+
+        self.line_offset_cache = self
+            .line_offset_cache
+            .drain()
+            .filter(|(row, offset)| offset.version == previous && (*row <= first || *row > last))
+            .filter_map(|(row, mut offset)| {
+                offset.version = event.version;
+                if row > last {
+                    offset.offset = offset.offset.checked_add_signed(byte_delta)?;
+                    return Some((row.checked_add_signed(row_delta)?, offset));
+                }
+                Some((row, offset))
+            })
+            .collect();
+    }
+
     /// Invalidate specific line
     pub fn invalidate_line(&mut self, line: usize) {
         self.line_cache.remove(&line);
@@ -152,20 +399,27 @@ impl ViewportRenderer {
 
     /// 🚀 ULTRA-OPTIMIZED: Render viewport with FAST regex-based syntax highlighting
     /// Uses InstantHighlighter instead of slow tree-sitter (100-1000x faster!)
+    /// Returns the gutter/text interaction (if any) for the app to apply
     pub fn render_with_highlighting(
         &mut self,
         ui: &mut egui::Ui,
         editor: &crate::Editor,
         cursor_blink: bool,
         should_auto_scroll: bool,
-    ) {
+    ) -> Option<ViewportAction> {
         self.frame_count += 1;
+        self.stats.rope_queries = 0;
+        self.stats.highlight_time = Duration::ZERO;
+        self.stats.deferred_lines = 0;
+        self.highlight_started = Instant::now();
 
         let cursor = editor.cursor();
         let current_version = editor.version();
-        let font_id = FontId::monospace(14.0);
+        let font_id = FontId::monospace(self.font_size);
         let line_height = ui.fonts(|f| f.row_height(&font_id)) + 4.0;
-        let cursor_y = cursor.row as f32 * line_height;
+        let wrap = editor.wrap_map();
+        let cursor_display_row = wrap.map_or(cursor.row, |map| map.to_display_point(cursor).row);
+        let cursor_y = cursor_display_row as f32 * line_height;
 
         // Clear caches if version changed
         if self.last_version != current_version {
@@ -182,6 +436,9 @@ impl ViewportRenderer {
             if self.width_cache.len() > 200 {
                 self.width_cache.clear();
             }
+            if self.highlight_blocks.len() > 200 {
+                self.highlight_blocks.clear();
+            }
             if self.line_offset_cache.len() > 10_000 {
                 // Keep only viewport lines and nearby buffer
                 let viewport_start = self.last_viewport.0.saturating_sub(100);
@@ -192,16 +449,43 @@ impl ViewportRenderer {
         }
 
         let file_path = editor.file_path();
+        let language = match editor.language() {
+            Some(config) => InstantHighlighter::language_key(config.id),
+            None => InstantHighlighter::detect_language(file_path),
+        };
+        let mut action = None;
 
-        egui::ScrollArea::both()
+        if let Some((id, offset)) = self.scroll_id.zip(self.pending_scroll.take()) {
+            if let Some(mut state) = egui::scroll_area::State::load(ui.ctx(), id) {
+                state.offset.y = offset;
+                state.store(ui.ctx(), id);
+            }
+        }
+        let output = egui::ScrollArea::both()
             .auto_shrink([false, false])
             .show_viewport(ui, |ui, viewport| {
                 let total_lines = editor.line_count().max(1);
-                let content_height = total_lines as f32 * line_height;
+                let total_rows = wrap.map_or(total_lines, |map| map.display_row_count().max(1));
+                let content_height = total_rows as f32 * line_height;
+
+                // Display rows on screen, and the buffer lines they show
+                let display_start = (viewport.min.y / line_height).floor().max(0.0) as usize;
+                let display_end =
+                    ((viewport.max.y / line_height).ceil() as usize + 1).min(total_rows);
+                let (visible_start, visible_end) = match wrap {
+                    Some(map) => (
+                        map.segment_at(display_start).0,
+                        map.segment_at(display_end.saturating_sub(1)).0 + 1,
+                    ),
+                    None => (display_start, display_end),
+                };
 
-                let visible_start = (viewport.min.y / line_height).floor().max(0.0) as usize;
-                let visible_end =
-                    ((viewport.max.y / line_height).ceil() as usize + 1).min(total_lines);
+                // Columns that fit beside the gutter, for soft wrap
+                let char_width = self.measure_width(ui, "M", &font_id).max(1.0);
+                self.wrap_columns = Some(
+                    ((viewport.width() - LINE_NUMBER_WIDTH - char_width) / char_width).max(10.0)
+                        as usize,
+                );
 
                 // 🚀 SCROLL PREDICTION: Track scroll delta for predictive caching
                 let scroll_delta = ui.input(|i| i.smooth_scroll_delta.y);
@@ -239,6 +523,7 @@ impl ViewportRenderer {
                     // Pre-cache the predicted range
                     for line_idx in precache_range {
                         if !self.line_offset_cache.contains_key(&line_idx) {
+                            self.stats.rope_queries += 1;
                             let offset = rope.line_to_byte(line_idx);
                             if self.line_offset_cache.len() < 10_000 {
                                 self.line_offset_cache.insert(
@@ -253,16 +538,223 @@ impl ViewportRenderer {
                     }
                 }
 
+                // Wide enough for the longest line (in chars) so long lines
+                // scroll sideways; wrapped ones fit the viewport
+                let content_width = match wrap {
+                    Some(_) => ui.available_width(),
+                    None => {
+                        let (_, longest) = editor.buffer().rope().longest_line();
+                        let text_width = (longest + 1) as f32 * char_width;
+                        ui.available_width().max(LINE_NUMBER_WIDTH + text_width)
+                    }
+                };
                 let (response, painter) = ui.allocate_painter(
-                    Vec2::new(ui.available_width(), content_height),
-                    egui::Sense::click(),
+                    Vec2::new(content_width, content_height),
+                    egui::Sense::click_and_drag(),
                 );
 
-                let line_number_width = 60.0;
-                let text_start_x = response.rect.min.x + line_number_width;
+                let text_start_x = response.rect.min.x + LINE_NUMBER_WIDTH;
+
+                // Buffer row beside the pointer's gutter position
+                let gutter_row_at = |pos: Pos2| {
+                    let row = ((pos.y - response.rect.min.y) / line_height).max(0.0) as usize;
+                    let row = row.min(total_rows - 1);
+                    wrap.map_or(row, |map| map.segment_at(row).0)
+                };
+
+                // Alt + hovering the gutter shows relative jump targets
+                let alt_held = ui.input(|i| i.modifiers.alt);
+                let gutter_hover_row = response
+                    .hover_pos()
+                    .filter(|pos| alt_held && pos.x < text_start_x)
+                    .map(gutter_row_at);
+
+                if let Some(row) = gutter_hover_row {
+                    if response.clicked() {
+                        action = Some(ViewportAction::JumpToLine(row));
+                    }
+                }
+
+                // Buffer row under the pointer, the pointer's column in its
+                // display row, and where that display row starts in the line
+                let pointer_at = |pos: Pos2| {
+                    let display_row = (((pos.y - response.rect.min.y) / line_height).max(0.0)
+                        as usize)
+                        .min(total_rows - 1);
+                    let column = ((pos.x - text_start_x).max(0.0) / char_width) as usize;
+                    match wrap {
+                        Some(map) => {
+                            let (row, segment) = map.segment_at(display_row);
+                            (row, column, segment)
+                        }
+                        None => (display_row, column, 0..usize::MAX),
+                    }
+                };
+                // Screen columns, so clicks past tabs and wide characters land
+                // on the character drawn there
+                let tab_width = editor.indent_width();
+                let point_at = |pos: Pos2| {
+                    let (row, column, segment) = pointer_at(pos);
+                    let buffer = editor.buffer();
+                    let start = buffer.display_column(Point::new(row, segment.start), tab_width);
+                    let point = buffer.point_at_display_column(row, start + column, tab_width);
+                    Point::new(row, point.column.min(segment.end))
+                };
+                // Same, but the column is a screen column from the line start
+                let screen_point_at = |pos: Pos2| {
+                    let (row, column, segment) = pointer_at(pos);
+                    let start = editor
+                        .buffer()
+                        .display_column(Point::new(row, segment.start), tab_width);
+                    Point::new(row, start + column)
+                };
+
+                let (pressed, shift_held, escape) = ui.input(|i| {
+                    (
+                        i.pointer.primary_pressed(),
+                        i.modifiers.shift,
+                        i.key_pressed(egui::Key::Escape),
+                    )
+                });
+                let selection = editor.selection();
+                if let Some(pos) = response
+                    .hover_pos()
+                    .filter(|pos| pressed && !alt_held && pos.x >= text_start_x)
+                {
+                    let point = point_at(pos);
+                    let (start, end) = selection.range();
+                    if !shift_held && start < point && point < end {
+                        // Pressing inside the selection may start moving it
+                        self.text_drag = Some(TextDrag::Pressed);
+                    } else {
+                        action = Some(ViewportAction::PlaceCursor {
+                            point,
+                            extend: shift_held,
+                        });
+                    }
+                }
+
+                // Pressing the line numbers selects lines; dragging extends them
+                let bulb_width = 10.0 + self.font_size * 0.5;
+                if let Some(pos) = response
+                    .hover_pos()
+                    .filter(|pos| pressed && !alt_held && pos.x < text_start_x)
+                {
+                    let row = gutter_row_at(pos);
+                    let on_bulb =
+                        self.lightbulb_row == Some(row) && pos.x < response.rect.min.x + bulb_width;
+                    if !on_bulb {
+                        self.line_drag = Some(row);
+                        action = Some(ViewportAction::SelectLines {
+                            anchor: row,
+                            head: row,
+                        });
+                    }
+                }
+                if let Some(anchor) = self.line_drag {
+                    if let Some(pos) = response
+                        .interact_pointer_pos()
+                        .filter(|_| response.dragged() && !pressed)
+                    {
+                        action = Some(ViewportAction::SelectLines {
+                            anchor,
+                            head: gutter_row_at(pos),
+                        });
+                    }
+                    if !ui.input(|i| i.pointer.primary_down()) {
+                        self.line_drag = None;
+                    }
+                } else if let Some(drag) = self.text_drag {
+                    let pos = response.interact_pointer_pos();
+                    if escape {
+                        self.text_drag = Some(TextDrag::Cancelled);
+                    } else if let Some(pos) = pos.filter(|_| drag != TextDrag::Cancelled) {
+                        if response.dragged() && !pressed {
+                            let point = point_at(pos);
+                            let (start, end) = selection.range();
+                            self.text_drag = Some(TextDrag::Over(point));
+                            // Dropping back inside the selection leaves it alone
+                            if (start..=end).contains(&point) {
+                                self.text_drag = Some(TextDrag::Pressed);
+                            }
+                            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                        }
+                    }
+                    if response.drag_stopped() {
+                        if let Some(TextDrag::Over(point)) = self.text_drag {
+                            action = Some(ViewportAction::MoveSelection(point));
+                        }
+                        self.text_drag = None;
+                    } else if response.clicked() {
+                        // Released without moving - a plain click
+                        if let Some(pos) = pos {
+                            action = Some(ViewportAction::PlaceCursor {
+                                point: point_at(pos),
+                                extend: false,
+                            });
+                        }
+                        self.text_drag = None;
+                    }
+                } else if let Some(pos) = response
+                    .interact_pointer_pos()
+                    .filter(|_| response.dragged() && self.block_anchor.is_none() && !pressed)
+                {
+                    action = Some(ViewportAction::SelectTo(point_at(pos)));
+                }
+
+                if let Some(pos) = response
+                    .interact_pointer_pos()
+                    .filter(|pos| response.double_clicked() && pos.x >= text_start_x)
+                {
+                    action = Some(ViewportAction::SelectWordAt(point_at(pos)));
+                }
+
+                if response.drag_started() && alt_held {
+                    self.block_anchor = response
+                        .interact_pointer_pos()
+                        .filter(|pos| pos.x >= text_start_x)
+                        .map(screen_point_at);
+                }
+                if let Some(anchor) = self.block_anchor {
+                    if let Some(pos) = response
+                        .interact_pointer_pos()
+                        .filter(|_| response.dragged())
+                    {
+                        action = Some(ViewportAction::SelectBlock {
+                            anchor,
+                            head: screen_point_at(pos),
+                        });
+                    }
+                    if response.drag_stopped() {
+                        self.block_anchor = None;
+                    }
+                }
+
+                if let Some(row) = self.lightbulb_row.filter(|_| gutter_hover_row.is_none()) {
+                    let row = wrap.map_or(row, |map| map.display_row(row));
+                    let y = response.rect.min.y + row as f32 * line_height;
+                    let bulb = Rect::from_min_size(
+                        Pos2::new(response.rect.min.x, y),
+                        Vec2::new(bulb_width, line_height),
+                    );
+                    painter.text(
+                        bulb.left_top(),
+                        egui::Align2::LEFT_TOP,
+                        "💡",
+                        FontId::proportional(self.font_size * 0.8),
+                        Color32::from_rgb(240, 200, 80),
+                    );
+                    if response.clicked()
+                        && response
+                            .interact_pointer_pos()
+                            .is_some_and(|pos| bulb.contains(pos))
+                    {
+                        action = Some(ViewportAction::ShowCodeActions);
+                    }
+                }
 
                 // 🚀 SIMPLIFIED: Just calculate highlights for visible region (regex is fast!)
-                let language = InstantHighlighter::detect_language(file_path);
+                let highlight_start = Instant::now();
                 let highlights = self.get_highlights_for_viewport(
                     editor,
                     visible_start,
@@ -270,33 +762,140 @@ impl ViewportRenderer {
                     language,
                     current_version,
                 );
+                self.stats.highlight_time += highlight_start.elapsed();
+                if self.stats.deferred_lines > 0 || self.restyle.is_some() {
+                    // Finish the plain or restyled lines next frame
+                    ui.ctx().request_repaint();
+                }
 
-                // Render visible lines only
-                for row in visible_start..visible_end {
-                    let y = response.rect.min.y + row as f32 * line_height;
-
-                    let line = self.get_line_cached(editor, row, current_version);
+                let selections: Vec<Selection> = editor
+                    .selections()
+                    .into_iter()
+                    .filter(|selection| !selection.is_empty())
+                    .collect();
+
+                // Extra cursors, drawn as dimmer carets after the text
+                let extra_cursors: Vec<Point> = editor
+                    .selections()
+                    .iter()
+                    .map(|selection| selection.end)
+                    .filter(|point| *point != cursor)
+                    .collect();
+
+                if self.conflicts.0 != current_version {
+                    self.conflicts = (current_version, editor.conflicts());
+                }
+                let conflicts = std::mem::take(&mut self.conflicts.1);
 
-                    // Line number
-                    let line_num = format!("{:4}", row + 1);
-                    painter.text(
-                        Pos2::new(response.rect.min.x + 10.0, y),
-                        egui::Align2::LEFT_TOP,
-                        line_num,
-                        font_id.clone(),
-                        Color32::from_rgb(100, 100, 100),
-                    );
+                // Render visible rows only
+                for display_row in display_start..display_end {
+                    let y = response.rect.min.y + display_row as f32 * line_height;
 
-                    // Get highlights for this specific line
-                    let line_highlights =
+                    // With soft wrap each display row shows a slice of its line
+                    let (row, segment) = match wrap {
+                        Some(map) => {
+                            let (row, segment) = map.segment_at(display_row);
+                            (row, Some(segment))
+                        }
+                        None => (display_row, None),
+                    };
+                    let full_line = self.get_line_cached(editor, row, current_version);
+                    let mut line_highlights =
                         self.filter_highlights_for_line(&highlights, editor, row, current_version);
+                    let full_len = full_line.chars().count();
+                    let (line, is_last_segment) = match &segment {
+                        Some(segment) => {
+                            line_highlights = Self::clip_highlights(&line_highlights, segment);
+                            let chars = full_line.chars().skip(segment.start);
+                            let line: String = chars.take(segment.len()).collect();
+                            let is_last = segment.end == full_line.chars().count();
+                            (Cow::Owned(line), is_last)
+                        }
+                        None => (Cow::Borrowed(&*full_line), true),
+                    };
 
-                    if row == cursor.row {
+                    if let Some(conflict) = conflicts.iter().find(|c| c.contains_row(row)) {
+                        let row_rect = Rect::from_min_size(
+                            Pos2::new(text_start_x, y),
+                            Vec2::new(response.rect.max.x - text_start_x, line_height),
+                        );
+                        painter.rect_filled(row_rect, 0.0, Self::conflict_color(conflict, row));
+                    }
+
+                    let segment_range = segment.clone().unwrap_or(0..full_len);
+                    // Tabs drawn as spaces to their tab stops, with the row's
+                    // columns mapped to where they land in the drawn text
+                    let tab_stops = line
+                        .contains('\t')
+                        .then(|| expand_tabs(&full_line, &segment_range, tab_width));
+                    let (line, shown_columns) = match tab_stops {
+                        Some((text, columns)) => (Cow::Owned(text), Some(columns)),
+                        None => (line, None),
+                    };
+                    let shown = |column: usize| {
+                        shown_columns
+                            .as_ref()
+                            .map_or(column, |columns| columns[column.min(columns.len() - 1)])
+                    };
+                    if shown_columns.is_some() {
+                        for (start, end, _) in &mut line_highlights {
+                            (*start, *end) = (shown(*start), shown(*end));
+                        }
+                    }
+                    for selection in &selections {
+                        let Some((columns, past_end)) = selection.row_span(row, full_len) else {
+                            continue;
+                        };
+                        // Columns within this display row; a selected newline
+                        // shows as half a character past the text
+                        let clamp = |column: usize| {
+                            column.clamp(segment_range.start, segment_range.end)
+                                - segment_range.start
+                        };
+                        let (from, to) = (clamp(columns.start), clamp(columns.end));
+                        let newline = past_end && is_last_segment;
+                        if from >= to && !newline {
+                            continue;
+                        }
+                        let (left, mut right) =
+                            self.columns_span(ui, &line, shown(from)..shown(to), &font_id);
+                        if newline {
+                            right += char_width * 0.5;
+                        }
+                        painter.rect_filled(
+                            Rect::from_min_max(
+                                Pos2::new(text_start_x + left, y),
+                                Pos2::new(text_start_x + right, y + line_height),
+                            ),
+                            0.0,
+                            theme::SELECTION_COLOR,
+                        );
+                    }
+
+                    // Line number (relative offsets while previewing a gutter jump);
+                    // wrapped continuation rows leave the gutter empty
+                    let (line_num, line_num_color) = if gutter_hover_row.is_some() {
+                        Self::relative_line_label(row, cursor.row, gutter_hover_row == Some(row))
+                    } else {
+                        (format!("{:4}", row + 1), Color32::from_rgb(100, 100, 100))
+                    };
+                    if segment.as_ref().is_none_or(|segment| segment.start == 0) {
+                        painter.text(
+                            Pos2::new(response.rect.min.x + 10.0, y),
+                            egui::Align2::LEFT_TOP,
+                            line_num,
+                            font_id.clone(),
+                            line_num_color,
+                        );
+                    }
+
+                    if row == cursor.row && display_row == cursor_display_row {
+                        let segment_start = segment.as_ref().map_or(0, |segment| segment.start);
                         self.render_cursor_line_highlighted(
                             &painter,
                             ui,
                             &line,
-                            cursor.column,
+                            shown(cursor.column - segment_start),
                             cursor_blink,
                             text_start_x,
                             y,
@@ -314,8 +913,97 @@ impl ViewportRenderer {
                             &line_highlights,
                         );
                     }
+
+                    if cursor_blink {
+                        let segment = segment.clone().unwrap_or(0..usize::MAX);
+                        // A cursor at a wrap boundary shows on the later row
+                        let shown_here = |point: &&Point| {
+                            point.row == row
+                                && (segment.contains(&point.column)
+                                    || (is_last_segment && point.column == segment.end))
+                        };
+                        for point in extra_cursors.iter().filter(shown_here) {
+                            let before: String = line
+                                .chars()
+                                .take(shown(point.column - segment.start))
+                                .collect();
+                            let x = text_start_x + self.measure_width(ui, &before, &font_id);
+                            Self::render_caret(&painter, x, y, line_height);
+                        }
+                    }
+
+                    // Where dragged text would be dropped
+                    if let Some(TextDrag::Over(point)) = self.text_drag {
+                        let segment = segment.clone().unwrap_or(0..usize::MAX);
+                        if point.row == row
+                            && (segment.contains(&point.column)
+                                || (is_last_segment && point.column == segment.end))
+                        {
+                            let before: String = line
+                                .chars()
+                                .take(shown(point.column - segment.start))
+                                .collect();
+                            let x = text_start_x + self.measure_width(ui, &before, &font_id);
+                            painter.rect_filled(
+                                Rect::from_min_size(Pos2::new(x, y), Vec2::new(2.0, line_height)),
+                                0.0,
+                                Color32::from_rgb(240, 200, 80),
+                            );
+                        }
+                    }
+
+                    if let Some(conflict) = conflicts
+                        .iter()
+                        .find(|c| c.start == row)
+                        .filter(|_| is_last_segment)
+                    {
+                        let mut x = text_start_x
+                            + self.measure_width(ui, &line, &font_id)
+                            + self.font_size * 2.0;
+                        for resolution in ConflictResolution::ALL {
+                            let label = resolution.label();
+                            let width = self.measure_width(ui, label, &font_id);
+                            let button =
+                                Rect::from_min_size(Pos2::new(x, y), Vec2::new(width, line_height));
+                            let hovered =
+                                response.hover_pos().is_some_and(|pos| button.contains(pos));
+                            painter.text(
+                                button.left_top(),
+                                egui::Align2::LEFT_TOP,
+                                label,
+                                font_id.clone(),
+                                if hovered {
+                                    Color32::WHITE
+                                } else {
+                                    Color32::from_rgb(120, 170, 230)
+                                },
+                            );
+                            if hovered && response.clicked() {
+                                action = Some(ViewportAction::ResolveConflict(
+                                    conflict.start,
+                                    resolution,
+                                ));
+                            }
+                            x += width + self.font_size;
+                        }
+                    }
+
+                    if let Some(diagnostic) =
+                        editor.diagnostic_on_row(row).filter(|_| is_last_segment)
+                    {
+                        let line_width = self.measure_width(ui, &line, &font_id);
+                        Self::render_diagnostic_tail(
+                            &painter,
+                            diagnostic,
+                            text_start_x + line_width,
+                            y,
+                            &font_id,
+                        );
+                    }
                 }
 
+                self.conflicts.1 = conflicts;
+
                 if should_auto_scroll {
                     let scroll_margin = line_height;
                     let cursor_rect = Rect::from_min_size(
@@ -328,40 +1016,326 @@ impl ViewportRenderer {
                     ui.scroll_to_rect(cursor_rect, None);
                 }
             });
+
+        self.scroll_id = Some(output.id);
+        self.scroll_offset = output.state.offset.y;
+
+        // Hovering the vertical scrollbar previews the lines at that height
+        let scroll = &ui.spacing().scroll;
+        let bar = scroll.bar_width + scroll.bar_inner_margin + scroll.bar_outer_margin;
+        let track = Rect::from_x_y_ranges(
+            output.inner_rect.max.x - bar..=output.inner_rect.max.x + bar,
+            output.inner_rect.y_range(),
+        );
+        let hover_y = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|pos| track.contains(*pos))
+            .map(|pos| pos.y);
+        if let Some(y) = hover_y.filter(|_| output.content_size.y > output.inner_rect.height()) {
+            let fraction = (y - track.min.y) / track.height().max(1.0);
+            let total_rows = wrap.map_or(editor.line_count(), |map| map.display_row_count());
+            let display_row = ((fraction * total_rows as f32) as usize).min(total_rows - 1);
+            let row = wrap.map_or(display_row, |map| map.segment_at(display_row).0);
+            egui::show_tooltip_at_pointer(
+                ui.ctx(),
+                ui.layer_id(),
+                output.id.with("scroll_preview"),
+                |ui| self.render_scroll_preview(ui, editor, row, language, current_version),
+            );
+        }
+
+        self.stats.line_cache =
+            CacheStats::new(self.line_hits, self.line_misses, self.line_cache.len(), 500);
+        self.stats.offset_cache = CacheStats::new(
+            self.offset_hits,
+            self.offset_misses,
+            self.line_offset_cache.len(),
+            10_000,
+        );
+
+        action
     }
 
-    /// 🚀 SIMPLIFIED: No caching - just calculate fresh (regex is fast!)
-    /// This matches the old editor's proven approach
+    /// The lines around `row`, small and highlighted, with `row` marked -
+    /// the scrollbar hover preview
+    fn render_scroll_preview(
+        &mut self,
+        ui: &mut egui::Ui,
+        editor: &crate::Editor,
+        row: usize,
+        language: &'static str,
+        current_version: u64,
+    ) {
+        let font_id = FontId::monospace(self.font_size * 0.8);
+        let line_height = ui.fonts(|f| f.row_height(&font_id)) + 2.0;
+        let line_count = editor.line_count();
+        let start = row
+            .saturating_sub(PREVIEW_LINES / 2)
+            .min(line_count.saturating_sub(PREVIEW_LINES));
+        let end = (start + PREVIEW_LINES).min(line_count);
+
+        let highlights =
+            self.get_highlights_for_viewport(editor, start, end, language, current_version);
+        let gutter = self.measure_width(ui, &format!("{} ", end), &font_id);
+        let text_width = self.measure_width(ui, &"M".repeat(PREVIEW_COLUMNS), &font_id);
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(gutter + text_width, (end - start) as f32 * line_height),
+            egui::Sense::hover(),
+        );
+        let rect = response.rect;
+
+        let visible = 0..PREVIEW_COLUMNS;
+        for (i, line_idx) in (start..end).enumerate() {
+            let y = rect.min.y + i as f32 * line_height;
+            if line_idx == row {
+                painter.rect_filled(
+                    Rect::from_min_size(
+                        Pos2::new(rect.min.x, y),
+                        Vec2::new(rect.width(), line_height),
+                    ),
+                    0.0,
+                    Color32::from_rgba_unmultiplied(255, 255, 255, 20),
+                );
+            }
+            painter.text(
+                Pos2::new(rect.min.x, y),
+                egui::Align2::LEFT_TOP,
+                line_idx + 1,
+                font_id.clone(),
+                Color32::from_rgb(100, 100, 100),
+            );
+
+            let line: String = self
+                .get_line_cached(editor, line_idx, current_version)
+                .chars()
+                .take(PREVIEW_COLUMNS)
+                .collect();
+            let line_highlights =
+                self.filter_highlights_for_line(&highlights, editor, line_idx, current_version);
+            let line_highlights = Self::clip_highlights(&line_highlights, &visible);
+            self.render_highlighted_line(
+                &painter,
+                &line,
+                rect.min.x + gutter,
+                y,
+                &font_id,
+                &line_highlights,
+            );
+        }
+    }
+
+    /// Highlights (character columns of a line) cut down to one wrapped
+    /// segment and made relative to its start
+    fn clip_highlights(
+        highlights: &[(usize, usize, Color32)],
+        segment: &std::ops::Range<usize>,
+    ) -> Vec<(usize, usize, Color32)> {
+        highlights
+            .iter()
+            .filter(|(start, end, _)| *end > segment.start && *start < segment.end)
+            .map(|&(start, end, color)| {
+                (
+                    start.max(segment.start) - segment.start,
+                    end.min(segment.end) - segment.start,
+                    color,
+                )
+            })
+            .collect()
+    }
+
+    /// Row background inside a merge conflict: green for our side, blue for
+    /// theirs, stronger on the marker rows and grey over a diff3 base
+    fn conflict_color(conflict: &Conflict, row: usize) -> Color32 {
+        let (ours, theirs) = (conflict.ours(), conflict.theirs());
+        if row == conflict.start || ours.contains(&row) {
+            let alpha = if row == conflict.start { 60 } else { 30 };
+            Color32::from_rgba_unmultiplied(80, 180, 100, alpha)
+        } else if row == conflict.end || theirs.contains(&row) {
+            let alpha = if row == conflict.end { 60 } else { 30 };
+            Color32::from_rgba_unmultiplied(80, 140, 230, alpha)
+        } else {
+            Color32::from_rgba_unmultiplied(150, 150, 150, 30)
+        }
+    }
+
+    /// Error Lens style: the diagnostic's message, dimmed, after the line's text
+    fn render_diagnostic_tail(
+        painter: &egui::Painter,
+        diagnostic: &Diagnostic,
+        x: f32,
+        y: f32,
+        font_id: &FontId,
+    ) {
+        let color = match diagnostic.severity {
+            Severity::Error => Color32::from_rgb(230, 90, 90),
+            Severity::Warning => Color32::from_rgb(230, 160, 60),
+            Severity::Info => Color32::from_rgb(100, 160, 220),
+            Severity::Hint => Color32::from_rgb(150, 150, 150),
+        };
+        let message = diagnostic.message.lines().next().unwrap_or_default();
+        painter.text(
+            Pos2::new(x + font_id.size * 2.0, y),
+            egui::Align2::LEFT_TOP,
+            format!("● {}", message),
+            font_id.clone(),
+            color.gamma_multiply(0.6),
+        );
+    }
+
+    /// Gutter label for relative jump preview: cursor row stays absolute,
+    /// other rows show the signed distance from the cursor
+    fn relative_line_label(row: usize, cursor_row: usize, hovered: bool) -> (String, Color32) {
+        let label = if row == cursor_row {
+            format!("{:4}", row + 1)
+        } else if row > cursor_row {
+            format!("{:>4}", format!("+{}", row - cursor_row))
+        } else {
+            format!("{:>4}", format!("-{}", cursor_row - row))
+        };
+
+        let color = if hovered {
+            Color32::from_rgb(220, 220, 220)
+        } else {
+            Color32::from_rgb(100, 160, 220)
+        };
+
+        (label, color)
+    }
+
+    /// 🚀 Highlights for the visible lines, computed a block at a time within
+    /// the frame's `HIGHLIGHT_BUDGET` so long lines can't drop frames.
+    /// Colors are looked up when drawing, so only a language change needs
+    /// new ranges: blocks keep their old ones until `restyle` delivers them
     fn get_highlights_for_viewport(
         &mut self,
         editor: &crate::Editor,
         visible_start: usize,
         visible_end: usize,
-        language: &str,
+        language: &'static str,
         current_version: u64,
     ) -> Vec<HighlightedRange> {
+        use std::hash::{Hash, Hasher};
+
+        self.receive_restyle();
         let rope = editor.buffer().rope();
+        let line_count = editor.line_count();
+        let mut highlights = Vec::new();
+        let mut stale = Vec::new();
+
+        // Whole blocks, cached by their text; once the frame's budget is
+        // spent the remaining blocks stay plain until a later frame
+        let last_block = visible_end.saturating_sub(1) / HIGHLIGHT_BLOCK_LINES;
+        for block in visible_start / HIGHLIGHT_BLOCK_LINES..=last_block {
+            let first_line = block * HIGHLIGHT_BLOCK_LINES;
+            let end_line = (first_line + HIGHLIGHT_BLOCK_LINES).min(line_count);
+            let start_byte = self.get_line_offset_cached(rope, first_line, current_version);
+            let end_byte = if end_line < line_count {
+                self.get_line_offset_cached(rope, end_line, current_version)
+            } else {
+                rope.len()
+            };
+
+            self.stats.rope_queries += 1;
+            let text = rope.slice_bytes(start_byte, end_byte);
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            text.hash(&mut hasher);
+            let text_hash = hasher.finish();
+
+            let cached = self
+                .highlight_blocks
+                .get(&block)
+                .filter(|cached| cached.text_hash == text_hash);
+            match cached {
+                Some(cached) if cached.language != language => {
+                    stale.push((block, text_hash, text));
+                }
+                Some(_) => {}
+                None => {
+                    if self.highlight_started.elapsed() > HIGHLIGHT_BUDGET {
+                        self.stats.deferred_lines += end_line
+                            .min(visible_end)
+                            .saturating_sub(first_line.max(visible_start));
+                        continue;
+                    }
+                    let ranges =
+                        self.highlighter
+                            .highlight_lines(&text, language, MAX_HIGHLIGHT_LINE_LEN);
+                    self.highlight_blocks.insert(
+                        block,
+                        CachedHighlights {
+                            text_hash,
+                            language,
+                            ranges,
+                        },
+                    );
+                }
+            }
 
-        // 🚀 USE CACHED line_to_byte calls!
-        let visible_start_byte = self.get_line_offset_cached(rope, visible_start, current_version);
-        let visible_end_byte = if visible_end < editor.line_count() {
-            self.get_line_offset_cached(rope, visible_end, current_version)
-        } else {
-            rope.len()
-        };
+            highlights.extend(self.highlight_blocks[&block].ranges.iter().map(|h| {
+                let mut h = h.clone();
+                h.start += start_byte;
+                h.end += start_byte;
+                h
+            }));
+        }
 
-        let visible_text = rope.slice_bytes(visible_start_byte, visible_end_byte);
+        if !stale.is_empty() {
+            self.start_restyle(language, stale);
+        }
+        highlights
+    }
 
-        // 🚀 ALWAYS FRESH: Regex is fast enough, caching adds overhead
-        self.highlighter
-            .highlight_visible_region(&visible_text, 0, visible_text.len(), language)
-            .into_iter()
-            .map(|mut h| {
-                h.start += visible_start_byte;
-                h.end += visible_start_byte;
-                h
-            })
-            .collect()
+    /// Re-highlight `blocks` (index, text hash, text) for `language` on a
+    /// background task, unless one is already doing that language
+    fn start_restyle(&mut self, language: &'static str, blocks: Vec<(usize, u64, String)>) {
+        if self
+            .restyle
+            .as_ref()
+            .is_some_and(|restyle| restyle.language == language)
+        {
+            return;
+        }
+
+        let (sender, result) = mpsc::channel();
+        let highlighter = Arc::clone(&self.highlighter);
+        task::spawn(move || {
+            let blocks = blocks
+                .into_iter()
+                .map(|(block, text_hash, text)| {
+                    let ranges =
+                        highlighter.highlight_lines(&text, language, MAX_HIGHLIGHT_LINE_LEN);
+                    let highlights = CachedHighlights {
+                        text_hash,
+                        language,
+                        ranges,
+                    };
+                    (block, highlights)
+                })
+                .collect();
+            sender.send(blocks).ok();
+        });
+        self.restyle = Some(Restyle { language, result });
+    }
+
+    /// Swap in a finished restyle's blocks, all at once; blocks edited since
+    /// it started keep their newer highlights
+    fn receive_restyle(&mut self) {
+        let Some(restyle) = &self.restyle else {
+            return;
+        };
+        let blocks = match restyle.result.try_recv() {
+            Ok(blocks) => blocks,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Vec::new(),
+        };
+        self.restyle = None;
+        for (block, highlights) in blocks {
+            let current = self.highlight_blocks.get(&block);
+            if current.is_none_or(|current| current.text_hash == highlights.text_hash) {
+                self.highlight_blocks.insert(block, highlights);
+            }
+        }
     }
 
     /// 🚀 Filter highlights to only those affecting a specific line
@@ -383,7 +1357,7 @@ impl ViewportRenderer {
             rope.len()
         };
 
-        let line_content = editor.buffer().line(line_idx).unwrap_or_default();
+        let line_content = self.get_line_cached(editor, line_idx, current_version);
 
         highlights
             .iter()
@@ -410,7 +1384,7 @@ impl ViewportRenderer {
                     .count();
 
                 if start_char < end_char {
-                    Some((start_char, end_char, h.highlight.to_color()))
+                    Some((start_char, end_char, h.highlight.to_color().into()))
                 } else {
                     None
                 }
@@ -575,6 +1549,39 @@ impl ViewportRenderer {
         }
     }
 
+    /// Left and right edges (from the text start) of `columns` of a line,
+    /// measured rather than counted so tabs and wide characters line up
+    /// with the drawn text
+    fn columns_span(
+        &mut self,
+        ui: &egui::Ui,
+        text: &str,
+        columns: std::ops::Range<usize>,
+        font_id: &FontId,
+    ) -> (f32, f32) {
+        let before: String = text.chars().take(columns.start).collect();
+        let selected: String = text
+            .chars()
+            .skip(columns.start)
+            .take(columns.len())
+            .collect();
+        let left = self.measure_width(ui, &before, font_id);
+        (left, left + self.measure_width(ui, &selected, font_id))
+    }
+
+    /// Thin caret for an extra cursor: the main cursor's shape, dimmer
+    fn render_caret(painter: &egui::Painter, x: f32, y: f32, line_height: f32) {
+        let height = line_height * 0.85;
+        painter.rect_filled(
+            Rect::from_min_size(
+                Pos2::new(x, y + (line_height - height) / 2.0),
+                Vec2::new(2.0, height),
+            ),
+            0.0,
+            Color32::WHITE.gamma_multiply(0.5),
+        );
+    }
+
     /// Simplified render method (no external highlighter needed)
     pub fn render(
         &mut self,
@@ -582,9 +1589,9 @@ impl ViewportRenderer {
         editor: &crate::Editor,
         cursor_blink: bool,
         should_auto_scroll: bool,
-    ) {
+    ) -> Option<ViewportAction> {
         // Just call the highlighting version (highlighter is built-in now)
-        self.render_with_highlighting(ui, editor, cursor_blink, should_auto_scroll);
+        self.render_with_highlighting(ui, editor, cursor_blink, should_auto_scroll)
     }
 }
 
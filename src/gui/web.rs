@@ -0,0 +1,41 @@
+//! Browser stand-ins for the native file dialogs: there is no file system,
+//! so files come in through the browser's picker and leave as downloads
+
+use std::sync::mpsc::{self, Receiver};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// A picked file's name and contents
+pub type PickedFile = (String, Vec<u8>);
+
+/// Ask the browser for a file; it arrives on the returned channel (None if
+/// the picker was dismissed)
+pub fn pick_file() -> Receiver<Option<PickedFile>> {
+    let (sender, receiver) = mpsc::channel();
+    wasm_bindgen_futures::spawn_local(async move {
+        let picked = match rfd::AsyncFileDialog::new().pick_file().await {
+            Some(file) => Some((file.file_name(), file.read().await)),
+            None => None,
+        };
+        sender.send(picked).ok();
+    });
+    receiver
+}
+
+/// Offer `text` as a download named `file_name` (the browser decides or asks
+/// where it goes)
+pub fn download(file_name: &str, text: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(text));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("text/plain;charset=utf-8");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url)
+}
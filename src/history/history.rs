@@ -29,8 +29,12 @@ impl History {
         self.current = Arc::new(new_buffer);
     }
 
+    /// Edits share all but the touched chunks and tree path with the buffer
+    /// they came from, so each entry costs about the size of its edit; the
+    /// old buffer's caches are dropped rather than kept alongside
     pub fn push(&mut self, old_buffer: Buffer, new_buffer: Buffer, transaction: Transaction) {
-        self.undo_stack.push((Arc::new(old_buffer), transaction));
+        self.undo_stack
+            .push((Arc::new(old_buffer.without_caches()), transaction));
         self.current = Arc::new(new_buffer);
         self.redo_stack.clear();
     }
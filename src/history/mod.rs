@@ -2,4 +2,4 @@ pub mod history;
 pub mod transaction;
 
 pub use history::History;
-pub use transaction::{EditKind, Transaction};
+pub use transaction::{EditKind, Hunk, Transaction};
@@ -1,36 +1,66 @@
 use crate::buffer::Point;
+use crate::editor::Selection;
 
-/// A single edit operation
+/// A single edit operation. `ReplaceHunks` is a whole-buffer replace as
+/// the spans that changed; `Snapshot` is one too large to keep as hunks,
+/// whose undo relies on the buffer saved with it
 #[derive(Debug, Clone)]
 pub enum EditKind {
     Insert { text: String },
     Delete { text: String },
     Replace { old_text: String, new_text: String },
+    ReplaceHunks { hunks: Vec<Hunk> },
+    Snapshot,
+}
+
+/// One changed span of a whole-buffer replace: `old_text` at byte `offset`
+/// of the old text became `new_text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub offset: usize,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+impl Hunk {
+    /// Bytes of text this hunk keeps
+    pub fn size(&self) -> usize {
+        self.old_text.len() + self.new_text.len()
+    }
+
+    /// `(start, old_end, new_end)` spanning every hunk, sorted by offset
+    pub fn span(hunks: &[Hunk]) -> (usize, usize, usize) {
+        let (Some(first), Some(last)) = (hunks.first(), hunks.last()) else {
+            return (0, 0, 0);
+        };
+        let old_end = last.offset + last.old_text.len();
+        let grown: isize = hunks
+            .iter()
+            .map(|hunk| hunk.new_text.len() as isize - hunk.old_text.len() as isize)
+            .sum();
+        (first.offset, old_end, old_end.saturating_add_signed(grown))
+    }
 }
 
 /// A transaction represents a group of edits
 #[derive(Debug, Clone)]
 pub struct Transaction {
-    pub cursor_before: Point,
-    pub cursor_after: Point,
+    /// Selections before the edit (restored by undo), primary first
+    pub selections_before: Vec<Selection>,
+    /// Selections after the edit (restored by redo), primary first
+    pub selections_after: Vec<Selection>,
     pub edit: EditKind,
+    /// Byte offset an insert, delete or replace starts at (hunks carry their own)
+    pub offset: usize,
 }
 
 impl Transaction {
     pub fn insert(text: String, cursor_before: Point, cursor_after: Point) -> Self {
-        Self {
-            cursor_before,
-            cursor_after,
-            edit: EditKind::Insert { text },
-        }
+        Self::new(EditKind::Insert { text }, cursor_before, cursor_after)
     }
 
     pub fn delete(text: String, cursor_before: Point, cursor_after: Point) -> Self {
-        Self {
-            cursor_before,
-            cursor_after,
-            edit: EditKind::Delete { text },
-        }
+        Self::new(EditKind::Delete { text }, cursor_before, cursor_after)
     }
 
     pub fn replace(
@@ -39,10 +69,87 @@ impl Transaction {
         cursor_before: Point,
         cursor_after: Point,
     ) -> Self {
-        Self {
+        Self::new(
+            EditKind::Replace { old_text, new_text },
             cursor_before,
             cursor_after,
-            edit: EditKind::Replace { old_text, new_text },
+        )
+    }
+
+    /// A whole-buffer replace as `hunks`, or as a bare snapshot once their
+    /// text is over `max_bytes`
+    pub fn replace_hunks(
+        hunks: Vec<Hunk>,
+        max_bytes: usize,
+        cursor_before: Point,
+        cursor_after: Point,
+    ) -> Self {
+        let size: usize = hunks.iter().map(Hunk::size).sum();
+        let edit = if size <= max_bytes {
+            EditKind::ReplaceHunks { hunks }
+        } else {
+            EditKind::Snapshot
+        };
+        Self::new(edit, cursor_before, cursor_after)
+    }
+
+    fn new(edit: EditKind, cursor_before: Point, cursor_after: Point) -> Self {
+        Self {
+            selections_before: vec![Selection::cursor(cursor_before)],
+            selections_after: vec![Selection::cursor(cursor_after)],
+            edit,
+            offset: 0,
+        }
+    }
+
+    /// Record where the edit starts
+    pub fn at(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Record full selections (primary first) instead of bare cursors
+    pub fn with_selections(mut self, before: Vec<Selection>, after: Vec<Selection>) -> Self {
+        self.selections_before = before;
+        self.selections_after = after;
+        self
+    }
+
+    /// `(start, old_end, new_end)`: the byte span the transaction replaced,
+    /// ending in the old and in the new text. None for a snapshot, which
+    /// doesn't know what changed
+    pub fn changed_range(&self) -> Option<(usize, usize, usize)> {
+        let span = |old_len: usize, new_len: usize| {
+            Some((self.offset, self.offset + old_len, self.offset + new_len))
+        };
+        match &self.edit {
+            EditKind::Insert { text } => span(0, text.len()),
+            EditKind::Delete { text } => span(text.len(), 0),
+            EditKind::Replace { old_text, new_text } => span(old_text.len(), new_text.len()),
+            EditKind::ReplaceHunks { hunks } => Some(Hunk::span(hunks)),
+            EditKind::Snapshot => None,
+        }
+    }
+
+    /// Add `text` inserted at byte `offset` to this transaction if it
+    /// directly follows the transaction's new text (the space ending a typed
+    /// word); false if it's somewhere else
+    pub fn extend_insert(&mut self, offset: usize, text: &str) -> bool {
+        match &mut self.edit {
+            EditKind::Insert { text: inserted } if self.offset + inserted.len() == offset => {
+                inserted.push_str(text);
+            }
+            EditKind::Replace { new_text, .. } if self.offset + new_text.len() == offset => {
+                new_text.push_str(text);
+            }
+            EditKind::Delete { text: deleted } if self.offset == offset => {
+                self.edit = EditKind::Replace {
+                    old_text: std::mem::take(deleted),
+                    new_text: text.to_string(),
+                };
+            }
+            _ => return false,
         }
+        true
     }
 }
@@ -0,0 +1,152 @@
+use crate::rope::Rope;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Part of a file to print in headless `--cat` mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatRange {
+    All,
+    /// 1-based, inclusive line numbers (`--range 10:20`, open ends allowed)
+    Lines {
+        start: usize,
+        end: Option<usize>,
+    },
+    /// 0-based, half-open byte offsets (`--bytes 0:512`, open ends allowed)
+    Bytes {
+        start: usize,
+        end: Option<usize>,
+    },
+}
+
+impl CatRange {
+    /// Parse a `start:end` spec for `--range`
+    pub fn parse_lines(spec: &str) -> Result<Self, String> {
+        let (start, end) = parse_bounds(spec)?;
+        let start = start.unwrap_or(1);
+        if start == 0 {
+            return Err("line numbers start at 1".to_string());
+        }
+        if end.is_some_and(|end| end < start) {
+            return Err(format!("range end is before start: {}", spec));
+        }
+        Ok(Self::Lines { start, end })
+    }
+
+    /// Parse a `start:end` spec for `--bytes`
+    pub fn parse_bytes(spec: &str) -> Result<Self, String> {
+        let (start, end) = parse_bounds(spec)?;
+        let start = start.unwrap_or(0);
+        if end.is_some_and(|end| end < start) {
+            return Err(format!("range end is before start: {}", spec));
+        }
+        Ok(Self::Bytes { start, end })
+    }
+
+    /// Byte range in `rope` (clamped to its length); byte offsets must fall on
+    /// character boundaries
+    pub fn byte_range(&self, rope: &Rope) -> io::Result<(usize, usize)> {
+        let len = rope.len();
+        match *self {
+            Self::All => Ok((0, len)),
+            Self::Lines { start, end } => {
+                let start_byte = rope.line_to_byte(start - 1).min(len);
+                let end_byte = end.map_or(len, |end| rope.line_to_byte(end).min(len));
+                Ok((start_byte, end_byte))
+            }
+            Self::Bytes { start, end } => {
+                let start = start.min(len);
+                let end = end.map_or(len, |end| end.min(len));
+                for offset in [start, end] {
+                    if !is_char_boundary(rope, offset) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("byte offset {} is inside a UTF-8 character", offset),
+                        ));
+                    }
+                }
+                Ok((start, end))
+            }
+        }
+    }
+}
+
+/// Parsed arguments for headless mode: `--cat <file> [--range a:b | --bytes a:b]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatArgs {
+    pub path: PathBuf,
+    pub range: CatRange,
+}
+
+impl CatArgs {
+    /// `Ok(None)` when the arguments don't ask for headless mode
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        if !args
+            .iter()
+            .any(|arg| matches!(arg.as_str(), "--cat" | "--range" | "--bytes"))
+        {
+            return Ok(None);
+        }
+
+        let mut path = None;
+        let mut range = CatRange::All;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--cat" => {}
+                "--range" | "--bytes" => {
+                    if range != CatRange::All {
+                        return Err("only one of --range/--bytes may be given".to_string());
+                    }
+                    let spec = iter
+                        .next()
+                        .ok_or_else(|| format!("{} needs a start:end value", arg))?;
+                    range = if arg == "--range" {
+                        CatRange::parse_lines(spec)?
+                    } else {
+                        CatRange::parse_bytes(spec)?
+                    };
+                }
+                _ if path.is_none() => path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument: {}", arg)),
+            }
+        }
+
+        let path = path.ok_or("missing file to print")?;
+        Ok(Some(Self { path, range }))
+    }
+}
+
+/// Load a file into a Rope, rejecting invalid UTF-8 with the offending offset
+pub fn load_rope<P: AsRef<Path>>(path: P) -> io::Result<Rope> {
+    Rope::from_reader(std::fs::File::open(path)?)
+}
+
+/// Stream part of a rope to `out` chunk by chunk (no full-string conversion)
+pub fn write_range<W: Write>(rope: &Rope, range: CatRange, out: &mut W) -> io::Result<()> {
+    let (start, end) = range.byte_range(rope)?;
+    for piece in rope.chunks_in(start, end) {
+        out.write_all(piece.as_bytes())?;
+    }
+    out.flush()
+}
+
+fn parse_bounds(spec: &str) -> Result<(Option<usize>, Option<usize>), String> {
+    let (start, end) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("expected start:end, got {:?}", spec))?;
+    let parse = |part: &str| -> Result<Option<usize>, String> {
+        if part.is_empty() {
+            Ok(None)
+        } else {
+            part.parse()
+                .map(Some)
+                .map_err(|_| format!("not a number: {:?}", part))
+        }
+    };
+    Ok((parse(start)?, parse(end)?))
+}
+
+fn is_char_boundary(rope: &Rope, offset: usize) -> bool {
+    // Rounding only moves an offset that falls inside a character
+    rope.ceil_char_boundary(offset) == offset
+}
@@ -1,9 +1,20 @@
+pub mod cat;
 pub mod mmap_reader;
+pub mod project_search;
 pub mod reader;
+pub mod recovery;
 pub mod streaming;
+pub mod tail;
 pub mod writer;
 
+pub use cat::{load_rope, write_range, CatArgs, CatRange};
 pub use mmap_reader::MmapReader;
-pub use reader::{read_file, read_file_chunked};
+pub use project_search::{search_project, ProjectMatch, MAX_PROJECT_MATCHES};
+pub use reader::{
+    decode_text, read_file, read_file_chunked, read_file_decoded, read_file_with_progress,
+    read_stdin, read_text, TextEncoding,
+};
+pub use recovery::{RecoveryEntry, RecoveryJournal};
 pub use streaming::{FileInfo, StreamingLoader};
-pub use writer::{write_file, write_file_from_rope}; // 🚀 NEW: Export efficient rope writer
+pub use tail::{FileTail, TailUpdate};
+pub use writer::{write_file, write_file_from_chunks, write_file_from_rope}; // 🚀 NEW: Export efficient rope writer
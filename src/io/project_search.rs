@@ -0,0 +1,106 @@
+use crate::progress::ProgressHandle;
+use crate::rope::Rope;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 4_000_000;
+
+/// Stop collecting after this many matches
+pub const MAX_PROJECT_MATCHES: usize = 1_000;
+
+/// Directories never searched
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// A line containing the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectMatch {
+    pub path: PathBuf,
+    /// 0-based
+    pub line: usize,
+    pub text: String,
+}
+
+/// Every line under `root` containing `query` (case-sensitive), skipping
+/// hidden and build directories and files that aren't UTF-8 text; stops
+/// with an `Interrupted` error once the task is cancelled
+pub fn search_project(
+    root: &Path,
+    query: &str,
+    progress: &ProgressHandle,
+) -> io::Result<Vec<ProjectMatch>> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files, progress)?;
+    files.sort();
+
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return Ok(matches);
+    }
+    for (index, path) in files.iter().enumerate() {
+        if progress.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        progress.set_fraction(index as f32 / files.len() as f32);
+
+        let Ok(rope) = fs::File::open(path).and_then(Rope::from_reader) else {
+            continue;
+        };
+        // One match per line: after a hit, carry on from the next line
+        let mut from = 0;
+        while let Some(found) = rope.find(query, from) {
+            let (line, _) = rope.byte_to_line_col(found.start);
+            let content = rope.line(line).unwrap_or_default();
+            matches.push(ProjectMatch {
+                path: path.clone(),
+                line,
+                text: content.trim().to_string(),
+            });
+            if matches.len() >= MAX_PROJECT_MATCHES {
+                return Ok(matches);
+            }
+            from = rope.line_to_byte(line + 1);
+            if from <= found.start {
+                break;
+            }
+        }
+    }
+    Ok(matches)
+}
+
+fn collect_files(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    progress: &ProgressHandle,
+) -> io::Result<()> {
+    if progress.is_cancelled() {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+    }
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                // Unreadable subdirectories are left out rather than failing the search
+                match collect_files(&entry.path(), files, progress) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => return Err(e),
+                    _ => {}
+                }
+            }
+        } else if file_type.is_file()
+            && entry
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() <= MAX_FILE_BYTES)
+        {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,7 @@
+use crate::progress::ProgressHandle;
+use crate::rope::Rope;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
 
 /// Read file contents
@@ -7,6 +9,128 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
     std::fs::read_to_string(path)
 }
 
+/// Read a file as UTF-8 text into a rope, reporting the fraction read to
+/// `progress`; stops with an `Interrupted` error once the task is cancelled
+pub fn read_file_with_progress<P: AsRef<Path>>(
+    path: P,
+    progress: &ProgressHandle,
+) -> io::Result<Rope> {
+    let file = File::open(path)?;
+    let file_size = file.metadata()?.len() as usize;
+    Rope::from_reader(ProgressReader::new(file, |bytes_read| {
+        if progress.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        if file_size > 0 {
+            progress.set_fraction(bytes_read as f32 / file_size as f32);
+        }
+        Ok(())
+    }))
+}
+
+/// Reader adapter that calls `on_read` with the running byte total before
+/// each read and after the last one; an error from it aborts the read
+pub(crate) struct ProgressReader<R, F> {
+    inner: R,
+    bytes_read: usize,
+    on_read: F,
+}
+
+impl<R: Read, F: FnMut(usize) -> io::Result<()>> ProgressReader<R, F> {
+    pub(crate) fn new(inner: R, on_read: F) -> Self {
+        Self {
+            inner,
+            bytes_read: 0,
+            on_read,
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(usize) -> io::Result<()>> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (self.on_read)(self.bytes_read)?;
+        let n = self.inner.read(buf)?;
+        self.bytes_read += n;
+        if n == 0 {
+            (self.on_read)(self.bytes_read)?;
+        }
+        Ok(n)
+    }
+}
+
+/// Read a stream to the end as UTF-8 text (invalid UTF-8 is an InvalidData error)
+pub fn read_text<R: Read>(mut reader: R) -> io::Result<String> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// How `decode_text` turned a file's bytes into text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Not valid UTF-8 and no BOM: each byte read as its own char
+    Latin1,
+}
+
+impl TextEncoding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 with BOM",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Decode bytes as text for inserting into a buffer: UTF-16 when a BOM says
+/// so, otherwise UTF-8, falling back to Latin-1. The BOM itself is dropped
+pub fn decode_text(bytes: Vec<u8>) -> (String, TextEncoding) {
+    let utf16 = |rest: &[u8], unit: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| unit([pair[0], pair[1]]))
+            .collect();
+        let mut text = String::from_utf16_lossy(&units);
+        if rest.len() % 2 == 1 {
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+        text
+    };
+    match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => (utf16(rest, u16::from_le_bytes), TextEncoding::Utf16Le),
+        [0xFE, 0xFF, rest @ ..] => (utf16(rest, u16::from_be_bytes), TextEncoding::Utf16Be),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => (
+            String::from_utf8_lossy(rest).into_owned(),
+            TextEncoding::Utf8Bom,
+        ),
+        _ => match String::from_utf8(bytes) {
+            Ok(text) => (text, TextEncoding::Utf8),
+            Err(e) => (
+                e.as_bytes().iter().map(|&byte| char::from(byte)).collect(),
+                TextEncoding::Latin1,
+            ),
+        },
+    }
+}
+
+/// Read a whole file as text for inserting, whatever its encoding
+pub fn read_file_decoded<P: AsRef<Path>>(path: P) -> io::Result<(String, TextEncoding)> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    Ok(decode_text(bytes))
+}
+
+/// Stream all of stdin into a rope (for `cat file | editor -`)
+pub fn read_stdin() -> io::Result<Rope> {
+    Rope::from_reader(io::stdin().lock())
+}
+
 /// Read large file line by line (for huge files)
 pub fn read_file_chunked<P: AsRef<Path>>(path: P, max_size: usize) -> io::Result<String> {
     let file = File::open(path)?;
@@ -0,0 +1,149 @@
+use std::fs;
+use std::io::{self, Result};
+use std::path::{Path, PathBuf};
+
+/// Unsaved buffer content kept across restarts (hot exit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryEntry {
+    /// File the buffer belongs to (None for untitled buffers)
+    pub path: Option<PathBuf>,
+    /// Buffer content at exit
+    pub text: String,
+}
+
+/// Recovery journal - one file per dirty buffer in a state directory
+///
+/// Each entry file starts with a `path=` header line followed by the raw text,
+/// so the journal stays readable (and recoverable by hand) if the editor breaks.
+pub struct RecoveryJournal {
+    dir: PathBuf,
+}
+
+impl RecoveryJournal {
+    const ENTRY_PREFIX: &'static str = "buffer-";
+    const ENTRY_EXTENSION: &'static str = "recovery";
+    const PATH_HEADER: &'static str = "path=";
+
+    /// Create journal stored in a specific directory
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Journal in the user's state directory
+    /// ($ZED_EDITOR_RECOVERY_DIR, then ~/.local/state, then the temp dir)
+    pub fn default_location() -> Self {
+        let dir = std::env::var_os("ZED_EDITOR_RECOVERY_DIR")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".local/state/zed-text-editor"))
+            })
+            .unwrap_or_else(|| std::env::temp_dir().join("zed-text-editor"));
+
+        Self::new(dir.join("recovery"))
+    }
+
+    /// Directory holding the entries
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Replace the journal contents with these entries
+    pub fn save_all(&self, entries: &[RecoveryEntry]) -> Result<()> {
+        self.clear()?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        for (idx, entry) in entries.iter().enumerate() {
+            let path_line = entry
+                .path
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            let mut contents = String::with_capacity(entry.text.len() + path_line.len() + 8);
+            contents.push_str(Self::PATH_HEADER);
+            contents.push_str(&path_line);
+            contents.push('\n');
+            contents.push_str(&entry.text);
+
+            fs::write(self.entry_path(idx), contents)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load all entries (in the order they were saved)
+    pub fn load_all(&self) -> Result<Vec<RecoveryEntry>> {
+        let mut files = self.entry_files()?;
+        files.sort_by_key(|(idx, _)| *idx);
+
+        let mut entries = Vec::with_capacity(files.len());
+        for (_, file) in files {
+            let contents = fs::read_to_string(&file)?;
+            let (header, text) = contents.split_once('\n').unwrap_or((&contents, ""));
+
+            let Some(path) = header.strip_prefix(Self::PATH_HEADER) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Malformed recovery entry: {}", file.display()),
+                ));
+            };
+
+            entries.push(RecoveryEntry {
+                path: (!path.is_empty()).then(|| PathBuf::from(path)),
+                text: text.to_string(),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) -> Result<()> {
+        for (_, file) in self.entry_files()? {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, idx: usize) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}.{}",
+            Self::ENTRY_PREFIX,
+            idx,
+            Self::ENTRY_EXTENSION
+        ))
+    }
+
+    /// (index, path) of every entry file currently in the journal
+    fn entry_files(&self) -> Result<Vec<(usize, PathBuf)>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut files = Vec::new();
+        for dir_entry in read_dir {
+            let path = dir_entry?.path();
+            let idx = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(Self::ENTRY_PREFIX))
+                .and_then(|n| n.strip_suffix(Self::ENTRY_EXTENSION))
+                .and_then(|n| n.strip_suffix('.'))
+                .and_then(|n| n.parse::<usize>().ok());
+
+            if let Some(idx) = idx {
+                files.push((idx, path));
+            }
+        }
+
+        Ok(files)
+    }
+}
@@ -1,3 +1,5 @@
+use super::reader::ProgressReader;
+use crate::rope::Rope;
 use std::fs::File;
 use std::io::{BufReader, Read, Result};
 use std::path::Path;
@@ -84,6 +86,37 @@ impl StreamingLoader {
 
         Ok(result)
     }
+
+    /// Load file straight into a rope, chunking each read as it arrives
+    /// (invalid UTF-8 is an `InvalidData` error rather than replaced)
+    pub fn load_rope<P: AsRef<Path>>(
+        &self,
+        path: P,
+        mut on_progress: impl FnMut(f32, &str),
+    ) -> Result<Rope> {
+        let file = File::open(path.as_ref())?;
+        let file_size = file.metadata()?.len() as usize;
+        let reader = BufReader::with_capacity(self.chunk_size, file);
+
+        let filename = path
+            .as_ref()
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file");
+
+        Rope::from_reader(ProgressReader::new(reader, |bytes_read| {
+            if bytes_read > 0 {
+                let progress = if file_size > 0 {
+                    (bytes_read as f32 / file_size as f32).min(1.0)
+                } else {
+                    0.0
+                };
+                let msg = format!("Loading {} ({:.1}%)", filename, progress * 100.0);
+                on_progress(progress, &msg);
+            }
+            Ok(())
+        }))
+    }
 }
 
 /// Detect if file is text or binary (simple heuristic)
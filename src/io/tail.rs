@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::{Read, Result, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Lines appended since the last poll
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TailUpdate {
+    /// File shrank (truncated or rotated) - previously read lines are stale
+    pub truncated: bool,
+    /// Complete new lines (a trailing partial line waits for its newline)
+    pub lines: Vec<String>,
+}
+
+/// Follows appends to a file by polling its length
+///
+/// The file is reopened on every poll, so rotation (rename + recreate) is
+/// picked up as a truncation instead of following the old inode forever.
+pub struct FileTail {
+    path: PathBuf,
+    position: u64,
+    pending: Vec<u8>,
+}
+
+impl FileTail {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            position: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read everything appended since the last call (the first call returns the whole file)
+    pub fn poll(&mut self) -> Result<TailUpdate> {
+        let mut file = File::open(&self.path)?;
+        let len = file.metadata()?.len();
+
+        let mut update = TailUpdate::default();
+        if len < self.position {
+            self.position = 0;
+            self.pending.clear();
+            update.truncated = true;
+        }
+        if len == self.position {
+            return Ok(update);
+        }
+
+        file.seek(SeekFrom::Start(self.position))?;
+        let read = file.read_to_end(&mut self.pending)?;
+        self.position += read as u64;
+
+        // Split only on complete lines; bytes after the last newline may be a
+        // half-written line (or half a UTF-8 sequence)
+        if let Some(last_newline) = self.pending.iter().rposition(|b| *b == b'\n') {
+            let rest = self.pending.split_off(last_newline + 1);
+            update.lines = self.pending[..last_newline]
+                .split(|b| *b == b'\n')
+                .map(|line| {
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    String::from_utf8_lossy(line).into_owned()
+                })
+                .collect();
+            self.pending = rest;
+        }
+
+        Ok(update)
+    }
+}
@@ -9,14 +9,19 @@ pub fn write_file<P: AsRef<Path>>(path: P, contents: &str) -> io::Result<()> {
 
 /// 🚀 ULTIMATE OPTIMIZED: Write from Rope chunk-by-chunk (ZERO string conversion!)
 pub fn write_file_from_rope<P: AsRef<Path>>(path: P, rope: &crate::rope::Rope) -> io::Result<()> {
+    write_file_from_chunks(path, rope.chunks_in(0, rope.len()))
+}
+
+/// Write text given in pieces (`Rope::chunks_in`, `Editor::selected_chunks`)
+/// without joining them first
+pub fn write_file_from_chunks<'a, P: AsRef<Path>>(
+    path: P,
+    chunks: impl IntoIterator<Item = &'a str>,
+) -> io::Result<()> {
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
-
-    // 🚀 Write each chunk directly - NO full string allocation!
-    rope.for_each_chunk(|chunk| {
-        writer.write_all(chunk.as_bytes()).unwrap();
-    });
-
-    writer.flush()?;
-    Ok(())
+    for chunk in chunks {
+        writer.write_all(chunk.as_bytes())?;
+    }
+    writer.flush()
 }
@@ -0,0 +1,247 @@
+use super::chord::Chord;
+use super::command::Command;
+use super::sequence::KeySequence;
+use crate::settings::Settings;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Result};
+use std::path::{Path, PathBuf};
+
+/// Key sequence → command bindings; a command may have several sequences,
+/// and most sequences are a single chord
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Command)>,
+}
+
+impl Keymap {
+    /// Built-in bindings
+    pub fn defaults() -> Self {
+        let key = Chord::new;
+        let bindings = vec![
+            (key("Z").ctrl(), Command::Undo),
+            (key("Y").ctrl(), Command::Redo),
+            (key("S").ctrl(), Command::Save),
+            (key("O").ctrl(), Command::Open),
+            (key("Q").ctrl(), Command::GoToLastEdit),
+            (key("F").ctrl().shift(), Command::FormatCode),
+            (key("Slash").ctrl(), Command::ToggleComment),
+            (key("K").ctrl().shift(), Command::DeleteLine),
+            (key("D").ctrl().shift(), Command::DuplicateLine),
+            (key("Up").ctrl().shift(), Command::MoveLineUp),
+            (key("Down").ctrl().shift(), Command::MoveLineDown),
+            (key("K").ctrl(), Command::DeleteToLineEnd),
+            (key("U").ctrl(), Command::DeleteToLineStart),
+            (key("T").ctrl(), Command::TransposeChars),
+            (key("T").alt(), Command::TransposeWords),
+            (
+                key("Backslash").ctrl().shift(),
+                Command::JumpToMatchingBracket,
+            ),
+            (key("Backslash").alt(), Command::SelectToBracket),
+            (
+                key("Backslash").alt().shift(),
+                Command::SelectToBracketInclusive,
+            ),
+            (key("Backslash").ctrl().alt(), Command::DeleteToBracket),
+            (
+                key("Backslash").ctrl().alt().shift(),
+                Command::DeleteToBracketInclusive,
+            ),
+            (key("U").alt().shift(), Command::UnwrapSurrounding),
+            (key("OpenBracket").alt(), Command::SelectInsideBrackets),
+            (
+                key("OpenBracket").alt().shift(),
+                Command::SelectAroundBrackets,
+            ),
+            (key("Quote").alt(), Command::SelectInsideQuotes),
+            (key("Quote").alt().shift(), Command::SelectAroundQuotes),
+            (key("W").alt(), Command::SelectInsideWord),
+            (key("W").alt().shift(), Command::SelectAroundWord),
+            (key("P").alt(), Command::SelectInsideParagraph),
+            (key("P").alt().shift(), Command::SelectAroundParagraph),
+            (key("Up").alt(), Command::ExpandSelection),
+            (key("Down").alt(), Command::ShrinkSelection),
+            (key("Up").ctrl().alt(), Command::AddCursorAbove),
+            (key("Down").ctrl().alt(), Command::AddCursorBelow),
+            (key("Up").ctrl(), Command::MoveToPreviousParagraph),
+            (key("Down").ctrl(), Command::MoveToNextParagraph),
+            (key("D").ctrl(), Command::SelectNextOccurrence),
+            (key("L").ctrl().shift(), Command::SelectAllOccurrences),
+            (key("I").alt().shift(), Command::AddCursorsToLineEnds),
+            (key("L").ctrl().alt(), Command::SplitSelectionIntoLines),
+            (key("A").ctrl().alt(), Command::AlignCursors),
+            (key("Q").alt(), Command::ReflowParagraph),
+            (key("F12"), Command::ToggleProfiler),
+            (key("U").ctrl().shift(), Command::ToggleOutput),
+            (key("N").ctrl().shift(), Command::ToggleNotifications),
+            (key("Backslash").ctrl(), Command::ToggleSplit),
+            (key("S").ctrl().alt(), Command::ToggleScrollLock),
+            (key("F").ctrl().alt(), Command::FindInFiles),
+            (key("Comma").ctrl(), Command::OpenSettings),
+            (key("Comma").ctrl().shift(), Command::OpenKeybindings),
+            (key("Period").ctrl().shift(), Command::RepeatLastCommand),
+            (key("F8"), Command::NextProblem),
+            (key("F8").shift(), Command::PreviousProblem),
+            (key("Period").ctrl(), Command::ShowCodeActions),
+            (key("O").alt().shift(), Command::OrganizeImports),
+            (key("F7"), Command::NextConflict),
+            (key("F7").shift(), Command::PreviousConflict),
+            (key("1").ctrl().alt(), Command::AcceptOurs),
+            (key("2").ctrl().alt(), Command::AcceptTheirs),
+            (key("3").ctrl().alt(), Command::AcceptBoth),
+        ];
+        let bindings = bindings
+            .into_iter()
+            .map(|(chord, command)| (KeySequence::from(chord), command))
+            .collect();
+        Self { bindings }
+    }
+
+    /// Command bound to a chord on its own (the first binding wins on
+    /// conflicts)
+    pub fn command_for(&self, chord: &Chord) -> Option<Command> {
+        self.command_for_sequence(std::slice::from_ref(chord))
+    }
+
+    /// Command bound to exactly these chords pressed in turn
+    pub fn command_for_sequence(&self, chords: &[Chord]) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound.chords() == chords)
+            .map(|(_, command)| *command)
+    }
+
+    /// Bindings longer than `chords` that start with them, in binding order
+    pub fn continuations(&self, chords: &[Chord]) -> Vec<(&KeySequence, Command)> {
+        self.bindings
+            .iter()
+            .filter(|(bound, _)| bound.len() > chords.len() && bound.chords().starts_with(chords))
+            .map(|(bound, command)| (bound, *command))
+            .collect()
+    }
+
+    pub fn sequences_for(&self, command: Command) -> Vec<&KeySequence> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == command)
+            .map(|(sequence, _)| sequence)
+            .collect()
+    }
+
+    /// Sequences bound to more than one command, in binding order. A
+    /// sequence that starts a longer one isn't a conflict: it runs once the
+    /// longer one times out
+    pub fn conflicts(&self) -> Vec<(KeySequence, Vec<Command>)> {
+        let mut conflicts: Vec<(KeySequence, Vec<Command>)> = Vec::new();
+        for (sequence, command) in &self.bindings {
+            match conflicts.iter_mut().find(|(seen, _)| seen == sequence) {
+                Some((_, commands)) if !commands.contains(command) => commands.push(*command),
+                Some(_) => {}
+                None => conflicts.push((sequence.clone(), vec![*command])),
+            }
+        }
+        conflicts.retain(|(_, commands)| commands.len() > 1);
+        conflicts
+    }
+
+    /// Other commands sharing any of this command's sequences
+    pub fn conflicts_with(&self, command: Command) -> Vec<Command> {
+        let sequences = self.sequences_for(command);
+        let mut others = Vec::new();
+        for (sequence, other) in &self.bindings {
+            if *other != command && sequences.contains(&sequence) && !others.contains(other) {
+                others.push(*other);
+            }
+        }
+        others
+    }
+
+    /// Replace a command's sequences with a single new one
+    pub fn bind(&mut self, command: Command, sequence: impl Into<KeySequence>) {
+        self.unbind(command);
+        self.bindings.push((sequence.into(), command));
+    }
+
+    /// Remove every sequence of a command
+    pub fn unbind(&mut self, command: Command) {
+        self.bindings.retain(|(_, bound)| *bound != command);
+    }
+
+    /// Restore a command's built-in chords
+    pub fn reset(&mut self, command: Command) {
+        self.unbind(command);
+        let defaults = Self::defaults();
+        for sequence in defaults.sequences_for(command) {
+            self.bindings.push((sequence.clone(), command));
+        }
+    }
+
+    /// keymap.toml next to settings.json
+    pub fn default_path() -> PathBuf {
+        Settings::config_dir().join("keymap.toml")
+    }
+
+    /// Load a keymap file over the defaults
+    ///
+    /// The file maps command ids to lists of key sequences ("Ctrl+K" or
+    /// "Space F S"); commands it doesn't mention keep their default chords,
+    /// and an empty list unbinds a command.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = match fs::read_to_string(path.as_ref()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::defaults()),
+            Err(e) => return Err(e),
+        };
+
+        let file: BTreeMap<String, Vec<String>> = toml::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut keymap = Self::defaults();
+        for (id, sequences) in file {
+            let command = Command::from_id(&id).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown command {:?}", id),
+                )
+            })?;
+            keymap.unbind(command);
+            for sequence in sequences {
+                let sequence = sequence
+                    .parse()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                keymap.bindings.push((sequence, command));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Write every command's sequences, creating the parent directory if needed
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file: BTreeMap<&str, Vec<String>> = Command::ALL
+            .iter()
+            .map(|command| {
+                let sequences = self
+                    .sequences_for(*command)
+                    .iter()
+                    .map(|sequence| sequence.to_string())
+                    .collect();
+                (command.id(), sequences)
+            })
+            .collect();
+        let contents = toml::to_string_pretty(&file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, contents)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
@@ -0,0 +1,81 @@
+use super::input::normalize_key;
+use std::fmt;
+use std::str::FromStr;
+
+/// A key plus modifiers, written like "Ctrl+Shift+K"
+///
+/// Key names follow egui's `Key::name()` ("K", "F12", "Up", "Comma", "Slash");
+/// parsing normalizes other spellings ("k", ",", "ArrowUp").
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: String,
+}
+
+impl Chord {
+    pub fn new(key: &str) -> Self {
+        Self {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            key: key.to_string(),
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    /// Modifiers are case-insensitive and may come in any order
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            return Err(format!("missing key in {:?}", s));
+        };
+
+        let mut chord = Chord::new(&normalize_key(key));
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                // Cmd is folded into Ctrl, as for key events
+                "ctrl" | "control" | "cmd" | "super" => chord.ctrl = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return Err(format!("unknown modifier {:?} in {:?}", modifier, s)),
+            }
+        }
+        Ok(chord)
+    }
+}
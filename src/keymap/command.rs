@@ -0,0 +1,297 @@
+/// Editor commands that can be bound to a key chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    Undo,
+    Redo,
+    Save,
+    Open,
+    GoToLastEdit,
+    FormatCode,
+    ToggleComment,
+    DeleteLine,
+    DuplicateLine,
+    MoveLineUp,
+    MoveLineDown,
+    DeleteToLineEnd,
+    DeleteToLineStart,
+    TransposeChars,
+    TransposeWords,
+    JumpToMatchingBracket,
+    SelectToBracket,
+    SelectToBracketInclusive,
+    DeleteToBracket,
+    DeleteToBracketInclusive,
+    UnwrapSurrounding,
+    SelectInsideBrackets,
+    SelectAroundBrackets,
+    SelectInsideQuotes,
+    SelectAroundQuotes,
+    SelectInsideWord,
+    SelectAroundWord,
+    SelectInsideParagraph,
+    SelectAroundParagraph,
+    ExpandSelection,
+    ShrinkSelection,
+    AddCursorAbove,
+    AddCursorBelow,
+    MoveToPreviousParagraph,
+    MoveToNextParagraph,
+    SelectNextOccurrence,
+    SelectAllOccurrences,
+    AddCursorsToLineEnds,
+    SplitSelectionIntoLines,
+    AlignCursors,
+    ReflowParagraph,
+    ToggleProfiler,
+    ToggleOutput,
+    ToggleNotifications,
+    ToggleSplit,
+    ToggleScrollLock,
+    FindInFiles,
+    OpenSettings,
+    OpenKeybindings,
+    RepeatLastCommand,
+    NextProblem,
+    PreviousProblem,
+    ShowCodeActions,
+    OrganizeImports,
+    RemoveBom,
+    UnifyLineEndings,
+    NextConflict,
+    PreviousConflict,
+    AcceptOurs,
+    AcceptTheirs,
+    AcceptBoth,
+}
+
+impl Command {
+    pub const ALL: [Command; 61] = [
+        Command::Undo,
+        Command::Redo,
+        Command::Save,
+        Command::Open,
+        Command::GoToLastEdit,
+        Command::FormatCode,
+        Command::ToggleComment,
+        Command::DeleteLine,
+        Command::DuplicateLine,
+        Command::MoveLineUp,
+        Command::MoveLineDown,
+        Command::DeleteToLineEnd,
+        Command::DeleteToLineStart,
+        Command::TransposeChars,
+        Command::TransposeWords,
+        Command::JumpToMatchingBracket,
+        Command::SelectToBracket,
+        Command::SelectToBracketInclusive,
+        Command::DeleteToBracket,
+        Command::DeleteToBracketInclusive,
+        Command::UnwrapSurrounding,
+        Command::SelectInsideBrackets,
+        Command::SelectAroundBrackets,
+        Command::SelectInsideQuotes,
+        Command::SelectAroundQuotes,
+        Command::SelectInsideWord,
+        Command::SelectAroundWord,
+        Command::SelectInsideParagraph,
+        Command::SelectAroundParagraph,
+        Command::ExpandSelection,
+        Command::ShrinkSelection,
+        Command::AddCursorAbove,
+        Command::AddCursorBelow,
+        Command::MoveToPreviousParagraph,
+        Command::MoveToNextParagraph,
+        Command::SelectNextOccurrence,
+        Command::SelectAllOccurrences,
+        Command::AddCursorsToLineEnds,
+        Command::SplitSelectionIntoLines,
+        Command::AlignCursors,
+        Command::ReflowParagraph,
+        Command::ToggleProfiler,
+        Command::ToggleOutput,
+        Command::ToggleNotifications,
+        Command::ToggleSplit,
+        Command::ToggleScrollLock,
+        Command::FindInFiles,
+        Command::OpenSettings,
+        Command::OpenKeybindings,
+        Command::RepeatLastCommand,
+        Command::NextProblem,
+        Command::PreviousProblem,
+        Command::ShowCodeActions,
+        Command::OrganizeImports,
+        Command::RemoveBom,
+        Command::UnifyLineEndings,
+        Command::NextConflict,
+        Command::PreviousConflict,
+        Command::AcceptOurs,
+        Command::AcceptTheirs,
+        Command::AcceptBoth,
+    ];
+
+    /// Stable identifier used in keymap.toml
+    pub fn id(&self) -> &'static str {
+        match self {
+            Command::Undo => "undo",
+            Command::Redo => "redo",
+            Command::Save => "save",
+            Command::Open => "open",
+            Command::GoToLastEdit => "go_to_last_edit",
+            Command::FormatCode => "format_code",
+            Command::ToggleComment => "toggle_comment",
+            Command::DeleteLine => "delete_line",
+            Command::DuplicateLine => "duplicate_line",
+            Command::MoveLineUp => "move_line_up",
+            Command::MoveLineDown => "move_line_down",
+            Command::DeleteToLineEnd => "delete_to_line_end",
+            Command::DeleteToLineStart => "delete_to_line_start",
+            Command::TransposeChars => "transpose_chars",
+            Command::TransposeWords => "transpose_words",
+            Command::JumpToMatchingBracket => "jump_to_matching_bracket",
+            Command::SelectToBracket => "select_to_bracket",
+            Command::SelectToBracketInclusive => "select_to_bracket_inclusive",
+            Command::DeleteToBracket => "delete_to_bracket",
+            Command::DeleteToBracketInclusive => "delete_to_bracket_inclusive",
+            Command::UnwrapSurrounding => "unwrap_surrounding",
+            Command::SelectInsideBrackets => "select_inside_brackets",
+            Command::SelectAroundBrackets => "select_around_brackets",
+            Command::SelectInsideQuotes => "select_inside_quotes",
+            Command::SelectAroundQuotes => "select_around_quotes",
+            Command::SelectInsideWord => "select_inside_word",
+            Command::SelectAroundWord => "select_around_word",
+            Command::SelectInsideParagraph => "select_inside_paragraph",
+            Command::SelectAroundParagraph => "select_around_paragraph",
+            Command::ExpandSelection => "expand_selection",
+            Command::ShrinkSelection => "shrink_selection",
+            Command::AddCursorAbove => "add_cursor_above",
+            Command::AddCursorBelow => "add_cursor_below",
+            Command::MoveToPreviousParagraph => "move_to_previous_paragraph",
+            Command::MoveToNextParagraph => "move_to_next_paragraph",
+            Command::SelectNextOccurrence => "select_next_occurrence",
+            Command::SelectAllOccurrences => "select_all_occurrences",
+            Command::AddCursorsToLineEnds => "add_cursors_to_line_ends",
+            Command::SplitSelectionIntoLines => "split_selection_into_lines",
+            Command::AlignCursors => "align_cursors",
+            Command::ReflowParagraph => "reflow_paragraph",
+            Command::ToggleProfiler => "toggle_profiler",
+            Command::ToggleOutput => "toggle_output",
+            Command::ToggleNotifications => "toggle_notifications",
+            Command::ToggleSplit => "toggle_split",
+            Command::ToggleScrollLock => "toggle_scroll_lock",
+            Command::FindInFiles => "find_in_files",
+            Command::OpenSettings => "open_settings",
+            Command::OpenKeybindings => "open_keybindings",
+            Command::RepeatLastCommand => "repeat_last_command",
+            Command::NextProblem => "next_problem",
+            Command::PreviousProblem => "previous_problem",
+            Command::ShowCodeActions => "show_code_actions",
+            Command::OrganizeImports => "organize_imports",
+            Command::RemoveBom => "remove_bom",
+            Command::UnifyLineEndings => "unify_line_endings",
+            Command::NextConflict => "next_conflict",
+            Command::PreviousConflict => "previous_conflict",
+            Command::AcceptOurs => "accept_ours",
+            Command::AcceptTheirs => "accept_theirs",
+            Command::AcceptBoth => "accept_both",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Command> {
+        Self::ALL.into_iter().find(|command| command.id() == id)
+    }
+
+    /// Human-readable name
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::Undo => "Undo",
+            Command::Redo => "Redo",
+            Command::Save => "Save",
+            Command::Open => "Open File",
+            Command::GoToLastEdit => "Go to Last Edit Location",
+            Command::FormatCode => "Format Code",
+            Command::ToggleComment => "Toggle Comment",
+            Command::DeleteLine => "Delete Line",
+            Command::DuplicateLine => "Duplicate Line",
+            Command::MoveLineUp => "Move Line Up",
+            Command::MoveLineDown => "Move Line Down",
+            Command::DeleteToLineEnd => "Delete to Line End",
+            Command::DeleteToLineStart => "Delete to Line Start",
+            Command::TransposeChars => "Transpose Characters",
+            Command::TransposeWords => "Transpose Words",
+            Command::JumpToMatchingBracket => "Go to Matching Bracket",
+            Command::SelectToBracket => "Select to Bracket",
+            Command::SelectToBracketInclusive => "Select to Bracket (Inclusive)",
+            Command::DeleteToBracket => "Delete to Bracket",
+            Command::DeleteToBracketInclusive => "Delete to Bracket (Inclusive)",
+            Command::UnwrapSurrounding => "Remove Surrounding Brackets or Quotes",
+            Command::SelectInsideBrackets => "Select Inside Brackets",
+            Command::SelectAroundBrackets => "Select Around Brackets",
+            Command::SelectInsideQuotes => "Select Inside Quotes",
+            Command::SelectAroundQuotes => "Select Around Quotes",
+            Command::SelectInsideWord => "Select Word",
+            Command::SelectAroundWord => "Select Word and Surrounding Space",
+            Command::SelectInsideParagraph => "Select Paragraph",
+            Command::SelectAroundParagraph => "Select Paragraph and Following Blank Lines",
+            Command::ExpandSelection => "Expand Selection",
+            Command::ShrinkSelection => "Shrink Selection",
+            Command::AddCursorAbove => "Add Cursor Above",
+            Command::AddCursorBelow => "Add Cursor Below",
+            Command::MoveToPreviousParagraph => "Move to Previous Paragraph",
+            Command::MoveToNextParagraph => "Move to Next Paragraph",
+            Command::SelectNextOccurrence => "Add Next Occurrence to Selection",
+            Command::SelectAllOccurrences => "Select All Occurrences",
+            Command::AddCursorsToLineEnds => "Add Cursors to Line Ends",
+            Command::SplitSelectionIntoLines => "Split Selection into Lines",
+            Command::AlignCursors => "Align Cursors",
+            Command::ReflowParagraph => "Reflow Paragraph",
+            Command::ToggleProfiler => "Toggle Profiler",
+            Command::ToggleOutput => "Toggle Output Panel",
+            Command::ToggleNotifications => "Toggle Notification History",
+            Command::ToggleSplit => "Toggle Split View",
+            Command::ToggleScrollLock => "Toggle Split Scroll Lock",
+            Command::FindInFiles => "Find in Files",
+            Command::OpenSettings => "Open Settings",
+            Command::OpenKeybindings => "Open Keyboard Shortcuts",
+            Command::RepeatLastCommand => "Repeat Last Command",
+            Command::NextProblem => "Go to Next Problem",
+            Command::PreviousProblem => "Go to Previous Problem",
+            Command::ShowCodeActions => "Show Code Actions",
+            Command::OrganizeImports => "Organize Imports",
+            Command::RemoveBom => "Remove Byte Order Mark",
+            Command::UnifyLineEndings => "Unify Line Endings",
+            Command::NextConflict => "Go to Next Merge Conflict",
+            Command::PreviousConflict => "Go to Previous Merge Conflict",
+            Command::AcceptOurs => "Merge Conflict: Accept Ours",
+            Command::AcceptTheirs => "Merge Conflict: Accept Theirs",
+            Command::AcceptBoth => "Merge Conflict: Accept Both",
+        }
+    }
+
+    /// Commands that change the text - what "Repeat Last Command" replays
+    pub fn is_edit(&self) -> bool {
+        matches!(
+            self,
+            Command::FormatCode
+                | Command::ToggleComment
+                | Command::DeleteLine
+                | Command::DuplicateLine
+                | Command::MoveLineUp
+                | Command::MoveLineDown
+                | Command::DeleteToLineEnd
+                | Command::DeleteToLineStart
+                | Command::TransposeChars
+                | Command::TransposeWords
+                | Command::DeleteToBracket
+                | Command::DeleteToBracketInclusive
+                | Command::UnwrapSurrounding
+                | Command::AlignCursors
+                | Command::ReflowParagraph
+                | Command::OrganizeImports
+                | Command::RemoveBom
+                | Command::UnifyLineEndings
+                | Command::AcceptOurs
+                | Command::AcceptTheirs
+                | Command::AcceptBoth
+        )
+    }
+}
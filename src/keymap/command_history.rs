@@ -0,0 +1,87 @@
+use super::command::Command;
+use crate::editor::{SortOptions, Transform};
+
+/// An edit that can be replayed, including its arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditAction {
+    Command(Command),
+    Transform(Transform),
+    SortLines(SortOptions),
+    ReverseLines,
+}
+
+impl EditAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EditAction::Command(command) => command.label(),
+            EditAction::Transform(transform) => transform.label(),
+            EditAction::SortLines(_) => "Sort Lines",
+            EditAction::ReverseLines => "Reverse Lines",
+        }
+    }
+}
+
+/// Executed commands: most recently used first, plus the last edit for
+/// "Repeat Last Command"
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    recent: Vec<Command>,
+    last_edit: Option<EditAction>,
+}
+
+impl CommandHistory {
+    /// How many distinct commands `recent` remembers
+    pub const MAX_RECENT: usize = 20;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that a command ran (repeats themselves aren't recorded)
+    pub fn record(&mut self, command: Command) {
+        if command == Command::RepeatLastCommand {
+            return;
+        }
+        self.recent.retain(|&recent| recent != command);
+        self.recent.insert(0, command);
+        self.recent.truncate(Self::MAX_RECENT);
+
+        if command.is_edit() {
+            self.last_edit = Some(EditAction::Command(command));
+        }
+    }
+
+    /// Note an edit that isn't a keymap command (menu transforms, sorting)
+    pub fn record_edit(&mut self, action: EditAction) {
+        match action {
+            EditAction::Command(command) => self.record(command),
+            other => self.last_edit = Some(other),
+        }
+    }
+
+    pub fn last_edit(&self) -> Option<EditAction> {
+        self.last_edit
+    }
+
+    /// Recently used commands, newest first
+    pub fn recent(&self) -> &[Command] {
+        &self.recent
+    }
+
+    /// `commands` reordered with recently used ones first (for a command list)
+    pub fn recent_first(&self, commands: &[Command]) -> Vec<Command> {
+        let mut ordered: Vec<Command> = self
+            .recent
+            .iter()
+            .copied()
+            .filter(|command| commands.contains(command))
+            .collect();
+        ordered.extend(
+            commands
+                .iter()
+                .copied()
+                .filter(|command| !self.recent.contains(command)),
+        );
+        ordered
+    }
+}
@@ -0,0 +1,166 @@
+use super::chord::Chord;
+
+/// Modifier state as a front-end reports it, before normalization
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, Super/Windows key elsewhere
+    pub cmd: bool,
+}
+
+/// A key press from either front-end (egui or crossterm), translated into one
+/// model before it reaches the keymap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// Canonical key name (see `normalize_key`)
+    pub key: String,
+    pub modifiers: Modifiers,
+    /// Character the press types, when the front-end reports one
+    pub text: Option<char>,
+}
+
+impl KeyEvent {
+    pub fn new(key: &str, modifiers: Modifiers, text: Option<char>) -> Self {
+        let mut key = normalize_key(key);
+        if modifiers.shift {
+            if let Some(base) = unshifted_name(&key) {
+                key = base.to_string();
+            }
+        }
+        Self {
+            key,
+            modifiers,
+            text,
+        }
+    }
+
+    /// A typed character (terminals report keys this way); shifted characters
+    /// ('K', '{', '?') imply Shift so they match the same chords as egui keys
+    pub fn from_char(c: char, mut modifiers: Modifiers) -> Self {
+        modifiers.shift |= c.is_uppercase() || SHIFTED_SYMBOLS.contains(c);
+        Self::new(&c.to_string(), modifiers, Some(c))
+    }
+
+    /// AltGr arrives as Ctrl+Alt together with a typed symbol (`@`, `{`, `€`
+    /// on many European layouts) - that's text, not a shortcut
+    pub fn is_altgr_text(&self) -> bool {
+        self.modifiers.ctrl
+            && self.modifiers.alt
+            && self.text.is_some_and(|c| !c.is_ascii_alphanumeric())
+    }
+
+    /// Chord for keymap lookup; Cmd counts as Ctrl so one binding works on
+    /// every platform. `None` for AltGr text.
+    pub fn chord(&self) -> Option<Chord> {
+        if self.is_altgr_text() {
+            return None;
+        }
+        Some(Chord {
+            ctrl: self.modifiers.ctrl || self.modifiers.cmd,
+            alt: self.modifiers.alt,
+            shift: self.modifiers.shift,
+            key: self.key.clone(),
+        })
+    }
+
+    /// Character to insert when the press isn't bound to a command
+    pub fn typed_text(&self) -> Option<char> {
+        if self.is_altgr_text() {
+            return self.text;
+        }
+        if self.modifiers.ctrl || self.modifiers.cmd || self.modifiers.alt {
+            return None;
+        }
+        self.text
+    }
+}
+
+/// Canonical key names (egui's `Key::name()` spelling): letters uppercase,
+/// punctuation by name, numpad keys folded into their main-keyboard twins
+pub fn normalize_key(name: &str) -> String {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return normalize_char(c);
+    }
+
+    let numpad = name
+        .strip_prefix("Numpad")
+        .or_else(|| name.strip_prefix("Keypad"));
+    if let Some(rest) = numpad {
+        let folded = match rest {
+            "Enter" => "Enter",
+            "Add" | "Plus" => "Plus",
+            "Subtract" | "Minus" => "Minus",
+            "Decimal" | "Period" => "Period",
+            "Divide" | "Slash" => "Slash",
+            "Equals" | "Equal" => "Equals",
+            digit if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) => digit,
+            _ => return name.to_string(),
+        };
+        return folded.to_string();
+    }
+
+    match name {
+        "ArrowUp" => "Up",
+        "ArrowDown" => "Down",
+        "ArrowLeft" => "Left",
+        "ArrowRight" => "Right",
+        "Esc" => "Escape",
+        "Return" => "Enter",
+        "BackTab" => "Tab",
+        "Del" => "Delete",
+        "PgUp" => "PageUp",
+        "PgDown" => "PageDown",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Symbols typed with Shift on a US layout, folded onto their base key
+const SHIFTED_SYMBOLS: &str = "{}\"<>?|_+:~";
+
+/// Base key for a shifted symbol name ("Questionmark" is Shift+Slash)
+fn unshifted_name(name: &str) -> Option<&'static str> {
+    let base = match name {
+        "Questionmark" => "Slash",
+        "Pipe" => "Backslash",
+        "Colon" => "Semicolon",
+        "Plus" => "Equals",
+        "OpenCurlyBracket" => "OpenBracket",
+        "CloseCurlyBracket" => "CloseBracket",
+        _ => return None,
+    };
+    Some(base)
+}
+
+fn normalize_char(c: char) -> String {
+    let name = match c {
+        ' ' => "Space",
+        ',' => "Comma",
+        '.' => "Period",
+        '/' => "Slash",
+        '\\' => "Backslash",
+        '[' => "OpenBracket",
+        ']' => "CloseBracket",
+        '\'' => "Quote",
+        ';' => "Semicolon",
+        ':' => "Colon",
+        '-' => "Minus",
+        '+' => "Plus",
+        '=' => "Equals",
+        '`' => "Backtick",
+        '|' => "Pipe",
+        '?' => "Questionmark",
+        '{' => "OpenCurlyBracket",
+        '}' => "CloseCurlyBracket",
+        '"' => "Quote",
+        '<' => "Comma",
+        '>' => "Period",
+        '_' => "Minus",
+        '~' => "Backtick",
+        _ => return c.to_uppercase().collect(),
+    };
+    name.to_string()
+}
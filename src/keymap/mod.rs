@@ -0,0 +1,13 @@
+pub mod bindings;
+pub mod chord;
+pub mod command;
+pub mod command_history;
+pub mod input;
+pub mod sequence;
+
+pub use bindings::Keymap;
+pub use chord::Chord;
+pub use command::Command;
+pub use command_history::{CommandHistory, EditAction};
+pub use input::{normalize_key, KeyEvent, Modifiers};
+pub use sequence::{Abandoned, Dispatch, KeyAction, KeySequence, PendingKeys, SEQUENCE_TIMEOUT};
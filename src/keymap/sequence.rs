@@ -0,0 +1,209 @@
+use super::bindings::Keymap;
+use super::chord::Chord;
+use super::command::Command;
+use super::input::KeyEvent;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use web_time::Instant;
+
+/// How long a partly typed sequence waits for its next key
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Chords pressed one after another, written space-separated like
+/// "Ctrl+K Ctrl+S" or "Space F S". Most bindings are a single chord
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<Chord>);
+
+impl KeySequence {
+    pub fn new(chords: Vec<Chord>) -> Self {
+        Self(chords)
+    }
+
+    pub fn chords(&self) -> &[Chord] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Chord> for KeySequence {
+    fn from(chord: Chord) -> Self {
+        Self(vec![chord])
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, chord) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Chord>, _>>()?;
+        if chords.is_empty() {
+            return Err(format!("no keys in {:?}", s));
+        }
+        Ok(Self(chords))
+    }
+}
+
+/// What a key press means once the keys before it are taken into account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyAction {
+    /// The key completed a binding
+    Run(Command),
+    /// The key continues a sequence; wait for the next one
+    Wait,
+    /// Not part of any binding: handle it as an ordinary key
+    Unbound,
+}
+
+/// A sequence that stopped short, by timing out or by a key that doesn't
+/// continue it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Abandoned {
+    /// The keys typed so far are a binding of their own
+    Run(Command),
+    /// Handle the keys as ordinary keys after all (a leader like "Space"
+    /// still types a space)
+    Replay(Vec<KeyEvent>),
+}
+
+/// Result of `PendingKeys::press`: first whatever the abandoned sequence
+/// before this key resolves to, then the key's own action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dispatch {
+    pub abandoned: Option<Abandoned>,
+    pub action: KeyAction,
+}
+
+/// Keys typed so far toward a multi-key binding
+#[derive(Debug, Clone, Default)]
+pub struct PendingKeys {
+    keys: Vec<KeyEvent>,
+    chords: Vec<Chord>,
+    last_press: Option<Instant>,
+}
+
+impl PendingKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a sequence is waiting for its next key
+    pub fn is_pending(&self) -> bool {
+        !self.chords.is_empty()
+    }
+
+    /// The chords typed so far
+    pub fn chords(&self) -> &[Chord] {
+        &self.chords
+    }
+
+    /// Feed one key press. A key that continues a longer binding waits for
+    /// the next; one that doesn't abandons the sequence and then counts on
+    /// its own
+    pub fn press(&mut self, keymap: &Keymap, key: &KeyEvent, now: Instant) -> Dispatch {
+        let abandoned = self.expire(keymap, now);
+        let Some(chord) = key.chord() else {
+            return Dispatch {
+                abandoned: abandoned.or_else(|| self.abandon(keymap)),
+                action: KeyAction::Unbound,
+            };
+        };
+
+        let mut typed = self.chords.clone();
+        typed.push(chord.clone());
+        if !keymap.continuations(&typed).is_empty() {
+            self.keys.push(key.clone());
+            self.chords = typed;
+            self.last_press = Some(now);
+            return Dispatch {
+                abandoned,
+                action: KeyAction::Wait,
+            };
+        }
+        if let Some(command) = keymap.command_for_sequence(&typed) {
+            self.clear();
+            return Dispatch {
+                abandoned,
+                action: KeyAction::Run(command),
+            };
+        }
+
+        // A key that breaks a sequence may still start one or be bound
+        if self.is_pending() {
+            let abandoned = self.abandon(keymap);
+            let action = self.press(keymap, key, now).action;
+            return Dispatch { abandoned, action };
+        }
+        Dispatch {
+            abandoned,
+            action: KeyAction::Unbound,
+        }
+    }
+
+    /// Give up on a sequence that has waited longer than `SEQUENCE_TIMEOUT`
+    /// (call this regularly, e.g. once per frame)
+    pub fn expire(&mut self, keymap: &Keymap, now: Instant) -> Option<Abandoned> {
+        let last_press = self.last_press?;
+        if now.saturating_duration_since(last_press) < SEQUENCE_TIMEOUT {
+            return None;
+        }
+        self.abandon(keymap)
+    }
+
+    /// Drop the keys typed so far without running or replaying anything
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.chords.clear();
+        self.last_press = None;
+    }
+
+    /// Bindings that could still complete, with the chords left to press
+    /// for each (which-key hints)
+    pub fn hints(&self, keymap: &Keymap) -> Vec<(KeySequence, Command)> {
+        if !self.is_pending() {
+            return Vec::new();
+        }
+        keymap
+            .continuations(&self.chords)
+            .into_iter()
+            .map(|(sequence, command)| {
+                let rest = sequence.chords()[self.chords.len()..].to_vec();
+                (KeySequence::new(rest), command)
+            })
+            .collect()
+    }
+
+    fn abandon(&mut self, keymap: &Keymap) -> Option<Abandoned> {
+        if !self.is_pending() {
+            return None;
+        }
+        let abandoned = match keymap.command_for_sequence(&self.chords) {
+            Some(command) => Abandoned::Run(command),
+            None => Abandoned::Replay(self.keys.clone()),
+        };
+        self.clear();
+        Some(abandoned)
+    }
+}
@@ -1,23 +1,59 @@
+//! A rope-based text editing engine, with optional egui and terminal
+//! front-ends.
+//!
+//! The re-exports below are the stable surface and follow semver. Modules
+//! hidden from the docs are internals and may change in any release.
+//!
+//! Features:
+//! - `syntax` (default): tree-sitter grammars - highlighting
+//!   (`SyntaxHighlighter`), grammar-aware indent, bracket matching, selection
+//!   expansion, syntax errors and import sorting. Without it those fall back
+//!   to plain-text heuristics (or do nothing)
+//! - `gui` (default): the egui front-end (`GuiApp`), natively or in the
+//!   browser. For the web build use `--no-default-features --features gui`
+//!   (tree-sitter's C grammars don't build for wasm32) and `trunk serve`
+//! - `tui` (default): the terminal front-end (`App`, `render`)
+//! - `serde`: serializable ropes and buffer snapshots
+//!
+//! Embed just the engine with `default-features = false`; it then needs no
+//! native dependencies (no tree-sitter, no egui).
+
 pub mod buffer;
 pub mod editor;
 pub mod formatter;
+#[cfg(feature = "gui")]
 pub mod gui;
 pub mod history;
 pub mod io;
+pub mod keymap;
+pub mod notifications;
+pub mod progress;
+#[doc(hidden)]
+pub mod render;
 pub mod rope;
-pub mod syntax; // ADD THIS
+pub mod settings;
+pub mod syntax;
+#[doc(hidden)]
 pub mod tree;
+#[cfg(feature = "tui")]
 pub mod ui;
+#[doc(hidden)]
 pub mod util;
 
 // Re-export commonly used types
-pub use buffer::{Buffer, Offset, Point};
-pub use editor::{Editor, Selection};
+pub use buffer::{Anchor, Bias, Buffer, BufferSnapshot, Offset, Point};
+pub use editor::{EditEvent, Editor, Selection};
 pub use formatter::{FormatResult, Formatter, FormatterConfig, FormatterProvider};
+#[cfg(feature = "gui")]
 pub use gui::GuiApp;
 pub use history::{History, Transaction};
 pub use io::{read_file, write_file};
+pub use keymap::{Chord, Command, Keymap};
 pub use rope::{Chunk, Rope, TextMetrics};
-pub use syntax::{IndentCalculator, SyntaxHighlighter, SyntaxTheme}; // ADD THIS
-pub use tree::{Count, Item, SumTree, Summary, TextSummary};
+pub use settings::Settings;
+pub use syntax::IndentCalculator;
+#[cfg(feature = "syntax")]
+pub use syntax::SyntaxHighlighter;
+pub use syntax::{Color, SyntaxTheme};
+#[cfg(feature = "tui")]
 pub use ui::{render, App};
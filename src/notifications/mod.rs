@@ -0,0 +1,5 @@
+pub mod notification;
+pub mod store;
+
+pub use notification::{Level, Notification, NotificationAction};
+pub use store::{Notifications, HISTORY_LIMIT};
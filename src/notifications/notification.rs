@@ -0,0 +1,91 @@
+use std::time::Duration;
+use web_time::Instant;
+
+/// How important a notification is; decides its icon and how long its toast stays up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Info => "Info",
+            Level::Warning => "Warning",
+            Level::Error => "Error",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Level::Info => "ℹ",
+            Level::Warning => "⚠",
+            Level::Error => "❌",
+        }
+    }
+
+    /// How long a toast of this level shows before fading into the history
+    pub fn toast_duration(&self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(3),
+            Level::Warning => Duration::from_secs(6),
+            Level::Error => Duration::from_secs(10),
+        }
+    }
+}
+
+/// A follow-up offered as a button on a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationAction {
+    RetrySave,
+    RetryFormat,
+    ReloadFile,
+    ShowOutput,
+    OpenSettings,
+    OpenKeybindings,
+    RemoveBom,
+    UnifyLineEndings,
+    InsertAnyway,
+}
+
+impl NotificationAction {
+    /// Button label
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationAction::RetrySave | NotificationAction::RetryFormat => "Retry",
+            NotificationAction::ReloadFile => "Reload",
+            NotificationAction::ShowOutput => "Show output",
+            NotificationAction::OpenSettings => "Open settings",
+            NotificationAction::OpenKeybindings => "Open keybindings",
+            NotificationAction::RemoveBom => "Remove BOM",
+            NotificationAction::UnifyLineEndings => "Unify line endings",
+            NotificationAction::InsertAnyway => "Insert anyway",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub id: u64,
+    pub level: Level,
+    pub message: String,
+    pub actions: Vec<NotificationAction>,
+    pub created: Instant,
+    /// Closed (or acted on) before its toast timed out
+    pub dismissed: bool,
+}
+
+impl Notification {
+    /// Offer a follow-up button
+    pub fn action(&mut self, action: NotificationAction) -> &mut Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Whether its toast is still showing at `now`
+    pub fn is_toast(&self, now: Instant) -> bool {
+        !self.dismissed && now.saturating_duration_since(self.created) < self.level.toast_duration()
+    }
+}
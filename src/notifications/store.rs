@@ -0,0 +1,77 @@
+use super::notification::{Level, Notification};
+use web_time::Instant;
+
+/// Notifications kept for the history panel (older ones are dropped)
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Every notification raised so far, oldest first; the recent ones not yet
+/// dismissed show as toasts
+#[derive(Debug, Default)]
+pub struct Notifications {
+    history: Vec<Notification>,
+    next_id: u64,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raise a notification; the returned reference adds actions to it
+    pub fn push(&mut self, level: Level, message: impl Into<String>) -> &mut Notification {
+        if self.history.len() >= HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.next_id += 1;
+        self.history.push(Notification {
+            id: self.next_id,
+            level,
+            message: message.into(),
+            actions: Vec::new(),
+            created: Instant::now(),
+            dismissed: false,
+        });
+        self.history.last_mut().unwrap()
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) -> &mut Notification {
+        self.push(Level::Info, message)
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) -> &mut Notification {
+        self.push(Level::Warning, message)
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) -> &mut Notification {
+        self.push(Level::Error, message)
+    }
+
+    /// Toasts showing at `now`, oldest first
+    pub fn toasts(&self, now: Instant) -> impl Iterator<Item = &Notification> {
+        self.history.iter().filter(move |n| n.is_toast(now))
+    }
+
+    pub fn history(&self) -> &[Notification] {
+        &self.history
+    }
+
+    /// Close a toast (it stays in the history)
+    pub fn dismiss(&mut self, id: u64) {
+        if let Some(notification) = self.history.iter_mut().find(|n| n.id == id) {
+            notification.dismissed = true;
+        }
+    }
+
+    /// Close the info toasts, e.g. once the user goes back to typing
+    pub fn dismiss_info(&mut self) {
+        for notification in &mut self.history {
+            if notification.level == Level::Info {
+                notification.dismissed = true;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Shared flag a worker polls to stop early
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A running task as the status bar shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStatus {
+    pub id: u64,
+    /// What is running ("Loading big.log", "Formatting main.rs")
+    pub name: String,
+    /// 0.0..=1.0, or None while the amount of work is unknown
+    pub fraction: Option<f32>,
+    pub cancelled: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    status: TaskStatus,
+    cancel: CancelHandle,
+}
+
+/// Registry of running tasks, shared by the UI (which draws and cancels
+/// them) and the workers (which report through their `ProgressHandle`)
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    tasks: Arc<Mutex<Vec<Entry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a task; it ends when the returned handle is dropped
+    pub fn start(&self, name: impl Into<String>) -> ProgressHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancel = CancelHandle::default();
+        self.with_tasks(|tasks| {
+            tasks.push(Entry {
+                status: TaskStatus {
+                    id,
+                    name: name.into(),
+                    fraction: None,
+                    cancelled: false,
+                },
+                cancel: cancel.clone(),
+            })
+        });
+        ProgressHandle {
+            id,
+            progress: self.clone(),
+            cancel,
+        }
+    }
+
+    /// Running tasks, oldest first
+    pub fn tasks(&self) -> Vec<TaskStatus> {
+        self.with_tasks(|tasks| tasks.iter().map(|entry| entry.status.clone()).collect())
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.with_tasks(|tasks| !tasks.is_empty())
+    }
+
+    /// Ask a task to stop (it finishes once its worker notices)
+    pub fn cancel(&self, id: u64) {
+        self.update(id, |entry| {
+            entry.status.cancelled = true;
+            entry.cancel.cancel();
+        });
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut Entry)) {
+        self.with_tasks(|tasks| {
+            if let Some(entry) = tasks.iter_mut().find(|entry| entry.status.id == id) {
+                f(entry);
+            }
+        });
+    }
+
+    fn with_tasks<R>(&self, f: impl FnOnce(&mut Vec<Entry>) -> R) -> R {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut tasks)
+    }
+}
+
+/// Worker side of a task: reports how far it got and checks for
+/// cancellation; the task disappears when this is dropped
+#[derive(Debug)]
+pub struct ProgressHandle {
+    id: u64,
+    progress: Progress,
+    cancel: CancelHandle,
+}
+
+impl ProgressHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn set_fraction(&self, fraction: f32) {
+        self.progress.update(self.id, |entry| {
+            entry.status.fraction = Some(fraction.clamp(0.0, 1.0));
+        });
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// The flag to hand to code that only needs to check for cancellation
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.progress
+            .with_tasks(|tasks| tasks.retain(|entry| entry.status.id != id));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod wrap_map;
+
+pub use wrap_map::{DisplayPoint, WrapMap};
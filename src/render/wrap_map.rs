@@ -0,0 +1,181 @@
+use crate::buffer::{Buffer, Point};
+use crate::editor::EditEvent;
+use crate::util::tabs::char_widths;
+use std::ops::Range;
+
+/// Position on screen with soft wrap: display row, and character column
+/// from the start of that display row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DisplayPoint {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl DisplayPoint {
+    pub fn new(row: usize, column: usize) -> Self {
+        Self { row, column }
+    }
+}
+
+/// How one buffer line is split into display rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WrappedLine {
+    /// Character column where each display row starts (the first is 0)
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl WrappedLine {
+    fn segment(&self, index: usize) -> Range<usize> {
+        let end = self.starts.get(index + 1).copied().unwrap_or(self.len);
+        self.starts[index]..end
+    }
+}
+
+/// Character columns where `line` wraps to fit `width` screen columns:
+/// after the last whitespace that fits, or mid-word if a word is too long
+pub fn wrap_points(line: &str, width: usize, tab_width: usize) -> Vec<usize> {
+    let width = width.max(1);
+    let mut display = vec![0];
+    for char_width in char_widths(line, tab_width) {
+        display.push(display[display.len() - 1] + char_width);
+    }
+
+    let mut starts = Vec::new();
+    let mut segment_start = 0;
+    let mut word_break = None;
+    for (column, c) in line.chars().enumerate() {
+        if display[column + 1] - display[segment_start] > width && column > segment_start {
+            let start = word_break.filter(|&b| b > segment_start).unwrap_or(column);
+            starts.push(start);
+            segment_start = start;
+            word_break = None;
+        }
+        if c.is_whitespace() {
+            word_break = Some(column + 1);
+        }
+    }
+    starts
+}
+
+/// Buffer rows ↔ display rows for soft wrap at a fixed width
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapMap {
+    width: usize,
+    tab_width: usize,
+    lines: Vec<WrappedLine>,
+    /// First display row of each buffer row, plus the total at the end
+    row_starts: Vec<usize>,
+}
+
+impl WrapMap {
+    pub fn new(buffer: &Buffer, width: usize, tab_width: usize) -> Self {
+        let mut map = Self {
+            width: width.max(1),
+            tab_width: tab_width.max(1),
+            lines: Vec::new(),
+            row_starts: Vec::new(),
+        };
+        map.lines = (0..buffer.line_count())
+            .map(|row| map.wrap(&buffer.line(row).unwrap_or_default()))
+            .collect();
+        map.update_row_starts();
+        map
+    }
+
+    /// Wrap width in screen columns
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    pub fn display_row_count(&self) -> usize {
+        self.row_starts.last().copied().unwrap_or(0)
+    }
+
+    /// Display rows a buffer row takes (at least 1)
+    pub fn rows_in_line(&self, row: usize) -> usize {
+        self.lines.get(row).map_or(1, |line| line.starts.len())
+    }
+
+    /// First display row of a buffer row
+    pub fn display_row(&self, row: usize) -> usize {
+        self.row_starts[row.min(self.lines.len())]
+    }
+
+    /// Buffer row shown on a display row, and the character range of that
+    /// row's text shown there
+    pub fn segment_at(&self, display_row: usize) -> (usize, Range<usize>) {
+        let row = self
+            .row_starts
+            .partition_point(|&start| start <= display_row)
+            .saturating_sub(1)
+            .min(self.lines.len().saturating_sub(1));
+        let line = &self.lines[row];
+        let index = (display_row - self.row_starts[row]).min(line.starts.len() - 1);
+        (row, line.segment(index))
+    }
+
+    /// A column on a wrap boundary belongs to the later display row
+    pub fn to_display_point(&self, point: Point) -> DisplayPoint {
+        let row = point.row.min(self.lines.len().saturating_sub(1));
+        let line = &self.lines[row];
+        let index = line
+            .starts
+            .partition_point(|&start| start <= point.column)
+            .saturating_sub(1);
+        DisplayPoint::new(
+            self.row_starts[row] + index,
+            point.column.min(line.len) - line.starts[index],
+        )
+    }
+
+    /// Buffer point for a display point, clamped to that display row's text
+    pub fn to_point(&self, display: DisplayPoint) -> Point {
+        let (row, segment) = self.segment_at(display.row);
+        let is_last = segment.end == self.lines[row].len;
+        let max = if is_last || segment.is_empty() {
+            segment.end
+        } else {
+            segment.end - 1
+        };
+        Point::new(row, (segment.start + display.column).min(max))
+    }
+
+    /// Re-wrap the rows an edit touched (`buffer` is the text after it)
+    pub fn apply_edit(&mut self, event: &EditEvent, buffer: &Buffer) {
+        let old_rows = event.start.row..event.old_end.row + 1;
+        if old_rows.end > self.lines.len() {
+            *self = Self::new(buffer, self.width, self.tab_width);
+            return;
+        }
+
+        let new_lines: Vec<WrappedLine> = (event.start.row..=event.new_end.row)
+            .map(|row| self.wrap(&buffer.line(row).unwrap_or_default()))
+            .collect();
+        self.lines.splice(old_rows, new_lines);
+        self.update_row_starts();
+    }
+
+    fn wrap(&self, line: &str) -> WrappedLine {
+        let mut starts = vec![0];
+        starts.extend(wrap_points(line, self.width, self.tab_width));
+        WrappedLine {
+            starts,
+            len: line.chars().count(),
+        }
+    }
+
+    fn update_row_starts(&mut self) {
+        self.row_starts.clear();
+        let mut total = 0;
+        for line in &self.lines {
+            self.row_starts.push(total);
+            total += line.starts.len();
+        }
+        self.row_starts.push(total);
+    }
+}
@@ -6,6 +6,16 @@ pub struct Chunk {
     text: Arc<String>,
     /// 🚀 CACHED newline positions for O(1) line lookups
     newline_positions: Arc<Vec<usize>>,
+    /// Number of chars (not bytes) in the text
+    chars: usize,
+    /// Number of UTF-16 code units (what LSP positions count)
+    utf16: usize,
+    /// Chars before the first newline and after the last one
+    first_line_chars: usize,
+    last_line_chars: usize,
+    /// The line (counting newlines before it) with the most chars, and how
+    /// many; the first of several
+    longest_line: (usize, usize),
 }
 
 impl Chunk {
@@ -18,10 +28,24 @@ impl Chunk {
             .filter(|(_, b)| *b == b'\n')
             .map(|(i, _)| i)
             .collect();
+        let chars = text.chars().count();
+        let utf16 = text.chars().map(char::len_utf16).sum();
+        let line_chars: Vec<usize> = text.split('\n').map(|line| line.chars().count()).collect();
+        let mut longest_line = (0, line_chars[0]);
+        for (row, &chars) in line_chars.iter().enumerate() {
+            if chars > longest_line.1 {
+                longest_line = (row, chars);
+            }
+        }
 
         Self {
             text: Arc::new(text),
             newline_positions: Arc::new(newline_positions),
+            chars,
+            utf16,
+            first_line_chars: line_chars[0],
+            last_line_chars: line_chars[line_chars.len() - 1],
+            longest_line,
         }
     }
 
@@ -35,11 +59,85 @@ impl Chunk {
         self.text.len()
     }
 
+    /// Length in chars
+    pub fn char_count(&self) -> usize {
+        self.chars
+    }
+
+    /// Chars starting before byte `offset` (a char `offset` falls inside counts)
+    pub fn byte_to_char(&self, offset: usize) -> usize {
+        self.text
+            .char_indices()
+            .take_while(|(i, _)| *i < offset)
+            .count()
+    }
+
+    /// The first char boundary at or after byte `offset` (the length past
+    /// the end)
+    pub fn ceil_char_boundary(&self, offset: usize) -> usize {
+        (offset..self.text.len())
+            .find(|&offset| self.text.is_char_boundary(offset))
+            .unwrap_or(self.text.len())
+    }
+
+    /// Byte offset of char `index` (the length past the last char)
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Length in UTF-16 code units
+    pub fn utf16_len(&self) -> usize {
+        self.utf16
+    }
+
+    /// UTF-16 code units of the chars starting before byte `offset` (a char
+    /// `offset` falls inside counts)
+    pub fn byte_to_utf16(&self, offset: usize) -> usize {
+        self.text
+            .char_indices()
+            .take_while(|(i, _)| *i < offset)
+            .map(|(_, c)| c.len_utf16())
+            .sum()
+    }
+
+    /// Byte offset of the first char starting at or after UTF-16 code unit
+    /// `index` (so an index inside a surrogate pair moves past that char;
+    /// the length past the last char)
+    pub fn utf16_to_byte(&self, index: usize) -> usize {
+        let mut units = 0;
+        for (i, c) in self.text.char_indices() {
+            if units >= index {
+                return i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
     /// Check if empty
     pub fn is_empty(&self) -> bool {
         self.text.is_empty()
     }
 
+    /// Chars before the first newline (all of them without one)
+    pub fn first_line_chars(&self) -> usize {
+        self.first_line_chars
+    }
+
+    /// Chars after the last newline (all of them without one)
+    pub fn last_line_chars(&self) -> usize {
+        self.last_line_chars
+    }
+
+    /// The line, counting newlines before it, with the most chars and how
+    /// many (the first of several)
+    pub fn longest_line(&self) -> (usize, usize) {
+        self.longest_line
+    }
+
     /// 🚀 OPTIMIZED: Count newlines using cached data (O(1) instead of O(n))
     pub fn count_lines(&self) -> usize {
         self.newline_positions.len()
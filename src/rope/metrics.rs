@@ -4,28 +4,60 @@ use std::ops::Add;
 
 /// Text metrics - what we track at each node
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextMetrics {
-    pub len: usize,   // Byte length
-    pub lines: usize, // Number of newlines
+    pub len: usize,              // Byte length
+    pub lines: usize,            // Number of newlines
+    pub chars: usize,            // Number of chars
+    pub utf16: usize,            // Number of UTF-16 code units
+    pub first_line_chars: usize, // Chars before the first newline
+    pub last_line_chars: usize,  // Chars after the last newline
+    /// The line (counting newlines before it) with the most chars, and its
+    /// chars; the first of several. For sizing the horizontal scroll and
+    /// wrapping without visiting every line
+    pub longest_row: usize,
+    pub longest_row_chars: usize,
 }
 
 impl Add for TextMetrics {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
+        // Our last line and their first are one line
+        let joined = self.last_line_chars + other.first_line_chars;
+        let (mut longest_row, mut longest_row_chars) = (self.longest_row, self.longest_row_chars);
+        if joined > longest_row_chars {
+            (longest_row, longest_row_chars) = (self.lines, joined);
+        }
+        if other.longest_row_chars > longest_row_chars {
+            (longest_row, longest_row_chars) =
+                (self.lines + other.longest_row, other.longest_row_chars);
+        }
+
         TextMetrics {
             len: self.len + other.len,
             lines: self.lines + other.lines,
+            chars: self.chars + other.chars,
+            utf16: self.utf16 + other.utf16,
+            first_line_chars: if self.lines == 0 {
+                joined
+            } else {
+                self.first_line_chars
+            },
+            last_line_chars: if other.lines == 0 {
+                joined
+            } else {
+                other.last_line_chars
+            },
+            longest_row,
+            longest_row_chars,
         }
     }
 }
 
 impl Summary for TextMetrics {
     fn add_summary(&self, other: &Self) -> Self {
-        TextMetrics {
-            len: self.len + other.len,
-            lines: self.lines + other.lines,
-        }
+        *self + *other
     }
 }
 
@@ -37,6 +69,12 @@ impl Item for Chunk {
         TextMetrics {
             len: self.len(),
             lines: self.count_lines(),
+            chars: self.char_count(),
+            utf16: self.utf16_len(),
+            first_line_chars: self.first_line_chars(),
+            last_line_chars: self.last_line_chars(),
+            longest_row: self.longest_line().0,
+            longest_row_chars: self.longest_line().1,
         }
     }
 }
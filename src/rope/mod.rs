@@ -1,7 +1,12 @@
 pub mod chunk;
 pub mod metrics;
 pub mod rope;
+mod search;
+#[cfg(feature = "serde")]
+pub mod snapshot;
 
 pub use chunk::Chunk;
 pub use metrics::TextMetrics;
-pub use rope::Rope;
+pub use rope::{Lines, Rope};
+#[cfg(feature = "serde")]
+pub use snapshot::RopeSnapshot;
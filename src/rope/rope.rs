@@ -1,14 +1,31 @@
 use super::chunk::Chunk;
-use crate::tree::SumTree;
+use super::metrics::TextMetrics;
+use crate::tree::{Items, SumTree};
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::ops::Range;
 
 /// Rope - optimized text storage using SumTree
+///
+/// Chunks hold at most `CHUNK_SIZE` bytes and, unless the rope is a single
+/// chunk, at least `MIN_CHUNK_SIZE`. Building from text, `insert` / `delete`
+/// and `append` keep to that; `from_chunks`, `slice` and `split_at` keep the
+/// boundaries they're given or cut, and `compact` brings those back in line
+///
+/// Byte offsets past the end are clamped to it, and ones that fall inside a
+/// multi-byte char round up to the end of that char (`ceil_char_boundary`)
 #[derive(Clone)]
 pub struct Rope {
     tree: SumTree<Chunk>,
 }
 
 impl Rope {
-    const CHUNK_SIZE: usize = 1024;
+    /// Largest chunk, in bytes
+    pub const CHUNK_SIZE: usize = 1024;
+    /// Smallest chunk an edit may leave behind, in bytes
+    pub const MIN_CHUNK_SIZE: usize = Self::CHUNK_SIZE / 4;
+    /// Bytes `from_reader` asks for per read
+    const READ_SIZE: usize = 64 * 1024;
 
     pub fn new() -> Self {
         Self {
@@ -22,25 +39,100 @@ impl Rope {
             return Self::new();
         }
 
-        // Chunk the text
+        // 🚀 Build tree in one go (balanced, iterative)
+        Self {
+            tree: SumTree::from_items(Self::split_chunks(text)),
+        }
+    }
+
+    /// Build from a stream, chunking each read as it arrives instead of
+    /// collecting one big string first. A char split across reads is carried
+    /// over to the next, and so is the last whole chunk with the text past
+    /// it (split together, so neither short reads nor the end of the stream
+    /// leave small chunks); invalid UTF-8 (or a truncated char at the end) is
+    /// an `InvalidData` error naming its byte offset
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
         let mut chunks = Vec::new();
+        let mut buffer = vec![0u8; Self::READ_SIZE];
+        // Bytes of an incomplete char left at the front of `buffer`
+        let mut carried = 0;
+        // Offset of `buffer[0]` in the stream
+        let mut offset = 0;
+
+        loop {
+            let n = reader.read(&mut buffer[carried..])?;
+            let filled = carried + n;
+            let valid = match std::str::from_utf8(&buffer[..filled]) {
+                Ok(_) => filled,
+                Err(e) if e.error_len().is_none() && n > 0 => e.valid_up_to(),
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "not valid UTF-8 (invalid byte at offset {})",
+                            offset + e.valid_up_to()
+                        ),
+                    ))
+                }
+            };
+
+            let text = std::str::from_utf8(&buffer[..valid]).expect("validated above");
+            let mut whole = if n == 0 {
+                valid
+            } else {
+                (valid / Self::CHUNK_SIZE).saturating_sub(1) * Self::CHUNK_SIZE
+            };
+            while !text.is_char_boundary(whole) {
+                whole -= 1;
+            }
+            chunks.extend(Self::split_chunks(&text[..whole]));
+            buffer.copy_within(whole..filled, 0);
+            carried = filled - whole;
+            offset += whole;
+            if n == 0 {
+                break;
+            }
+        }
+
+        Ok(Self {
+            tree: SumTree::from_items(chunks),
+        })
+    }
+
+    /// Cut text on char boundaries into as few chunks of at most
+    /// `CHUNK_SIZE` bytes as hold it, all about the same size, so that of
+    /// several none is below `MIN_CHUNK_SIZE`
+    fn split_chunks(text: &str) -> Vec<Chunk> {
+        let mut chunks = Vec::with_capacity(text.len().div_ceil(Self::CHUNK_SIZE));
         let mut start = 0;
 
         while start < text.len() {
-            let mut end = (start + Self::CHUNK_SIZE).min(text.len());
+            let remaining = text.len() - start;
+            let pieces = remaining.div_ceil(Self::CHUNK_SIZE);
+            let mut end = start + remaining.div_ceil(pieces);
 
-            // Align to character boundary
-            while end < text.len() && !text.is_char_boundary(end) {
-                end += 1;
+            // Back up to a character boundary
+            while !text.is_char_boundary(end) {
+                end -= 1;
             }
 
             chunks.push(Chunk::from(&text[start..end]));
             start = end;
         }
 
-        // 🚀 Build tree in one go (balanced, iterative)
+        chunks
+    }
+
+    /// Build from pre-split chunks, keeping their boundaries (empty chunks are dropped)
+    pub fn from_chunks<I: IntoIterator<Item = String>>(chunks: I) -> Self {
         Self {
-            tree: SumTree::from_items(chunks),
+            tree: SumTree::from_items(
+                chunks
+                    .into_iter()
+                    .filter(|chunk| !chunk.is_empty())
+                    .map(Chunk::new)
+                    .collect(),
+            ),
         }
     }
 
@@ -48,6 +140,11 @@ impl Rope {
         self.tree.summary().len
     }
 
+    /// Byte length and newline count of the whole rope
+    pub fn summary(&self) -> TextMetrics {
+        self.tree.summary()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.tree.is_empty()
     }
@@ -56,226 +153,239 @@ impl Rope {
         self.tree.summary().lines
     }
 
-    /// Get a specific line by index
+    /// The line with the most chars and how many it has (the first of
+    /// several), from the root summary in O(1)
+    pub fn longest_line(&self) -> (usize, usize) {
+        let summary = self.tree.summary();
+        (summary.longest_row, summary.longest_row_chars)
+    }
+
+    /// Get a specific line by index (without its newline): two O(log n)
+    /// seeks for its byte range, then a copy of just that range
     pub fn line(&self, line_idx: usize) -> Option<String> {
-        let mut current_line = 0;
-        let mut line_content = String::new();
-        let mut found_line = false;
+        let (start, end) = self.line_byte_range(line_idx)?;
+        Some(self.slice_bytes(start, end))
+    }
 
-        for chunk in self.tree.iter() {
-            let chunk_text = chunk.as_str();
-
-            for ch in chunk_text.chars() {
-                if current_line == line_idx {
-                    found_line = true;
-                    if ch == '\n' {
-                        return Some(line_content);
-                    }
-                    line_content.push(ch);
-                } else if ch == '\n' {
-                    current_line += 1;
-                    if current_line > line_idx {
-                        break;
-                    }
-                }
-            }
+    /// Lines without their newlines; borrowed from the chunks, copied only
+    /// when a line spans chunk boundaries. A trailing newline doesn't add
+    /// an empty last line
+    pub fn lines(&self) -> Lines<'_> {
+        self.lines_at(0)
+    }
 
-            if current_line > line_idx {
-                break;
-            }
-        }
+    /// `lines` starting at `line_idx`
+    pub fn lines_at(&self, line_idx: usize) -> Lines<'_> {
+        let start = self.line_to_byte(line_idx);
+        let (before, mut chunks) = self.tree.items_from(start, |metrics| metrics.len);
+        let rest = chunks
+            .next()
+            .map_or("", |chunk| &chunk.as_str()[start - before.len..]);
+        Lines { chunks, rest }
+    }
 
-        if found_line {
-            Some(line_content)
-        } else {
-            None
-        }
+    /// Number of chars (not bytes)
+    pub fn len_chars(&self) -> usize {
+        self.tree.summary().chars
     }
 
-    /// 🚀 SUPER OPTIMIZED: Get byte offset for a line using chunk newline cache
+    /// 🚀 Byte offset of a line's start, in O(log n): seek to the chunk
+    /// holding the newline before it and read its cached position
     pub fn line_to_byte(&self, target_line: usize) -> usize {
         if target_line == 0 {
             return 0;
         }
 
-        let mut current_line = 0;
-        let mut byte_offset = 0;
-
-        for chunk in self.tree.iter() {
-            let newlines_in_chunk = chunk.count_lines();
-
-            if current_line + newlines_in_chunk >= target_line {
-                // Target line is in this chunk
-                let line_in_chunk = target_line - current_line;
-
-                if line_in_chunk == 0 {
-                    return byte_offset;
-                }
-
-                // 🚀 Use cached newline position (O(1) lookup!)
-                if let Some(newline_pos) = chunk.get_newline_position(line_in_chunk - 1) {
-                    return byte_offset + newline_pos + 1;
-                }
-
-                return byte_offset + chunk.len();
+        match self.tree.seek(target_line - 1, |metrics| metrics.lines) {
+            Some((before, chunk)) => {
+                let newline = chunk
+                    .get_newline_position(target_line - 1 - before.lines)
+                    .unwrap_or(chunk.len());
+                before.len + newline + 1
             }
+            None => self.len(),
+        }
+    }
 
-            current_line += newlines_in_chunk;
-            byte_offset += chunk.len();
+    /// Char index of a byte offset (a char the offset falls inside counts),
+    /// in O(log n)
+    pub fn byte_to_char(&self, byte: usize) -> usize {
+        match self.tree.seek(byte, |metrics| metrics.len) {
+            Some((before, chunk)) => before.chars + chunk.byte_to_char(byte - before.len),
+            None => self.len_chars(),
         }
+    }
 
-        byte_offset
+    /// Byte offset of a char index (the length past the end), in O(log n)
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        match self.tree.seek(char_idx, |metrics| metrics.chars) {
+            Some((before, chunk)) => before.len + chunk.char_to_byte(char_idx - before.chars),
+            None => self.len(),
+        }
     }
 
-    /// 🚀 FIXED: Get line and column from byte offset
-    /// Returns (line, column) where column is CHARACTER count, not bytes
-    pub fn byte_to_line_col(&self, target_byte: usize) -> (usize, usize) {
-        if target_byte == 0 {
-            return (0, 0);
+    /// The first char boundary at or after byte `offset` (the length past
+    /// the end), in O(log n). Methods taking byte offsets round one that
+    /// falls inside a char up to this, like `byte_to_char` does
+    pub fn ceil_char_boundary(&self, offset: usize) -> usize {
+        match self.chunk_at(offset) {
+            Some((start, chunk)) => start + chunk.ceil_char_boundary(offset - start),
+            None => self.len(),
         }
+    }
 
-        let mut byte_offset = 0;
-        let mut line = 0;
-        let mut column = 0;
+    /// Length in UTF-16 code units (the unit of LSP positions)
+    pub fn len_utf16(&self) -> usize {
+        self.tree.summary().utf16
+    }
 
-        for chunk in self.tree.iter() {
-            let chunk_len = chunk.len();
-
-            if byte_offset + chunk_len > target_byte {
-                // Found the chunk containing target_byte
-                let offset_in_chunk = target_byte - byte_offset;
-                let chunk_text = chunk.as_str();
-
-                // Count characters up to this byte offset
-                let mut bytes_counted = 0;
-
-                for ch in chunk_text.chars() {
-                    if bytes_counted >= offset_in_chunk {
-                        break;
-                    }
-                    bytes_counted += ch.len_utf8();
-
-                    if ch == '\n' {
-                        line += 1;
-                        column = 0;
-                    } else {
-                        column += 1;
-                    }
-                }
+    /// UTF-16 offset of a byte offset (a char the offset falls inside
+    /// counts), in O(log n)
+    pub fn offset_to_utf16(&self, byte: usize) -> usize {
+        match self.tree.seek(byte, |metrics| metrics.len) {
+            Some((before, chunk)) => before.utf16 + chunk.byte_to_utf16(byte - before.len),
+            None => self.len_utf16(),
+        }
+    }
+
+    /// Byte offset of a UTF-16 offset, in O(log n); an offset inside a
+    /// surrogate pair moves past that char (the length past the end)
+    pub fn utf16_to_offset(&self, utf16: usize) -> usize {
+        match self.tree.seek(utf16, |metrics| metrics.utf16) {
+            Some((before, chunk)) => before.len + chunk.utf16_to_byte(utf16 - before.utf16),
+            None => self.len(),
+        }
+    }
 
-                return (line, column);
+    /// Newlines before a byte offset
+    fn lines_before(&self, byte: usize) -> usize {
+        match self.tree.seek(byte, |metrics| metrics.len) {
+            Some((before, chunk)) => {
+                let offset = byte - before.len;
+                before.lines
+                    + chunk
+                        .newline_positions()
+                        .partition_point(|&newline| newline < offset)
             }
+            None => self.line_count(),
+        }
+    }
 
-            // Count newlines in this chunk
-            line += chunk.count_lines();
+    /// 🚀 Line and column of a byte offset, in O(log n)
+    /// Returns (line, column) where column is CHARACTER count, not bytes
+    pub fn byte_to_line_col(&self, target_byte: usize) -> (usize, usize) {
+        let target_byte = target_byte.min(self.len());
+        let line = self.lines_before(target_byte);
+        let line_start = self.line_to_byte(line);
+        (
+            line,
+            self.byte_to_char(target_byte) - self.byte_to_char(line_start),
+        )
+    }
 
-            // Set column to 0 if chunk ends with newline, otherwise count chars after last newline
-            let chunk_text = chunk.as_str();
-            if let Some(last_newline_pos) = chunk.newline_positions().last() {
-                // Count characters after last newline
-                column = chunk_text[last_newline_pos + 1..].chars().count();
-            } else {
-                // No newline in chunk - add all characters
-                column += chunk_text.chars().count();
+    /// Byte offset of a line and CHARACTER column, the inverse of
+    /// `byte_to_line_col`; a column past the line's end gives its end (before
+    /// the newline). Seeks to the line in O(log n) and reads the column from
+    /// the chunk there without copying; only a column beyond that chunk needs
+    /// the seeks by char
+    pub fn line_col_to_byte(&self, line: usize, column: usize) -> usize {
+        let line_start = self.line_to_byte(line);
+        if let Some((chunk_start, chunk)) = self.chunk_at(line_start) {
+            let from = line_start - chunk_start;
+            let newlines = chunk.newline_positions();
+            let newline = newlines.get(newlines.partition_point(|&newline| newline < from));
+            let text = &chunk.as_str()[from..newline.copied().unwrap_or(chunk.len())];
+            match text.char_indices().nth(column) {
+                Some((offset, _)) => return line_start + offset,
+                None if newline.is_some() => return line_start + text.len(),
+                None => {}
             }
-
-            byte_offset += chunk_len;
         }
 
-        (line, column)
+        // The line runs on past its first chunk
+        let line_end = if line < self.line_count() {
+            self.line_to_byte(line + 1) - 1
+        } else {
+            self.len()
+        };
+        let offset = self.char_to_byte(self.byte_to_char(line_start) + column);
+        offset.min(line_end)
     }
 
     /// 🚀 NEW: Extract a substring by byte range (EFFICIENT - no full conversion!)
+    /// Seeks to `start` in O(log n), then copies only the chunks in range.
     /// This is critical for syntax highlighting performance
     pub fn slice_bytes(&self, start: usize, end: usize) -> String {
-        if start >= end || start >= self.len() {
-            return String::new();
-        }
-
-        let end = end.min(self.len());
-        let mut result = String::new();
-        let mut current_pos = 0;
-
-        for chunk in self.tree.iter() {
-            let chunk_len = chunk.len();
-            let chunk_end = current_pos + chunk_len;
-
-            // Skip chunks before our range
-            if chunk_end <= start {
-                current_pos = chunk_end;
-                continue;
-            }
-
-            // Stop if we're past our range
-            if current_pos >= end {
-                break;
-            }
-
-            let chunk_text = chunk.as_str();
-
-            // Determine what part of this chunk to include
-            let slice_start = if current_pos < start {
-                start - current_pos
-            } else {
-                0
-            };
+        let mut result = String::with_capacity(end.min(self.len()).saturating_sub(start));
+        result.extend(self.chunks_in(start, end));
+        result
+    }
 
-            let slice_end = if chunk_end > end {
-                end - current_pos
-            } else {
-                chunk_len
-            };
+    /// The text of a byte range as borrowed pieces of the chunks holding it,
+    /// found in O(log n): for streaming a range (a copy, a selection written
+    /// to a file) without building one string
+    pub fn chunks_in(&self, start: usize, end: usize) -> impl Iterator<Item = &str> + '_ {
+        let start = self.ceil_char_boundary(start);
+        let end = self.ceil_char_boundary(end).max(start);
+        let (before, chunks) = self.tree.items_from(start, |metrics| metrics.len);
+        let mut next_start = before.len;
+        chunks
+            .map(move |chunk| {
+                let chunk_start = next_start;
+                next_start += chunk.len();
+                (chunk_start, chunk.as_str())
+            })
+            .take_while(move |(chunk_start, _)| *chunk_start < end)
+            .map(move |(chunk_start, text)| {
+                &text[start.saturating_sub(chunk_start)..(end - chunk_start).min(text.len())]
+            })
+            .filter(|piece| !piece.is_empty())
+    }
 
-            result.push_str(&chunk_text[slice_start..slice_end]);
-            current_pos = chunk_end;
+    /// 🚀 Sub-rope of a byte range that shares the chunks inside it; only
+    /// the (at most two) chunks cut by the range ends are copied
+    pub fn slice(&self, range: Range<usize>) -> Rope {
+        let end = self.ceil_char_boundary(range.end);
+        let start = self.ceil_char_boundary(range.start).min(end);
+        if start == end {
+            return Self::new();
         }
 
-        result
+        let (mut offset, chunks) = self.tree.slice(start..end, |metrics| metrics.len);
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| {
+                let chunk_start = offset;
+                offset += chunk.len();
+                if chunk_start >= start && offset <= end {
+                    chunk
+                } else {
+                    chunk.slice(
+                        start.max(chunk_start) - chunk_start,
+                        end.min(offset) - chunk_start,
+                    )
+                }
+            })
+            .collect();
+        Self {
+            tree: SumTree::from_items(chunks),
+        }
     }
 
-    /// 🚀 NEW: Get byte range for a specific line (returns start, end)
-    /// Used by syntax highlighter for efficient line extraction
+    /// 🚀 Byte range of a line without its newline (returns start, end),
+    /// in O(log n). Used by syntax highlighter for efficient line extraction
     pub fn line_byte_range(&self, line_idx: usize) -> Option<(usize, usize)> {
         let start = self.line_to_byte(line_idx);
-
-        // Find end of line
-        let mut current_line = 0;
-        let mut byte_offset = 0;
-        let mut found_start = false;
-
-        for chunk in self.tree.iter() {
-            let chunk_text = chunk.as_str();
-
-            for ch in chunk_text.chars() {
-                if current_line == line_idx {
-                    found_start = true;
-                }
-
-                if found_start && ch == '\n' {
-                    return Some((start, byte_offset));
-                }
-
-                byte_offset += ch.len_utf8();
-
-                if ch == '\n' {
-                    current_line += 1;
-                    if current_line > line_idx {
-                        break;
-                    }
-                }
-            }
-
-            if current_line > line_idx {
-                break;
-            }
+        let lines = self.line_count();
+        if line_idx > lines || (line_idx == lines && start == self.len()) {
+            return None;
         }
-
-        // Line doesn't end with newline (last line)
-        if found_start {
-            Some((start, byte_offset))
+        let end = if line_idx < lines {
+            self.line_to_byte(line_idx + 1) - 1
         } else {
-            None
-        }
+            self.len()
+        };
+        Some((start, end))
     }
 
     /// Convert to string (avoid on large files!)
@@ -287,159 +397,201 @@ impl Rope {
         result
     }
 
-    /// 🚀 OPTIMIZED INSERT
+    /// 🚀 Insert by re-chunking only the chunk containing `pos`
     pub fn insert(&mut self, pos: usize, text: &str) {
         if text.is_empty() {
             return;
         }
 
-        // For small files (<1MB), simple rebuild is acceptable
-        if self.len() < 1_000_000 {
-            let mut content = self.to_string();
-            content.insert_str(pos, text);
-            *self = Self::from_text(&content);
-            return;
-        }
-
-        // For large files: Use optimized chunked insertion
-        self.insert_optimized(pos, text);
+        let pos = self.ceil_char_boundary(pos);
+        let mut edited = 0;
+        self.tree.splice(
+            pos..pos,
+            |metrics| metrics.len,
+            |start, chunks| {
+                let mut content = Self::concat(&chunks);
+                content.insert_str(pos - start, text);
+                edited = start;
+                Self::split_chunks(&content)
+            },
+        );
+        self.merge_small_chunk(edited);
     }
 
-    /// 🚀 Optimized insertion for large files
-    fn insert_optimized(&mut self, pos: usize, text: &str) {
-        let chunks: Vec<Chunk> = self.tree.iter().collect();
-
-        let mut current_pos = 0;
-        let mut insert_chunk_idx = 0;
-        let mut insert_offset = 0;
-
-        for (idx, chunk) in chunks.iter().enumerate() {
-            let chunk_len = chunk.as_str().len();
-            let chunk_end = current_pos + chunk_len;
-
-            if pos >= current_pos && pos < chunk_end {
-                insert_chunk_idx = idx;
-                insert_offset = pos - current_pos;
-                break;
-            }
-
-            if pos >= chunk_end {
-                insert_chunk_idx = idx + 1;
-                insert_offset = 0;
-            }
-
-            current_pos = chunk_end;
-        }
-
-        let mut new_chunks = Vec::new();
-
-        if insert_chunk_idx < chunks.len() && insert_offset > 0 {
-            for (idx, chunk) in chunks.iter().enumerate() {
-                if idx < insert_chunk_idx {
-                    new_chunks.push(chunk.clone());
-                } else if idx == insert_chunk_idx {
-                    let chunk_text = chunk.as_str();
-                    let before = &chunk_text[..insert_offset];
-                    let after = &chunk_text[insert_offset..];
-
-                    new_chunks.push(Chunk::from(before));
-
-                    let mut start = 0;
-                    while start < text.len() {
-                        let mut end = (start + Self::CHUNK_SIZE).min(text.len());
-                        while end < text.len() && !text.is_char_boundary(end) {
-                            end += 1;
-                        }
-                        new_chunks.push(Chunk::from(&text[start..end]));
-                        start = end;
-                    }
+    /// Append text, re-chunking only the last chunk
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.len(), text);
+    }
 
-                    new_chunks.push(Chunk::from(after));
-                } else {
-                    new_chunks.push(chunk.clone());
-                }
-            }
+    /// Append another rope by joining the trees; only a rope smaller than
+    /// a chunk is copied (into the last chunk, so pushes don't leave tiny
+    /// chunks behind). Small chunks on either side of the seam are merged
+    pub fn append(&mut self, other: Rope) {
+        if other.len() < Self::CHUNK_SIZE {
+            other.for_each_chunk(|text| self.push_str(text));
         } else {
-            for (idx, chunk) in chunks.iter().enumerate() {
-                if idx == insert_chunk_idx {
-                    let mut start = 0;
-                    while start < text.len() {
-                        let mut end = (start + Self::CHUNK_SIZE).min(text.len());
-                        while end < text.len() && !text.is_char_boundary(end) {
-                            end += 1;
-                        }
-                        new_chunks.push(Chunk::from(&text[start..end]));
-                        start = end;
-                    }
-                }
-                new_chunks.push(chunk.clone());
+            let seam = self.len();
+            self.tree.append(other.tree);
+            if seam > 0 {
+                self.merge_small_chunk(seam - 1);
             }
+            self.merge_small_chunk(seam);
         }
+    }
 
-        self.tree = SumTree::from_items(new_chunks);
+    /// The text before and after byte `offset` (clamped to the end). Both
+    /// halves share the original's chunks; only the chunk cut at `offset`
+    /// is copied
+    pub fn split_at(&self, offset: usize) -> (Rope, Rope) {
+        let offset = self.ceil_char_boundary(offset);
+        let mut left = self.tree.clone();
+        let mut right = left.split_off(offset, |metrics| metrics.len);
+
+        // `right` starts with the chunk containing `offset`: give its head back
+        let cut = offset - left.summary().len;
+        if cut > 0 {
+            right.splice(
+                0..0,
+                |metrics| metrics.len,
+                |_, chunks| {
+                    let (head, tail) = chunks[0].split_at(cut);
+                    left.push(head);
+                    vec![tail]
+                },
+            );
+        }
+        (Rope { tree: left }, Rope { tree: right })
     }
 
-    /// 🚀 OPTIMIZED DELETE
+    /// 🚀 Delete by re-chunking only the chunks overlapping `start..end`
     pub fn delete(&mut self, start: usize, end: usize) {
+        let (start, end) = (self.ceil_char_boundary(start), self.ceil_char_boundary(end));
         if start >= end {
             return;
         }
 
-        if self.len() < 1_000_000 {
-            let mut content = self.to_string();
-            content.drain(start..end);
-            *self = Self::from_text(&content);
+        let mut edited = 0;
+        self.tree.splice(
+            start..end,
+            |metrics| metrics.len,
+            |first, chunks| {
+                let mut content = Self::concat(&chunks);
+                content.drain(start - first..end - first);
+                edited = first;
+                Self::split_chunks(&content)
+            },
+        );
+        self.merge_small_chunk(edited);
+    }
+
+    /// Join the chunk holding byte `offset` with a neighbour when an edit
+    /// left it below `MIN_CHUNK_SIZE` (the two are re-split evenly if they
+    /// don't fit in one)
+    fn merge_small_chunk(&mut self, offset: usize) {
+        let Some((start, chunk)) = self.chunk_at(offset) else {
+            return;
+        };
+        if chunk.len() >= Self::MIN_CHUNK_SIZE || chunk.len() == self.len() {
             return;
         }
-
-        self.delete_optimized(start, end);
+        let end = start + chunk.len();
+        let neighbours = if start > 0 {
+            start - 1..end
+        } else {
+            start..end + 1
+        };
+        self.tree.splice(
+            neighbours,
+            |metrics| metrics.len,
+            |_, chunks| Self::split_chunks(&Self::concat(&chunks)),
+        );
     }
 
-    fn delete_optimized(&mut self, start: usize, end: usize) {
-        let mut new_chunks = Vec::new();
-        let mut current_pos = 0;
+    /// Re-chunk so every chunk is between `MIN_CHUNK_SIZE` and `CHUNK_SIZE`
+    /// bytes again; chunks already in that range are kept (and shared) as
+    /// they are, and a rope that needs nothing isn't touched
+    pub fn compact(&mut self) {
+        let in_range =
+            |chunk: &Chunk| (Self::MIN_CHUNK_SIZE..=Self::CHUNK_SIZE).contains(&chunk.len());
+        let single = self.len() <= Self::CHUNK_SIZE && self.chunk_count() <= 1;
+        if single || self.tree.iter().all(|chunk| in_range(&chunk)) {
+            return;
+        }
 
+        let mut chunks = Vec::new();
+        // Text of small or oversized chunks waiting to be re-split
+        let mut pending = String::new();
         for chunk in self.tree.iter() {
-            let chunk_text = chunk.as_str();
-            let chunk_len = chunk_text.len();
-            let chunk_end = current_pos + chunk_len;
-
-            if chunk_end <= start {
-                new_chunks.push(chunk);
-            } else if current_pos >= end {
-                new_chunks.push(chunk);
-            } else {
-                let keep_start = if current_pos < start {
-                    start - current_pos
-                } else {
-                    0
-                };
-
-                let keep_end = if chunk_end > end {
-                    end - current_pos
-                } else {
-                    chunk_len
-                };
-
-                if keep_start > 0 {
-                    new_chunks.push(Chunk::from(&chunk_text[..keep_start]));
-                }
-
-                if keep_end < chunk_len {
-                    new_chunks.push(Chunk::from(&chunk_text[keep_end..]));
-                }
+            if pending.is_empty() && in_range(&chunk) {
+                chunks.push(chunk);
+                continue;
             }
-
-            current_pos = chunk_end;
+            pending.push_str(chunk.as_str());
+            if pending.len() >= Self::MIN_CHUNK_SIZE {
+                chunks.extend(Self::split_chunks(&pending));
+                pending.clear();
+            }
+        }
+        // A short tail joins the chunk before it
+        if !pending.is_empty() {
+            if let Some(last) = chunks.pop() {
+                pending.insert_str(0, last.as_str());
+            }
+            chunks.extend(Self::split_chunks(&pending));
         }
+        self.tree = SumTree::from_items(chunks);
+    }
 
-        self.tree = SumTree::from_items(new_chunks);
+    fn concat(chunks: &[Chunk]) -> String {
+        let mut content = String::with_capacity(chunks.iter().map(Chunk::len).sum());
+        for chunk in chunks {
+            content.push_str(chunk.as_str());
+        }
+        content
     }
 
     pub fn chunk_count(&self) -> usize {
         self.tree.iter().count()
     }
 
+    /// The chunks in order (clones share their text)
+    pub fn chunks(&self) -> impl Iterator<Item = Chunk> + '_ {
+        self.tree.iter()
+    }
+
+    /// The chunks from the one holding byte `offset` to the end, and where
+    /// the first of them starts
+    pub(crate) fn chunks_from(&self, offset: usize) -> (usize, Items<'_, Chunk>) {
+        let (before, chunks) = self.tree.items_from(offset, |metrics| metrics.len);
+        (before.len, chunks)
+    }
+
+    /// The chunk holding byte `offset` and where it starts, in O(log n)
+    pub(crate) fn chunk_at(&self, offset: usize) -> Option<(usize, Chunk)> {
+        self.tree
+            .seek(offset, |metrics| metrics.len)
+            .map(|(before, chunk)| (before.len, chunk))
+    }
+
+    /// The byte at `offset` (None past the end)
+    pub(crate) fn byte_at(&self, offset: usize) -> Option<u8> {
+        let (start, chunk) = self.chunk_at(offset)?;
+        chunk.as_str().as_bytes().get(offset - start).copied()
+    }
+
+    /// The bytes before `offset`, nearest first, walking back a chunk at a
+    /// time so a scan that stops early only visits the chunks it reads
+    pub fn bytes_before(&self, offset: usize) -> impl Iterator<Item = u8> + '_ {
+        let mut end = offset.min(self.len());
+        std::iter::from_fn(move || {
+            let (start, chunk) = self.chunk_at(end.checked_sub(1)?)?;
+            let len = end - start;
+            end = start;
+            Some((chunk, len))
+        })
+        .flat_map(|(chunk, len)| (0..len).rev().map(move |i| chunk.as_str().as_bytes()[i]))
+    }
+
     /// 🚀 NEW: Call a function for each chunk (zero-copy)
     pub fn for_each_chunk<F>(&self, mut f: F)
     where
@@ -466,3 +618,40 @@ impl std::fmt::Display for Rope {
         write!(f, "{}", self.to_string())
     }
 }
+
+/// Iterator returned by `Rope::lines`
+pub struct Lines<'a> {
+    chunks: Items<'a, Chunk>,
+    /// Unread text of the current chunk
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        if self.rest.is_empty() {
+            self.rest = self.chunks.next()?.as_str();
+        }
+        if let Some(newline) = self.rest.find('\n') {
+            let line = &self.rest[..newline];
+            self.rest = &self.rest[newline + 1..];
+            return Some(Cow::Borrowed(line));
+        }
+
+        // The line continues into the next chunks
+        let mut line = Cow::Borrowed(std::mem::take(&mut self.rest));
+        for chunk in self.chunks.by_ref() {
+            let text = chunk.as_str();
+            match text.find('\n') {
+                Some(newline) => {
+                    line.to_mut().push_str(&text[..newline]);
+                    self.rest = &text[newline + 1..];
+                    break;
+                }
+                None => line.to_mut().push_str(text),
+            }
+        }
+        Some(line)
+    }
+}
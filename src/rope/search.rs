@@ -0,0 +1,223 @@
+//! Literal and regex search that walks the rope's chunks instead of joining
+//! them into one string first
+
+use super::rope::Rope;
+use regex::Regex;
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::hybrid::regex::Regex as LazyRegex;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchError};
+use std::ops::Range;
+
+impl Rope {
+    /// First occurrence of `pattern` starting at or after byte `from` (empty
+    /// patterns match nothing). Each chunk is searched in place; only the
+    /// last `pattern.len() - 1` bytes of one are carried over to catch
+    /// occurrences that straddle two chunks
+    pub fn find(&self, pattern: &str, from: usize) -> Option<Range<usize>> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let overlap = pattern.len() - 1;
+        let (mut chunk_start, chunks) = self.chunks_from(from);
+        // Tail of the text searched so far that could start an occurrence
+        let mut carry = String::new();
+        for chunk in chunks {
+            let text = chunk.as_str();
+            if !carry.is_empty() {
+                let window_start = chunk_start - carry.len();
+                let window = [carry.as_str(), &text[..ceil_boundary(text, overlap)]].concat();
+                let skip = ceil_boundary(&window, from.saturating_sub(window_start));
+                let found = window[skip..]
+                    .find(pattern)
+                    .map(|index| skip + index)
+                    .filter(|&index| index < carry.len());
+                if let Some(index) = found {
+                    let start = window_start + index;
+                    return Some(start..start + pattern.len());
+                }
+            }
+
+            let skip = ceil_boundary(text, from.saturating_sub(chunk_start));
+            if let Some(index) = text[skip..].find(pattern) {
+                let start = chunk_start + skip + index;
+                return Some(start..start + pattern.len());
+            }
+
+            carry.push_str(text);
+            let keep = floor_boundary(&carry, carry.len().saturating_sub(overlap));
+            carry.drain(..keep);
+            chunk_start += text.len();
+        }
+        None
+    }
+
+    /// Every match of `regex`, left to right and non-overlapping, as byte
+    /// ranges. The chunks are fed through a lazy DFA built from
+    /// `regex.as_str()`, so flags have to be inline (`(?i)`) rather than set
+    /// with `RegexBuilder`. Patterns it can't run that way (a Unicode `\b`
+    /// next to non-ASCII text, or one that keeps overflowing its cache) are
+    /// searched in the joined text instead
+    pub fn find_all(&self, regex: &Regex) -> Vec<Range<usize>> {
+        LazyRegex::builder()
+            .dfa(DFA::config().unicode_word_boundary(true))
+            .build(regex.as_str())
+            .ok()
+            .and_then(|lazy| lazy_find_all(self, &lazy).ok())
+            .unwrap_or_else(|| {
+                regex
+                    .find_iter(&self.to_string())
+                    .map(|found| found.range())
+                    .collect()
+            })
+    }
+}
+
+/// The forward DFA finds where each match ends, then the reverse DFA walks
+/// back from there to where it starts. Like `Regex::find_iter`, an empty
+/// match right where the previous one ended is skipped
+fn lazy_find_all(rope: &Rope, regex: &LazyRegex) -> Result<Vec<Range<usize>>, MatchError> {
+    let mut cache = regex.create_cache();
+    let (forward, reverse) = cache.as_parts_mut();
+    let mut matches = Vec::new();
+    let mut from = 0;
+    let mut last_end = None;
+    while let Some(end) = find_end(rope, regex.forward(), forward, from)? {
+        let start = if end == from {
+            end
+        } else {
+            find_start(rope, regex.reverse(), reverse, from, end)?
+        };
+        if start == end && last_end == Some(end) {
+            if end == rope.len() {
+                break;
+            }
+            from = rope.char_to_byte(rope.byte_to_char(end) + 1);
+            continue;
+        }
+        matches.push(start..end);
+        last_end = Some(end);
+        from = end;
+    }
+    Ok(matches)
+}
+
+/// End of the leftmost match starting at or after `from`
+fn find_end(
+    rope: &Rope,
+    dfa: &DFA,
+    cache: &mut Cache,
+    from: usize,
+) -> Result<Option<usize>, MatchError> {
+    let config =
+        start::Config::new().look_behind(from.checked_sub(1).and_then(|at| rope.byte_at(at)));
+    let mut state = dfa
+        .start_state(cache, &config)
+        .map_err(|_| MatchError::gave_up(from))?;
+    let mut end = None;
+    let (mut chunk_start, chunks) = rope.chunks_from(from);
+    for chunk in chunks {
+        let bytes = chunk.as_str().as_bytes();
+        for (index, &byte) in bytes
+            .iter()
+            .enumerate()
+            .skip(from.saturating_sub(chunk_start))
+        {
+            let at = chunk_start + index;
+            state = dfa
+                .next_state(cache, state, byte)
+                .map_err(|_| MatchError::gave_up(at))?;
+            // Matches show up one byte late, so this one ends at `at`
+            if state.is_match() {
+                end = Some(at);
+            } else if state.is_dead() {
+                return Ok(end);
+            } else if state.is_quit() {
+                return Err(MatchError::quit(byte, at));
+            }
+        }
+        chunk_start += bytes.len();
+    }
+    state = dfa
+        .next_eoi_state(cache, state)
+        .map_err(|_| MatchError::gave_up(rope.len()))?;
+    if state.is_match() {
+        end = Some(rope.len());
+    }
+    Ok(end)
+}
+
+/// Start of the match ending at `end`, no earlier than `from`
+fn find_start(
+    rope: &Rope,
+    dfa: &DFA,
+    cache: &mut Cache,
+    from: usize,
+    end: usize,
+) -> Result<usize, MatchError> {
+    let config = start::Config::new()
+        .anchored(Anchored::Yes)
+        .look_behind(rope.byte_at(end));
+    let mut state = dfa
+        .start_state(cache, &config)
+        .map_err(|_| MatchError::gave_up(end))?;
+    let mut start = None;
+    let mut at = end;
+    while at > from {
+        let Some((chunk_start, chunk)) = rope.chunk_at(at - 1) else {
+            break;
+        };
+        let bytes = chunk.as_str().as_bytes();
+        let lowest = from.max(chunk_start);
+        for offset in (lowest..at).rev() {
+            let byte = bytes[offset - chunk_start];
+            state = dfa
+                .next_state(cache, state, byte)
+                .map_err(|_| MatchError::gave_up(offset))?;
+            if state.is_match() {
+                start = Some(offset + 1);
+            } else if state.is_dead() {
+                return start.ok_or_else(|| MatchError::gave_up(offset));
+            } else if state.is_quit() {
+                return Err(MatchError::quit(byte, offset));
+            }
+        }
+        at = lowest;
+    }
+
+    // The byte before `from` (or the start of the text) can complete a match
+    // that reaches all the way back to `from`
+    state = match from.checked_sub(1).and_then(|before| rope.byte_at(before)) {
+        Some(byte) => {
+            let next = dfa
+                .next_state(cache, state, byte)
+                .map_err(|_| MatchError::gave_up(from))?;
+            if next.is_quit() {
+                return Err(MatchError::quit(byte, from - 1));
+            }
+            next
+        }
+        None => dfa
+            .next_eoi_state(cache, state)
+            .map_err(|_| MatchError::gave_up(from))?,
+    };
+    if state.is_match() {
+        start = Some(from);
+    }
+    start.ok_or_else(|| MatchError::gave_up(from))
+}
+
+/// The first char boundary at or after `index` (clamped to the length)
+fn ceil_boundary(text: &str, index: usize) -> usize {
+    (index.min(text.len())..=text.len())
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(text.len())
+}
+
+/// The last char boundary at or before `index`
+fn floor_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0)
+}
@@ -0,0 +1,52 @@
+use super::metrics::TextMetrics;
+use super::rope::Rope;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialized form of a Rope: its chunks as stored (so chunk boundaries
+/// round-trip exactly) plus the summary, checked again on load
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RopeSnapshot {
+    pub summary: TextMetrics,
+    pub chunks: Vec<String>,
+}
+
+impl From<&Rope> for RopeSnapshot {
+    fn from(rope: &Rope) -> Self {
+        let mut chunks = Vec::with_capacity(rope.chunk_count());
+        rope.for_each_chunk(|chunk| chunks.push(chunk.to_string()));
+        Self {
+            summary: rope.summary(),
+            chunks,
+        }
+    }
+}
+
+impl TryFrom<RopeSnapshot> for Rope {
+    type Error = String;
+
+    /// Rebuild the rope, rejecting snapshots whose summary doesn't match the chunks
+    fn try_from(snapshot: RopeSnapshot) -> Result<Self, Self::Error> {
+        let rope = Rope::from_chunks(snapshot.chunks);
+        if rope.summary() != snapshot.summary {
+            return Err(format!(
+                "rope snapshot summary mismatch: expected {:?}, chunks add up to {:?}",
+                snapshot.summary,
+                rope.summary()
+            ));
+        }
+        Ok(rope)
+    }
+}
+
+impl Serialize for Rope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RopeSnapshot::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = RopeSnapshot::deserialize(deserializer)?;
+        Rope::try_from(snapshot).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,166 @@
+use crate::formatter::Sandbox;
+use crate::syntax::FileAssociations;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Result};
+use std::path::{Path, PathBuf};
+
+/// User settings, persisted as JSON (missing sections fall back to defaults)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub editor: EditorSettings,
+    /// Glob pattern → language overrides, checked before file extensions
+    pub file_associations: FileAssociations,
+    /// Environment, working directory and limits for formatters and tasks
+    pub tools: Sandbox,
+}
+
+/// Editor behaviour and appearance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Font size in points
+    pub font_size: f32,
+    pub cursor_blink: bool,
+    /// Run the language's formatter before writing the file
+    pub format_on_save: bool,
+    /// Keep unsaved changes across restarts instead of discarding them
+    pub hot_exit: bool,
+    /// Columns per indent level and tab stop
+    pub tab_size: usize,
+    /// Indent with tab characters instead of spaces
+    pub hard_tabs: bool,
+    /// Re-indent pasted lines to fit the cursor line (Ctrl+Shift+V pastes
+    /// verbatim either way)
+    pub reindent_on_paste: bool,
+    /// Language name → what pasting multi-line text does there, instead of
+    /// following `reindent_on_paste` (`"Python": "verbatim"`, `"Rust": "format"`)
+    pub paste_modes: BTreeMap<String, PasteMode>,
+    /// Close brackets and quotes while typing
+    pub auto_close_pairs: bool,
+    /// Language name → pairs to auto-close instead of the language's own,
+    /// written back to back (`"Markdown": "()[]**``"`)
+    pub autopairs: BTreeMap<String, String>,
+    /// Characters that count as part of words besides letters and digits,
+    /// for word movement, double-click and Ctrl+D
+    pub word_chars: String,
+    /// Language name → word characters instead of `word_chars` (`"CSS": "_-"`)
+    pub language_word_chars: BTreeMap<String, String>,
+    /// Language name → text new files start with (`$0` marks the cursor)
+    pub file_templates: BTreeMap<String, String>,
+    /// Wrap long lines at the window width instead of scrolling sideways
+    pub soft_wrap: bool,
+    /// Column Reflow Paragraph fills lines up to
+    pub reflow_width: usize,
+    /// Apply tab width, language and wrap from vim/emacs modelines in files
+    pub modelines: bool,
+    /// Replace-alls changing more text than this (in KB) keep a snapshot
+    /// for undo instead of the changed hunks
+    pub undo_hunk_limit_kb: usize,
+}
+
+impl EditorSettings {
+    pub const MIN_FONT_SIZE: f32 = 8.0;
+    pub const MAX_FONT_SIZE: f32 = 32.0;
+    pub const MIN_TAB_SIZE: usize = 1;
+    pub const MAX_TAB_SIZE: usize = 16;
+    pub const MIN_REFLOW_WIDTH: usize = 20;
+    pub const MAX_REFLOW_WIDTH: usize = 200;
+
+    /// Template for new files in a language (names compare case-insensitively)
+    pub fn file_template(&self, language: &str) -> Option<&str> {
+        self.file_templates
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            .map(|(_, template)| template.as_str())
+    }
+
+    /// How pasting works in languages without an entry in `paste_modes`
+    pub fn paste_mode(&self) -> PasteMode {
+        if self.reindent_on_paste {
+            PasteMode::Reindent
+        } else {
+            PasteMode::Verbatim
+        }
+    }
+}
+
+/// What pasting multi-line text does to it (Ctrl+Shift+V always pastes
+/// verbatim)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteMode {
+    /// Insert it as given
+    Verbatim,
+    /// Fit its lines to the cursor line's indentation
+    Reindent,
+    /// Run small pastes through the language's formatter, then reindent
+    Format,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            font_size: 14.0,
+            cursor_blink: true,
+            format_on_save: true,
+            hot_exit: true,
+            tab_size: 4,
+            hard_tabs: false,
+            reindent_on_paste: true,
+            paste_modes: BTreeMap::new(),
+            auto_close_pairs: true,
+            autopairs: BTreeMap::new(),
+            word_chars: "_".to_string(),
+            language_word_chars: BTreeMap::new(),
+            file_templates: BTreeMap::new(),
+            soft_wrap: false,
+            reflow_width: 80,
+            modelines: true,
+            undo_hunk_limit_kb: 1024,
+        }
+    }
+}
+
+impl Settings {
+    /// Config directory ($ZED_EDITOR_CONFIG_DIR, then ~/.config/zed-text-editor)
+    pub fn config_dir() -> PathBuf {
+        std::env::var_os("ZED_EDITOR_CONFIG_DIR")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".config/zed-text-editor"))
+            })
+            .unwrap_or_else(|| std::env::temp_dir().join("zed-text-editor"))
+    }
+
+    /// Settings file location
+    pub fn default_path() -> PathBuf {
+        Self::config_dir().join("settings.json")
+    }
+
+    /// Load settings; a missing file gives the defaults
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = match fs::read_to_string(path.as_ref()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write settings, creating the parent directory if needed
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json)
+    }
+}
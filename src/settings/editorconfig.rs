@@ -0,0 +1,126 @@
+use crate::editor::LineEnding;
+use crate::syntax::file_associations::glob_match;
+use std::path::Path;
+
+/// Name of the files read by `EditorConfig::for_path`
+pub const EDITORCONFIG_FILE: &str = ".editorconfig";
+
+/// Project style from `.editorconfig` files (https://editorconfig.org): the
+/// indentation and line ending a file should use. Modelines override it;
+/// it overrides the user's settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    /// Indent and tab stop width
+    pub tab_size: Option<usize>,
+    pub hard_tabs: Option<bool>,
+    pub line_ending: Option<LineEnding>,
+}
+
+impl EditorConfig {
+    /// Settings for `path` (which need not exist yet) from the
+    /// `.editorconfig` files in its directory and the ones above, up to one
+    /// saying `root = true`; nearer files win
+    pub fn for_path(path: &Path) -> EditorConfig {
+        let mut config = EditorConfig::default();
+        for dir in path.ancestors().skip(1) {
+            let Ok(text) = std::fs::read_to_string(dir.join(EDITORCONFIG_FILE)) else {
+                continue;
+            };
+            let Some(relative) = path.strip_prefix(dir).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            let (found, root) = Self::parse(text.as_str(), &relative.replace('\\', "/"));
+            config = config.or(found);
+            if root {
+                break;
+            }
+        }
+        config
+    }
+
+    /// The settings one `.editorconfig` gives a file at `relative` (a
+    /// `/`-separated path from the config's directory), later sections
+    /// winning, and whether the config says `root = true`
+    pub fn parse(text: &str, relative: &str) -> (EditorConfig, bool) {
+        let file_name = relative.rsplit('/').next().unwrap_or(relative);
+        let mut config = EditorConfig::default();
+        let (mut root, mut matching, mut in_preamble) = (false, false, true);
+        let (mut indent_size, mut tab_width) = (None, None);
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_preamble = false;
+                matching = Self::section_matches(section, relative, file_name);
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_lowercase(), value.trim().to_lowercase());
+            if in_preamble {
+                root |= key == "root" && value == "true";
+                continue;
+            }
+            if !matching {
+                continue;
+            }
+            match (key.as_str(), value.as_str()) {
+                ("indent_style", "tab") => config.hard_tabs = Some(true),
+                ("indent_style", "space") => config.hard_tabs = Some(false),
+                // `indent_size = tab` means "whatever tab_width is"
+                ("indent_size", "tab") => indent_size = None,
+                ("indent_size", size) => indent_size = size.parse().ok().filter(|&n| n > 0),
+                ("tab_width", width) => tab_width = width.parse().ok().filter(|&n| n > 0),
+                ("end_of_line", "lf") => config.line_ending = Some(LineEnding::Lf),
+                ("end_of_line", "crlf") => config.line_ending = Some(LineEnding::CrLf),
+                _ => {}
+            }
+        }
+        // The editor has one width for both; the indent width matters more
+        config.tab_size = indent_size.or(tab_width);
+        (config, root)
+    }
+
+    /// Each setting from `self`, or from `other` where `self` has none
+    pub fn or(self, other: EditorConfig) -> EditorConfig {
+        EditorConfig {
+            tab_size: self.tab_size.or(other.tab_size),
+            hard_tabs: self.hard_tabs.or(other.hard_tabs),
+            line_ending: self.line_ending.or(other.line_ending),
+        }
+    }
+
+    /// Sections without a `/` match the file name at any depth, others the
+    /// path from the config's directory. `{a,b}` alternatives are expanded;
+    /// `*` and `**` both match across directories
+    fn section_matches(section: &str, relative: &str, file_name: &str) -> bool {
+        expand_braces(section)
+            .iter()
+            .any(|glob| match glob.strip_prefix('/') {
+                Some(anchored) => glob_match(anchored, relative),
+                None if glob.contains('/') => glob_match(glob, relative),
+                None => glob_match(glob, file_name),
+            })
+    }
+}
+
+/// `*.{js,ts}` → `*.js`, `*.ts` (braces can't nest)
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some((open, close)) = glob
+        .find('{')
+        .and_then(|open| Some((open, open + glob[open..].find('}')?)))
+    else {
+        return vec![glob.to_string()];
+    };
+    let rest = expand_braces(&glob[close + 1..]);
+    glob[open + 1..close]
+        .split(',')
+        .flat_map(|alternative| {
+            rest.iter()
+                .map(move |tail| format!("{}{}{}", &glob[..open], alternative, tail))
+        })
+        .collect()
+}
@@ -0,0 +1,7 @@
+pub mod config;
+pub mod editorconfig;
+pub mod modeline;
+
+pub use config::{EditorSettings, PasteMode, Settings};
+pub use editorconfig::{EditorConfig, EDITORCONFIG_FILE};
+pub use modeline::Modeline;
@@ -0,0 +1,118 @@
+/// Lines checked at each end of a file (vim's default `modelines=5`)
+pub const MODELINE_LINES: usize = 5;
+
+/// Per-buffer settings from a vim (`vim: ts=2 sw=2 et ft=yaml`) or emacs
+/// (`-*- mode: python; tab-width: 4 -*-`) modeline. Only these harmless
+/// options are read; everything else in the modeline is ignored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modeline {
+    /// Indent and tab stop width
+    pub tab_size: Option<usize>,
+    pub hard_tabs: Option<bool>,
+    /// Language name or extension as the modeline wrote it ("python", "js")
+    pub language: Option<String>,
+    pub soft_wrap: Option<bool>,
+}
+
+impl Modeline {
+    /// The first modeline among a file's first and last `MODELINE_LINES` lines
+    pub fn find<S: AsRef<str>>(lines: &[S]) -> Option<Modeline> {
+        let head = lines.len().min(MODELINE_LINES);
+        let tail = lines.len().saturating_sub(MODELINE_LINES).max(head);
+        lines[..head]
+            .iter()
+            .chain(&lines[tail..])
+            .find_map(|line| Self::parse(line.as_ref()))
+    }
+
+    /// Read a modeline from one line (None if it has none, or sets nothing
+    /// we understand)
+    pub fn parse(line: &str) -> Option<Modeline> {
+        [Self::parse_emacs(line), Self::parse_vim(line)]
+            .into_iter()
+            .flatten()
+            .find(|modeline| *modeline != Modeline::default())
+    }
+
+    /// `vim: set ts=2 sw=2 et:` or `vi: ts=2:ft=yaml`, after whitespace or
+    /// at the start of the line
+    fn parse_vim(line: &str) -> Option<Modeline> {
+        let start = ["vim:", "vi:", "ex:"]
+            .iter()
+            .filter_map(|marker| {
+                line.match_indices(marker)
+                    .find(|(at, _)| line[..*at].chars().last().is_none_or(char::is_whitespace))
+                    .map(|(at, _)| at + marker.len())
+            })
+            .min()?;
+        let rest = line[start..].trim_start();
+        let options: Vec<&str> = match rest.strip_prefix("set ").or(rest.strip_prefix("se ")) {
+            // The `set` form ends at the next colon; text after it isn't ours
+            Some(set) => set
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect(),
+            None => rest
+                .split([' ', '\t', ':'])
+                .filter(|option| !option.is_empty())
+                .collect(),
+        };
+
+        let mut modeline = Modeline::default();
+        let (mut tab_stop, mut shift_width) = (None, None);
+        for option in options {
+            let (name, value) = option.split_once('=').unwrap_or((option, ""));
+            match name {
+                "ts" | "tabstop" => tab_stop = value.parse().ok(),
+                "sw" | "shiftwidth" => shift_width = value.parse().ok(),
+                "et" | "expandtab" => modeline.hard_tabs = Some(false),
+                "noet" | "noexpandtab" => modeline.hard_tabs = Some(true),
+                "ft" | "filetype" | "syn" | "syntax" if !value.is_empty() => {
+                    modeline.language = Some(value.to_string())
+                }
+                "wrap" => modeline.soft_wrap = Some(true),
+                "nowrap" => modeline.soft_wrap = Some(false),
+                _ => {}
+            }
+        }
+        // The editor has one width for both; the indent width matters more
+        modeline.tab_size = shift_width.filter(|&width| width > 0).or(tab_stop);
+        Some(modeline)
+    }
+
+    /// `-*- mode: rust; tab-width: 4; indent-tabs-mode: nil -*-`, or just
+    /// `-*- python -*-`
+    fn parse_emacs(line: &str) -> Option<Modeline> {
+        let (_, rest) = line.split_once("-*-")?;
+        let (variables, _) = rest.split_once("-*-")?;
+
+        let mut modeline = Modeline::default();
+        if !variables.contains(':') {
+            modeline.language = Self::emacs_mode(variables);
+            return Some(modeline);
+        }
+        for variable in variables.split(';') {
+            let Some((name, value)) = variable.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_lowercase().as_str() {
+                "mode" => modeline.language = Self::emacs_mode(value),
+                "tab-width" => modeline.tab_size = value.parse().ok(),
+                "indent-tabs-mode" => modeline.hard_tabs = Some(value != "nil"),
+                "truncate-lines" => modeline.soft_wrap = Some(value == "nil"),
+                _ => {}
+            }
+        }
+        Some(modeline)
+    }
+
+    /// `python-mode` → "python"
+    fn emacs_mode(mode: &str) -> Option<String> {
+        let mode = mode.trim();
+        let mode = mode.strip_suffix("-mode").unwrap_or(mode);
+        (!mode.is_empty()).then(|| mode.to_lowercase())
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A glob pattern mapped to a language name (e.g. "Dockerfile*" → "dockerfile")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileAssociation {
+    pub pattern: String,
+    pub language: String,
+}
+
+/// User-configured file associations, consulted before extension matching
+///
+/// Patterns without a `/` match the file name, patterns with one match the
+/// whole path. `*` matches any run of characters and `?` a single one.
+/// Earlier entries win; `set` puts the newest choice first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FileAssociations {
+    entries: Vec<FileAssociation>,
+}
+
+impl FileAssociations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Associate a pattern with a language (replaces an existing entry for the pattern)
+    pub fn set(&mut self, pattern: &str, language: &str) {
+        self.remove(pattern);
+        self.entries.insert(
+            0,
+            FileAssociation {
+                pattern: pattern.to_string(),
+                language: language.to_string(),
+            },
+        );
+    }
+
+    /// Remove the association for a pattern, returns true if there was one
+    pub fn remove(&mut self, pattern: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.pattern != pattern);
+        self.entries.len() != before
+    }
+
+    pub fn entries(&self) -> &[FileAssociation] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Language name for a path, if any pattern matches it
+    pub fn language_for(&self, path: &Path) -> Option<&str> {
+        let file_name = path.file_name()?.to_str()?;
+        let full_path = path.to_str()?;
+
+        self.entries
+            .iter()
+            .find(|entry| {
+                let target = if entry.pattern.contains('/') {
+                    full_path
+                } else {
+                    file_name
+                };
+                glob_match(&entry.pattern, target)
+            })
+            .map(|entry| entry.language.as_str())
+    }
+}
+
+/// Match `*` / `?` wildcards (no character classes)
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Last `*` seen and the text position it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -1,14 +1,14 @@
 use crate::syntax::languages::{LanguageConfig, LanguageRegistry};
-use crate::syntax::theme::SyntaxTheme;
-use egui::Color32;
+use crate::syntax::rope_input::RopeInput;
+use crate::syntax::theme::{Color, SyntaxTheme};
 use std::path::Path;
-use tree_sitter::{Query, QueryCursor, Tree};
+use tree_sitter::{Node, Query, QueryCursor, Tree};
 
 #[derive(Debug, Clone)]
 pub struct HighlightSpan {
     pub start: usize,
     pub end: usize,
-    pub color: Color32,
+    pub color: Color,
 }
 
 struct ParseCache {
@@ -123,14 +123,14 @@ impl SyntaxHighlighter {
 
         // 🚀 CRITICAL FIX: Extract ONLY the context window, not the entire file!
         // OLD CODE: let full_text = editor.text(); // Converted entire rope to string!
-        // NEW CODE: Only extract ~100 lines of context
-        let context_text = rope.slice_bytes(context_start_byte, context_end_byte);
+        // NEW CODE: Only ~100 lines of context, sharing the rope's chunks
+        let context = RopeInput::new(&rope.slice(context_start_byte..context_end_byte));
 
         if should_log {
             eprintln!(
                 "[CONTEXT] Line {}: extracting {} bytes (lines {}-{})",
                 line_number,
-                context_text.len(),
+                context_end_byte - context_start_byte,
                 context_start_line,
                 context_end_line
             );
@@ -138,7 +138,7 @@ impl SyntaxHighlighter {
 
         // Parse only the context window (not the entire file!)
         let mut parser = self.registry.create_parser(config);
-        let tree = match parser.parse(&context_text, None) {
+        let tree = match context.parse(&mut parser) {
             Some(t) => t,
             None => {
                 if should_log {
@@ -174,13 +174,14 @@ impl SyntaxHighlighter {
         let target_line_start_in_context = line_start_byte - context_start_byte;
         let target_line_end_in_context = line_end_byte - context_start_byte;
 
-        let line_text = rope.slice_bytes(line_start_byte, line_end_byte);
+        let line_text = rope.lines_at(line_number).next().unwrap_or_default();
         let line_char_len = line_text.chars().count();
 
         let mut highlights = Vec::new();
 
         // Query for highlights in the context window
-        for match_ in cursor.matches(&query, root_node, context_text.as_bytes()) {
+        let text = |node: Node| context.bytes_in(node.byte_range());
+        for match_ in cursor.matches(&query, root_node, text) {
             for capture in match_.captures {
                 let node = capture.node;
                 let start = node.start_byte();
@@ -195,7 +196,7 @@ impl SyntaxHighlighter {
                     let span_start = if start <= target_line_start_in_context {
                         0
                     } else {
-                        context_text[target_line_start_in_context..start]
+                        line_text[..start - target_line_start_in_context]
                             .chars()
                             .count()
                     };
@@ -203,7 +204,7 @@ impl SyntaxHighlighter {
                     let span_end = if end >= target_line_end_in_context {
                         line_char_len
                     } else {
-                        context_text[target_line_start_in_context..end]
+                        line_text[..end - target_line_start_in_context]
                             .chars()
                             .count()
                     };
@@ -0,0 +1,229 @@
+use super::languages::LanguageId;
+use std::ops::Range;
+use tree_sitter::Tree;
+
+/// Python modules grouped with the standard library (the common ones)
+const PYTHON_STDLIB: &[&str] = &[
+    "abc",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "base64",
+    "bisect",
+    "builtins",
+    "collections",
+    "concurrent",
+    "contextlib",
+    "copy",
+    "csv",
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "difflib",
+    "enum",
+    "errno",
+    "functools",
+    "gc",
+    "getpass",
+    "glob",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "importlib",
+    "inspect",
+    "io",
+    "itertools",
+    "json",
+    "logging",
+    "math",
+    "multiprocessing",
+    "operator",
+    "os",
+    "pathlib",
+    "pickle",
+    "platform",
+    "pprint",
+    "queue",
+    "random",
+    "re",
+    "shlex",
+    "shutil",
+    "signal",
+    "socket",
+    "sqlite3",
+    "statistics",
+    "string",
+    "struct",
+    "subprocess",
+    "sys",
+    "tempfile",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "traceback",
+    "types",
+    "typing",
+    "unittest",
+    "urllib",
+    "uuid",
+    "warnings",
+    "weakref",
+    "xml",
+    "zipfile",
+    "zlib",
+];
+
+/// Node kinds of top-level import statements
+pub fn import_kinds(language: LanguageId) -> &'static [&'static str] {
+    match language {
+        LanguageId::Rust => &["use_declaration"],
+        LanguageId::Python => &[
+            "import_statement",
+            "import_from_statement",
+            "future_import_statement",
+        ],
+        LanguageId::JavaScript => &["import_statement"],
+        LanguageId::Unknown => &[],
+    }
+}
+
+/// Byte ranges of top-level imports, grouped into blocks separated only by
+/// whitespace (a comment or any other statement ends a block)
+pub fn import_blocks(tree: &Tree, text: &str, language: LanguageId) -> Vec<Vec<Range<usize>>> {
+    let kinds = import_kinds(language);
+    let mut blocks: Vec<Vec<Range<usize>>> = Vec::new();
+    let mut current: Vec<Range<usize>> = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for node in root.children(&mut cursor) {
+        if !kinds.contains(&node.kind()) {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let range = node.byte_range();
+        let continues = current
+            .last()
+            .is_some_and(|last| text[last.end..range.start].trim().is_empty());
+        if !continues && !current.is_empty() {
+            blocks.push(std::mem::take(&mut current));
+        }
+        current.push(range);
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+    blocks
+}
+
+/// Where an import goes: its group (lower first) and the module it names
+fn classify(statement: &str, language: LanguageId) -> (u8, String) {
+    match language {
+        LanguageId::Rust => {
+            let mut path = statement.trim_start();
+            if let Some(rest) = path.strip_prefix("pub") {
+                path = rest.trim_start();
+                if path.starts_with('(') {
+                    path = path.split_once(')').map_or(path, |(_, rest)| rest);
+                }
+            }
+            let path = path.trim_start().trim_start_matches("use").trim_start();
+            let root: String = path
+                .trim_start_matches("::")
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            let group = match root.as_str() {
+                "std" | "core" | "alloc" | "proc_macro" | "test" => 0,
+                "crate" | "self" | "super" => 2,
+                _ => 1,
+            };
+            (group, path.to_string())
+        }
+        LanguageId::Python => {
+            let module = statement
+                .trim_start()
+                .trim_start_matches("from")
+                .trim_start_matches("import")
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches(',');
+            let root = module.split('.').next().unwrap_or_default();
+            let group = if module == "__future__" {
+                0
+            } else if module.starts_with('.') {
+                3
+            } else if PYTHON_STDLIB.contains(&root) {
+                1
+            } else {
+                2
+            };
+            (group, module.to_string())
+        }
+        LanguageId::JavaScript => {
+            let source = statement
+                .rsplit(['"', '\''])
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+            let group = if source.starts_with('.') || source.starts_with('/') {
+                1
+            } else {
+                0
+            };
+            (group, source)
+        }
+        LanguageId::Unknown => (0, statement.to_string()),
+    }
+}
+
+/// Edits that organize each import block: duplicates removed, statements
+/// grouped (Rust: std / external / crate, Python: __future__ / stdlib /
+/// third-party / relative, JS: packages / relative paths), groups separated
+/// by a blank line and sorted by module. Empty if nothing would change
+pub fn organize_imports(
+    tree: &Tree,
+    text: &str,
+    language: LanguageId,
+) -> Vec<(Range<usize>, String)> {
+    import_blocks(tree, text, language)
+        .into_iter()
+        .filter_map(|block| {
+            let range = block[0].start..block[block.len() - 1].end;
+            let mut seen: Vec<String> = Vec::new();
+            let mut statements: Vec<((u8, String), &str)> = Vec::new();
+            for statement in block.iter().map(|range| &text[range.clone()]) {
+                let normalized = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+                if seen.contains(&normalized) {
+                    continue;
+                }
+                seen.push(normalized);
+                statements.push((classify(statement, language), statement));
+            }
+            statements.sort();
+
+            let mut organized = String::new();
+            let mut last_group = None;
+            for ((group, _), statement) in &statements {
+                match last_group {
+                    Some(last) if last != *group => organized.push_str("\n\n"),
+                    Some(_) => organized.push('\n'),
+                    None => {}
+                }
+                organized.push_str(statement);
+                last_group = Some(*group);
+            }
+
+            (organized != text[range.clone()]).then_some((range, organized))
+        })
+        .collect()
+}
@@ -1,11 +1,18 @@
-use crate::syntax::languages::{LanguageConfig, LanguageRegistry};
+use crate::syntax::file_associations::FileAssociations;
+#[cfg(feature = "syntax")]
+use crate::syntax::languages::LanguageConfig;
+use crate::syntax::languages::LanguageRegistry;
+#[cfg(feature = "syntax")]
+use crate::syntax::rope_input::RopeInput;
 use std::path::Path;
+#[cfg(feature = "syntax")]
 use tree_sitter::{Node, Tree};
 
 #[derive(Clone)]
 pub struct IndentCalculator {
     registry: LanguageRegistry,
     indent_width: usize,
+    hard_tabs: bool,
 }
 
 impl IndentCalculator {
@@ -13,48 +20,115 @@ impl IndentCalculator {
         Self {
             registry: LanguageRegistry::new(),
             indent_width: 4,
+            hard_tabs: false,
         }
     }
 
+    /// Use the user's file associations when detecting the language
+    pub fn set_file_associations(&mut self, associations: FileAssociations) {
+        self.registry.set_associations(associations);
+    }
+
+    /// Columns per indent level (also the tab stop width)
+    pub fn indent_width(&self) -> usize {
+        self.indent_width
+    }
+
+    /// Indent with `width`-column levels, using tab characters if `hard_tabs`
+    pub fn set_indent(&mut self, width: usize, hard_tabs: bool) {
+        self.indent_width = width.max(1);
+        self.hard_tabs = hard_tabs;
+    }
+
+    pub fn hard_tabs(&self) -> bool {
+        self.hard_tabs
+    }
+
+    /// Whitespace for one indent level
+    pub fn indent_unit(&self) -> String {
+        if self.hard_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+
+    /// `indent` with its last level removed (a tab, or up to a level of spaces)
+    #[cfg(feature = "syntax")]
+    fn dedent(&self, indent: &str) -> String {
+        if let Some(rest) = indent.strip_suffix('\t') {
+            return rest.to_string();
+        }
+        let spaces = indent.len() - indent.trim_end_matches(' ').len();
+        indent[..indent.len() - spaces.min(self.indent_width)].to_string()
+    }
+
     /// 🚀 LEGACY METHOD: Keep for backward compatibility
     /// This still converts to string, but it's only used in non-hot paths
+    #[cfg_attr(not(feature = "syntax"), allow(unused_variables))]
     pub fn calculate_indent(
         &self,
         text: &str,
         cursor_line: usize,
         file_path: Option<&Path>,
     ) -> String {
-        let Some(path) = file_path else {
-            return self.fallback_indent(text, cursor_line);
-        };
-
-        let Some(lang_config) = self.registry.detect_language(path) else {
-            return self.fallback_indent(text, cursor_line);
-        };
-
-        let mut parser = self.registry.create_parser(lang_config);
-        let Some(tree) = parser.parse(text, None) else {
-            return self.fallback_indent(text, cursor_line);
-        };
-
-        self.tree_based_indent(text, cursor_line, &tree, lang_config)
+        #[cfg(feature = "syntax")]
+        if let Some(indent) = self.syntax_indent(text, cursor_line, file_path) {
+            return indent;
+        }
+        self.fallback_indent(text, cursor_line)
     }
 
     /// 🚀 NEW OPTIMIZED METHOD: Uses Rope directly with context window!
     /// This is called on EVERY newline, so it must be fast
+    #[cfg_attr(not(feature = "syntax"), allow(unused_variables))]
     pub fn calculate_indent_with_rope(
         &self,
         rope: &crate::rope::Rope,
         cursor_line: usize,
         file_path: Option<&Path>,
     ) -> String {
-        let Some(path) = file_path else {
-            return self.fallback_indent_with_rope(rope, cursor_line);
-        };
+        #[cfg(feature = "syntax")]
+        if let Some(indent) = self.syntax_indent_with_rope(rope, cursor_line, file_path) {
+            return indent;
+        }
+        self.fallback_indent_with_rope(rope, cursor_line)
+    }
+
+    /// Grammar-based `calculate_indent`; None falls back to bracket counting
+    #[cfg(feature = "syntax")]
+    fn syntax_indent(
+        &self,
+        text: &str,
+        cursor_line: usize,
+        file_path: Option<&Path>,
+    ) -> Option<String> {
+        let lang_config = self.registry.detect_language(file_path?)?;
+        let mut parser = self.registry.create_parser(lang_config);
+        let tree = parser.parse(text, None)?;
 
-        let Some(lang_config) = self.registry.detect_language(path) else {
-            return self.fallback_indent_with_rope(rope, cursor_line);
+        let Some(current_line) = text.lines().nth(cursor_line) else {
+            return Some(String::new());
         };
+        let line_end = text
+            .lines()
+            .take(cursor_line + 1)
+            .map(|line| line.len() + 1)
+            .sum::<usize>()
+            .saturating_sub(1);
+        Some(self.tree_based_indent(current_line, line_end, &tree, lang_config))
+    }
+
+    /// Grammar-based `calculate_indent_with_rope`; None falls back to
+    /// bracket counting
+    #[cfg(feature = "syntax")]
+    fn syntax_indent_with_rope(
+        &self,
+        rope: &crate::rope::Rope,
+        cursor_line: usize,
+        file_path: Option<&Path>,
+    ) -> Option<String> {
+        let lang_config = self.registry.detect_language(file_path?)?;
 
         // 🚀 PERFORMANCE FIX: Only parse a context window, not entire file!
         // Similar to syntax highlighter - we only need context around cursor
@@ -70,63 +144,57 @@ impl IndentCalculator {
             rope.len()
         };
 
-        // Extract ONLY the context window (not entire file!)
-        let context_text = rope.slice_bytes(context_start_byte, context_end_byte);
+        // Extract ONLY the context window (not entire file!), sharing its chunks
+        let context = rope.slice(context_start_byte..context_end_byte);
 
         // Parse only the context
         let mut parser = self.registry.create_parser(lang_config);
-        let Some(tree) = parser.parse(&context_text, None) else {
-            return self.fallback_indent_with_rope(rope, cursor_line);
-        };
+        let tree = RopeInput::new(&context).parse(&mut parser)?;
 
         // Calculate which line within context window
         let line_in_context = cursor_line - context_start_line;
+        let (Some(current_line), Some((_, line_end))) = (
+            context.line(line_in_context),
+            context.line_byte_range(line_in_context),
+        ) else {
+            return Some(String::new());
+        };
 
-        self.tree_based_indent(&context_text, line_in_context, &tree, lang_config)
+        Some(self.tree_based_indent(
+            current_line.trim_end_matches('\r'),
+            line_end,
+            &tree,
+            lang_config,
+        ))
     }
 
+    /// Indent for the line after `current_line`, which ends at byte `line_end`
+    #[cfg(feature = "syntax")]
     fn tree_based_indent(
         &self,
-        text: &str,
-        cursor_line: usize,
+        current_line: &str,
+        line_end: usize,
         tree: &Tree,
         _config: &LanguageConfig,
     ) -> String {
-        let lines: Vec<&str> = text.lines().collect();
-
-        if cursor_line >= lines.len() {
-            return String::new();
-        }
-
-        let current_line = lines[cursor_line];
         let current_indent = Self::get_line_indent(current_line);
 
-        let byte_offset: usize = lines
-            .iter()
-            .take(cursor_line + 1)
-            .map(|line| line.len() + 1)
-            .sum::<usize>()
-            .saturating_sub(1);
-
         let root = tree.root_node();
-        let node_at_cursor = self.find_node_at_position(root, byte_offset);
+        let node_at_cursor = self.find_node_at_position(root, line_end);
 
         let should_indent = self.should_increase_indent(&node_at_cursor, current_line);
         let should_dedent = self.should_decrease_indent(&node_at_cursor, current_line);
 
         if should_dedent {
-            if current_indent.len() >= self.indent_width {
-                current_indent[..current_indent.len() - self.indent_width].to_string()
-            } else {
-                String::new()
-            }
+            self.dedent(&current_indent)
         } else if should_indent {
-            format!("{}{}", current_indent, " ".repeat(self.indent_width))
+            format!("{}{}", current_indent, self.indent_unit())
         } else {
             current_indent
         }
     }
 
+    #[cfg(feature = "syntax")]
     fn find_node_at_position<'a>(&self, node: Node<'a>, byte_offset: usize) -> Node<'a> {
         let mut current = node;
 
@@ -149,6 +217,7 @@ impl IndentCalculator {
         current
     }
 
+    #[cfg(feature = "syntax")]
     fn should_increase_indent(&self, node: &Node, line: &str) -> bool {
         let kind = node.kind();
         let trimmed = line.trim();
@@ -194,6 +263,7 @@ impl IndentCalculator {
         false
     }
 
+    #[cfg(feature = "syntax")]
     fn should_decrease_indent(&self, _node: &Node, line: &str) -> bool {
         let trimmed = line.trim();
 
@@ -234,7 +304,7 @@ impl IndentCalculator {
             
             // Simple: if more opens than closes, indent
             if opens > closes {
-                "    ".to_string()  // 4 spaces indent
+                self.indent_unit()  // One indent level
             } else {
                 String::new()  // No indent
             }
@@ -263,7 +333,7 @@ impl IndentCalculator {
             + trimmed.matches(')').count();
 
         if opens > closes || trimmed.ends_with(':') {
-            format!("{}{}", indent, " ".repeat(self.indent_width))
+            format!("{}{}", indent, self.indent_unit())
         } else {
             indent
         }
@@ -1,3 +1,5 @@
+use super::languages::LanguageId;
+use super::theme::Color;
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -17,19 +19,19 @@ pub enum Highlight {
 }
 
 impl Highlight {
-    pub fn to_color(&self) -> egui::Color32 {
+    pub fn to_color(&self) -> Color {
         match self {
-            Highlight::COMMENT => egui::Color32::from_rgb(100, 160, 100), // Green
-            Highlight::KEYWORD => egui::Color32::from_rgb(200, 120, 200), // Purple
-            Highlight::STRING => egui::Color32::from_rgb(200, 150, 100),  // Orange
-            Highlight::NUMBER => egui::Color32::from_rgb(100, 180, 255),  // Blue
-            Highlight::FUNCTION => egui::Color32::from_rgb(220, 220, 100), // Yellow
-            Highlight::TYPE => egui::Color32::from_rgb(100, 200, 255),    // Light Blue
-            Highlight::VARIABLE => egui::Color32::WHITE,                  // White
-            Highlight::CONSTANT => egui::Color32::from_rgb(200, 100, 100), // Red
-            Highlight::ATTRIBUTE => egui::Color32::from_rgb(200, 200, 100), // Light Yellow
-            Highlight::OPERATOR => egui::Color32::from_rgb(200, 200, 200), // Light Gray
-            Highlight::PUNCTUATION => egui::Color32::from_rgb(150, 150, 150), // Gray
+            Highlight::COMMENT => Color::from_rgb(100, 160, 100), // Green
+            Highlight::KEYWORD => Color::from_rgb(200, 120, 200), // Purple
+            Highlight::STRING => Color::from_rgb(200, 150, 100),  // Orange
+            Highlight::NUMBER => Color::from_rgb(100, 180, 255),  // Blue
+            Highlight::FUNCTION => Color::from_rgb(220, 220, 100), // Yellow
+            Highlight::TYPE => Color::from_rgb(100, 200, 255),    // Light Blue
+            Highlight::VARIABLE => Color::WHITE,                  // White
+            Highlight::CONSTANT => Color::from_rgb(200, 100, 100), // Red
+            Highlight::ATTRIBUTE => Color::from_rgb(200, 200, 100), // Light Yellow
+            Highlight::OPERATOR => Color::from_rgb(200, 200, 200), // Light Gray
+            Highlight::PUNCTUATION => Color::from_rgb(150, 150, 150), // Gray
         }
     }
 }
@@ -156,7 +158,49 @@ impl InstantHighlighter {
         ranges
     }
 
+    /// `highlight_visible_region` over all of `content`, leaving lines longer
+    /// than `max_line_len` bytes plain (minified files, data blobs)
+    pub fn highlight_lines(
+        &self,
+        content: &str,
+        language: &str,
+        max_line_len: usize,
+    ) -> Vec<HighlightedRange> {
+        let mut ranges = Vec::new();
+        let mut segment_start = 0;
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            if line.len() > max_line_len {
+                ranges.extend(self.highlight_visible_region(
+                    content,
+                    segment_start,
+                    offset,
+                    language,
+                ));
+                segment_start = offset + line.len();
+            }
+            offset += line.len();
+        }
+        ranges.extend(self.highlight_visible_region(
+            content,
+            segment_start,
+            content.len(),
+            language,
+        ));
+        ranges
+    }
+
     /// Detect language from file extension
+    /// Pattern set name for a registry language
+    pub fn language_key(id: LanguageId) -> &'static str {
+        match id {
+            LanguageId::Rust => "rust",
+            LanguageId::Python => "python",
+            LanguageId::JavaScript => "javascript",
+            LanguageId::Unknown => "unknown",
+        }
+    }
+
     pub fn detect_language(file_path: Option<&std::path::Path>) -> &'static str {
         if let Some(path) = file_path {
             if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
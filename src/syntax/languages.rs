@@ -1,9 +1,14 @@
+use super::file_associations::FileAssociations;
 use std::path::Path;
+#[cfg(feature = "syntax")]
 use tree_sitter::{Language, Parser};
 
 // Use the safe bindings provided by the crates (lowercase 'language')
+#[cfg(feature = "syntax")]
 use tree_sitter_javascript::language as tree_sitter_javascript_lang;
+#[cfg(feature = "syntax")]
 use tree_sitter_python::language as tree_sitter_python_lang;
+#[cfg(feature = "syntax")]
 use tree_sitter_rust::language as tree_sitter_rust_lang;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,8 +23,15 @@ pub enum LanguageId {
 pub struct LanguageConfig {
     pub id: LanguageId,
     pub name: &'static str,
+    /// Tree-sitter grammar (only with the `syntax` feature)
+    #[cfg(feature = "syntax")]
     pub language: Language,
     pub extensions: &'static [&'static str],
+    pub interpreters: &'static [&'static str], // Shebang interpreters
+    pub line_comment: Option<&'static str>,
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Brackets and quotes closed automatically while typing
+    pub autopairs: &'static [(char, char)],
     pub indent_query: &'static str,
     pub highlight_query: &'static str,
 }
@@ -29,8 +41,14 @@ impl LanguageConfig {
         Self {
             id: LanguageId::Rust,
             name: "Rust",
+            #[cfg(feature = "syntax")]
             language: tree_sitter_rust_lang(),
             extensions: &["rs"],
+            interpreters: &["rust-script"],
+            line_comment: Some("//"),
+            block_comment: Some(("/*", "*/")),
+            // No single quote: lifetimes and labels ('a) are far more common than chars
+            autopairs: &[('(', ')'), ('[', ']'), ('{', '}'), ('"', '"')],
             indent_query: include_str!("queries/rust/indents.scm"),
             highlight_query: include_str!("queries/rust/highlights.scm"),
         }
@@ -40,8 +58,20 @@ impl LanguageConfig {
         Self {
             id: LanguageId::JavaScript,
             name: "JavaScript",
+            #[cfg(feature = "syntax")]
             language: tree_sitter_javascript_lang(),
             extensions: &["js", "jsx", "mjs"],
+            interpreters: &["node"],
+            line_comment: Some("//"),
+            block_comment: Some(("/*", "*/")),
+            autopairs: &[
+                ('(', ')'),
+                ('[', ']'),
+                ('{', '}'),
+                ('"', '"'),
+                ('\'', '\''),
+                ('`', '`'),
+            ],
             indent_query: include_str!("queries/javascript/indents.scm"),
             highlight_query: include_str!("queries/javascript/highlights.scm"),
         }
@@ -51,8 +81,13 @@ impl LanguageConfig {
         Self {
             id: LanguageId::Python,
             name: "Python",
+            #[cfg(feature = "syntax")]
             language: tree_sitter_python_lang(),
             extensions: &["py"],
+            interpreters: &["python", "python2", "python3"],
+            line_comment: Some("#"),
+            block_comment: None,
+            autopairs: &[('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')],
             indent_query: include_str!("queries/python/indents.scm"),
             highlight_query: include_str!("queries/python/highlights.scm"),
         }
@@ -62,6 +97,7 @@ impl LanguageConfig {
 #[derive(Clone)]
 pub struct LanguageRegistry {
     languages: Vec<LanguageConfig>,
+    associations: FileAssociations,
 }
 
 impl LanguageRegistry {
@@ -72,20 +108,62 @@ impl LanguageRegistry {
                 LanguageConfig::javascript(),
                 LanguageConfig::python(),
             ],
+            associations: FileAssociations::new(),
         }
     }
 
+    /// Replace the user file associations (checked before extensions)
+    pub fn set_associations(&mut self, associations: FileAssociations) {
+        self.associations = associations;
+    }
+
+    pub fn associations(&self) -> &FileAssociations {
+        &self.associations
+    }
+
+    /// All registered languages
+    pub fn languages(&self) -> &[LanguageConfig] {
+        &self.languages
+    }
+
+    /// Look up a language by name, case-insensitively ("rust", "Python", ...)
+    pub fn language_by_name(&self, name: &str) -> Option<&LanguageConfig> {
+        self.languages
+            .iter()
+            .find(|lang| lang.name.eq_ignore_ascii_case(name))
+    }
+
     pub fn detect_language(&self, path: &Path) -> Option<&LanguageConfig> {
+        // An association wins even when it names a language we can't parse,
+        // so "*.rs.in" → "plaintext" really turns Rust handling off
+        if let Some(name) = self.associations.language_for(path) {
+            return self.language_by_name(name);
+        }
+
         let extension = path.extension()?.to_str()?;
         self.languages
             .iter()
             .find(|lang| lang.extensions.contains(&extension))
     }
 
+    /// Detect a language from a `#!` line (`#!/usr/bin/python3`, `#!/usr/bin/env node`)
+    pub fn detect_from_shebang(&self, first_line: &str) -> Option<&LanguageConfig> {
+        let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+        let mut interpreter = words.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            interpreter = words.find(|word| !word.starts_with('-'))?;
+        }
+
+        self.languages
+            .iter()
+            .find(|lang| lang.interpreters.contains(&interpreter))
+    }
+
     pub fn get_language(&self, id: LanguageId) -> Option<&LanguageConfig> {
         self.languages.iter().find(|lang| lang.id == id)
     }
 
+    #[cfg(feature = "syntax")]
     pub fn create_parser(&self, config: &LanguageConfig) -> Parser {
         let mut parser = Parser::new();
         parser.set_language(&config.language).unwrap();
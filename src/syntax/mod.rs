@@ -1,11 +1,19 @@
+pub mod file_associations;
+#[cfg(feature = "syntax")]
 pub mod highlighter;
+#[cfg(feature = "syntax")]
+pub mod imports;
 pub mod indent;
+pub mod instant_highlighter;
 pub mod languages;
+pub mod rope_input;
 pub mod theme;
 
-pub mod instant_highlighter;
+pub use file_associations::{FileAssociation, FileAssociations};
+#[cfg(feature = "syntax")]
 pub use highlighter::{HighlightSpan, SyntaxHighlighter};
 pub use indent::IndentCalculator;
 pub use instant_highlighter::{Highlight, HighlightedRange, InstantHighlighter};
 pub use languages::{LanguageConfig, LanguageId, LanguageRegistry};
-pub use theme::SyntaxTheme;
+pub use rope_input::RopeInput;
+pub use theme::{Color, SyntaxTheme};
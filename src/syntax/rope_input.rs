@@ -0,0 +1,52 @@
+use crate::rope::{Chunk, Rope};
+use std::ops::Range;
+#[cfg(feature = "syntax")]
+use tree_sitter::{Parser, Tree};
+
+/// A rope's chunks with their start offsets, so tree-sitter can parse and
+/// query the text without it being joined into one string
+pub struct RopeInput {
+    chunks: Vec<(usize, Chunk)>,
+}
+
+impl RopeInput {
+    pub fn new(rope: &Rope) -> Self {
+        let mut offset = 0;
+        let chunks = rope
+            .chunks()
+            .map(|chunk| {
+                let start = offset;
+                offset += chunk.len();
+                (start, chunk)
+            })
+            .collect();
+        Self { chunks }
+    }
+
+    /// From `offset` to the end of its chunk (empty past the end)
+    pub fn text_at(&self, offset: usize) -> &[u8] {
+        let index = self
+            .chunks
+            .partition_point(|(start, chunk)| start + chunk.len() <= offset);
+        match self.chunks.get(index) {
+            Some((start, chunk)) => &chunk.as_str().as_bytes()[offset - start..],
+            None => &[],
+        }
+    }
+
+    /// The bytes of `range`, chunk by chunk
+    pub fn bytes_in(&self, range: Range<usize>) -> impl Iterator<Item = &[u8]> {
+        self.chunks
+            .iter()
+            .filter(move |(start, chunk)| *start < range.end && start + chunk.len() > range.start)
+            .map(move |(start, chunk)| {
+                let bytes = chunk.as_str().as_bytes();
+                &bytes[range.start.saturating_sub(*start)..(range.end - start).min(bytes.len())]
+            })
+    }
+
+    #[cfg(feature = "syntax")]
+    pub fn parse(&self, parser: &mut Parser) -> Option<Tree> {
+        parser.parse_with(&mut |offset, _| self.text_at(offset), None)
+    }
+}
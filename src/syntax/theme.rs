@@ -1,59 +1,83 @@
-use egui::Color32;
+/// An sRGB color with alpha, independent of any UI toolkit (the GUI
+/// converts it to `egui::Color32`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::from_rgb(255, 255, 255);
+
+    /// Opaque color from its red, green and blue components
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl From<Color> for egui::Color32 {
+    fn from(color: Color) -> Self {
+        egui::Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct SyntaxTheme {
-    pub keyword: Color32,
-    pub function: Color32,
-    pub function_call: Color32,
-    pub type_name: Color32,
-    pub string: Color32,
-    pub number: Color32,
-    pub comment: Color32,
-    pub operator: Color32,
-    pub punctuation: Color32,
-    pub variable: Color32,
-    pub constant: Color32,
-    pub default: Color32,
+    pub keyword: Color,
+    pub function: Color,
+    pub function_call: Color,
+    pub type_name: Color,
+    pub string: Color,
+    pub number: Color,
+    pub comment: Color,
+    pub operator: Color,
+    pub punctuation: Color,
+    pub variable: Color,
+    pub constant: Color,
+    pub default: Color,
 }
 
 impl SyntaxTheme {
     /// Dark theme (inspired by One Dark)
     pub fn dark() -> Self {
         Self {
-            keyword: Color32::from_rgb(198, 120, 221),      // Purple
-            function: Color32::from_rgb(97, 175, 239),      // Blue
-            function_call: Color32::from_rgb(97, 175, 239), // Blue
-            type_name: Color32::from_rgb(229, 192, 123),    // Yellow
-            string: Color32::from_rgb(152, 195, 121),       // Green
-            number: Color32::from_rgb(209, 154, 102),       // Orange
-            comment: Color32::from_rgb(92, 99, 112),        // Gray
-            operator: Color32::from_rgb(86, 182, 194),      // Cyan
-            punctuation: Color32::from_rgb(171, 178, 191),  // Light gray
-            variable: Color32::from_rgb(224, 108, 117),     // Red
-            constant: Color32::from_rgb(209, 154, 102),     // Orange
-            default: Color32::from_rgb(171, 178, 191),      // Light gray
+            keyword: Color::from_rgb(198, 120, 221),      // Purple
+            function: Color::from_rgb(97, 175, 239),      // Blue
+            function_call: Color::from_rgb(97, 175, 239), // Blue
+            type_name: Color::from_rgb(229, 192, 123),    // Yellow
+            string: Color::from_rgb(152, 195, 121),       // Green
+            number: Color::from_rgb(209, 154, 102),       // Orange
+            comment: Color::from_rgb(92, 99, 112),        // Gray
+            operator: Color::from_rgb(86, 182, 194),      // Cyan
+            punctuation: Color::from_rgb(171, 178, 191),  // Light gray
+            variable: Color::from_rgb(224, 108, 117),     // Red
+            constant: Color::from_rgb(209, 154, 102),     // Orange
+            default: Color::from_rgb(171, 178, 191),      // Light gray
         }
     }
 
     /// Light theme (inspired by One Light)
     pub fn light() -> Self {
         Self {
-            keyword: Color32::from_rgb(166, 38, 164),       // Purple
-            function: Color32::from_rgb(64, 120, 242),      // Blue
-            function_call: Color32::from_rgb(64, 120, 242), // Blue
-            type_name: Color32::from_rgb(193, 132, 1),      // Yellow
-            string: Color32::from_rgb(80, 161, 79),         // Green
-            number: Color32::from_rgb(152, 104, 1),         // Orange
-            comment: Color32::from_rgb(160, 161, 167),      // Gray
-            operator: Color32::from_rgb(0, 132, 137),       // Cyan
-            punctuation: Color32::from_rgb(56, 58, 66),     // Dark gray
-            variable: Color32::from_rgb(228, 86, 73),       // Red
-            constant: Color32::from_rgb(152, 104, 1),       // Orange
-            default: Color32::from_rgb(56, 58, 66),         // Dark gray
+            keyword: Color::from_rgb(166, 38, 164),       // Purple
+            function: Color::from_rgb(64, 120, 242),      // Blue
+            function_call: Color::from_rgb(64, 120, 242), // Blue
+            type_name: Color::from_rgb(193, 132, 1),      // Yellow
+            string: Color::from_rgb(80, 161, 79),         // Green
+            number: Color::from_rgb(152, 104, 1),         // Orange
+            comment: Color::from_rgb(160, 161, 167),      // Gray
+            operator: Color::from_rgb(0, 132, 137),       // Cyan
+            punctuation: Color::from_rgb(56, 58, 66),     // Dark gray
+            variable: Color::from_rgb(228, 86, 73),       // Red
+            constant: Color::from_rgb(152, 104, 1),       // Orange
+            default: Color::from_rgb(56, 58, 66),         // Dark gray
         }
     }
 
-    pub fn get_color(&self, capture_name: &str) -> Color32 {
+    pub fn get_color(&self, capture_name: &str) -> Color {
         match capture_name {
             "keyword" => self.keyword,
             "function" | "function.method" => self.function,
@@ -2,5 +2,5 @@ pub mod cursor;
 pub mod sum_tree;
 pub mod summary;
 
-pub use sum_tree::{Item, Node, SumTree};
+pub use sum_tree::{Item, Items, Node, SumTree};
 pub use summary::{Count, Summary, TextSummary};
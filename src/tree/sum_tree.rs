@@ -1,13 +1,19 @@
 use super::summary::Summary;
+use std::ops::Range;
 use std::sync::Arc;
 
+/// Branching factor: nodes hold `TREE_BASE..=2 * TREE_BASE` children (or
+/// items, for leaves) and split when they outgrow that. Only the root and
+/// nodes along the right edge may hold fewer
+pub const TREE_BASE: usize = 6;
+
 /// Item stored in the tree - must be able to produce a Summary
 pub trait Item: Clone {
     type Summary: Summary;
     fn summary(&self) -> Self::Summary;
 }
 
-/// SumTree node - either leaf or internal
+/// SumTree node - either leaf or internal. All leaves are at the same depth
 #[derive(Clone)]
 pub enum Node<T: Item> {
     Leaf {
@@ -17,69 +23,65 @@ pub enum Node<T: Item> {
     Internal {
         children: Vec<Arc<Node<T>>>,
         summary: T::Summary,
+        /// Levels down to the leaves (1 for a parent of leaves)
+        height: usize,
     },
 }
 
-/// The main SumTree structure
+/// The main SumTree structure: a B-tree whose nodes cache their subtree's summary
 #[derive(Clone)]
 pub struct SumTree<T: Item> {
     root: Option<Arc<Node<T>>>,
 }
 
 impl<T: Item> SumTree<T> {
-    const MAX_CHILDREN: usize = 8;
+    const MAX_CHILDREN: usize = 2 * TREE_BASE;
 
     /// Create empty tree
     pub fn new() -> Self {
         Self { root: None }
     }
 
-    /// 🚀 NEW: Build tree from items efficiently (NO RECURSION!)
+    /// 🚀 Build a balanced tree bottom-up: full leaves, then full levels above
+    /// them (NO RECURSION!)
     pub fn from_items(items: Vec<T>) -> Self {
-        if items.is_empty() {
-            return Self::new();
+        let mut nodes = Vec::new();
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            let leaf_items: Vec<T> = items.by_ref().take(Self::MAX_CHILDREN).collect();
+            nodes.push(Self::create_leaf(leaf_items));
         }
 
-        // Create leaf nodes
-        let mut nodes: Vec<Arc<Node<T>>> = items
-            .into_iter()
-            .map(|item| {
-                let summary = item.summary();
-                Arc::new(Node::Leaf {
-                    items: vec![item],
-                    summary,
-                })
-            })
-            .collect();
-
-        // Build tree bottom-up iteratively
+        let mut height = 0;
         while nodes.len() > 1 {
-            let mut next_level = Vec::new();
-
-            for chunk in nodes.chunks(Self::MAX_CHILDREN) {
-                if chunk.len() == 1 {
-                    next_level.push(chunk[0].clone());
-                } else {
-                    next_level.push(Self::create_internal(chunk.to_vec()));
-                }
-            }
-
-            nodes = next_level;
+            height += 1;
+            nodes = nodes
+                .chunks(Self::MAX_CHILDREN)
+                .map(|children| Self::create_internal(children.to_vec(), height))
+                .collect();
         }
 
-        Self {
-            root: nodes.into_iter().next(),
-        }
+        Self { root: nodes.pop() }
     }
 
-    fn create_internal(children: Vec<Arc<Node<T>>>) -> Arc<Node<T>> {
-        let mut summary = T::Summary::default();
+    fn create_leaf(items: Vec<T>) -> Arc<Node<T>> {
+        let summary = Self::sum(items.iter().map(|item| item.summary()));
+        Arc::new(Node::Leaf { items, summary })
+    }
 
-        for child in &children {
-            summary = summary.add_summary(child.summary());
-        }
+    fn create_internal(children: Vec<Arc<Node<T>>>, height: usize) -> Arc<Node<T>> {
+        let summary = Self::sum(children.iter().map(|child| child.summary().clone()));
+        Arc::new(Node::Internal {
+            children,
+            summary,
+            height,
+        })
+    }
 
-        Arc::new(Node::Internal { children, summary })
+    fn sum(summaries: impl Iterator<Item = T::Summary>) -> T::Summary {
+        summaries.fold(T::Summary::default(), |total, summary| {
+            total.add_summary(&summary)
+        })
     }
 
     /// Get total summary of entire tree
@@ -95,38 +97,435 @@ impl<T: Item> SumTree<T> {
         self.root.is_none()
     }
 
-    /// Push item to end (DEPRECATED - use from_items for bulk operations)
+    /// Levels above the leaves (0 for an empty tree or a single leaf)
+    pub fn height(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.height())
+    }
+
+    /// Push item to end (use `from_items` or `extend` for bulk operations)
     pub fn push(&mut self, item: T) {
-        let new_summary = item.summary();
-        let new_leaf = Arc::new(Node::Leaf {
-            items: vec![item],
-            summary: new_summary.clone(),
+        self.append(Self::from_items(vec![item]));
+    }
+
+    /// Move all of `other`'s items after this tree's, keeping every leaf at
+    /// the same depth: the shorter tree is grafted onto the taller one's
+    /// edge, splitting nodes that overflow on the way back up
+    pub fn append(&mut self, other: Self) {
+        let Some(other_root) = other.root else {
+            return;
+        };
+        let Some(mut root) = self.root.take() else {
+            self.root = Some(other_root);
+            return;
+        };
+
+        if root.height() < other_root.height() {
+            self.root = Some(root);
+            for child in other_root.children() {
+                self.append(Self {
+                    root: Some(child.clone()),
+                });
+            }
+            return;
+        }
+
+        let split = Self::push_node(&mut root, other_root);
+        self.root = Some(match split {
+            Some(split) => {
+                let height = root.height() + 1;
+                Self::create_internal(vec![root, split], height)
+            }
+            None => root,
         });
+    }
+
+    /// Attach `other` (no taller than `node`) at `node`'s right edge;
+    /// returns the right half if `node` had to split
+    fn push_node(node: &mut Arc<Node<T>>, other: Arc<Node<T>>) -> Option<Arc<Node<T>>> {
+        match Arc::make_mut(node) {
+            Node::Internal {
+                children,
+                summary,
+                height,
+            } => {
+                *summary = summary.add_summary(other.summary());
+                let height_delta = *height - other.height();
+                if height_delta == 0 {
+                    children.extend(other.children().iter().cloned());
+                } else if height_delta == 1 && !other.is_underflowing() {
+                    children.push(other);
+                } else if let Some(split) = Self::push_node(
+                    children.last_mut().expect("internal nodes have children"),
+                    other,
+                ) {
+                    children.push(split);
+                }
+
+                if children.len() > Self::MAX_CHILDREN {
+                    let right = children.split_off(children.len().div_ceil(2));
+                    *summary = Self::sum(children.iter().map(|child| child.summary().clone()));
+                    Some(Self::create_internal(right, *height))
+                } else {
+                    None
+                }
+            }
+            Node::Leaf { items, summary } => {
+                *summary = summary.add_summary(other.summary());
+                items.extend(other.items().iter().cloned());
+
+                if items.len() > Self::MAX_CHILDREN {
+                    let right = items.split_off(items.len().div_ceil(2));
+                    *summary = Self::sum(items.iter().map(|item| item.summary()));
+                    Some(Self::create_leaf(right))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Replace the items overlapping `range`, measured along `dimension`
+    /// (e.g. byte length), with `splice(start, items)`, where `start` is
+    /// where the first of them begins. An empty range selects the item
+    /// containing its position, or the last item at the very end. When the
+    /// items all sit in one leaf only the nodes on the path down to it are
+    /// copied, so a clone of the tree taken before keeps sharing every
+    /// other node; otherwise the subtrees on either side are reused whole
+    /// and appended back together
+    pub fn splice<D, F>(&mut self, range: Range<usize>, dimension: D, splice: F)
+    where
+        D: Fn(&T::Summary) -> usize,
+        F: FnOnce(usize, Vec<T>) -> Vec<T>,
+    {
+        let Some(root) = self.root.take() else {
+            *self = Self::from_items(splice(0, Vec::new()));
+            return;
+        };
+
+        let total = dimension(root.summary());
+        let range = if range.is_empty() {
+            let at = range.start.min(total.saturating_sub(1));
+            at..at + 1
+        } else {
+            range
+        };
+
+        if range.start < total && Self::within_one_leaf(&root, &range, &dimension) {
+            let nodes = Self::splice_path(root, &range, &dimension, 0, splice);
+            *self = Self::from_nodes(nodes);
+            return;
+        }
+
+        let mut split = Split {
+            range,
+            dimension,
+            before: Self::new(),
+            removed: Vec::new(),
+            start: None,
+            after: Self::new(),
+        };
+        split.visit(&root, 0);
+
+        // Nothing overlapped (the range is past the end): append instead
+        let start = split.start.unwrap_or(total);
+        let mut tree = split.before;
+        tree.extend(splice(start, split.removed));
+        tree.append(split.after);
+        *self = tree;
+    }
+
+    /// Whether the items overlapping `range` all belong to one leaf
+    fn within_one_leaf<D>(root: &Node<T>, range: &Range<usize>, dimension: &D) -> bool
+    where
+        D: Fn(&T::Summary) -> usize,
+    {
+        let mut node = root;
+        let mut offset = 0;
+        while let Node::Internal { children, .. } = node {
+            let mut overlapping = children.iter().filter_map(|child| {
+                let child_offset = offset;
+                offset += dimension(child.summary());
+                overlaps(range, child_offset, offset - child_offset)
+                    .then_some((child, child_offset))
+            });
+            let Some((child, child_offset)) = overlapping.next() else {
+                return false;
+            };
+            if overlapping.next().is_some() {
+                return false;
+            }
+            node = child;
+            offset = child_offset;
+        }
+        true
+    }
+
+    /// `splice` inside the one leaf under `node` (which starts at `offset`),
+    /// copying just the nodes on the way down (none when `node` isn't
+    /// shared). Returns what replaces `node`, all of its height: itself,
+    /// several nodes after an overflow, or none once it's empty. A node
+    /// left with too few children is merged into a sibling
+    fn splice_path<D, F>(
+        node: Arc<Node<T>>,
+        range: &Range<usize>,
+        dimension: &D,
+        mut offset: usize,
+        splice: F,
+    ) -> Vec<Arc<Node<T>>>
+    where
+        D: Fn(&T::Summary) -> usize,
+        F: FnOnce(usize, Vec<T>) -> Vec<T>,
+    {
+        let height = node.height();
+        let node = Arc::try_unwrap(node).unwrap_or_else(|shared| (*shared).clone());
+        match node {
+            Node::Leaf { mut items, .. } => {
+                let mut start = None;
+                let mut run = 0..0;
+                for (index, item) in items.iter().enumerate() {
+                    let len = dimension(&item.summary());
+                    if overlaps(range, offset, len) {
+                        if start.is_none() {
+                            start = Some(offset);
+                            run.start = index;
+                        }
+                        run.end = index + 1;
+                    }
+                    offset += len;
+                }
+                let removed: Vec<T> = items.drain(run.clone()).collect();
+                let inserted = splice(start.expect("the range overlaps this leaf"), removed);
+                items.splice(run.start..run.start, inserted);
+                split_evenly(items)
+                    .into_iter()
+                    .map(Self::create_leaf)
+                    .collect()
+            }
+            Node::Internal { mut children, .. } => {
+                let index = children
+                    .iter()
+                    .position(|child| {
+                        let len = dimension(child.summary());
+                        if overlaps(range, offset, len) {
+                            return true;
+                        }
+                        offset += len;
+                        false
+                    })
+                    .expect("the range overlaps one child");
+                let child = children.remove(index);
+                let replacement = Self::splice_path(child, range, dimension, offset, splice);
+                let count = replacement.len();
+                children.splice(index..index, replacement);
+
+                if count == 1 && children.len() > 1 && children[index].is_underflowing() {
+                    let pair = if index > 0 {
+                        index - 1..index + 1
+                    } else {
+                        index..index + 2
+                    };
+                    let merged = Self::regroup(&children[pair.clone()], height - 1);
+                    children.splice(pair, merged);
+                }
+                split_evenly(children)
+                    .into_iter()
+                    .map(|children| Self::create_internal(children, height))
+                    .collect()
+            }
+        }
+    }
+
+    /// The contents of adjacent `nodes` repacked into as few nodes of
+    /// `height` as fit
+    fn regroup(nodes: &[Arc<Node<T>>], height: usize) -> Vec<Arc<Node<T>>> {
+        if height == 0 {
+            let items: Vec<T> = nodes
+                .iter()
+                .flat_map(|node| node.items())
+                .cloned()
+                .collect();
+            split_evenly(items)
+                .into_iter()
+                .map(Self::create_leaf)
+                .collect()
+        } else {
+            let children: Vec<Arc<Node<T>>> = nodes
+                .iter()
+                .flat_map(|node| node.children())
+                .cloned()
+                .collect();
+            split_evenly(children)
+                .into_iter()
+                .map(|children| Self::create_internal(children, height))
+                .collect()
+        }
+    }
+
+    /// A tree over sibling `nodes` of equal height, adding levels above them
+    /// until one root is left (and dropping a root with a single child)
+    fn from_nodes(mut nodes: Vec<Arc<Node<T>>>) -> Self {
+        while nodes.len() > 1 {
+            let height = nodes[0].height() + 1;
+            nodes = split_evenly(nodes)
+                .into_iter()
+                .map(|children| Self::create_internal(children, height))
+                .collect();
+        }
+        let mut root = nodes.pop();
+        while let Some(Node::Internal { children, .. }) = root.as_deref() {
+            if children.len() > 1 {
+                break;
+            }
+            root = children.first().cloned();
+        }
+        Self { root }
+    }
+
+    /// Move the items from the one where `dimension` passes `target` (see
+    /// `seek`) onwards into a new tree; subtrees on either side of that
+    /// item are moved over whole
+    pub fn split_off<D>(&mut self, target: usize, dimension: D) -> Self
+    where
+        D: Fn(&T::Summary) -> usize,
+    {
+        let Some(root) = self.root.take() else {
+            return Self::new();
+        };
+
+        let mut split = Split {
+            range: target..target + 1,
+            dimension,
+            before: Self::new(),
+            removed: Vec::new(),
+            start: None,
+            after: Self::new(),
+        };
+        split.visit(&root, 0);
+
+        *self = split.before;
+        let mut rest = Self::from_items(split.removed);
+        rest.append(split.after);
+        rest
+    }
+
+    /// Clones of the items overlapping `range` along `dimension`, and where
+    /// the first of them begins; subtrees outside the range are skipped
+    pub fn slice<D>(&self, range: Range<usize>, dimension: D) -> (usize, Vec<T>)
+    where
+        D: Fn(&T::Summary) -> usize,
+    {
+        let mut start = None;
+        let mut items = Vec::new();
+        if let Some(root) = &self.root {
+            root.collect(&range, &dimension, 0, &mut start, &mut items);
+        }
+        (start.unwrap_or(0), items)
+    }
+
+    /// The item where `dimension` passes `target` (the first whose end is
+    /// beyond it) and the summary of everything before that item; None past
+    /// the end. Descends one path, so O(log n)
+    pub fn seek<D>(&self, target: usize, dimension: D) -> Option<(T::Summary, T)>
+    where
+        D: Fn(&T::Summary) -> usize,
+    {
+        let mut node = self.root.as_ref()?;
+        let mut before = T::Summary::default();
+        loop {
+            match node.as_ref() {
+                Node::Leaf { items, .. } => {
+                    for item in items {
+                        let end = before.add_summary(&item.summary());
+                        if dimension(&end) > target {
+                            return Some((before, item.clone()));
+                        }
+                        before = end;
+                    }
+                    return None;
+                }
+                Node::Internal { children, .. } => {
+                    node = children.iter().find(|child| {
+                        let end = before.add_summary(child.summary());
+                        if dimension(&end) > target {
+                            return true;
+                        }
+                        before = end;
+                        false
+                    })?;
+                }
+            }
+        }
+    }
 
-        match self.root.take() {
-            None => {
-                self.root = Some(new_leaf);
-            }
-            Some(old_root) => {
-                // Try to append to existing structure if possible
-                if let Node::Internal { children, .. } = old_root.as_ref() {
-                    if children.len() < Self::MAX_CHILDREN {
-                        // Can add to existing internal node
-                        let mut new_children = children.clone();
-                        new_children.push(new_leaf);
-                        self.root = Some(Self::create_internal(new_children));
-                        return;
+    /// Borrowing iterator from the item where `dimension` passes `target`
+    /// (see `seek`) to the end, with the summary of everything before it
+    pub fn items_from<D>(&self, target: usize, dimension: D) -> (T::Summary, Items<'_, T>)
+    where
+        D: Fn(&T::Summary) -> usize,
+    {
+        let mut before = T::Summary::default();
+        let mut items = Items {
+            stack: Vec::new(),
+            leaf: [].iter(),
+        };
+        let Some(mut node) = self.root.as_deref() else {
+            return (before, items);
+        };
+        loop {
+            match node {
+                Node::Leaf { items: leaf, .. } => {
+                    let mut skipped = 0;
+                    for item in leaf {
+                        let end = before.add_summary(&item.summary());
+                        if dimension(&end) > target {
+                            break;
+                        }
+                        before = end;
+                        skipped += 1;
                     }
+                    items.leaf = leaf[skipped..].iter();
+                    return (before, items);
                 }
+                Node::Internal { children, .. } => {
+                    let mut siblings = children.iter();
+                    let child = siblings.by_ref().find(|child| {
+                        let end = before.add_summary(child.summary());
+                        if dimension(&end) > target {
+                            return true;
+                        }
+                        before = end;
+                        false
+                    });
+                    items.stack.push(siblings);
+                    match child {
+                        Some(child) => node = child.as_ref(),
+                        None => return (before, items),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Nodes of this tree that `base` doesn't share, i.e. what keeping both
+    /// costs over keeping `base` alone (for an edited clone of `base`, about
+    /// one node per level)
+    pub fn unshared_nodes(&self, base: &Self) -> usize {
+        let mut shared = std::collections::HashSet::new();
+        let mut stack: Vec<&Arc<Node<T>>> = base.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            shared.insert(Arc::as_ptr(node));
+            stack.extend(node.children());
+        }
 
-                // Otherwise create new level
-                let combined_summary = old_root.summary().add_summary(&new_summary);
-                self.root = Some(Arc::new(Node::Internal {
-                    children: vec![old_root, new_leaf],
-                    summary: combined_summary,
-                }));
+        let mut unshared = 0;
+        let mut stack: Vec<&Arc<Node<T>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if !shared.contains(&Arc::as_ptr(node)) {
+                unshared += 1;
+                stack.extend(node.children());
             }
         }
+        unshared
     }
 
     /// Iterate over all items in the tree (in-order)
@@ -149,6 +548,186 @@ impl<T: Item> Node<T> {
             Node::Internal { summary, .. } => summary,
         }
     }
+
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Internal { height, .. } => *height,
+        }
+    }
+
+    fn children(&self) -> &[Arc<Node<T>>] {
+        match self {
+            Node::Leaf { .. } => &[],
+            Node::Internal { children, .. } => children,
+        }
+    }
+
+    fn items(&self) -> &[T] {
+        match self {
+            Node::Leaf { items, .. } => items,
+            Node::Internal { .. } => &[],
+        }
+    }
+
+    fn collect<D: Fn(&T::Summary) -> usize>(
+        &self,
+        range: &Range<usize>,
+        dimension: &D,
+        mut offset: usize,
+        start: &mut Option<usize>,
+        out: &mut Vec<T>,
+    ) {
+        let overlaps = |offset: usize, len: usize| offset < range.end && offset + len > range.start;
+        match self {
+            Node::Leaf { items, .. } => {
+                for item in items {
+                    let len = dimension(&item.summary());
+                    if overlaps(offset, len) {
+                        start.get_or_insert(offset);
+                        out.push(item.clone());
+                    }
+                    offset += len;
+                }
+            }
+            Node::Internal { children, .. } => {
+                for child in children {
+                    if offset >= range.end {
+                        break;
+                    }
+                    let len = dimension(child.summary());
+                    if overlaps(offset, len) {
+                        child.collect(range, dimension, offset, start, out);
+                    }
+                    offset += len;
+                }
+            }
+        }
+    }
+
+    fn is_underflowing(&self) -> bool {
+        match self {
+            Node::Leaf { items, .. } => items.len() < TREE_BASE,
+            Node::Internal { children, .. } => children.len() < TREE_BASE,
+        }
+    }
+}
+
+/// Whether the span of `len` at `offset` overlaps `range` (a span of zero
+/// length does when it's strictly inside)
+fn overlaps(range: &Range<usize>, offset: usize, len: usize) -> bool {
+    offset < range.end && offset + len > range.start
+}
+
+/// Cut `parts` into as few runs of at most `2 * TREE_BASE` as hold them,
+/// all about the same size (so none of several is short of `TREE_BASE`)
+fn split_evenly<U>(parts: Vec<U>) -> Vec<Vec<U>> {
+    let count = parts.len().div_ceil(2 * TREE_BASE);
+    let mut runs = Vec::with_capacity(count);
+    let mut parts = parts.into_iter();
+    for index in 0..count {
+        let remaining = parts.len();
+        let size = remaining.div_ceil(count - index);
+        runs.push(parts.by_ref().take(size).collect());
+    }
+    runs
+}
+
+/// `SumTree::splice` walking the tree: everything before the first item
+/// overlapping `range` goes to `before`, the overlapping items to `removed`
+/// and the rest to `after`, with untouched subtrees moved over whole
+struct Split<T: Item, D> {
+    range: Range<usize>,
+    dimension: D,
+    before: SumTree<T>,
+    removed: Vec<T>,
+    /// Where the first removed item starts
+    start: Option<usize>,
+    after: SumTree<T>,
+}
+
+impl<T: Item, D: Fn(&T::Summary) -> usize> Split<T, D> {
+    fn overlaps(&self, offset: usize, len: usize) -> bool {
+        overlaps(&self.range, offset, len)
+    }
+
+    fn keep(&mut self, tree: SumTree<T>) {
+        if self.start.is_none() {
+            self.before.append(tree);
+        } else {
+            self.after.append(tree);
+        }
+    }
+
+    fn visit(&mut self, node: &Arc<Node<T>>, mut offset: usize) {
+        match node.as_ref() {
+            Node::Leaf { items, .. } => {
+                for item in items {
+                    let len = (self.dimension)(&item.summary());
+                    if self.overlaps(offset, len) {
+                        self.start.get_or_insert(offset);
+                        self.removed.push(item.clone());
+                    } else {
+                        self.keep(SumTree::from_items(vec![item.clone()]));
+                    }
+                    offset += len;
+                }
+            }
+            Node::Internal { children, .. } => {
+                for child in children {
+                    let len = (self.dimension)(child.summary());
+                    if self.overlaps(offset, len) {
+                        self.visit(child, offset);
+                    } else {
+                        self.keep(SumTree {
+                            root: Some(child.clone()),
+                        });
+                    }
+                    offset += len;
+                }
+            }
+        }
+    }
+}
+
+/// Items of a `SumTree` by reference, in order
+pub struct Items<'a, T: Item> {
+    /// Children still to visit at each level above the current leaf
+    stack: Vec<std::slice::Iter<'a, Arc<Node<T>>>>,
+    leaf: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T: Item> Iterator for Items<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                return Some(item);
+            }
+            let mut node = loop {
+                match self.stack.last_mut()?.next() {
+                    Some(child) => break child.as_ref(),
+                    None => {
+                        self.stack.pop();
+                    }
+                }
+            };
+            // Down to the leftmost leaf of the next subtree
+            while let Node::Internal { children, .. } = node {
+                let mut siblings = children.iter();
+                node = siblings.next()?.as_ref();
+                self.stack.push(siblings);
+            }
+            self.leaf = node.items().iter();
+        }
+    }
+}
+
+impl<T: Item> Extend<T> for SumTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        self.append(Self::from_items(items.into_iter().collect()));
+    }
 }
 
 impl<T: Item> Default for SumTree<T> {
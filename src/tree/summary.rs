@@ -31,11 +31,12 @@ impl Summary for Count {
     }
 }
 
-/// Text summary - tracks length AND line count
+/// Text summary - tracks length, line count AND UTF-16 length
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct TextSummary {
     pub len: usize,   // Byte length
     pub lines: usize, // Number of lines
+    pub utf16: usize, // Number of UTF-16 code units
 }
 
 impl Add for TextSummary {
@@ -45,6 +46,7 @@ impl Add for TextSummary {
         TextSummary {
             len: self.len + other.len,
             lines: self.lines + other.lines,
+            utf16: self.utf16 + other.utf16,
         }
     }
 }
@@ -54,6 +56,7 @@ impl Summary for TextSummary {
         TextSummary {
             len: self.len + other.len,
             lines: self.lines + other.lines,
+            utf16: self.utf16 + other.utf16,
         }
     }
 }
@@ -1,96 +1,451 @@
+use super::input::key_event_from_crossterm;
+use crate::editor::{ConflictResolution, TextObject};
+use crate::io::write_file_from_rope;
+use crate::keymap::{
+    Abandoned, Chord, Command, CommandHistory, EditAction, KeyAction, KeyEvent, Keymap, PendingKeys,
+};
+use crate::rope::Rope;
+use crate::settings::{EditorConfig, Modeline, Settings};
 use crate::Editor;
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode};
 use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
 
 /// Application state
 pub struct App {
     pub editor: Editor,
     pub should_quit: bool,
     pub status_message: String,
+    pub file_path: Option<PathBuf>,
+    /// Path typed into the "Save as" prompt (Some while the prompt is open)
+    pub save_prompt: Option<String>,
+    pub keymap: Keymap,
+    /// Keys typed so far toward a multi-key binding
+    pub pending_keys: PendingKeys,
+    pub command_history: CommandHistory,
+    pub settings: Settings,
 }
 
 impl App {
     /// Create new app with empty editor
     pub fn new() -> Self {
-        Self {
-            editor: Editor::new(),
-            should_quit: false,
-            status_message: "Press Ctrl+Q to quit | Ctrl+S to save".to_string(),
-        }
+        Self::with_editor(Editor::new())
     }
 
     /// Create app with text
     pub fn with_text(text: &str) -> Self {
+        Self::with_editor(Editor::from_text(text))
+    }
+
+    /// Wrap an editor, applying the settings shared with the GUI
+    fn with_editor(mut editor: Editor) -> Self {
+        let settings = Settings::load(Settings::default_path()).unwrap_or_default();
+        let modeline = if settings.editor.modelines {
+            editor.modeline().unwrap_or_default()
+        } else {
+            Modeline::default()
+        };
+        editor.set_indent(
+            modeline.tab_size.unwrap_or(settings.editor.tab_size),
+            modeline.hard_tabs.unwrap_or(settings.editor.hard_tabs),
+        );
+        editor.set_language_override(modeline.language);
+        editor.set_undo_hunk_limit(settings.editor.undo_hunk_limit_kb * 1024);
+        editor.set_paste_modes(
+            settings.editor.paste_mode(),
+            settings.editor.paste_modes.clone(),
+        );
+        editor.set_word_chars(
+            settings.editor.word_chars.clone(),
+            settings.editor.language_word_chars.clone(),
+        );
         Self {
-            editor: Editor::from_text(text),
+            editor,
             should_quit: false,
             status_message: "Press Ctrl+Q to quit | Ctrl+S to save".to_string(),
+            file_path: None,
+            save_prompt: None,
+            keymap: Self::load_keymap(),
+            pending_keys: PendingKeys::new(),
+            command_history: CommandHistory::new(),
+            settings,
         }
     }
 
-    /// Handle keyboard input
-    pub fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                // Handle Ctrl+Q - Quit
-                if key.code == KeyCode::Char('q') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                    self.should_quit = true;
-                    return Ok(());
+    /// User keymap shared with the GUI (defaults if missing or unreadable)
+    fn load_keymap() -> Keymap {
+        Keymap::load(Keymap::default_path()).unwrap_or_else(|_| Keymap::defaults())
+    }
+
+    /// Untitled, unsaved buffer (e.g. text piped in on stdin)
+    pub fn untitled(rope: Rope) -> Self {
+        let mut app = Self::with_editor(Editor::from_rope(rope));
+        app.editor.mark_dirty();
+        app.status_message = format!(
+            "Read {} lines from stdin | Ctrl+S to save | Ctrl+Q to quit",
+            app.editor.line_count()
+        );
+        app
+    }
+
+    /// Save to the current path, or open the "Save as" prompt for untitled buffers
+    fn save(&mut self) {
+        let Some(path) = self.file_path.clone() else {
+            self.save_prompt = Some(String::new());
+            return;
+        };
+
+        // A file's first save takes the project's line ending; files already
+        // on disk keep theirs
+        if !path.exists() {
+            if let Some(ending) = EditorConfig::for_path(&path).line_ending {
+                self.editor.convert_line_endings(ending);
+            }
+        }
+        match write_file_from_rope(&path, self.editor.buffer().rope()) {
+            Ok(_) => {
+                self.editor.mark_saved();
+                self.editor.check_syntax();
+                self.status_message = format!("Saved {}", path.display());
+            }
+            Err(e) => self.status_message = format!("Error saving: {}", e),
+        }
+    }
+
+    /// Keys while the "Save as" prompt is open
+    fn handle_prompt_key(&mut self, code: KeyCode) {
+        let Some(input) = self.save_prompt.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let path = self.save_prompt.take().unwrap_or_default();
+                if !path.trim().is_empty() {
+                    self.file_path = Some(PathBuf::from(path.trim()));
+                    self.editor.set_file_path(self.file_path.clone());
+                    self.save();
                 }
+            }
+            KeyCode::Esc => {
+                self.save_prompt = None;
+                self.status_message = "Save cancelled".to_string();
+            }
+            _ => {}
+        }
+    }
 
-                // Handle Ctrl+Z - Undo
-                if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if self.editor.can_undo() {
-                        self.editor.undo();
-                        self.status_message = "Undo".to_string();
-                    } else {
-                        self.status_message = "Nothing to undo".to_string();
-                    }
-                    return Ok(());
+    /// Handle keyboard input, then report where the cursor ended up to the
+    /// editor's observers
+    pub fn handle_input(&mut self) -> io::Result<()> {
+        let result = self.handle_event();
+        self.editor.flush_events();
+        result
+    }
+
+    fn handle_event(&mut self) -> io::Result<()> {
+        if let Some(abandoned) = self.pending_keys.expire(&self.keymap, Instant::now()) {
+            self.handle_abandoned_keys(abandoned);
+        }
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            return Ok(());
+        }
+        let raw = match event::read()? {
+            Event::Key(raw) => raw,
+            Event::Paste(text) if self.save_prompt.is_none() => {
+                // No formatters in the terminal: `format` pastes re-indent
+                self.editor.paste_with_mode(&text, None);
+                self.status_message.clear();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        let Some(key) = key_event_from_crossterm(&raw) else {
+            return Ok(());
+        };
+
+        // Handle Ctrl+Q - Quit (always, even while prompting)
+        if key.chord() == Some(Chord::new("Q").ctrl()) {
+            self.should_quit = true;
+            return Ok(());
+        }
+
+        if self.save_prompt.is_some() {
+            self.handle_prompt_key(raw.code);
+            return Ok(());
+        }
+
+        let dispatch = self.pending_keys.press(&self.keymap, &key, Instant::now());
+        if let Some(abandoned) = dispatch.abandoned {
+            self.handle_abandoned_keys(abandoned);
+        }
+        match dispatch.action {
+            KeyAction::Run(command) => self.run_command(command),
+            KeyAction::Wait => {}
+            KeyAction::Unbound => self.handle_editing_key(&key),
+        }
+        Ok(())
+    }
+
+    /// A key sequence that stopped short: run the binding it spelled out, or
+    /// handle its keys as ordinary keys
+    fn handle_abandoned_keys(&mut self, abandoned: Abandoned) {
+        match abandoned {
+            Abandoned::Run(command) => self.run_command(command),
+            Abandoned::Replay(keys) => {
+                for key in &keys {
+                    self.handle_editing_key(key);
                 }
+            }
+        }
+    }
 
-                // Handle Ctrl+Y - Redo
-                if key.code == KeyCode::Char('y') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if self.editor.can_redo() {
-                        self.editor.redo();
-                        self.status_message = "Redo".to_string();
-                    } else {
-                        self.status_message = "Nothing to redo".to_string();
-                    }
-                    return Ok(());
+    /// Cursor movement, typing and basic editing keys (not rebindable)
+    fn handle_editing_key(&mut self, key: &KeyEvent) {
+        if let Some(c) = key.typed_text() {
+            self.editor.insert(&c.to_string());
+            self.status_message.clear();
+            return;
+        }
+
+        match key.key.as_str() {
+            "Enter" => {
+                self.editor.insert("\n");
+                self.status_message.clear();
+            }
+            "Backspace" => {
+                self.editor.backspace();
+                self.status_message.clear();
+            }
+            "Delete" => {
+                self.editor.delete();
+                self.status_message.clear();
+            }
+            "Tab" if key.modifiers.shift => self.editor.outdent_selection(),
+            "Tab" => {
+                let (start, end) = self.editor.selection().range();
+                if start.row != end.row {
+                    self.editor.indent_selection();
+                } else {
+                    self.editor.insert_indent();
                 }
+                self.status_message.clear();
+            }
+            "Left" if key.modifiers.ctrl || key.modifiers.alt => {
+                self.editor.move_to_previous_word()
+            }
+            "Right" if key.modifiers.ctrl || key.modifiers.alt => self.editor.move_to_next_word(),
+            "Left" => self.editor.move_left(),
+            "Right" => self.editor.move_right(),
+            "Up" => self.editor.move_up(),
+            "Down" => self.editor.move_down(),
+            "Home" => self.editor.move_to_line_indent_start(),
+            "End" => self.editor.move_to_line_end(),
+            "Escape" => {
+                self.editor.clear_extra_cursors();
+            }
+            _ => {}
+        }
+    }
 
-                // Regular key handling
-                match key.code {
-                    KeyCode::Char(c) => {
-                        if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                            self.editor.insert(&c.to_string());
-                            self.status_message.clear();
-                        }
-                    }
-                    KeyCode::Enter => {
-                        self.editor.insert("\n");
-                        self.status_message.clear();
-                    }
-                    KeyCode::Backspace => {
-                        self.editor.backspace();
-                        self.status_message.clear();
-                    }
-                    KeyCode::Delete => {
-                        self.editor.delete();
-                        self.status_message.clear();
+    /// Execute a keymap command (window-only commands just report so)
+    fn run_command(&mut self, command: Command) {
+        self.command_history.record(command);
+        match command {
+            Command::Undo => {
+                if self.editor.can_undo() {
+                    self.editor.undo();
+                    self.status_message = "Undo".to_string();
+                } else {
+                    self.status_message = "Nothing to undo".to_string();
+                }
+            }
+            Command::Redo => {
+                if self.editor.can_redo() {
+                    self.editor.redo();
+                    self.status_message = "Redo".to_string();
+                } else {
+                    self.status_message = "Nothing to redo".to_string();
+                }
+            }
+            Command::Save => self.save(),
+            Command::GoToLastEdit => {
+                if self.editor.go_to_last_edit_location() {
+                    self.status_message = "Last edit location".to_string();
+                }
+            }
+            Command::ToggleComment => self.editor.toggle_comment(),
+            Command::DeleteLine => self.editor.delete_line(),
+            Command::DuplicateLine => self.editor.duplicate_line(),
+            Command::MoveLineUp => self.editor.move_line_up(),
+            Command::MoveLineDown => self.editor.move_line_down(),
+            Command::DeleteToLineEnd => self.editor.delete_to_line_end(),
+            Command::DeleteToLineStart => self.editor.delete_to_line_start(),
+            Command::TransposeChars => self.editor.transpose_chars(),
+            Command::TransposeWords => self.editor.transpose_words(),
+            Command::JumpToMatchingBracket => {
+                self.editor.jump_to_matching_bracket();
+            }
+            Command::SelectToBracket => {
+                self.editor.select_to_bracket(false);
+            }
+            Command::SelectToBracketInclusive => {
+                self.editor.select_to_bracket(true);
+            }
+            Command::DeleteToBracket => {
+                self.editor.delete_to_bracket(false);
+            }
+            Command::DeleteToBracketInclusive => {
+                self.editor.delete_to_bracket(true);
+            }
+            Command::UnwrapSurrounding => {
+                if !self.editor.unwrap_surrounding() {
+                    self.status_message = "No enclosing brackets or quotes".to_string();
+                }
+            }
+            Command::SelectInsideBrackets => {
+                self.editor.select_text_object(TextObject::Brackets, false);
+            }
+            Command::SelectAroundBrackets => {
+                self.editor.select_text_object(TextObject::Brackets, true);
+            }
+            Command::SelectInsideQuotes => {
+                self.editor.select_text_object(TextObject::Quotes, false);
+            }
+            Command::SelectAroundQuotes => {
+                self.editor.select_text_object(TextObject::Quotes, true);
+            }
+            Command::SelectInsideWord => {
+                self.editor.select_text_object(TextObject::Word, false);
+            }
+            Command::SelectAroundWord => {
+                self.editor.select_text_object(TextObject::Word, true);
+            }
+            Command::SelectInsideParagraph => {
+                self.editor.select_text_object(TextObject::Paragraph, false);
+            }
+            Command::SelectAroundParagraph => {
+                self.editor.select_text_object(TextObject::Paragraph, true);
+            }
+            Command::ExpandSelection => {
+                self.editor.expand_selection();
+            }
+            Command::ShrinkSelection => {
+                self.editor.shrink_selection();
+            }
+            Command::AddCursorAbove => {
+                self.editor.add_cursor_vertically(true);
+            }
+            Command::AddCursorBelow => {
+                self.editor.add_cursor_vertically(false);
+            }
+            Command::MoveToPreviousParagraph => self.editor.move_to_previous_paragraph(),
+            Command::MoveToNextParagraph => self.editor.move_to_next_paragraph(),
+            Command::SelectNextOccurrence => {
+                self.editor.select_next_occurrence();
+            }
+            Command::SelectAllOccurrences => {
+                let count = self.editor.select_all_occurrences();
+                self.status_message = format!("{} occurrences selected", count);
+            }
+            Command::AddCursorsToLineEnds => {
+                self.editor.split_selection_into_lines(false);
+            }
+            Command::SplitSelectionIntoLines => {
+                self.editor.split_selection_into_lines(true);
+            }
+            Command::AlignCursors => {
+                if !self.editor.align_cursors() {
+                    self.status_message = "Cursors already aligned".to_string();
+                }
+            }
+            Command::ReflowParagraph => {
+                let width = self.settings.editor.reflow_width;
+                if self.editor.reflow_paragraph(width).is_none() {
+                    self.status_message = "Nothing to reflow".to_string();
+                }
+            }
+            Command::RepeatLastCommand => match self.command_history.last_edit() {
+                Some(EditAction::Command(command)) => self.run_command(command),
+                Some(EditAction::Transform(transform)) => {
+                    self.editor.transform_selection(transform)
+                }
+                Some(EditAction::SortLines(options)) => self.editor.sort_lines(options),
+                Some(EditAction::ReverseLines) => self.editor.reverse_lines(),
+                None => self.status_message = "Nothing to repeat".to_string(),
+            },
+            Command::OrganizeImports => {
+                if !self.editor.organize_imports() {
+                    self.status_message = "Imports are already organized".to_string();
+                }
+            }
+            Command::RemoveBom => {
+                self.status_message = if self.editor.remove_bom() {
+                    self.editor.check_syntax();
+                    "Byte order mark removed".to_string()
+                } else {
+                    "The file has no byte order mark".to_string()
+                };
+            }
+            Command::UnifyLineEndings => {
+                let ending = self.editor.text_format().line_ending();
+                self.status_message = if self.editor.unify_line_endings() {
+                    self.editor.check_syntax();
+                    format!("Line endings converted to {}", ending.label())
+                } else {
+                    "Line endings are already consistent".to_string()
+                };
+            }
+            Command::NextProblem | Command::PreviousProblem => {
+                let diagnostic = if command == Command::NextProblem {
+                    self.editor.go_to_next_problem()
+                } else {
+                    self.editor.go_to_prev_problem()
+                };
+                self.status_message = match diagnostic {
+                    Some(diagnostic) => {
+                        format!("{}: {}", diagnostic.severity.label(), diagnostic.message)
                     }
-                    KeyCode::Left => self.editor.move_left(),
-                    KeyCode::Right => self.editor.move_right(),
-                    KeyCode::Up => self.editor.move_up(),
-                    KeyCode::Down => self.editor.move_down(),
-                    KeyCode::Home => self.editor.move_to_line_start(),
-                    KeyCode::End => self.editor.move_to_line_end(),
-                    _ => {}
+                    None => "No problems".to_string(),
+                };
+            }
+            Command::NextConflict | Command::PreviousConflict => {
+                let forward = command == Command::NextConflict;
+                if self.editor.go_to_conflict(forward).is_none() {
+                    self.status_message = "No merge conflicts".to_string();
+                }
+            }
+            Command::AcceptOurs | Command::AcceptTheirs | Command::AcceptBoth => {
+                let resolution = match command {
+                    Command::AcceptOurs => ConflictResolution::Ours,
+                    Command::AcceptTheirs => ConflictResolution::Theirs,
+                    _ => ConflictResolution::Both,
+                };
+                let row = self.editor.cursor().row;
+                if !self.editor.resolve_conflict(row, resolution) {
+                    self.status_message = "No merge conflict at the cursor".to_string();
                 }
             }
+            Command::Open
+            | Command::FormatCode
+            | Command::ToggleProfiler
+            | Command::ToggleOutput
+            | Command::ToggleNotifications
+            | Command::ToggleSplit
+            | Command::ToggleScrollLock
+            | Command::FindInFiles
+            | Command::OpenSettings
+            | Command::OpenKeybindings
+            | Command::ShowCodeActions => {
+                self.status_message =
+                    format!("{} is not available in the terminal", command.label());
+            }
         }
-        Ok(())
     }
 }
 
@@ -0,0 +1,50 @@
+use crate::keymap::{KeyEvent, Modifiers};
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
+/// Translate a crossterm key press into the shared key model
+///
+/// Returns `None` for key releases (reported on Windows and by terminals with
+/// the kitty protocol) and keys the editor has no name for.
+pub fn key_event_from_crossterm(event: &crossterm::event::KeyEvent) -> Option<KeyEvent> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let modifiers = Modifiers {
+        ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: event.modifiers.contains(KeyModifiers::ALT),
+        shift: event.modifiers.contains(KeyModifiers::SHIFT),
+        cmd: event.modifiers.contains(KeyModifiers::SUPER),
+    };
+
+    let name = match event.code {
+        KeyCode::Char(c) => return Some(KeyEvent::from_char(c, modifiers)),
+        KeyCode::BackTab => {
+            return Some(KeyEvent::new(
+                "Tab",
+                Modifiers {
+                    shift: true,
+                    ..modifiers
+                },
+                None,
+            ))
+        }
+        KeyCode::F(n) => return Some(KeyEvent::new(&format!("F{}", n), modifiers, None)),
+        KeyCode::Enter => "Enter",
+        KeyCode::Tab => "Tab",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Delete => "Delete",
+        KeyCode::Insert => "Insert",
+        KeyCode::Esc => "Escape",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        KeyCode::Home => "Home",
+        KeyCode::End => "End",
+        KeyCode::PageUp => "PageUp",
+        KeyCode::PageDown => "PageDown",
+        _ => return None,
+    };
+    Some(KeyEvent::new(name, modifiers, None))
+}
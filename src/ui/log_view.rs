@@ -0,0 +1,293 @@
+use crate::io::FileTail;
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::io;
+use std::path::Path;
+
+/// Severity parsed from a log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Only the first few words are checked so message text ("... no error")
+    /// doesn't recolor a line
+    const SCAN_WORDS: usize = 5;
+
+    pub fn detect(line: &str) -> Option<Self> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .filter(|word| !word.is_empty())
+            .take(Self::SCAN_WORDS)
+            .find_map(|word| match word.to_ascii_uppercase().as_str() {
+                "ERROR" | "ERR" | "FATAL" | "CRITICAL" => Some(Self::Error),
+                "WARN" | "WARNING" => Some(Self::Warn),
+                "INFO" => Some(Self::Info),
+                "DEBUG" => Some(Self::Debug),
+                "TRACE" => Some(Self::Trace),
+                _ => None,
+            })
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Error => Color::Red,
+            Self::Warn => Color::Yellow,
+            Self::Info => Color::Green,
+            Self::Debug => Color::Cyan,
+            Self::Trace => Color::DarkGray,
+        }
+    }
+}
+
+/// Next line containing `query` (ASCII case-insensitive), searching from
+/// `from` (exclusive) and wrapping around
+pub fn find_match(lines: &[String], query: &str, from: usize, forward: bool) -> Option<usize> {
+    if query.is_empty() || lines.is_empty() {
+        return None;
+    }
+
+    let query = query.to_ascii_lowercase();
+    let len = lines.len();
+    (1..=len)
+        .map(|step| {
+            if forward {
+                (from + step) % len
+            } else {
+                (from + len - step % len) % len
+            }
+        })
+        .find(|&row| lines[row].to_ascii_lowercase().contains(&query))
+}
+
+/// Read-only `--tail` viewer: follows appends, colors levels, searches
+pub struct LogView {
+    tail: FileTail,
+    lines: Vec<String>,
+    top: usize,
+    height: usize,
+    follow: bool,
+    query: String,
+    search_input: Option<String>,
+    current_match: Option<usize>,
+    pub should_quit: bool,
+    pub status_message: String,
+}
+
+impl LogView {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            tail: FileTail::new(path),
+            lines: Vec::new(),
+            top: 0,
+            height: 1,
+            follow: true,
+            query: String::new(),
+            search_input: None,
+            current_match: None,
+            should_quit: false,
+            status_message: String::new(),
+        }
+    }
+
+    /// Pull appended lines from the file
+    pub fn refresh(&mut self) -> io::Result<()> {
+        let update = self.tail.poll()?;
+        if update.truncated {
+            self.lines.clear();
+            self.top = 0;
+            self.current_match = None;
+            self.status_message = "File truncated - reloaded".to_string();
+        }
+        self.lines.extend(update.lines);
+        Ok(())
+    }
+
+    fn max_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height)
+    }
+
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.max_top());
+        self.follow = self.top == self.max_top();
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        let len = self.lines.len().max(1);
+        let from = match self.current_match {
+            Some(row) => row,
+            // Nothing matched yet - include the first visible line
+            None if forward => (self.top + len - 1) % len,
+            None => self.top,
+        };
+
+        match find_match(&self.lines, &self.query, from, forward) {
+            Some(row) => {
+                self.current_match = Some(row);
+                self.scroll_to(row.saturating_sub(self.height / 2));
+                self.status_message.clear();
+            }
+            None => {
+                self.status_message = format!("Pattern not found: {}", self.query);
+            }
+        }
+    }
+
+    /// Handle keyboard input (search prompt first, then navigation)
+    pub fn handle_input(&mut self) -> io::Result<()> {
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            return Ok(());
+        }
+        let Event::Key(key) = event::read()? else {
+            return Ok(());
+        };
+
+        if let Some(input) = self.search_input.as_mut() {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    self.query = self.search_input.take().unwrap_or_default();
+                    self.current_match = None;
+                    self.jump_to_match(true);
+                }
+                KeyCode::Esc => self.search_input = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let page = self.height.max(1);
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true
+            }
+            KeyCode::Char('/') => self.search_input = Some(String::new()),
+            KeyCode::Char('n') => self.jump_to_match(true),
+            KeyCode::Char('N') => self.jump_to_match(false),
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.top = self.max_top();
+                }
+            }
+            KeyCode::Esc => {
+                self.query.clear();
+                self.current_match = None;
+                self.status_message.clear();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(self.top.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_to(self.top + 1),
+            KeyCode::PageUp => self.scroll_to(self.top.saturating_sub(page)),
+            KeyCode::PageDown => self.scroll_to(self.top + page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(0),
+            KeyCode::End | KeyCode::Char('G') => self.scroll_to(usize::MAX),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Title bar
+                Constraint::Min(0),    // Log lines
+                Constraint::Length(1), // Status / search prompt
+            ])
+            .split(frame.area());
+
+        self.height = chunks[1].height.saturating_sub(2) as usize;
+        if self.follow {
+            self.top = self.max_top();
+        }
+
+        let mode = if self.follow { "FOLLOW" } else { "PAUSED" };
+        let title = Line::from(vec![
+            Span::styled(
+                " tail ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("{} ", self.tail.path().display())),
+            Span::styled(format!("[{}]", mode), Style::default().fg(Color::Yellow)),
+        ]);
+        frame.render_widget(
+            Paragraph::new(title).style(Style::default().bg(Color::DarkGray)),
+            chunks[0],
+        );
+
+        let visible: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(self.top)
+            .take(self.height)
+            .map(|(row, line)| self.render_line(row, line))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(visible).block(Block::default().borders(Borders::ALL)),
+            chunks[1],
+        );
+
+        let status = if let Some(input) = &self.search_input {
+            format!("/{}", input)
+        } else if !self.status_message.is_empty() {
+            self.status_message.clone()
+        } else {
+            format!(
+                " {} lines | /: Search | n/N: Next/Prev | f: Follow | q: Quit",
+                self.lines.len()
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(status).style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+            chunks[2],
+        );
+    }
+
+    /// Line number, level-colored text, search matches highlighted
+    fn render_line<'a>(&self, row: usize, line: &'a str) -> Line<'a> {
+        let style = LogLevel::detect(line)
+            .map(|level| Style::default().fg(level.color()))
+            .unwrap_or_default();
+        let number_style = if self.current_match == Some(row) {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+
+        let mut spans = vec![Span::styled(format!("{:6} ", row + 1), number_style)];
+        if self.query.is_empty() {
+            spans.push(Span::styled(line, style));
+            return Line::from(spans);
+        }
+
+        // ASCII lowercasing keeps byte offsets valid for slicing `line`
+        let haystack = line.to_ascii_lowercase();
+        let needle = self.query.to_ascii_lowercase();
+        let highlight = style.bg(Color::Yellow).fg(Color::Black);
+        let mut last = 0;
+        for (start, _) in haystack.match_indices(&needle) {
+            spans.push(Span::styled(&line[last..start], style));
+            spans.push(Span::styled(&line[start..start + needle.len()], highlight));
+            last = start + needle.len();
+        }
+        spans.push(Span::styled(&line[last..], style));
+        Line::from(spans)
+    }
+}
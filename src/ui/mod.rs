@@ -1,7 +1,11 @@
 pub mod app;
+pub mod input;
+pub mod log_view;
 pub mod renderer;
 pub mod terminal;
 
 pub use app::App;
+pub use input::key_event_from_crossterm;
+pub use log_view::{LogLevel, LogView};
 pub use renderer::render;
 pub use terminal::{init, restore, Tui};
@@ -1,11 +1,18 @@
+use crate::buffer::Point;
+use crate::editor::{Selection, Severity};
 use crate::ui::app::App;
+use crate::util::tabs::char_widths;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
+use std::borrow::Cow;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub fn render(app: &App, frame: &mut Frame) {
     let chunks = Layout::default()
@@ -25,6 +32,8 @@ pub fn render(app: &App, frame: &mut Frame) {
 
     // Status bar
     render_status_bar(app, frame, chunks[2]);
+
+    render_key_hints(app, frame, chunks[1]);
 }
 
 fn render_title_bar(frame: &mut Frame, area: Rect) {
@@ -51,60 +60,26 @@ fn render_title_bar(frame: &mut Frame, area: Rect) {
 }
 
 fn render_editor(app: &App, frame: &mut Frame, area: Rect) {
-    let text = app.editor.text();
-    let cursor = app.editor.cursor();
+    let primary = app.editor.cursor();
+    let selections = app.editor.selections();
+    let tab_width = app.editor.indent_width();
 
-    let lines: Vec<Line> = text
+    let lines: Vec<Line> = app
+        .editor
+        .buffer()
+        .rope()
         .lines()
+        .map(|line| match line {
+            Cow::Borrowed(text) => Cow::Borrowed(text.trim_end_matches('\r')),
+            Cow::Owned(text) => Cow::Owned(text.trim_end_matches('\r').to_string()),
+        })
         .enumerate()
         .map(|(row_idx, line)| {
             let line_num = format!("{:4} ", row_idx + 1);
-
-            // If this is the cursor line, render with block cursor
-            if row_idx == cursor.row {
-                let mut spans = vec![Span::styled(line_num, Style::default().fg(Color::DarkGray))];
-
-                // Split line at cursor position
-                let chars: Vec<char> = line.chars().collect();
-
-                // Before cursor
-                if cursor.column > 0 {
-                    let before: String = chars.iter().take(cursor.column).collect();
-                    spans.push(Span::raw(before));
-                }
-
-                // Cursor character (block cursor)
-                if cursor.column < chars.len() {
-                    let cursor_char = chars[cursor.column].to_string();
-                    spans.push(Span::styled(
-                        cursor_char,
-                        Style::default()
-                            .bg(Color::White)
-                            .fg(Color::Black)
-                            .add_modifier(Modifier::BOLD),
-                    ));
-                } else {
-                    // Cursor at end of line - show space
-                    spans.push(Span::styled(
-                        " ",
-                        Style::default().bg(Color::White).fg(Color::Black),
-                    ));
-                }
-
-                // After cursor
-                if cursor.column + 1 < chars.len() {
-                    let after: String = chars.iter().skip(cursor.column + 1).collect();
-                    spans.push(Span::raw(after));
-                }
-
-                Line::from(spans)
-            } else {
-                // Regular line (no cursor)
-                Line::from(vec![
-                    Span::styled(line_num, Style::default().fg(Color::DarkGray)),
-                    Span::raw(line),
-                ])
-            }
+            let mut spans = vec![Span::styled(line_num, Style::default().fg(Color::DarkGray))];
+            spans.extend(line_spans(&line, row_idx, primary, &selections, tab_width));
+            spans.extend(diagnostic_tail(app, row_idx));
+            Line::from(spans)
         })
         .collect();
 
@@ -112,7 +87,7 @@ fn render_editor(app: &App, frame: &mut Frame, area: Rect) {
     let lines = if lines.is_empty() {
         vec![Line::from(vec![
             Span::styled("   1 ", Style::default().fg(Color::DarkGray)),
-            Span::styled(" ", Style::default().bg(Color::White).fg(Color::Black)),
+            Span::styled(" ", cursor_style(true)),
         ])]
     } else {
         lines
@@ -124,19 +99,168 @@ fn render_editor(app: &App, frame: &mut Frame, area: Rect) {
     frame.render_widget(editor_widget, area);
 }
 
+/// Block cursor (extra cursors in gray)
+fn cursor_style(is_primary: bool) -> Style {
+    if is_primary {
+        Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().bg(Color::Gray).fg(Color::Black)
+    }
+}
+
+/// A row's text with its cursors and selections. Styles cover whole grapheme
+/// clusters, so a cursor on a wide character (CJK, emoji) fills both of its
+/// cells, and tabs are expanded to spaces since the terminal won't do it
+fn line_spans(
+    line: &str,
+    row: usize,
+    primary: Point,
+    selections: &[Selection],
+    tab_width: usize,
+) -> Vec<Span<'static>> {
+    let line_len = line.chars().count();
+    let cursors: Vec<usize> = selections
+        .iter()
+        .filter(|selection| selection.end.row == row)
+        .map(|selection| selection.end.column)
+        .collect();
+    let selected: Vec<(Range<usize>, bool)> = selections
+        .iter()
+        .filter_map(|selection| selection.row_span(row, line_len))
+        .collect();
+    let selection_style = Style::default().bg(Color::Blue);
+    let style_of = |columns: &Range<usize>| {
+        if let Some(&column) = cursors.iter().find(|&column| columns.contains(column)) {
+            cursor_style(primary == Point::new(row, column))
+        } else if selected
+            .iter()
+            .any(|(span, _)| span.start < columns.end && columns.start < span.end)
+        {
+            selection_style
+        } else {
+            Style::default()
+        }
+    };
+
+    let widths = char_widths(line, tab_width);
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_style = Style::default();
+    let mut column = 0;
+    for grapheme in line.graphemes(true) {
+        let columns = column..column + grapheme.chars().count();
+        let style = style_of(&columns);
+        if style != run_style && !run.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_style = style;
+        if grapheme == "\t" {
+            run.extend(std::iter::repeat_n(' ', widths[column]));
+        } else {
+            run.push_str(grapheme);
+        }
+        column = columns.end;
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, run_style));
+    }
+
+    // Past the end of the line: a cursor, or the selected newline
+    if let Some(&column) = cursors.iter().find(|&&column| column >= line_len) {
+        spans.push(Span::styled(
+            " ",
+            cursor_style(primary == Point::new(row, column)),
+        ));
+    } else if selected.iter().any(|&(_, past_end)| past_end) {
+        spans.push(Span::styled(" ", selection_style));
+    }
+    spans
+}
+
+/// The row's first diagnostic message, dimmed after the text (Error Lens style)
+fn diagnostic_tail(app: &App, row: usize) -> Option<Span<'static>> {
+    let diagnostic = app.editor.diagnostic_on_row(row)?;
+    let color = match diagnostic.severity {
+        Severity::Error => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Info => Color::Blue,
+        Severity::Hint => Color::DarkGray,
+    };
+    let message = diagnostic.message.lines().next().unwrap_or_default();
+    Some(Span::styled(
+        format!("  ● {}", message),
+        Style::default().fg(color).add_modifier(Modifier::DIM),
+    ))
+}
+
+/// While a key sequence waits: the keys that can come next and what they
+/// run, in a box at the bottom right of the editor
+fn render_key_hints(app: &App, frame: &mut Frame, area: Rect) {
+    let hints = app.pending_keys.hints(&app.keymap);
+    if hints.is_empty() {
+        return;
+    }
+    let keys: Vec<String> = hints.iter().map(|(rest, _)| rest.to_string()).collect();
+    let key_width = keys.iter().map(|keys| keys.width()).max().unwrap_or(0);
+    let lines: Vec<Line> = keys
+        .iter()
+        .zip(&hints)
+        .map(|(keys, (_, command))| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:width$}  ", keys, width = key_width),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(command.label()),
+            ])
+        })
+        .collect();
+
+    let typed: Vec<String> = app
+        .pending_keys
+        .chords()
+        .iter()
+        .map(|chord| chord.to_string())
+        .collect();
+    let title = format!(" {} … ", typed.join(" "));
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0);
+    let width = (content_width.max(title.width()) as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.right().saturating_sub(width + 1),
+        area.bottom().saturating_sub(height + 1),
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+        popup,
+    );
+}
+
 fn render_status_bar(app: &App, frame: &mut Frame, area: Rect) {
-    let cursor = app.editor.cursor();
     let line_count = app.editor.line_count();
 
-    let status_text = if !app.status_message.is_empty() {
+    let status_text = if let Some(input) = &app.save_prompt {
+        format!(" Save as: {}", input)
+    } else if !app.status_message.is_empty() {
         app.status_message.clone()
+    } else if let Some(selection) = app.editor.selection_label() {
+        format!(
+            " {} | {} | Ctrl+Z: Undo | Ctrl+Y: Redo | Ctrl+Q: Quit",
+            app.editor.cursor_position_label(),
+            selection,
+        )
     } else {
         format!(
-            " Line {}, Col {} | {} lines | {} chars | Ctrl+Z: Undo | Ctrl+Y: Redo | Ctrl+Q: Quit",
-            cursor.row + 1,
-            cursor.column + 1,
+            " {} | {} lines | {} chars | Ctrl+Z: Undo | Ctrl+Y: Redo | Ctrl+Q: Quit",
+            app.editor.cursor_position_label(),
             line_count,
-            app.editor.text().len(),
+            app.editor.buffer().rope().len_chars(),
         )
     };
 
@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+/// `()`, `[]` and `{}` as (opener, closer) bytes. Brackets are ASCII, so
+/// scanning bytes never lands inside a multi-byte character, and the same
+/// scan runs over a `&str` or a rope's chunks
+pub const BRACKET_PAIRS: [(u8, u8); 3] = [(b'(', b')'), (b'[', b']'), (b'{', b'}')];
+
+pub fn closer_for(open: u8) -> Option<u8> {
+    BRACKET_PAIRS
+        .iter()
+        .find(|(o, _)| *o == open)
+        .map(|(_, c)| *c)
+}
+
+pub fn opener_for(close: u8) -> Option<u8> {
+    BRACKET_PAIRS
+        .iter()
+        .find(|(_, c)| *c == close)
+        .map(|(o, _)| *o)
+}
+
+/// Offset of the bracket closing the one at `offset`; `from` yields the
+/// bytes starting with that opener
+pub fn find_closer(offset: usize, from: impl IntoIterator<Item = u8>) -> Option<usize> {
+    let mut bytes = from.into_iter();
+    let open = bytes.next()?;
+    let close = closer_for(open)?;
+    let mut depth = 1;
+    for (i, b) in bytes.enumerate() {
+        if b == open {
+            depth += 1;
+        } else if b == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(offset + 1 + i);
+            }
+        }
+    }
+    None
+}
+
+/// Offset of the bracket opening the one at `offset`; `back` yields the
+/// bytes from that closer backwards
+pub fn find_opener(offset: usize, back: impl IntoIterator<Item = u8>) -> Option<usize> {
+    let mut bytes = back.into_iter();
+    let close = bytes.next()?;
+    let open = opener_for(close)?;
+    let mut depth = 1;
+    for (i, b) in bytes.enumerate() {
+        if b == close {
+            depth += 1;
+        } else if b == open {
+            depth -= 1;
+            if depth == 0 {
+                return Some(offset - 1 - i);
+            }
+        }
+    }
+    None
+}
+
+/// Offset of the nearest opener before `offset` whose pair doesn't close
+/// before it; `before` yields the bytes before `offset`, nearest first
+pub fn find_unclosed_opener(offset: usize, before: impl IntoIterator<Item = u8>) -> Option<usize> {
+    // Walk back, skipping over pairs that close before the cursor
+    let mut pending_closers = Vec::new();
+    for (i, b) in before.into_iter().enumerate() {
+        if opener_for(b).is_some() {
+            pending_closers.push(b);
+        } else if let Some(close) = closer_for(b) {
+            if pending_closers.last() == Some(&close) {
+                pending_closers.pop();
+            } else if pending_closers.is_empty() {
+                return Some(offset - 1 - i);
+            }
+        }
+    }
+    None
+}
+
+/// Byte range of the nearest bracket pair enclosing `offset`, brackets
+/// included; a cursor on an opening bracket counts as inside that pair.
+/// `before` yields the bytes before `offset` nearest first, `from(at)` the
+/// bytes starting at `at`
+pub fn enclosing_pair<I>(
+    offset: usize,
+    before: impl IntoIterator<Item = u8>,
+    from: impl Fn(usize) -> I,
+) -> Option<Range<usize>>
+where
+    I: IntoIterator<Item = u8>,
+{
+    let open = match from(offset).into_iter().next() {
+        Some(b) if closer_for(b).is_some() => offset,
+        _ => find_unclosed_opener(offset, before)?,
+    };
+    let close = find_closer(open, from(open))?;
+    Some(open..close + 1)
+}
@@ -0,0 +1,118 @@
+use std::ops::Range;
+
+/// Above this many cells (changed old lines × changed new lines) the LCS table
+/// is skipped and the changed middle becomes a single hunk
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A changed run of lines: `old` rows are replaced by `new` rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineHunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+impl LineHunk {
+    /// Change in line count when this hunk is applied
+    pub fn line_delta(&self) -> isize {
+        self.new.len() as isize - self.old.len() as isize
+    }
+}
+
+/// `diff_lines` as byte ranges: each `old` range of `old_text` becomes the
+/// `new` range of `new_text`, in order
+pub fn diff_text(old_text: &str, new_text: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let old: Vec<&str> = old_text.split('\n').collect();
+    let new: Vec<&str> = new_text.split('\n').collect();
+    let (old_starts, new_starts) = (line_starts(&old), line_starts(&new));
+
+    diff_lines(&old, &new)
+        .into_iter()
+        .map(|hunk| {
+            // Whole lines with their newlines, as if both texts ended in one
+            let old_range = old_starts[hunk.old.start]..old_starts[hunk.old.end];
+            let new_range = new_starts[hunk.new.start]..new_starts[hunk.new.end];
+            if old_range.end <= old_text.len() {
+                return (old_range, new_range);
+            }
+            // The last line has no newline: take the one before the hunk instead
+            let shift = usize::from(old_range.start > 0);
+            (
+                old_range.start - shift..old_range.end - 1,
+                new_range.start - shift..new_range.end - 1,
+            )
+        })
+        .collect()
+}
+
+/// Byte offset of each line, plus one past the end (as if the text ended
+/// in a newline)
+fn line_starts(lines: &[&str]) -> Vec<usize> {
+    let mut starts = vec![0];
+    for line in lines {
+        starts.push(starts.last().unwrap() + line.len() + 1);
+    }
+    starts
+}
+
+/// Line-level diff (longest common subsequence), hunks in order
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Vec<LineHunk> {
+    // Common prefix/suffix are cheap to strip and keep the table small
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return Vec::new();
+    }
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        return vec![LineHunk {
+            old: prefix..prefix + old_mid.len(),
+            new: prefix..prefix + new_mid.len(),
+        }];
+    }
+
+    // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<LineHunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let hunk = current.get_or_insert(LineHunk {
+            old: prefix + i..prefix + i,
+            new: prefix + j..prefix + j,
+        });
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+            hunk.new.end = prefix + j;
+        } else {
+            i += 1;
+            hunk.old.end = prefix + i;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
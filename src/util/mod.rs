@@ -0,0 +1,3 @@
+pub mod brackets;
+pub mod diff;
+pub mod tabs;
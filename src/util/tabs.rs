@@ -0,0 +1,58 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Screen columns each character of `line` takes: a tab runs to the next
+/// multiple of `tab_width`, a grapheme cluster takes its terminal width (two
+/// for CJK and emoji) on its first character and its other characters
+/// (combining marks, joiners) take none
+pub fn char_widths(line: &str, tab_width: usize) -> Vec<usize> {
+    let tab_width = tab_width.max(1);
+    let mut widths = Vec::with_capacity(line.len());
+    let mut display = 0;
+    for grapheme in line.graphemes(true) {
+        let width = if grapheme == "\t" {
+            tab_width - display % tab_width
+        } else {
+            grapheme.width()
+        };
+        widths.push(width);
+        widths.extend(grapheme.chars().skip(1).map(|_| 0));
+        display += width;
+    }
+    widths
+}
+
+/// Screen column of character `column` in `line`, with tabs advancing to the
+/// next multiple of `tab_width` and wide characters taking two columns
+pub fn display_column(line: &str, column: usize, tab_width: usize) -> usize {
+    char_widths(line, tab_width).iter().take(column).sum()
+}
+
+/// Character column in `line` closest to screen column `display` without
+/// passing it (the line length if the line is shorter)
+pub fn column_at_display(line: &str, display: usize, tab_width: usize) -> usize {
+    let widths = char_widths(line, tab_width);
+    let mut current = 0;
+    for (column, width) in widths.iter().enumerate() {
+        if current + width > display {
+            return column;
+        }
+        current += width;
+    }
+    widths.len()
+}
+
+/// Whitespace spanning `columns` screen columns: tabs then padding spaces
+/// with `hard_tabs`, otherwise only spaces
+pub fn indent_string(columns: usize, tab_width: usize, hard_tabs: bool) -> String {
+    let tab_width = tab_width.max(1);
+    if hard_tabs {
+        format!(
+            "{}{}",
+            "\t".repeat(columns / tab_width),
+            " ".repeat(columns % tab_width)
+        )
+    } else {
+        " ".repeat(columns)
+    }
+}
@@ -118,3 +118,464 @@ fn test_delete_at_point() {
 
     assert_eq!(buffer.to_string(), "HelloWorld");
 }
+
+#[test]
+fn test_edit_applies_ranges_from_the_original_text() {
+    let mut buffer = Buffer::from_text("one\ntwo\nthree\nfour");
+    // Warm the line cache so stale offsets would show up below
+    assert_eq!(
+        buffer.get_line_offsets_batch(&[0, 1, 2, 3]),
+        vec![0, 4, 8, 14]
+    );
+
+    // Out of order, with two insertions at the same offset
+    buffer.edit(&[
+        (Offset(14)..Offset(18), "4"),
+        (Offset(0)..Offset(3), "1"),
+        (Offset(7)..Offset(7), "!"),
+        (Offset(7)..Offset(7), "\n2b"),
+        (Offset(8)..Offset(14), ""),
+    ]);
+
+    assert_eq!(buffer.to_string(), "1\ntwo!\n2b\n4");
+    assert_eq!(buffer.line_count(), 4);
+    assert_eq!(
+        buffer.get_line_offsets_batch(&[0, 1, 2, 3]),
+        vec![0, 2, 7, 10]
+    );
+
+    // Nothing to do
+    buffer.edit(&[(Offset(3)..Offset(3), "")]);
+    assert_eq!(buffer.to_string(), "1\ntwo!\n2b\n4");
+}
+
+#[test]
+#[should_panic(expected = "overlapping edits")]
+fn test_edit_rejects_overlapping_ranges() {
+    let mut buffer = Buffer::from_text("Hello World");
+    buffer.edit(&[(Offset(0)..Offset(5), "Hi"), (Offset(4)..Offset(6), "")]);
+}
+
+#[test]
+fn test_snapshot_is_unaffected_by_later_edits() {
+    let mut buffer = Buffer::from_text("fn main() {\n}\n");
+    let snapshot = buffer.snapshot();
+
+    // Read on another thread while the buffer keeps changing
+    let reader = {
+        let snapshot = snapshot.clone();
+        std::thread::spawn(move || (snapshot.text(), snapshot.line_count()))
+    };
+    buffer.insert(Offset(11), "\n    run();");
+    buffer.delete(Offset(0), Offset(3));
+
+    assert_eq!(reader.join().unwrap(), ("fn main() {\n}\n".to_string(), 3));
+    assert_eq!(snapshot.line(0), Some("fn main() {".to_string()));
+    assert_eq!(snapshot.slice_bytes(3, 7), "main");
+    assert_eq!(buffer.to_string(), "main() {\n    run();\n}\n");
+    assert_eq!(snapshot.to_buffer().to_string(), "fn main() {\n}\n");
+}
+
+#[test]
+fn test_cache_stats_hit_rate() {
+    use zed_text_editor::buffer::CacheStats;
+
+    let stats = CacheStats::new(3, 1, 10, 500);
+    assert_eq!(stats.hit_rate, 0.75);
+    assert_eq!(stats.cached_lines, 10);
+
+    // No lookups yet - no division by zero
+    assert_eq!(CacheStats::new(0, 0, 0, 500).hit_rate, 0.0);
+}
+
+#[test]
+fn test_line_indent_len() {
+    let buffer = Buffer::from_text("  a\n\t\tb\nc\n   ");
+    assert_eq!(buffer.line_indent_len(0), 2);
+    assert_eq!(buffer.line_indent_len(1), 2);
+    assert_eq!(buffer.line_indent_len(2), 0);
+    assert_eq!(buffer.line_indent_len(3), 3);
+    assert_eq!(buffer.line_indent_len(10), 0);
+}
+
+#[test]
+fn test_read_text_from_stream() {
+    use zed_text_editor::io::read_text;
+
+    let text = read_text(std::io::Cursor::new("piped\ntext ✓\n")).unwrap();
+    assert_eq!(text, "piped\ntext ✓\n");
+
+    let err = read_text(std::io::Cursor::new(vec![b'a', 0xff, b'b'])).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_word_range_at() {
+    let buffer = Buffer::from_text("let foo_bar = (1);\n\nx");
+    let range = |row, column| {
+        let range = buffer.word_range_at(Point::new(row, column), "_");
+        (range.start.value(), range.end.value())
+    };
+
+    // Inside, at the start and just after a word
+    assert_eq!(range(0, 5), (4, 11));
+    assert_eq!(range(0, 4), (4, 11));
+    assert_eq!(range(0, 11), (4, 11));
+    // Whitespace and punctuation runs
+    assert_eq!(range(0, 13), (13, 14));
+    assert_eq!(range(0, 12), (12, 13));
+    assert_eq!(range(0, 17), (16, 18));
+    // Empty line
+    assert_eq!(range(1, 0), (19, 19));
+    assert_eq!(range(2, 1), (20, 21));
+}
+
+#[test]
+fn test_word_boundaries_and_word_at() {
+    let buffer = Buffer::from_text("  foo_bar(x, --y)  \r\nÉté-2 z");
+    let next = |offset, word_chars| {
+        buffer
+            .next_word_boundary(Offset(offset), word_chars)
+            .value()
+    };
+    let prev = |offset, word_chars| {
+        buffer
+            .prev_word_boundary(Offset(offset), word_chars)
+            .value()
+    };
+
+    // Whitespace is skipped, then a word or a punctuation run is crossed
+    let stops: Vec<usize> = std::iter::successors(Some(0), |&offset| {
+        Some(next(offset, "_")).filter(|&next| next != offset)
+    })
+    .collect();
+    assert_eq!(
+        stops,
+        [0, 9, 10, 11, 12, 15, 16, 17, 19, 21, 26, 27, 28, 30]
+    );
+    let stops: Vec<usize> = std::iter::successors(Some(buffer.len()), |&offset| {
+        Some(prev(offset, "_")).filter(|&prev| prev != offset)
+    })
+    .collect();
+    assert_eq!(stops, [30, 29, 27, 26, 21, 19, 16, 15, 13, 11, 10, 9, 2, 0]);
+
+    // Configured word characters join runs
+    assert_eq!(next(2, ""), 5);
+    assert_eq!(next(21, "-"), 28);
+    assert_eq!(prev(28, "-"), 21);
+
+    let word = |row, column, word_chars| {
+        buffer
+            .word_at(Point::new(row, column), word_chars)
+            .map(|range| (range.start.value(), range.end.value()))
+    };
+    assert_eq!(word(0, 5, "_"), Some((2, 9)));
+    assert_eq!(word(0, 5, ""), Some((2, 5)));
+    assert_eq!(word(0, 9, "_"), Some((2, 9)));
+    assert_eq!(word(0, 14, "_"), None);
+    assert_eq!(word(0, 14, "-"), Some((13, 16)));
+    assert_eq!(word(0, 0, "_"), None);
+    assert_eq!(word(1, 1, "-"), Some((21, 28)));
+}
+
+#[test]
+fn test_paragraph_range_at() {
+    let buffer = Buffer::from_text("a\nb\n\n\nc\nd");
+    let range = |row| {
+        let range = buffer.paragraph_range_at(Point::new(row, 0));
+        (range.start.value(), range.end.value())
+    };
+
+    assert_eq!(range(0), (0, 3));
+    assert_eq!(range(1), (0, 3));
+    assert_eq!(range(2), (4, 5));
+    assert_eq!(range(5), (6, 9));
+}
+
+#[test]
+fn test_bracket_pair_range_at() {
+    let buffer = Buffer::from_text("f(a, [b])\nx");
+
+    assert_eq!(
+        buffer.bracket_pair_range_at(Point::new(0, 6)),
+        Some(Offset(5)..Offset(8))
+    );
+    assert_eq!(
+        buffer.bracket_pair_range_at(Point::new(0, 3)),
+        Some(Offset(1)..Offset(9))
+    );
+    assert_eq!(buffer.bracket_pair_range_at(Point::new(1, 0)), None);
+
+    // A pair whose ends sit many chunks apart, with closed pairs between
+    let body = "(x) [é]\n".repeat(1000);
+    let buffer = Buffer::from_text(&format!("{{\n{}}}", body));
+    assert!(buffer.rope().chunk_count() > 2);
+    assert_eq!(
+        buffer.bracket_pair_range_at(Point::new(600, 3)),
+        Some(Offset(0)..Offset(buffer.len()))
+    );
+    assert_eq!(
+        buffer.bracket_pair_range_at(Point::new(600, 5)),
+        Some(Offset(2 + 599 * 9 + 4)..Offset(2 + 599 * 9 + 8))
+    );
+}
+
+#[test]
+fn test_anchors_follow_edits_with_bias() {
+    use zed_text_editor::{Anchor, Bias, Offset};
+
+    let buffer = Buffer::from_text("ab\ncd");
+    let mut left = buffer.anchor_at(Point::new(1, 1), Bias::Left);
+    let mut right = buffer.anchor_at(Point::new(1, 1), Bias::Right);
+    assert_eq!(left.offset, Offset(4));
+
+    // An edit before both shifts them; an insertion at them splits them
+    for anchor in [&mut left, &mut right] {
+        anchor.apply_edit(&(0..0), 2);
+    }
+    assert_eq!((left.offset, right.offset), (Offset(6), Offset(6)));
+    for anchor in [&mut left, &mut right] {
+        anchor.apply_edit(&(6..6), 3);
+    }
+    assert_eq!((left.offset, right.offset), (Offset(6), Offset(9)));
+
+    // Replaced text around an anchor leaves it at either end of the new text
+    let mut inside = Anchor::before(Offset(5));
+    inside.apply_edit(&(4..8), 1);
+    assert_eq!(inside.offset, Offset(4));
+    let mut inside = Anchor::after(Offset(5));
+    inside.apply_edit(&(4..8), 1);
+    assert_eq!(inside.offset, Offset(5));
+
+    // Edits after an anchor leave it alone
+    let mut anchor = Anchor::after(Offset(2));
+    anchor.apply_edit(&(3..5), 0);
+    assert_eq!(buffer.anchor_to_point(anchor), Point::new(0, 2));
+}
+
+#[test]
+fn test_rope_edits_rechunk_locally() {
+    use zed_text_editor::Rope;
+
+    let mut expected = "fn main() {\n    println!(\"héllo\");\n}\n".repeat(500);
+    let mut rope = Rope::from_text(&expected);
+    let chunks = rope.chunk_count();
+
+    let edits: [(usize, usize, &str); 5] = [
+        (0, 0, "// header\n"),
+        (4096, 4100, ""),
+        (1020, 1030, "ünïcode\n"),
+        (expected.len() - 1, expected.len() - 1, "tail"),
+        (2000, 5000, "x"),
+    ];
+    for (start, end, text) in edits {
+        let start = (start..).find(|&i| expected.is_char_boundary(i)).unwrap();
+        let end = (end.max(start)..)
+            .find(|&i| expected.is_char_boundary(i))
+            .unwrap();
+        rope.delete(start, end);
+        rope.insert(start, text);
+        expected.replace_range(start..end, text);
+        assert_eq!(rope.to_string(), expected);
+        assert_eq!(rope.line_count(), expected.matches('\n').count());
+    }
+    assert!(rope.chunk_count() <= chunks + 2);
+}
+
+#[test]
+fn test_rope_slice_shares_inner_chunks() {
+    use zed_text_editor::Rope;
+
+    let text = "let x = 1;\n".repeat(1000);
+    let rope = Rope::from_text(&text);
+    for (start, end) in [
+        (0, 0),
+        (5, 20),
+        (100, 5000),
+        (0, text.len()),
+        (9000, 20_000),
+    ] {
+        let end = end.min(text.len());
+        assert_eq!(rope.slice(start..end).to_string(), text[start..end]);
+    }
+
+    // Whole chunks inside the range keep pointing at the same text
+    let original: Vec<*const u8> = rope.chunks().map(|c| c.as_str().as_ptr()).collect();
+    let slice = rope.slice(100..5000);
+    let sliced: Vec<*const u8> = slice.chunks().map(|c| c.as_str().as_ptr()).collect();
+    assert!(sliced.len() > 2);
+    assert!(sliced[1..sliced.len() - 1]
+        .iter()
+        .all(|ptr| original.contains(ptr)));
+    assert!(!original.contains(&sliced[0]));
+}
+
+#[test]
+fn test_rope_char_conversions_on_multibyte_text() {
+    use zed_text_editor::{Buffer, Rope};
+
+    let text = "héllo wörld 🦀\nπ = 3.14\n".repeat(200);
+    let rope = Rope::from_text(&text);
+    assert!(rope.chunk_count() > 2);
+    assert_eq!(rope.len_chars(), text.chars().count());
+
+    for (char_idx, (byte, _)) in text.char_indices().enumerate().step_by(7) {
+        assert_eq!(rope.char_to_byte(char_idx), byte);
+        assert_eq!(rope.byte_to_char(byte), char_idx);
+    }
+    assert_eq!(rope.char_to_byte(rope.len_chars()), text.len());
+
+    // Columns are chars, offsets are bytes
+    let buffer = Buffer::from_text(&text);
+    let point = Point::new(301, 5);
+    let offset = buffer.point_to_offset(point);
+    assert_eq!(
+        offset.value(),
+        text.match_indices('\n').nth(300).unwrap().0 + 1 + 6
+    );
+    assert_eq!(buffer.offset_to_point(offset), point);
+    assert_eq!(
+        buffer.point_to_offset(Point::new(0, 100)).value(),
+        "héllo wörld 🦀".len()
+    );
+}
+
+#[test]
+fn test_rope_utf16_conversions_on_multibyte_text() {
+    use zed_text_editor::Rope;
+
+    let text = "héllo wörld 🦀\nπ = 3.14 😀😀\n".repeat(200);
+    let rope = Rope::from_text(&text);
+    assert!(rope.chunk_count() > 2);
+    assert_eq!(rope.len_utf16(), text.encode_utf16().count());
+
+    let mut utf16 = 0;
+    for (byte, c) in text.char_indices() {
+        assert_eq!(rope.offset_to_utf16(byte), utf16);
+        assert_eq!(rope.utf16_to_offset(utf16), byte);
+        utf16 += c.len_utf16();
+    }
+    assert_eq!(rope.offset_to_utf16(text.len()), rope.len_utf16());
+    assert_eq!(rope.utf16_to_offset(rope.len_utf16()), text.len());
+
+    // Inside a surrogate pair moves past the emoji
+    let crab = text.find('🦀').unwrap();
+    let crab_utf16 = rope.offset_to_utf16(crab);
+    assert_eq!(rope.utf16_to_offset(crab_utf16 + 1), crab + '🦀'.len_utf8());
+    assert_eq!(rope.offset_to_utf16(crab + 1), crab_utf16 + 2);
+}
+
+#[test]
+fn test_rope_lines_match_split_across_chunks() {
+    use std::borrow::Cow;
+    use zed_text_editor::Rope;
+
+    let text = format!("{}\n\nshort\r\n{}", "x".repeat(3000), "line\n".repeat(500));
+    let rope = Rope::from_text(&text);
+    assert!(rope.chunk_count() > 2);
+
+    let expected: Vec<&str> = text.strip_suffix('\n').unwrap().split('\n').collect();
+    let lines: Vec<Cow<str>> = rope.lines().collect();
+    assert_eq!(lines, expected);
+    // Lines inside one chunk are borrowed
+    assert!(matches!(lines[2], Cow::Borrowed(_)));
+
+    assert_eq!(rope.lines_at(3).next().as_deref(), Some("line"));
+    assert_eq!(rope.lines_at(3).count(), 500);
+    assert_eq!(rope.line(2).as_deref(), Some("short\r"));
+    assert_eq!(rope.line(expected.len()), None);
+    assert_eq!(rope.line_byte_range(1), Some((3001, 3001)));
+    assert_eq!(rope.line_byte_range(expected.len()), None);
+
+    let unterminated = Rope::from_text("a\nb");
+    assert_eq!(unterminated.lines().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(unterminated.line_byte_range(1), Some((2, 3)));
+    assert_eq!(Rope::from_text("").lines().count(), 0);
+}
+
+#[test]
+fn test_rope_line_and_slice_bytes_seek_into_chunks() {
+    use zed_text_editor::Rope;
+
+    let text: String = (0..3000).map(|i| format!("line {} ünïcode\n", i)).collect();
+    let rope = Rope::from_text(&text);
+    assert!(rope.chunk_count() > 10);
+
+    for (idx, line) in text.lines().enumerate().step_by(37) {
+        assert_eq!(rope.line(idx).as_deref(), Some(line));
+    }
+    assert_eq!(rope.line(2999).as_deref(), Some("line 2999 ünïcode"));
+    assert_eq!(rope.line(3000), None);
+
+    // Ranges starting and ending anywhere, including across chunk boundaries
+    let boundaries: Vec<usize> = text.char_indices().map(|(i, _)| i).step_by(997).collect();
+    for pair in boundaries.windows(2) {
+        assert_eq!(rope.slice_bytes(pair[0], pair[1]), text[pair[0]..pair[1]]);
+    }
+    let tail = "ünïcode\n";
+    assert_eq!(
+        rope.slice_bytes(text.len() - tail.len(), text.len() + 10),
+        tail
+    );
+    assert_eq!(rope.slice_bytes(5, 5), "");
+}
+
+#[test]
+fn test_rope_split_at_and_append_round_trip() {
+    use zed_text_editor::Rope;
+
+    let text: String = (0..2000).map(|i| format!("{} é\n", i)).collect();
+    let rope = Rope::from_text(&text);
+    for offset in [
+        0,
+        1,
+        1024,
+        1025,
+        5000,
+        text.len() - 1,
+        text.len(),
+        text.len() + 5,
+    ] {
+        let offset = offset.min(text.len());
+        let offset = (0..=offset)
+            .rev()
+            .find(|&i| text.is_char_boundary(i))
+            .unwrap();
+        let (left, right) = rope.split_at(offset);
+        assert_eq!(left.to_string(), text[..offset]);
+        assert_eq!(right.to_string(), text[offset..]);
+        assert_eq!(left.line_count() + right.line_count(), rope.line_count());
+
+        let mut joined = left;
+        joined.append(right);
+        assert_eq!(joined.to_string(), text);
+        assert_eq!(joined.len_chars(), rope.len_chars());
+    }
+
+    // Small pushes grow the last chunk instead of adding one each
+    let mut rope = Rope::new();
+    for _ in 0..100 {
+        rope.push_str("ab\n");
+    }
+    assert_eq!(rope.to_string(), "ab\n".repeat(100));
+    assert_eq!(rope.chunk_count(), 1);
+}
+
+#[test]
+fn test_display_columns_expand_tabs_and_wide_characters() {
+    let buffer = Buffer::from_text("a\tb\n日本x\n");
+    // The tab runs from column 1 to the tab stop at 4
+    assert_eq!(buffer.display_column(Point::new(0, 1), 4), 1);
+    assert_eq!(buffer.display_column(Point::new(0, 2), 4), 4);
+    assert_eq!(buffer.display_column(Point::new(0, 3), 4), 5);
+    // CJK characters take two columns each
+    assert_eq!(buffer.display_column(Point::new(1, 2), 4), 4);
+
+    // Inside a tab or wide character rounds back to its start
+    assert_eq!(buffer.point_at_display_column(0, 3, 4), Point::new(0, 1));
+    assert_eq!(buffer.point_at_display_column(0, 4, 4), Point::new(0, 2));
+    assert_eq!(buffer.point_at_display_column(1, 3, 4), Point::new(1, 1));
+    assert_eq!(buffer.point_at_display_column(1, 4, 4), Point::new(1, 2));
+    // Past the end is the line's end
+    assert_eq!(buffer.point_at_display_column(1, 40, 4), Point::new(1, 3));
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use zed_text_editor::io::{
+    decode_text, load_rope, read_file_decoded, write_range, CatArgs, CatRange, TextEncoding,
+};
+use zed_text_editor::Rope;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+fn cat(rope: &Rope, range: CatRange) -> String {
+    let mut out = Vec::new();
+    write_range(rope, range, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_cat_args_parse() {
+    assert_eq!(CatArgs::parse(&args(&["file.rs"])).unwrap(), None);
+
+    let parsed = CatArgs::parse(&args(&["--cat", "file.rs", "--range", "2:4"]))
+        .unwrap()
+        .unwrap();
+    assert_eq!(parsed.path.to_str(), Some("file.rs"));
+    assert_eq!(
+        parsed.range,
+        CatRange::Lines {
+            start: 2,
+            end: Some(4)
+        }
+    );
+
+    let parsed = CatArgs::parse(&args(&["--bytes", "10:", "file.rs"]))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        parsed.range,
+        CatRange::Bytes {
+            start: 10,
+            end: None
+        }
+    );
+
+    assert!(CatArgs::parse(&args(&["--cat"])).is_err());
+    assert!(CatArgs::parse(&args(&["--cat", "a", "--range", "0:3"])).is_err());
+    assert!(CatArgs::parse(&args(&["--cat", "a", "--range", "5:3"])).is_err());
+    assert!(CatArgs::parse(&args(&["--cat", "a", "--range", "x:3"])).is_err());
+}
+
+#[test]
+fn test_cat_line_and_byte_ranges() {
+    let rope = Rope::from_text("one\ntwo\nthree\nfour");
+
+    assert_eq!(cat(&rope, CatRange::All), "one\ntwo\nthree\nfour");
+    assert_eq!(
+        cat(&rope, CatRange::parse_lines("2:3").unwrap()),
+        "two\nthree\n"
+    );
+    assert_eq!(
+        cat(&rope, CatRange::parse_lines("3:").unwrap()),
+        "three\nfour"
+    );
+    assert_eq!(cat(&rope, CatRange::parse_lines(":1").unwrap()), "one\n");
+    assert_eq!(cat(&rope, CatRange::parse_lines("9:12").unwrap()), "");
+    assert_eq!(cat(&rope, CatRange::parse_bytes("4:7").unwrap()), "two");
+    assert_eq!(cat(&rope, CatRange::parse_bytes("14:99").unwrap()), "four");
+}
+
+#[test]
+fn test_cat_spans_chunks() {
+    let text: String = (0..500).map(|i| format!("line {}\n", i)).collect();
+    let rope = Rope::from_text(&text);
+
+    let expected: String = (99..400).map(|i| format!("line {}\n", i)).collect();
+    assert_eq!(
+        cat(&rope, CatRange::parse_lines("100:400").unwrap()),
+        expected
+    );
+    assert_eq!(
+        cat(&rope, CatRange::parse_bytes("1000:3000").unwrap()),
+        &text[1000..3000]
+    );
+}
+
+#[test]
+fn test_cat_rejects_split_characters() {
+    let rope = Rope::from_text("héllo");
+    let mut out = Vec::new();
+    let err = write_range(&rope, CatRange::parse_bytes("0:2").unwrap(), &mut out).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(cat(&rope, CatRange::parse_bytes("0:3").unwrap()), "hé");
+}
+
+#[test]
+fn test_load_rope_validates_utf8() {
+    let path = std::env::temp_dir().join(format!("zed-cat-{}.txt", std::process::id()));
+
+    fs::write(&path, "valid ✓\n").unwrap();
+    assert_eq!(load_rope(&path).unwrap().to_string(), "valid ✓\n");
+
+    fs::write(&path, [b'o', b'k', 0xff]).unwrap();
+    let Err(err) = load_rope(&path) else {
+        panic!("invalid UTF-8 should be rejected");
+    };
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("offset 2"));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_decode_text_reads_boms_and_falls_back_to_latin1() {
+    let utf16le: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain("hé\n".encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let utf16be: Vec<u8> = [0xFE, 0xFF]
+        .into_iter()
+        .chain("hé\n".encode_utf16().flat_map(u16::to_be_bytes))
+        .collect();
+    for (bytes, text, encoding) in [
+        ("hé\n".as_bytes().to_vec(), "hé\n", TextEncoding::Utf8),
+        (
+            b"\xEF\xBB\xBFh\xC3\xA9\n".to_vec(),
+            "hé\n",
+            TextEncoding::Utf8Bom,
+        ),
+        (utf16le, "hé\n", TextEncoding::Utf16Le),
+        (utf16be, "hé\n", TextEncoding::Utf16Be),
+        (b"h\xE9\n".to_vec(), "hé\n", TextEncoding::Latin1),
+        (Vec::new(), "", TextEncoding::Utf8),
+    ] {
+        assert_eq!(decode_text(bytes), (text.to_string(), encoding));
+    }
+
+    let path = std::env::temp_dir().join(format!("zed-insert-{}.txt", std::process::id()));
+    fs::write(&path, b"\xFF\xFEo\x00k\x00").unwrap();
+    assert_eq!(
+        read_file_decoded(&path).unwrap(),
+        ("ok".to_string(), TextEncoding::Utf16Le)
+    );
+    fs::remove_file(&path).unwrap();
+    assert!(read_file_decoded(&path).is_err());
+}
@@ -1,4 +1,9 @@
-use zed_text_editor::{Editor, Point};
+use std::collections::BTreeMap;
+use zed_text_editor::editor::{
+    AutoPairs, CodeActions, Diagnostic, EditEvent, EditorEvent, Severity,
+};
+use zed_text_editor::settings::PasteMode;
+use zed_text_editor::{Editor, Point, Selection};
 
 #[test]
 fn test_empty_editor() {
@@ -91,6 +96,43 @@ fn test_move_line_start_end() {
     assert_eq!(editor.cursor(), Point::new(0, 11));
 }
 
+#[test]
+fn test_line_start_end_count_chars_not_bytes() {
+    let mut editor = Editor::from_text("日本語\nhello world");
+    editor.move_to_line_end();
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+    editor.move_to_line_start();
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+
+    // Going to a shorter line clamps to its length in chars
+    editor.set_cursor(Point::new(1, 8));
+    editor.move_to_line(0);
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+
+    // A cursor past the end of replaced text lands at the end of its last line
+    editor.set_cursor(Point::new(1, 8));
+    editor.replace_all("日本語");
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+}
+
+#[test]
+fn test_smart_home_toggles_indent_and_column_zero() {
+    let mut editor = Editor::from_text("    let x = 1;\n\t\n");
+    editor.set_cursor(Point::new(0, 10));
+
+    editor.move_to_line_indent_start();
+    assert_eq!(editor.cursor(), Point::new(0, 4));
+    editor.move_to_line_indent_start();
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+    editor.move_to_line_indent_start();
+    assert_eq!(editor.cursor(), Point::new(0, 4));
+
+    // Whitespace-only line: the indent is the whole line
+    editor.set_cursor(Point::new(1, 0));
+    editor.move_to_line_indent_start();
+    assert_eq!(editor.cursor(), Point::new(1, 1));
+}
+
 #[test]
 fn test_typing_simulation() {
     let mut editor = Editor::new();
@@ -300,4 +342,1981 @@ fn test_word_by_word_undo_multiple_words() {
 
     editor.redo();
     assert_eq!(editor.text(), "hello world ", "Second redo should restore 'world '");
-}
\ No newline at end of file
+}
+#[test]
+fn test_move_to_line_clamps_row_and_column() {
+    let mut editor = Editor::from_text("Hello World\nHi\nThird line");
+    editor.set_cursor(Point::new(0, 8));
+
+    editor.move_to_line(1);
+    assert_eq!(editor.cursor(), Point::new(1, 2));
+
+    editor.move_to_line(99);
+    assert_eq!(editor.cursor(), Point::new(2, 2));
+}
+
+#[test]
+fn test_duplicate_line() {
+    let mut editor = Editor::from_text("one\ntwo\nthree");
+    editor.set_cursor(Point::new(1, 2));
+
+    editor.duplicate_line();
+    assert_eq!(editor.text(), "one\ntwo\ntwo\nthree");
+    assert_eq!(editor.cursor(), Point::new(2, 2));
+
+    editor.undo();
+    assert_eq!(editor.text(), "one\ntwo\nthree");
+}
+
+#[test]
+fn test_delete_line() {
+    let mut editor = Editor::from_text("one\ntwo\nthree");
+    editor.set_cursor(Point::new(1, 1));
+    editor.delete_line();
+    assert_eq!(editor.text(), "one\nthree");
+    assert_eq!(editor.cursor(), Point::new(1, 1));
+
+    // Deleting the last line removes the newline before it
+    editor.delete_line();
+    assert_eq!(editor.text(), "one");
+    assert_eq!(editor.cursor(), Point::new(0, 1));
+
+    editor.undo();
+    assert_eq!(editor.text(), "one\nthree");
+}
+
+#[test]
+fn test_move_line_up_down() {
+    let mut editor = Editor::from_text("a\nb\nc");
+    editor.set_cursor(Point::new(2, 1));
+
+    editor.move_line_up();
+    assert_eq!(editor.text(), "a\nc\nb");
+    assert_eq!(editor.cursor(), Point::new(1, 1));
+
+    editor.move_line_down();
+    assert_eq!(editor.text(), "a\nb\nc");
+    assert_eq!(editor.cursor(), Point::new(2, 1));
+
+    // No-op at the boundaries
+    editor.move_line_down();
+    assert_eq!(editor.text(), "a\nb\nc");
+
+    // A selection ending at column 0 of the last line stays inside the buffer
+    let mut editor = Editor::from_text("a\nb");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(1, 0)));
+    editor.move_line_down();
+    assert_eq!(editor.text(), "b\na");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 0), Point::new(1, 1))
+    );
+}
+
+#[test]
+fn test_line_commands_use_selected_rows() {
+    let mut editor = Editor::from_text("a\nb\nc\nd");
+    editor.set_selection(Selection::new(Point::new(1, 0), Point::new(2, 1)));
+
+    editor.move_line_down();
+    assert_eq!(editor.text(), "a\nd\nb\nc");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(2, 0), Point::new(3, 1))
+    );
+
+    editor.undo();
+    assert_eq!(editor.text(), "a\nb\nc\nd");
+}
+
+fn rust_editor(text: &str) -> Editor {
+    let mut editor = Editor::from_text(text);
+    editor.set_file_path(Some("main.rs".into()));
+    editor
+}
+
+#[test]
+fn test_toggle_comment_single_line() {
+    let mut editor = rust_editor("fn main() {\n    let x = 1;\n}");
+    editor.set_cursor(Point::new(1, 8));
+
+    editor.toggle_comment();
+    assert_eq!(editor.text(), "fn main() {\n    // let x = 1;\n}");
+    assert_eq!(editor.cursor(), Point::new(1, 11));
+
+    editor.toggle_comment();
+    assert_eq!(editor.text(), "fn main() {\n    let x = 1;\n}");
+    assert_eq!(editor.cursor(), Point::new(1, 8));
+}
+
+#[test]
+fn test_toggle_comment_selection_uses_min_indent() {
+    let mut editor = rust_editor("if a {\n    b();\n\n}");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(3, 1)));
+
+    editor.toggle_comment();
+    assert_eq!(editor.text(), "// if a {\n//     b();\n\n// }");
+
+    // One undo step reverts the whole block
+    editor.undo();
+    assert_eq!(editor.text(), "if a {\n    b();\n\n}");
+}
+
+#[test]
+fn test_toggle_comment_python_and_unknown_language() {
+    let mut editor = Editor::from_text("x = 1");
+    editor.set_file_path(Some("script.py".into()));
+    editor.toggle_comment();
+    assert_eq!(editor.text(), "# x = 1");
+
+    let mut plain = Editor::from_text("notes");
+    plain.toggle_comment();
+    assert_eq!(plain.text(), "notes");
+}
+
+#[test]
+fn test_indent_selection() {
+    let mut editor = Editor::from_text("a\n\nb\nc");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(2, 1)));
+
+    editor.indent_selection();
+    assert_eq!(editor.text(), "    a\n\n    b\nc");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 0), Point::new(2, 5))
+    );
+
+    editor.undo();
+    assert_eq!(editor.text(), "a\n\nb\nc");
+}
+
+#[test]
+fn test_outdent_selection() {
+    let mut editor = Editor::from_text("        a\n  b\n\tc\nd");
+    editor.set_selection(Selection::new(Point::new(0, 8), Point::new(3, 1)));
+
+    editor.outdent_selection();
+    assert_eq!(editor.text(), "    a\nb\nc\nd");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 4), Point::new(3, 1))
+    );
+
+    // Nothing left to outdent on the last line - no transaction is recorded
+    editor.set_cursor(Point::new(3, 0));
+    editor.outdent_selection();
+    editor.undo();
+    assert_eq!(editor.text(), "        a\n  b\n\tc\nd");
+}
+
+#[test]
+fn test_go_to_last_edit_location_cycles_back() {
+    let mut editor = Editor::from_text("a\nb\nc\nd\ne\nf\ng");
+    assert!(!editor.go_to_last_edit_location());
+
+    editor.set_cursor(Point::new(0, 1));
+    editor.insert("x");
+    editor.set_cursor(Point::new(3, 1));
+    editor.insert("y");
+    editor.set_cursor(Point::new(6, 1));
+    editor.insert("z");
+    editor.set_cursor(Point::new(2, 0));
+
+    assert!(editor.go_to_last_edit_location());
+    assert_eq!(editor.cursor(), Point::new(6, 2));
+    editor.go_to_last_edit_location();
+    assert_eq!(editor.cursor(), Point::new(3, 2));
+    editor.go_to_last_edit_location();
+    assert_eq!(editor.cursor(), Point::new(0, 2));
+
+    // Wraps around to the most recent edit
+    editor.go_to_last_edit_location();
+    assert_eq!(editor.cursor(), Point::new(6, 2));
+}
+
+#[test]
+fn test_jump_to_line_records_where_it_came_from() {
+    let mut editor = Editor::from_text("a\nb\nc\nd\ne\nf\ng");
+    editor.set_cursor(Point::new(1, 1));
+
+    editor.jump_to_line(5);
+    assert_eq!(editor.cursor(), Point::new(5, 1));
+    assert!(editor.go_to_last_edit_location());
+    assert_eq!(editor.cursor(), Point::new(1, 1));
+}
+
+#[test]
+fn test_edit_locations_coalesce_nearby_rows() {
+    let mut editor = Editor::from_text("one\ntwo\nthree");
+    editor.set_cursor(Point::new(0, 3));
+    editor.insert("!");
+    editor.set_cursor(Point::new(1, 3));
+    editor.insert("?");
+
+    assert_eq!(editor.edit_locations(), &[Point::new(1, 4)]);
+
+    // Sitting on the only edit - jumping is a no-op, not an error
+    assert!(editor.go_to_last_edit_location());
+    assert_eq!(editor.cursor(), Point::new(1, 4));
+}
+
+#[test]
+fn test_edit_locations_follow_later_edits() {
+    let mut editor = Editor::from_text(
+        "one
+two
+three
+four",
+    );
+    editor.set_cursor(Point::new(3, 4));
+    editor.insert("!");
+    assert_eq!(editor.edit_locations(), vec![Point::new(3, 5)]);
+
+    // Lines added above move it; a new edit on its row replaces it
+    editor.set_cursor(Point::new(0, 0));
+    editor.insert("zero\n");
+    editor.set_cursor(Point::new(4, 0));
+    editor.insert(">> ");
+    assert_eq!(
+        editor.edit_locations(),
+        vec![Point::new(1, 0), Point::new(4, 3)]
+    );
+    editor.set_cursor(Point::new(2, 0));
+    editor.go_to_last_edit_location();
+    assert_eq!(editor.cursor(), Point::new(4, 3));
+    editor.go_to_last_edit_location();
+    assert_eq!(editor.cursor(), Point::new(1, 0));
+}
+
+#[test]
+fn test_vertical_movement_keeps_goal_column() {
+    let mut editor = Editor::from_text("long line here\nab\n\nanother long line");
+    editor.set_cursor(Point::new(0, 10));
+
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 2));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(2, 0));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(3, 10));
+
+    // Horizontal movement resets the goal
+    editor.move_left();
+    editor.move_up();
+    editor.move_up();
+    editor.move_up();
+    assert_eq!(editor.cursor(), Point::new(0, 9));
+}
+
+#[test]
+fn test_goal_column_after_line_end() {
+    let mut editor = Editor::from_text("short\na much longer line\nmid line");
+    editor.move_to_line_end();
+    assert_eq!(editor.cursor(), Point::new(0, 5));
+
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 18));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(2, 8));
+}
+
+#[test]
+fn test_insert_indent_respects_tab_settings() {
+    let mut editor = Editor::from_text("ab");
+    editor.set_cursor(Point::new(0, 2));
+    editor.insert_indent();
+    assert_eq!(editor.text(), "ab  ");
+
+    editor.set_indent(2, true);
+    editor.insert_indent();
+    assert_eq!(editor.text(), "ab  \t");
+    assert_eq!(editor.indent_unit(), "\t");
+
+    let mut editor = Editor::from_text("a\nb");
+    editor.set_indent(4, true);
+    editor.set_selection(Selection::new(Point::new(0, 1), Point::new(1, 1)));
+    editor.indent_selection();
+    assert_eq!(editor.text(), "\ta\n\tb");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 2), Point::new(1, 2))
+    );
+}
+
+#[test]
+fn test_vertical_movement_expands_tabs() {
+    let mut editor = Editor::from_text("\tx = 1\n        y = 2\n\t\tz");
+    editor.set_cursor(Point::new(0, 1));
+
+    // The tab spans four screen columns, so "x" lines up with "y"
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 4));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(2, 1));
+
+    editor.set_indent(8, true);
+    editor.set_cursor(Point::new(1, 8));
+    editor.move_up();
+    assert_eq!(editor.cursor(), Point::new(0, 1));
+}
+
+#[test]
+fn test_paste_reindents_relative_to_cursor_line() {
+    // Copied from the first non-blank character of a more deeply nested block
+    let copied = "if ready {\n            go();\n        }";
+
+    let mut editor = Editor::from_text("fn main() {\n    let x = 1;\n\n}");
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_cursor(Point::new(2, 0));
+    editor.paste(copied);
+    assert_eq!(
+        editor.text(),
+        "fn main() {\n    let x = 1;\n    if ready {\n        go();\n    }\n}"
+    );
+    assert_eq!(editor.cursor(), Point::new(4, 5));
+
+    // One undo step removes the whole paste
+    editor.undo();
+    assert_eq!(editor.text(), "fn main() {\n    let x = 1;\n\n}");
+}
+
+#[test]
+fn test_paste_whole_lines_and_verbatim() {
+    let mut editor = Editor::from_text("    a\n    b");
+    editor.set_cursor(Point::new(1, 4));
+    editor.paste("  x\n    y\n");
+    assert_eq!(editor.text(), "    a\n    x\n      y\n    b");
+
+    let mut editor = Editor::from_text("    a\n    b");
+    editor.set_cursor(Point::new(1, 4));
+    editor.paste_verbatim("  x\n    y\n");
+    assert_eq!(editor.text(), "    a\n      x\n    y\nb");
+
+    // Replaces the selection; CRLF is normalized
+    let mut editor = Editor::from_text("one two");
+    editor.set_selection(Selection::new(Point::new(0, 4), Point::new(0, 7)));
+    editor.paste("2\r\n3");
+    assert_eq!(editor.text(), "one 2\n3");
+}
+
+#[test]
+fn test_paste_mode_per_language() {
+    let modes = BTreeMap::from([("python".to_string(), PasteMode::Verbatim)]);
+
+    let mut editor = Editor::from_text("if x:\n    a\n    ");
+    editor.set_file_path(Some("script.py".into()));
+    editor.set_paste_modes(PasteMode::Reindent, modes.clone());
+    assert_eq!(editor.paste_mode(), PasteMode::Verbatim);
+    editor.set_cursor(Point::new(2, 4));
+    editor.paste_with_mode("b\n  c", None);
+    assert_eq!(editor.text(), "if x:\n    a\n    b\n  c");
+
+    // Other languages use the default; `format` without a formatter reindents
+    let mut editor = Editor::from_text("    a\n    ");
+    editor.set_file_path(Some("app.js".into()));
+    editor.set_paste_modes(PasteMode::Format, modes);
+    assert_eq!(editor.paste_mode(), PasteMode::Format);
+    editor.set_cursor(Point::new(1, 4));
+    editor.paste_with_mode("b\n  c", None);
+    assert_eq!(editor.text(), "    a\n    b\n    c");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_format_on_paste_runs_small_pastes_through_the_formatter() {
+    use std::path::Path;
+    use zed_text_editor::{Formatter, FormatterProvider};
+
+    // Stands in for a real formatter: spaces after commas
+    struct CommaSpacer;
+    impl FormatterProvider for CommaSpacer {
+        fn name(&self) -> &str {
+            "sed"
+        }
+        fn supported_extensions(&self) -> &[&str] {
+            &["rs"]
+        }
+        fn is_available(&self) -> bool {
+            true
+        }
+        fn args(&self, _file_path: Option<&Path>) -> Vec<String> {
+            vec!["s/,/, /g".to_string()]
+        }
+    }
+    let mut formatter = Formatter::new();
+    formatter.register(Box::new(CommaSpacer));
+
+    let mut editor = Editor::from_text("fn main() {\n    let x = 1;\n\n}");
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_paste_modes(PasteMode::Format, BTreeMap::new());
+    editor.set_cursor(Point::new(2, 0));
+    editor.paste_with_mode("        f(a,b);\n        g(c,d);", Some(&formatter));
+    assert_eq!(
+        editor.text(),
+        "fn main() {\n    let x = 1;\n    f(a, b);\n    g(c, d);\n}"
+    );
+
+    // A single line goes in as it is
+    editor.paste_with_mode("h(e,f)", Some(&formatter));
+    assert!(editor.text().contains("g(c, d);h(e,f)"));
+}
+
+fn selected_text(editor: &Editor) -> String {
+    let (start, end) = editor.selection().range();
+    let buffer = editor.buffer();
+    buffer.slice_bytes(
+        buffer.point_to_offset(start).value(),
+        buffer.point_to_offset(end).value(),
+    )
+}
+
+#[test]
+fn test_select_inside_and_around_brackets() {
+    use zed_text_editor::editor::TextObject;
+
+    let mut editor = Editor::from_text("foo(a, [b], c)");
+    editor.set_cursor(Point::new(0, 12));
+
+    assert!(editor.select_text_object(TextObject::Brackets, false));
+    assert_eq!(selected_text(&editor), "a, [b], c");
+
+    editor.set_cursor(Point::new(0, 9));
+    assert!(editor.select_text_object(TextObject::Brackets, true));
+    assert_eq!(selected_text(&editor), "[b]");
+
+    editor.set_cursor(Point::new(0, 1));
+    assert!(!editor.select_text_object(TextObject::Brackets, false));
+}
+
+#[test]
+fn test_select_word_and_paragraph_text_objects() {
+    use zed_text_editor::editor::TextObject;
+
+    let mut editor = Editor::from_text("one two  three\nfour\n\n\nfive");
+    editor.set_cursor(Point::new(0, 5));
+    assert!(editor.select_text_object(TextObject::Word, false));
+    assert_eq!(selected_text(&editor), "two");
+    assert!(editor.select_text_object(TextObject::Word, true));
+    assert_eq!(selected_text(&editor), "two  ");
+
+    // The last word on a line takes the space before it instead
+    editor.set_cursor(Point::new(0, 12));
+    assert!(editor.select_text_object(TextObject::Word, true));
+    assert_eq!(selected_text(&editor), "  three");
+
+    editor.set_cursor(Point::new(1, 2));
+    assert!(editor.select_text_object(TextObject::Paragraph, false));
+    assert_eq!(selected_text(&editor), "one two  three\nfour");
+    assert!(editor.select_text_object(TextObject::Paragraph, true));
+    assert_eq!(selected_text(&editor), "one two  three\nfour\n\n\n");
+
+    editor.set_cursor(Point::new(2, 0));
+    assert!(!editor.select_text_object(TextObject::Word, false));
+
+    // Double-click selects whatever run is under the pointer
+    editor.select_word_at(Point::new(0, 8));
+    assert_eq!(selected_text(&editor), "  ");
+    editor.select_word_at(Point::new(4, 4));
+    assert_eq!(selected_text(&editor), "five");
+}
+
+#[test]
+fn test_word_movement_and_word_chars_per_language() {
+    let mut editor = Editor::from_text("let $el = a.b_c;\nx");
+    editor.set_cursor(Point::new(0, 0));
+    let mut stops = Vec::new();
+    for _ in 0..9 {
+        editor.move_to_next_word();
+        stops.push(editor.cursor());
+    }
+    let columns: Vec<(usize, usize)> = stops.iter().map(|p| (p.row, p.column)).collect();
+    assert_eq!(
+        columns,
+        [(0, 3), (0, 5), (0, 7), (0, 9), (0, 11), (0, 12), (0, 15), (0, 16), (1, 0)]
+    );
+    editor.move_to_previous_word();
+    assert_eq!(editor.cursor(), Point::new(0, 16));
+    editor.move_to_previous_word();
+    assert_eq!(editor.cursor(), Point::new(0, 15));
+
+    // Every cursor moves
+    editor.set_cursor(Point::new(0, 0));
+    editor.add_cursor(Point::new(1, 0));
+    editor.move_to_next_word();
+    let cursors: Vec<Point> = editor.selections().iter().map(|s| s.end).collect();
+    assert!(cursors.contains(&Point::new(0, 3)) && cursors.contains(&Point::new(1, 1)));
+    editor.clear_extra_cursors();
+
+    // `$` joins words in JavaScript only, for movement, Ctrl+D and double-click
+    editor.set_file_path(Some("app.js".into()));
+    editor.set_word_chars(
+        "_".to_string(),
+        BTreeMap::from([("javascript".to_string(), "_$".to_string())]),
+    );
+    assert_eq!(editor.word_chars(), "_$");
+    editor.set_cursor(Point::new(0, 3));
+    editor.move_to_next_word();
+    assert_eq!(editor.cursor(), Point::new(0, 7));
+    editor.set_cursor(Point::new(0, 6));
+    assert!(editor.select_next_occurrence());
+    assert_eq!(selected_text(&editor), "$el");
+    editor.select_word_at(Point::new(0, 13));
+    assert_eq!(selected_text(&editor), "b_c");
+
+    editor.set_file_path(Some("main.rs".into()));
+    assert_eq!(editor.word_chars(), "_");
+    editor.select_word_at(Point::new(0, 5));
+    assert_eq!(selected_text(&editor), "el");
+}
+
+#[test]
+fn test_select_inside_quotes_with_and_without_grammar() {
+    use zed_text_editor::editor::TextObject;
+
+    // Rust grammar: raw strings and escaped quotes are handled by the parser
+    let mut editor = rust_editor("let s = r#\"say \"hi\"\"#;");
+    editor.set_cursor(Point::new(0, 14));
+    assert!(editor.select_text_object(TextObject::Quotes, false));
+    assert_eq!(selected_text(&editor), "say \"hi\"");
+    assert!(editor.select_text_object(TextObject::Quotes, true));
+    assert_eq!(selected_text(&editor), "r#\"say \"hi\"\"#");
+
+    // Plain text: quotes are paired on the cursor line
+    let mut editor = Editor::from_text("a 'one' b \"two \\\" x\"");
+    editor.set_cursor(Point::new(0, 13));
+    assert!(editor.select_text_object(TextObject::Quotes, false));
+    assert_eq!(selected_text(&editor), "two \\\" x");
+}
+
+#[test]
+fn test_jump_to_matching_bracket() {
+    let mut editor = Editor::from_text("fn f() {\n    g(1);\n}");
+    editor.set_cursor(Point::new(0, 7));
+
+    assert!(editor.jump_to_matching_bracket());
+    assert_eq!(editor.cursor(), Point::new(2, 0));
+    assert!(editor.jump_to_matching_bracket());
+    assert_eq!(editor.cursor(), Point::new(0, 7));
+
+    // Cursor just after a bracket also works
+    editor.set_cursor(Point::new(1, 8));
+    assert!(editor.jump_to_matching_bracket());
+    assert_eq!(editor.cursor(), Point::new(1, 5));
+
+    editor.set_cursor(Point::new(1, 2));
+    assert!(!editor.jump_to_matching_bracket());
+}
+
+#[test]
+fn test_type_text_coalesces_keystrokes() {
+    let text = "let v = vec![a, (b)];";
+    let mut typed = Editor::new();
+    for c in text.chars() {
+        typed.insert_with_autopairs(&c.to_string());
+    }
+
+    // One frame's worth of keystrokes lands as one edit per plain run
+    let mut batched = Editor::new();
+    let version = batched.version();
+    batched.type_text("let v = vec");
+    assert_eq!(batched.version(), version + 1);
+    batched.type_text("![a, (b)];");
+    assert_eq!(batched.text(), typed.text());
+    assert_eq!(batched.cursor(), typed.cursor());
+}
+
+#[test]
+fn test_autopairs_close_and_skip_over() {
+    let mut editor = Editor::new();
+    editor.insert_with_autopairs("(");
+    assert_eq!(editor.text(), "()");
+    assert_eq!(editor.cursor(), Point::new(0, 1));
+
+    editor.insert_with_autopairs("x");
+    editor.insert_with_autopairs(")");
+    assert_eq!(editor.text(), "(x)");
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+
+    // No closer in front of a word, no quote pair after a word
+    let mut editor = Editor::from_text("word");
+    editor.insert_with_autopairs("[");
+    assert_eq!(editor.text(), "[word");
+    let mut editor = Editor::from_text("don");
+    editor.set_cursor(Point::new(0, 3));
+    editor.insert_with_autopairs("'");
+    assert_eq!(editor.text(), "don'");
+}
+
+#[test]
+fn test_autopairs_wrap_selection() {
+    let mut editor = Editor::from_text("call foo now");
+    editor.set_selection(Selection::new(Point::new(0, 5), Point::new(0, 8)));
+
+    editor.insert_with_autopairs("\"");
+    assert_eq!(editor.text(), "call \"foo\" now");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 6), Point::new(0, 9))
+    );
+
+    editor.undo();
+    assert_eq!(editor.text(), "call foo now");
+}
+
+#[test]
+fn test_autopairs_per_language_and_overrides() {
+    // Rust doesn't pair single quotes (lifetimes); JavaScript pairs backticks
+    let mut editor = Editor::new();
+    editor.set_file_path(Some("lib.rs".into()));
+    editor.insert_with_autopairs("'");
+    assert_eq!(editor.text(), "'");
+
+    let mut editor = Editor::new();
+    editor.set_file_path(Some("app.js".into()));
+    editor.insert_with_autopairs("`");
+    assert_eq!(editor.text(), "``");
+
+    // User overrides replace the language's pairs; disabling turns pairing off
+    let mut overrides = BTreeMap::new();
+    overrides.insert("RUST".to_string(), AutoPairs::parse("() <>").unwrap());
+    let mut editor = Editor::new();
+    editor.set_file_path(Some("lib.rs".into()));
+    editor.set_autopairs(true, overrides.clone());
+    editor.insert_with_autopairs("<");
+    editor.insert_with_autopairs("{");
+    assert_eq!(editor.text(), "<{>");
+
+    editor.set_autopairs(false, overrides);
+    editor.insert_with_autopairs("(");
+    assert_eq!(editor.text(), "<{(>");
+
+    assert!(AutoPairs::parse("()[").is_err());
+}
+
+#[test]
+fn test_autopairs_backspace_removes_empty_pair() {
+    let mut editor = Editor::from_text("f{}");
+    editor.set_cursor(Point::new(0, 2));
+
+    editor.backspace_with_autopairs();
+    assert_eq!(editor.text(), "f");
+    assert_eq!(editor.cursor(), Point::new(0, 1));
+
+    // Not an empty pair - plain backspace
+    editor.backspace_with_autopairs();
+    assert_eq!(editor.text(), "");
+}
+
+#[test]
+fn test_transform_conversions() {
+    use zed_text_editor::editor::Transform;
+
+    assert_eq!(Transform::Uppercase.apply("Hello wörld"), "HELLO WÖRLD");
+    assert_eq!(Transform::Lowercase.apply("Hello World"), "hello world");
+    assert_eq!(
+        Transform::TitleCase.apply("the QUICK  fox"),
+        "The Quick  Fox"
+    );
+    assert_eq!(
+        Transform::SnakeCase.apply("parseHTTPResponse2Body"),
+        "parse_http_response2_body"
+    );
+    assert_eq!(
+        Transform::SnakeCase.apply("  Some words-here\nnextLine"),
+        "  some_words_here\nnext_line"
+    );
+    assert_eq!(
+        Transform::CamelCase.apply("user_account-id"),
+        "userAccountId"
+    );
+    assert_eq!(
+        Transform::CamelCase.apply("XMLHttpRequest"),
+        "xmlHttpRequest"
+    );
+}
+
+#[test]
+fn test_transform_selection_single_undo() {
+    use zed_text_editor::editor::Transform;
+
+    let mut editor = Editor::from_text("let myVariableName = 1;");
+    editor.set_selection(Selection::new(Point::new(0, 4), Point::new(0, 18)));
+
+    editor.transform_selection(Transform::SnakeCase);
+    assert_eq!(editor.text(), "let my_variable_name = 1;");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 4), Point::new(0, 20))
+    );
+
+    editor.undo();
+    assert_eq!(editor.text(), "let myVariableName = 1;");
+
+    // No selection: the word under the cursor
+    editor.set_cursor(Point::new(0, 6));
+    editor.transform_selection(Transform::Uppercase);
+    assert_eq!(editor.text(), "let MYVARIABLENAME = 1;");
+}
+
+#[test]
+fn test_undo_redo_restore_selections() {
+    use zed_text_editor::editor::Transform;
+
+    let mut editor = Editor::from_text("let myVariableName = 1;");
+    let selected = Selection::new(Point::new(0, 18), Point::new(0, 4));
+    editor.set_selection(selected);
+    editor.transform_selection(Transform::SnakeCase);
+    let converted = editor.selection();
+
+    editor.undo();
+    assert_eq!(editor.selection(), selected);
+    editor.redo();
+    assert_eq!(editor.selection(), converted);
+
+    // Every cursor comes back, not just the primary one
+    let mut editor = Editor::from_text("a\nb\nc");
+    editor.set_cursor(Point::new(0, 1));
+    editor.add_cursor(Point::new(2, 1));
+    editor.insert(";");
+    editor.set_cursor(Point::new(1, 0));
+    editor.undo();
+    assert_eq!(editor.text(), "a\nb\nc");
+    assert_eq!(editor.cursor_count(), 2);
+    editor.redo();
+    assert_eq!(
+        editor.selections(),
+        vec![
+            Selection::cursor(Point::new(0, 2)),
+            Selection::cursor(Point::new(2, 2))
+        ]
+    );
+}
+
+#[test]
+fn test_sort_selected_lines() {
+    use zed_text_editor::editor::SortOptions;
+
+    let mut editor = Editor::from_text("header\ncherry\nApple\nbanana\napple\nfooter");
+    editor.set_selection(Selection::new(Point::new(1, 0), Point::new(5, 0)));
+
+    editor.sort_lines(SortOptions::default());
+    assert_eq!(
+        editor.text(),
+        "header\nApple\napple\nbanana\ncherry\nfooter"
+    );
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 0), Point::new(4, 6))
+    );
+
+    editor.undo();
+    assert_eq!(
+        editor.text(),
+        "header\ncherry\nApple\nbanana\napple\nfooter"
+    );
+}
+
+#[test]
+fn test_sort_lines_options() {
+    use zed_text_editor::editor::SortOptions;
+
+    let mut editor = Editor::from_text("b\nA\na\nB\nb\n");
+    editor.sort_lines(SortOptions {
+        ascending: false,
+        unique: true,
+        case_insensitive: true,
+    });
+    // Whole buffer when nothing is selected; the trailing newline stays put
+    assert_eq!(editor.text(), "b\nA\n");
+
+    let mut editor = Editor::from_text("b\na\nb\nc");
+    editor.sort_lines(SortOptions {
+        unique: true,
+        ..SortOptions::default()
+    });
+    assert_eq!(editor.text(), "a\nb\nc");
+}
+
+#[test]
+fn test_reverse_lines() {
+    let mut editor = Editor::from_text("one\ntwo\nthree\nfour");
+    editor.set_selection(Selection::new(Point::new(0, 1), Point::new(2, 2)));
+
+    editor.reverse_lines();
+    assert_eq!(editor.text(), "three\ntwo\none\nfour");
+
+    editor.undo();
+    assert_eq!(editor.text(), "one\ntwo\nthree\nfour");
+}
+
+#[test]
+fn test_delete_to_line_end_and_start() {
+    use zed_text_editor::history::EditKind;
+
+    let mut editor = Editor::from_text("hello world\nnext");
+    editor.set_cursor(Point::new(0, 5));
+
+    editor.delete_to_line_end();
+    assert_eq!(editor.text(), "hello\nnext");
+    assert_eq!(editor.cursor(), Point::new(0, 5));
+    assert!(matches!(
+        &editor.last_transaction().unwrap().edit,
+        EditKind::Delete { text } if text == " world"
+    ));
+
+    // At the end of the line the newline goes
+    editor.delete_to_line_end();
+    assert_eq!(editor.text(), "hellonext");
+
+    editor.set_cursor(Point::new(0, 5));
+    editor.delete_to_line_start();
+    assert_eq!(editor.text(), "next");
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+
+    editor.undo();
+    assert_eq!(editor.text(), "hellonext");
+    editor.undo();
+    editor.undo();
+    assert_eq!(editor.text(), "hello world\nnext");
+}
+
+#[test]
+fn test_delete_to_line_start_joins_lines() {
+    let mut editor = Editor::from_text("one\ntwo");
+    editor.set_cursor(Point::new(1, 0));
+    editor.delete_to_line_start();
+    assert_eq!(editor.text(), "onetwo");
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+}
+
+#[test]
+fn test_transpose_chars() {
+    let mut editor = Editor::from_text("abcd");
+    editor.set_cursor(Point::new(0, 1));
+    editor.transpose_chars();
+    assert_eq!(editor.text(), "bacd");
+    assert_eq!(editor.cursor(), Point::new(0, 2));
+
+    // End of line swaps the last two characters
+    editor.set_cursor(Point::new(0, 4));
+    editor.transpose_chars();
+    assert_eq!(editor.text(), "badc");
+
+    editor.set_cursor(Point::new(0, 0));
+    editor.transpose_chars();
+    assert_eq!(editor.text(), "badc");
+
+    editor.undo();
+    assert_eq!(editor.text(), "bacd");
+}
+
+#[test]
+fn test_transpose_words() {
+    let mut editor = Editor::from_text("let foo = bar;");
+    editor.set_cursor(Point::new(0, 5));
+    editor.transpose_words();
+    assert_eq!(editor.text(), "let bar = foo;");
+    assert_eq!(editor.cursor(), Point::new(0, 13));
+
+    // After the last word, the last two words swap
+    editor.transpose_words();
+    assert_eq!(editor.text(), "let foo = bar;");
+
+    editor.undo();
+    assert_eq!(editor.text(), "let bar = foo;");
+}
+
+#[test]
+fn test_diff_lines_hunks() {
+    use zed_text_editor::util::diff::{diff_lines, LineHunk};
+
+    let old = ["a", "b", "c", "d", "e"];
+    let new = ["a", "B", "c", "d", "x", "y", "e"];
+    assert_eq!(
+        diff_lines(&old, &new),
+        vec![
+            LineHunk {
+                old: 1..2,
+                new: 1..2
+            },
+            LineHunk {
+                old: 4..4,
+                new: 4..6
+            },
+        ]
+    );
+    assert!(diff_lines(&old, &old).is_empty());
+    assert_eq!(
+        diff_lines(&["a", "b"], &["b"]),
+        vec![LineHunk {
+            old: 0..1,
+            new: 0..0
+        }]
+    );
+}
+
+#[test]
+fn test_replace_by_hunks_undoes_near_cursor_first() {
+    let old = "fn a(){\n}\nkeep\nfn b(){\n}\nkeep\nfn c(){\n}";
+    let new = "fn a() {\n}\nkeep\nfn b() {\n}\nkeep\nfn c() {\n}";
+    let mut editor = Editor::from_text(old);
+    editor.set_cursor(Point::new(6, 7));
+
+    editor.replace_all_by_hunks(new);
+    assert_eq!(editor.text(), new);
+    // Cursor stays on its line, clamped into the reformatted text
+    assert_eq!(editor.cursor(), Point::new(6, 7));
+
+    // The hunk under the cursor is undone first, the farthest one last
+    editor.undo();
+    assert_eq!(
+        editor.text(),
+        "fn a() {\n}\nkeep\nfn b() {\n}\nkeep\nfn c(){\n}"
+    );
+    editor.undo();
+    assert_eq!(
+        editor.text(),
+        "fn a() {\n}\nkeep\nfn b(){\n}\nkeep\nfn c(){\n}"
+    );
+    editor.undo();
+    assert_eq!(editor.text(), old);
+    assert_eq!(editor.cursor(), Point::new(6, 7));
+
+    editor.redo();
+    editor.redo();
+    editor.redo();
+    assert_eq!(editor.text(), new);
+}
+
+#[test]
+fn test_replace_by_hunks_maps_cursor_across_line_changes() {
+    let mut editor = Editor::from_text("use b;\nuse a;\n\n\nfn main() {\n    go();\n}");
+    editor.set_cursor(Point::new(5, 6));
+
+    editor.replace_all_by_hunks("use a;\nuse b;\n\nfn main() {\n    go();\n}");
+    assert_eq!(editor.text(), "use a;\nuse b;\n\nfn main() {\n    go();\n}");
+    // A blank line above was removed - the cursor follows its line up
+    assert_eq!(editor.cursor(), Point::new(4, 6));
+}
+
+#[test]
+fn test_replace_all_keeps_hunks_up_to_limit() {
+    use zed_text_editor::history::{EditKind, Hunk};
+
+    let old = "keep\n".repeat(1_000) + "old tail";
+    let new = old
+        .replacen("keep", "changed", 1)
+        .replace("old tail", "new tail");
+    let mut editor = Editor::from_text(&old);
+    editor.replace_all(&new);
+    assert_eq!(editor.text(), new);
+    // Only the changed lines are kept for undo, not two copies of the buffer
+    assert!(matches!(
+        &editor.last_transaction().unwrap().edit,
+        EditKind::ReplaceHunks { hunks } if hunks.iter().map(Hunk::size).sum::<usize>() < 40
+    ));
+    editor.undo();
+    assert_eq!(editor.text(), old);
+    editor.redo();
+    assert_eq!(editor.text(), new);
+
+    // Over the limit the step is a bare snapshot, and undo still restores it
+    editor.set_undo_hunk_limit(10);
+    editor.replace_all(&old);
+    assert!(matches!(
+        editor.last_transaction().unwrap().edit,
+        EditKind::Snapshot
+    ));
+    editor.undo();
+    assert_eq!(editor.text(), new);
+
+    // Edges: trailing lines removed or added, whole text, empty text
+    for (from, to) in [
+        ("a\nx\ny", "a"),
+        ("a", "a\nb"),
+        ("x", "y"),
+        ("", "a\n"),
+        ("a\nb\n", ""),
+    ] {
+        let mut editor = Editor::from_text(from);
+        editor.replace_all(to);
+        assert_eq!(editor.text(), to);
+        editor.undo();
+        assert_eq!(editor.text(), from);
+    }
+}
+
+#[test]
+fn test_subscribe_reports_edit_events() {
+    let mut editor = Editor::from_text("one\ntwo");
+    let events = editor.subscribe();
+
+    editor.set_cursor(Point::new(0, 3));
+    editor.insert("\n");
+    editor.insert("x");
+    editor.set_cursor(Point::new(2, 3));
+    editor.backspace();
+
+    let received: Vec<EditEvent> = events.try_iter().collect();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[0].old_range, 3..3);
+    assert_eq!(received[0].new_text, "\n");
+    assert_eq!(received[0].row_delta(), 1);
+    assert_eq!(received[1].start, Point::new(1, 0));
+    assert_eq!(received[2].old_text, "o");
+    assert_eq!(received[2].new_end, Point::new(2, 2));
+    assert_eq!(received[2].version, editor.version());
+
+    // Undo reports the span its transaction recorded
+    editor.undo();
+    let undo = events.try_recv().unwrap();
+    assert_eq!(undo.old_text, "");
+    assert_eq!(undo.new_text, "o");
+    assert_eq!(undo.start, Point::new(2, 2));
+
+    // Dropping the receiver unsubscribes without breaking edits
+    drop(events);
+    editor.insert("y");
+    assert_eq!(editor.text(), "one\nx\ntwoy");
+}
+
+#[test]
+fn test_undo_and_redo_report_the_recorded_span() {
+    let mut editor = Editor::from_text("x\n");
+    let events = editor.subscribe();
+
+    // The space joins the word's undo step; "cd" is still pending
+    editor.set_cursor(Point::new(1, 0));
+    editor.insert("ab");
+    editor.insert(" ");
+    editor.insert("cd");
+    events.try_iter().count();
+
+    editor.undo();
+    let undo: Vec<EditEvent> = events.try_iter().collect();
+    assert_eq!(undo.len(), 1);
+    assert_eq!((undo[0].old_range.clone(), undo[0].new_text.as_str()), (5..7, ""));
+    assert_eq!(undo[0].old_text, "cd");
+
+    editor.undo();
+    let undo = events.try_recv().unwrap();
+    assert_eq!((undo.old_range, undo.old_text.as_str()), (2..5, "ab "));
+
+    editor.redo();
+    let redo = events.try_recv().unwrap();
+    assert_eq!((redo.old_range, redo.new_text.as_str()), (2..2, "ab "));
+    assert_eq!(redo.new_end, Point::new(1, 3));
+
+    // A replace spans its first to last hunk
+    editor.replace_all("y\nab z");
+    let replaced = events.try_recv().unwrap();
+    assert_eq!((replaced.old_range, replaced.new_text.as_str()), (0..5, "y\nab z"));
+    editor.undo();
+    let undo = events.try_recv().unwrap();
+    assert_eq!((undo.old_text.as_str(), undo.new_text.as_str()), ("y\nab z", "x\nab "));
+    assert_eq!(editor.text(), "x\nab ");
+}
+
+#[test]
+fn test_problem_navigation_wraps_and_follows_edits() {
+    let mut editor = Editor::from_text("let a = 1;\nlet b = ;\nlet c = d;\n");
+    editor.set_diagnostics(vec![
+        Diagnostic::new(29..30, Severity::Warning, "unknown `d`"),
+        Diagnostic::new(19..20, Severity::Error, "expected expression"),
+    ]);
+    assert_eq!(
+        editor.diagnostic_on_row(1).map(|d| d.message.as_str()),
+        Some("expected expression")
+    );
+    assert!(editor.diagnostic_on_row(0).is_none());
+
+    let next = editor.go_to_next_problem().unwrap();
+    assert_eq!(next.severity, Severity::Error);
+    assert_eq!(editor.cursor(), Point::new(1, 8));
+    editor.go_to_next_problem();
+    assert_eq!(editor.cursor(), Point::new(2, 8));
+    // Wraps back to the first problem
+    editor.go_to_next_problem();
+    assert_eq!(editor.cursor(), Point::new(1, 8));
+    editor.go_to_prev_problem();
+    assert_eq!(editor.cursor(), Point::new(2, 8));
+
+    // Inserting a line above moves the diagnostics down with their text
+    editor.set_cursor(Point::new(0, 0));
+    editor.insert("\n");
+    assert!(editor.diagnostic_on_row(1).is_none());
+    assert_eq!(
+        editor.diagnostic_on_row(3).map(|d| d.message.as_str()),
+        Some("unknown `d`")
+    );
+}
+
+#[test]
+fn test_check_syntax_reports_parse_errors() {
+    let mut editor = Editor::from_text("fn main() {\n    let x = ;\n}\n");
+    editor.set_file_path(Some("main.rs".into()));
+    assert!(editor.check_syntax() > 0);
+    assert!(editor.diagnostic_on_row(1).is_some());
+
+    editor.set_cursor(Point::new(1, 12));
+    editor.insert("1");
+    assert_eq!(editor.check_syntax(), 0);
+    assert!(editor.go_to_next_problem().is_none());
+}
+
+#[test]
+fn test_builtin_code_actions_apply_as_one_undo_step() {
+    let mut editor = Editor::from_text("use b;  \nuse a;\nfn main() {}");
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_cursor(Point::new(2, 3));
+
+    let context = editor.code_action_context();
+    let actions = CodeActions::new().actions(&context);
+    let titles: Vec<&str> = actions.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Remove trailing whitespace",
+            "Add missing newline at end of file",
+            "Organize imports"
+        ]
+    );
+    assert!(actions[1].touches(&context.line));
+    assert!(!actions[0].touches(&context.line));
+
+    let version = editor.version();
+    for action in &actions {
+        let context = editor.code_action_context();
+        let current = CodeActions::new().actions(&context);
+        if let Some(fresh) = current.iter().find(|a| a.title == action.title) {
+            assert!(editor.apply_code_action(fresh));
+        }
+    }
+    assert_eq!(editor.text(), "use a;\nuse b;\nfn main() {}\n");
+    // The cursor stays on the same text
+    assert_eq!(editor.cursor(), Point::new(2, 3));
+    assert!(editor.version() > version);
+
+    // Each action is a single undo step
+    editor.undo();
+    assert_eq!(editor.text(), "use b;\nuse a;\nfn main() {}\n");
+}
+
+#[test]
+fn test_organize_imports_is_one_undo_step() {
+    let original = "use std::io;\nuse b::c;\nuse std::fmt;\n\nfn main() {}\n";
+    let mut editor = Editor::from_text(original);
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_cursor(Point::new(4, 3));
+
+    assert!(editor.organize_imports());
+    assert_eq!(
+        editor.text(),
+        "use std::fmt;\nuse std::io;\n\nuse b::c;\n\nfn main() {}\n"
+    );
+    assert_eq!(editor.cursor(), Point::new(5, 3));
+    assert!(!editor.organize_imports());
+
+    editor.undo();
+    assert_eq!(editor.text(), original);
+}
+
+#[test]
+fn test_bom_and_mixed_line_endings_are_reported_and_fixed() {
+    use zed_text_editor::editor::{LineEnding, TextFormat};
+
+    let mut editor = Editor::from_text("\u{feff}a\r\nb\r\nc\nd\r\n");
+    let format = editor.text_format();
+    assert_eq!(
+        format,
+        TextFormat {
+            bom: true,
+            lf: 1,
+            crlf: 3
+        }
+    );
+    assert!(format.is_mixed());
+    assert_eq!(format.line_ending(), LineEnding::CrLf);
+    // A "\r\n" split between two chunks still counts once
+    assert_eq!(TextFormat::detect_chunks(["a\r", "\nb\n"]).crlf, 1);
+
+    assert_eq!(editor.check_syntax(), 2);
+    assert_eq!(
+        editor.diagnostic_on_row(0).unwrap().severity,
+        Severity::Warning
+    );
+    assert!(editor
+        .diagnostic_on_row(2)
+        .unwrap()
+        .message
+        .starts_with("Mixed line endings"));
+
+    // On the offending line the fix comes first, tied to its warning
+    editor.set_cursor(Point::new(2, 0));
+    let actions = CodeActions::new().actions(&editor.code_action_context());
+    assert_eq!(actions[0].title, "Convert line endings to CRLF");
+    assert!(actions[0].diagnostic.is_some());
+    assert!(actions.iter().any(|a| a.title == "Remove byte order mark"));
+
+    assert!(editor.remove_bom());
+    assert!(!editor.remove_bom());
+    assert!(editor.unify_line_endings());
+    assert!(!editor.unify_line_endings());
+    assert_eq!(editor.text(), "a\r\nb\r\nc\r\nd\r\n");
+    assert_eq!(editor.check_syntax(), 0);
+
+    editor.undo();
+    assert_eq!(editor.text(), "a\r\nb\r\nc\nd\r\n");
+}
+
+#[test]
+fn test_text_format_follows_edits() {
+    use zed_text_editor::editor::TextFormat;
+
+    let mut editor = Editor::from_text("a\rx\nb\r\n");
+    let rescanned = |editor: &Editor| TextFormat::detect(&editor.text());
+    assert_eq!((editor.text_format().lf, editor.text_format().crlf), (1, 1));
+
+    // Deleting the "x" pairs the "\r" with the "\n" after it
+    editor.set_cursor(Point::new(0, 2));
+    editor.delete();
+    assert_eq!((editor.text_format().lf, editor.text_format().crlf), (0, 2));
+    editor.set_cursor(Point::new(0, 1));
+    editor.insert("\n");
+    editor.insert("z");
+    assert_eq!(editor.text_format(), rescanned(&editor));
+
+    editor.set_cursor(Point::new(0, 0));
+    editor.insert("\u{feff}");
+    assert!(editor.text_format().bom);
+    editor.undo();
+    editor.undo();
+    assert_eq!(editor.text_format(), rescanned(&editor));
+    editor.redo();
+    assert_eq!(editor.text_format(), rescanned(&editor));
+    editor.replace_all("\u{feff}one\r\ntwo\n");
+    assert_eq!(editor.text_format(), rescanned(&editor));
+    editor.undo();
+    assert_eq!(editor.text_format(), rescanned(&editor));
+}
+
+#[test]
+fn test_wrap_map_maps_points_to_display_rows() {
+    use zed_text_editor::render::{wrap_map::wrap_points, DisplayPoint};
+
+    // Breaks after the last space that fits, or mid-word when none does
+    assert_eq!(wrap_points("aaa bbb ccc", 8, 4), vec![8]);
+    assert_eq!(wrap_points("abcdefghij", 4, 4), vec![4, 8]);
+    assert!(wrap_points("short", 10, 4).is_empty());
+
+    let mut editor = Editor::from_text("one two three\nx");
+    editor.set_soft_wrap(Some(8));
+    let map = editor.wrap_map().unwrap();
+    assert_eq!(map.display_row_count(), 3);
+    assert_eq!(map.segment_at(1), (0, 8..13));
+    assert_eq!(map.segment_at(2), (1, 0..1));
+    // A column on the wrap boundary starts the next display row
+    assert_eq!(
+        map.to_display_point(Point::new(0, 8)),
+        DisplayPoint::new(1, 0)
+    );
+    assert_eq!(map.to_point(DisplayPoint::new(0, 20)), Point::new(0, 7));
+    assert_eq!(map.to_point(DisplayPoint::new(1, 20)), Point::new(0, 13));
+
+    // Edits re-wrap the touched lines
+    editor.set_cursor(Point::new(1, 1));
+    editor.insert(" yyyyyyyyyy");
+    assert_eq!(editor.wrap_map().unwrap().display_row_count(), 5);
+    editor.undo();
+    assert_eq!(editor.wrap_map().unwrap().display_row_count(), 3);
+
+    editor.set_soft_wrap(None);
+    assert!(editor.wrap_map().is_none());
+}
+
+#[test]
+fn test_wide_characters_take_two_screen_columns() {
+    use zed_text_editor::render::wrap_map::wrap_points;
+    use zed_text_editor::util::tabs::{column_at_display, display_column};
+
+    // CJK and emoji take two columns, combining marks none
+    assert_eq!(display_column("a你b", 2, 4), 3);
+    assert_eq!(display_column("🦀\tx", 2, 4), 4);
+    assert_eq!(display_column("e\u{301}x", 2, 4), 1);
+    // A screen column inside a wide character maps to that character
+    assert_eq!(column_at_display("a你b", 2, 4), 1);
+    assert_eq!(column_at_display("a你b", 3, 4), 2);
+
+    assert_eq!(wrap_points("你好世界", 4, 4), vec![2]);
+
+    // Moving down keeps the screen column, not the character count
+    let mut editor = Editor::from_text("你好世界\nabcdefgh");
+    editor.set_cursor(Point::new(0, 2));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 4));
+}
+
+#[test]
+fn test_vertical_movement_follows_display_rows_with_soft_wrap() {
+    let mut editor = Editor::from_text("one two three four\nend");
+    editor.set_soft_wrap(Some(8));
+    editor.set_cursor(Point::new(0, 1));
+
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(0, 9));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(0, 15));
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 1));
+    editor.move_up();
+    editor.move_up();
+    assert_eq!(editor.cursor(), Point::new(0, 9));
+
+    // Without soft wrap up/down move by buffer line again
+    editor.set_soft_wrap(None);
+    editor.move_down();
+    assert_eq!(editor.cursor(), Point::new(1, 3));
+}
+
+#[test]
+fn test_multi_cursor_edits_every_cursor_as_one_undo_step() {
+    let mut editor = Editor::from_text("ab\nab\nab");
+    editor.set_cursor(Point::new(0, 1));
+    assert!(editor.add_cursor_vertically(false));
+    assert!(editor.add_cursor_vertically(false));
+    assert!(!editor.add_cursor_vertically(false));
+    assert_eq!(editor.cursor_count(), 3);
+
+    editor.insert("x");
+    editor.insert("y");
+    assert_eq!(editor.text(), "axyb\naxyb\naxyb");
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+
+    editor.backspace();
+    assert_eq!(editor.text(), "axb\naxb\naxb");
+    editor.delete();
+    assert_eq!(editor.text(), "ax\nax\nax");
+    let cursors: Vec<Point> = editor.selections().iter().map(|s| s.end).collect();
+    assert_eq!(
+        cursors,
+        vec![Point::new(0, 2), Point::new(1, 2), Point::new(2, 2)]
+    );
+
+    // Each keystroke is one transaction covering every cursor, and undo
+    // brings every cursor back
+    editor.undo();
+    assert_eq!(editor.text(), "axb\naxb\naxb");
+    assert_eq!(editor.cursor_count(), 3);
+}
+
+#[test]
+fn test_typing_replaces_a_single_selection() {
+    let mut editor = Editor::from_text("hello world\nfoo");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(0, 5)));
+    editor.insert("X");
+    assert_eq!(editor.text(), "X world\nfoo");
+    assert_eq!(editor.selection(), Selection::cursor(Point::new(0, 1)));
+
+    editor.undo();
+    assert_eq!(editor.text(), "hello world\nfoo");
+}
+
+#[test]
+fn test_backspace_deletes_a_single_selection() {
+    let mut editor = Editor::from_text("hello world\nfoo");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(0, 5)));
+    editor.backspace();
+    assert_eq!(editor.text(), " world\nfoo");
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+}
+
+#[test]
+fn test_delete_deletes_a_single_selection() {
+    let mut editor = Editor::from_text("hello world\nfoo");
+    editor.set_selection(Selection::new(Point::new(0, 11), Point::new(1, 1)));
+    editor.delete();
+    assert_eq!(editor.text(), "hello worldoo");
+    assert_eq!(editor.cursor(), Point::new(0, 11));
+}
+
+#[test]
+fn test_expand_and_shrink_selection_by_syntax() {
+    let mut editor = Editor::from_text("fn main() { let x = foo(1, 2); }");
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_cursor(Point::new(0, 24));
+    let selected = |editor: &Editor| {
+        let (start, end) = editor.selection().range();
+        editor.text()[start.column..end.column].to_string()
+    };
+
+    let mut expansions = Vec::new();
+    while editor.expand_selection() {
+        expansions.push(selected(&editor));
+    }
+    assert_eq!(
+        expansions,
+        vec![
+            "1",
+            "(1, 2)",
+            "foo(1, 2)",
+            "let x = foo(1, 2);",
+            "{ let x = foo(1, 2); }",
+            "fn main() { let x = foo(1, 2); }",
+        ]
+    );
+
+    // Shrinking retraces the expansions back to the cursor
+    for expected in expansions.iter().rev().skip(1) {
+        assert!(editor.shrink_selection());
+        assert_eq!(&selected(&editor), expected);
+    }
+    assert!(editor.shrink_selection());
+    assert_eq!(editor.selection(), Selection::cursor(Point::new(0, 24)));
+    assert!(!editor.shrink_selection());
+}
+
+#[test]
+fn test_align_cursors() {
+    let text = "a = 1, b = 2\nlong = 3, c = 4\n\tx = 5, dd = 6";
+    let mut editor = Editor::from_text(text);
+    editor.set_indent(4, false);
+    // Before every "="; the tab counts as four columns
+    editor.set_cursor(Point::new(0, 2));
+    for (row, column) in [(0, 9), (1, 5), (1, 12), (2, 3), (2, 11)] {
+        editor.add_cursor(Point::new(row, column));
+    }
+
+    assert!(editor.align_cursors());
+    assert_eq!(
+        editor.text(),
+        "a     = 1, b  = 2\nlong  = 3, c  = 4\n\tx = 5, dd = 6"
+    );
+    assert_eq!(editor.cursor_count(), 6);
+    assert!(!editor.align_cursors());
+
+    editor.undo();
+    assert_eq!(editor.text(), text);
+}
+
+#[test]
+fn test_multi_cursor_on_one_line_and_merging() {
+    let mut editor = Editor::from_text("a,b,c");
+    editor.set_cursor(Point::new(0, 1));
+    editor.add_cursor(Point::new(0, 3));
+    editor.add_cursor(Point::new(0, 3));
+    assert_eq!(editor.cursor_count(), 2);
+
+    editor.insert(" ");
+    assert_eq!(editor.text(), "a ,b ,c");
+    editor.insert("\n");
+    assert_eq!(editor.text(), "a \n,b \n,c");
+    assert_eq!(editor.cursor(), Point::new(1, 0));
+
+    // Cursors that move onto each other merge
+    editor.move_to_line_start();
+    editor.move_up();
+    editor.move_up();
+    assert_eq!(editor.cursor_count(), 1);
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+
+    editor.add_cursor(Point::new(0, 2));
+    assert!(editor.clear_extra_cursors());
+    assert!(!editor.clear_extra_cursors());
+    editor.insert("z");
+    assert_eq!(editor.text(), "za \n,b \n,c");
+}
+
+#[test]
+fn test_cursor_position_label_counts_tabs_as_visual_columns() {
+    let mut editor = Editor::from_text("\tx = 1\nplain");
+    editor.set_indent(4, true);
+
+    editor.set_cursor(Point::new(0, 1));
+    assert_eq!(editor.cursor_visual_column(), 4);
+    assert_eq!(editor.cursor_position_label(), "Line 1, Col 5 (Char 2)");
+
+    editor.set_cursor(Point::new(1, 3));
+    assert_eq!(editor.cursor_position_label(), "Line 2, Col 4");
+}
+
+#[test]
+fn test_add_cursor_below_keeps_visual_column_across_tabs() {
+    let mut editor = Editor::from_text("\tab\n    cd\nx");
+    editor.set_indent(4, true);
+    editor.set_cursor(Point::new(0, 2));
+
+    // Column 2 after a tab is screen column 5, i.e. after "    c"
+    assert!(editor.add_cursor_vertically(false));
+    assert!(editor.add_cursor_vertically(false));
+    let cursors: Vec<Point> = editor.selections().iter().map(|s| s.end).collect();
+    assert_eq!(
+        cursors,
+        vec![Point::new(0, 2), Point::new(1, 5), Point::new(2, 1)]
+    );
+
+    editor.set_cursor(Point::new(1, 5));
+    assert!(editor.add_cursor_vertically(true));
+    assert_eq!(editor.selections()[0].end, Point::new(0, 2));
+}
+
+#[test]
+fn test_select_and_delete_to_bracket() {
+    // The ")" inside the string isn't a bracket to the parser
+    let mut editor = rust_editor("fn f() { g(\")\", x); }");
+    editor.set_cursor(Point::new(0, 14));
+    assert!(editor.select_to_bracket(false));
+    assert_eq!(selected_text(&editor), ", x");
+    editor.set_cursor(Point::new(0, 14));
+    assert!(editor.select_to_bracket(true));
+    assert_eq!(selected_text(&editor), ", x)");
+
+    // On a closing bracket the target is its opener, before the cursor
+    editor.set_cursor(Point::new(0, 17));
+    assert!(editor.select_to_bracket(true));
+    assert_eq!(selected_text(&editor), "(\")\", x");
+
+    editor.set_cursor(Point::new(0, 14));
+    assert!(editor.delete_to_bracket(true));
+    assert_eq!(editor.text(), "fn f() { g(\")\"; }");
+    editor.undo();
+    assert!(editor.delete_to_bracket(false));
+    assert_eq!(editor.text(), "fn f() { g(\")\"); }");
+
+    // Plain text falls back to scanning characters
+    let mut editor = Editor::from_text("[a, b]");
+    editor.set_cursor(Point::new(0, 2));
+    assert!(editor.delete_to_bracket(false));
+    assert_eq!(editor.text(), "[a]");
+    assert_eq!(editor.cursor(), Point::new(0, 2));
+}
+
+#[test]
+fn test_unwrap_surrounding_keeps_inner_text() {
+    let mut editor = rust_editor("let v = foo(bar[1], \"s\");");
+    editor.set_cursor(Point::new(0, 17));
+    assert!(editor.unwrap_surrounding());
+    assert_eq!(editor.text(), "let v = foo(bar1, \"s\");");
+    assert_eq!(editor.cursor(), Point::new(0, 16));
+
+    // Quotes nearer than the brackets win
+    editor.set_cursor(Point::new(0, 20));
+    assert!(editor.unwrap_surrounding());
+    assert_eq!(editor.text(), "let v = foo(bar1, s);");
+
+    editor.set_cursor(Point::new(0, 1));
+    assert!(!editor.unwrap_surrounding());
+    editor.undo();
+    assert_eq!(editor.text(), "let v = foo(bar1, \"s\");");
+}
+
+#[test]
+fn test_move_by_paragraph() {
+    let mut editor = Editor::from_text("a\nb\n\n\nc\nd");
+    editor.set_cursor(Point::new(0, 1));
+
+    editor.move_to_next_paragraph();
+    assert_eq!(editor.cursor(), Point::new(2, 0));
+    // From blank lines, the run is skipped first
+    editor.move_to_next_paragraph();
+    assert_eq!(editor.cursor(), Point::new(5, 1));
+    editor.move_to_next_paragraph();
+    assert_eq!(editor.cursor(), Point::new(5, 1));
+
+    editor.move_to_previous_paragraph();
+    assert_eq!(editor.cursor(), Point::new(3, 0));
+    editor.move_to_previous_paragraph();
+    assert_eq!(editor.cursor(), Point::new(0, 0));
+}
+
+#[test]
+fn test_select_next_and_all_occurrences_for_mass_rename() {
+    let mut editor = Editor::from_text("let foo = 1;\nfoo + foo");
+    editor.set_cursor(Point::new(1, 1));
+
+    // First press selects the word, then each press adds the next match,
+    // wrapping around to the top
+    assert!(editor.select_next_occurrence());
+    assert_eq!(selected_text(&editor), "foo");
+    assert_eq!(editor.cursor_count(), 1);
+    assert!(editor.select_next_occurrence());
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 6), Point::new(1, 9))
+    );
+    assert!(editor.select_next_occurrence());
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 4), Point::new(0, 7))
+    );
+    assert!(!editor.select_next_occurrence());
+    assert_eq!(editor.cursor_count(), 3);
+
+    // Typing replaces every selection in one undo step
+    editor.insert("bar");
+    assert_eq!(editor.text(), "let bar = 1;\nbar + bar");
+    editor.undo();
+    assert_eq!(editor.text(), "let foo = 1;\nfoo + foo");
+
+    editor.set_cursor(Point::new(0, 5));
+    assert_eq!(editor.select_all_occurrences(), 3);
+    assert_eq!(selected_text(&editor), "foo");
+    editor.insert("x");
+    assert_eq!(editor.text(), "let x = 1;\nx + x");
+
+    editor.set_cursor(Point::new(0, 3));
+    assert_eq!(editor.select_all_occurrences(), 1);
+    assert_eq!(selected_text(&editor), "let");
+}
+
+#[test]
+fn test_reflow_paragraph_keeps_comment_prefix_and_list_indent() {
+    let mut editor = Editor::from_text(
+        "fn main() {}\n    /// one two three four\n    /// five six\n    ///\n    /// seven\nfn f() {}",
+    );
+    editor.set_file_path(Some("main.rs".into()));
+    editor.set_cursor(Point::new(2, 8));
+    assert_eq!(editor.reflow_paragraph(21), Some(1));
+    assert_eq!(
+        editor.text(),
+        "fn main() {}\n    /// one two three\n    /// four five six\n    ///\n    /// seven\nfn f() {}"
+    );
+    assert_eq!(editor.cursor(), Point::new(2, 21));
+
+    // One undo step, and a no-op once the paragraph already fits
+    editor.undo();
+    assert_eq!(
+        editor.buffer().line(1).unwrap(),
+        "    /// one two three four"
+    );
+    editor.set_cursor(Point::new(4, 0));
+    assert_eq!(editor.reflow_paragraph(21), None);
+
+    let mut editor = Editor::from_text("- alpha beta gamma\n- delta\n\n1. one two three");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(3, 16)));
+    editor.reflow_paragraph(12);
+    assert_eq!(
+        editor.text(),
+        "- alpha beta\n  gamma\n- delta\n\n1. one two\n   three"
+    );
+}
+
+#[test]
+fn test_split_selection_into_lines() {
+    let mut editor = Editor::from_text("one\ntwo\nthree\nfour");
+    editor.set_selection(Selection::new(Point::new(0, 1), Point::new(2, 3)));
+    assert!(editor.split_selection_into_lines(false));
+    assert_eq!(
+        editor.selections(),
+        vec![
+            Selection::cursor(Point::new(0, 3)),
+            Selection::cursor(Point::new(1, 3)),
+            Selection::cursor(Point::new(2, 3)),
+        ]
+    );
+    assert_eq!(editor.cursor(), Point::new(2, 3));
+    editor.insert(";");
+    assert_eq!(editor.text(), "one;\ntwo;\nthr;ee\nfour");
+
+    // Selected parts of each line; a selection ending at column 0 skips that line
+    let mut editor = Editor::from_text("one\ntwo\nthree\nfour");
+    editor.set_selection(Selection::new(Point::new(0, 1), Point::new(2, 0)));
+    assert!(editor.split_selection_into_lines(true));
+    assert_eq!(
+        editor.selections(),
+        vec![
+            Selection::new(Point::new(0, 1), Point::new(0, 3)),
+            Selection::new(Point::new(1, 0), Point::new(1, 3)),
+        ]
+    );
+
+    editor.set_selection(Selection::new(Point::new(3, 0), Point::new(3, 2)));
+    assert!(!editor.split_selection_into_lines(false));
+    assert_eq!(editor.cursor_count(), 1);
+}
+
+#[test]
+fn test_select_block_uses_screen_columns() {
+    let mut editor = Editor::from_text("abcdef\n\tgh\nx\nabcdef");
+    editor.set_indent(4, false);
+    // Screen columns 2..5; the tab spans columns 0-3, "x" is too short
+    editor.select_block(Point::new(0, 2), Point::new(3, 5));
+    assert_eq!(
+        editor.selections(),
+        vec![
+            Selection::new(Point::new(0, 2), Point::new(0, 5)),
+            Selection::new(Point::new(1, 0), Point::new(1, 2)),
+            Selection::new(Point::new(3, 2), Point::new(3, 5)),
+        ]
+    );
+    assert_eq!(editor.cursor(), Point::new(3, 5));
+
+    editor.insert("_");
+    assert_eq!(editor.text(), "ab_f\n_h\nx\nab_f");
+
+    // A zero-width block puts a cursor on every row, at the end of short ones
+    editor.select_block(Point::new(3, 3), Point::new(0, 3));
+    assert_eq!(editor.cursor_count(), 4);
+    assert_eq!(editor.cursor(), Point::new(0, 3));
+    assert!(editor
+        .selections()
+        .contains(&Selection::cursor(Point::new(2, 1))));
+}
+
+#[test]
+fn test_click_and_drag_select_text() {
+    let mut editor = Editor::from_text("hello\nworld");
+    // Clicks past the end of a line or the buffer land on the nearest point
+    editor.click_at(Point::new(0, 40), false);
+    assert_eq!(editor.cursor(), Point::new(0, 5));
+    editor.click_at(Point::new(9, 2), false);
+    assert_eq!(editor.cursor(), Point::new(1, 2));
+
+    // Dragging moves the head and keeps the anchor where the press was
+    editor.click_at(Point::new(0, 1), false);
+    editor.select_to(Point::new(1, 3));
+    editor.select_to(Point::new(0, 4));
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 1), Point::new(0, 4))
+    );
+
+    // Shift+click extends from the same anchor
+    editor.click_at(Point::new(1, 2), true);
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 1), Point::new(1, 2))
+    );
+    editor.click_at(Point::new(1, 0), false);
+    assert!(editor.selection().is_empty());
+}
+
+#[test]
+fn test_move_selection_by_drag_and_drop() {
+    let mut editor = Editor::from_text("one two\nthree");
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(0, 4)));
+    // Dropping inside the selection does nothing
+    assert!(!editor.move_selection_to(Point::new(0, 2)));
+
+    assert!(editor.move_selection_to(Point::new(1, 5)));
+    assert_eq!(editor.text(), "two\nthreeone ");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 5), Point::new(1, 9))
+    );
+
+    // Moving back before the original place, then one undo restores it
+    assert!(editor.move_selection_to(Point::new(0, 0)));
+    assert_eq!(editor.text(), "one two\nthree");
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(0, 0), Point::new(0, 4))
+    );
+    editor.undo();
+    assert_eq!(editor.text(), "two\nthreeone ");
+}
+
+#[test]
+fn test_selection_row_spans() {
+    // Backwards selections paint the same as forwards ones
+    let selection = Selection::new(Point::new(2, 3), Point::new(0, 4));
+    assert_eq!(selection.row_span(0, 10), Some((4..10, true)));
+    assert_eq!(selection.row_span(1, 0), Some((0..0, true)));
+    assert_eq!(selection.row_span(2, 8), Some((0..3, false)));
+    assert_eq!(selection.row_span(3, 8), None);
+
+    // Ending at column 0 selects only the previous line's newline
+    let selection = Selection::new(Point::new(0, 2), Point::new(1, 0));
+    assert_eq!(selection.row_span(1, 5), None);
+    assert_eq!(Selection::cursor(Point::new(0, 1)).row_span(0, 5), None);
+}
+
+#[test]
+fn test_merge_conflicts_navigate_and_resolve() {
+    use zed_text_editor::editor::ConflictResolution;
+
+    let text = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nb\n<<<<<<< HEAD\nx\n||||||| base\nbase\n=======\ny\n>>>>>>> branch";
+    let mut editor = Editor::from_text(text);
+    let conflicts = editor.conflicts();
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[1].ours(), 8..9);
+    assert_eq!(conflicts[1].theirs(), 12..13);
+
+    assert_eq!(editor.go_to_conflict(true).map(|c| c.start), Some(1));
+    assert_eq!(editor.go_to_conflict(true).map(|c| c.start), Some(7));
+    assert_eq!(editor.go_to_conflict(true).map(|c| c.start), Some(1));
+    assert_eq!(editor.go_to_conflict(false).map(|c| c.start), Some(7));
+
+    // The last conflict ends the file: no stray newline is left behind
+    assert!(editor.resolve_conflict(9, ConflictResolution::Both));
+    assert_eq!(
+        editor.text(),
+        "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nb\nx\ny"
+    );
+    assert!(editor.resolve_conflict(3, ConflictResolution::Theirs));
+    assert_eq!(editor.text(), "a\ntheirs\nb\nx\ny");
+    assert_eq!(editor.cursor(), Point::new(1, 0));
+    assert!(!editor.resolve_conflict(1, ConflictResolution::Ours));
+
+    editor.undo();
+    assert_eq!(editor.conflicts().len(), 1);
+}
+
+#[test]
+fn test_select_lines_from_gutter() {
+    let mut editor = Editor::from_text("one\ntwo\nthree\nfour");
+
+    // A click selects the line with its newline
+    editor.select_lines(1, 1);
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 0), Point::new(2, 0))
+    );
+
+    // Dragging down or up keeps the pressed line and moves the head
+    editor.select_lines(1, 2);
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(1, 0), Point::new(3, 0))
+    );
+    editor.select_lines(1, 0);
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(2, 0), Point::new(0, 0))
+    );
+
+    // The last line has no newline; rows past the end clamp to it
+    editor.select_lines(2, 9);
+    assert_eq!(
+        editor.selection(),
+        Selection::new(Point::new(2, 0), Point::new(3, 4))
+    );
+}
+
+#[test]
+fn test_cursor_moves_and_deletes_by_grapheme() {
+    // e + combining acute, a family emoji (ZWJ sequence), a flag
+    let mut editor = Editor::from_text("e\u{301}👨‍👩‍👧🇳🇴\r\nx");
+    editor.set_cursor(Point::new(0, 0));
+
+    editor.move_right();
+    assert_eq!(editor.cursor(), Point::new(0, 2));
+    editor.move_right();
+    assert_eq!(editor.cursor(), Point::new(0, 7));
+    editor.move_right();
+    assert_eq!(editor.cursor(), Point::new(0, 9));
+    // \r\n is one step
+    editor.move_right();
+    assert_eq!(editor.cursor(), Point::new(1, 0));
+    editor.move_left();
+    assert_eq!(editor.cursor(), Point::new(0, 9));
+
+    editor.backspace();
+    assert_eq!(editor.text(), "e\u{301}👨‍👩‍👧\r\nx");
+    editor.set_cursor(Point::new(0, 0));
+    editor.delete();
+    assert_eq!(editor.text(), "👨‍👩‍👧\r\nx");
+    editor.move_right();
+    editor.backspace();
+    assert_eq!(editor.text(), "\r\nx");
+}
+
+#[test]
+fn test_observe_reports_opens_edits_cursor_moves_and_saves() {
+    let mut editor = Editor::from_text("one\ntwo");
+    let events = editor.observe();
+    // Subscribers get the same stream's edits and nothing else
+    let edits = editor.subscribe();
+
+    // A burst of movement is one event, sent when the front-end flushes
+    editor.set_cursor(Point::new(1, 0));
+    editor.move_right();
+    editor.move_right();
+    editor.flush_events();
+    editor.flush_events();
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![EditorEvent::CursorMoved {
+            selection: Selection::cursor(Point::new(1, 2)),
+            version: 0,
+        }]
+    );
+
+    editor.insert("!");
+    editor.flush_events();
+    editor.set_file_path(Some("notes.txt".into()));
+    editor.mark_saved();
+    let received: Vec<EditorEvent> = events.try_iter().collect();
+    assert_eq!(received.len(), 3);
+    let EditorEvent::Edited(edit) = &received[0] else {
+        panic!("expected an edit first, got {:?}", received[0]);
+    };
+    assert_eq!(
+        (edit.old_range.clone(), edit.new_text.as_str()),
+        (6..6, "!")
+    );
+    assert_eq!(edits.try_iter().collect::<Vec<_>>(), vec![edit.clone()]);
+    assert_eq!(
+        received[1],
+        EditorEvent::CursorMoved {
+            selection: Selection::cursor(Point::new(1, 3)),
+            version: 1,
+        }
+    );
+    assert_eq!(
+        received[2],
+        EditorEvent::Saved {
+            path: Some("notes.txt".into()),
+            version: 1,
+        }
+    );
+
+    // Observers follow the editor a file is opened into
+    let mut opened = Editor::from_text("fresh");
+    opened.set_file_path(Some("fresh.txt".into()));
+    editor.replace(opened);
+    editor.flush_events();
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![EditorEvent::Opened {
+            path: Some("fresh.txt".into()),
+            version: 0,
+        }]
+    );
+    editor.insert("!");
+    assert!(matches!(events.try_recv(), Ok(EditorEvent::Edited(_))));
+    assert_eq!(edits.try_iter().map(|edit| edit.new_text).collect::<Vec<_>>(), ["!"]);
+
+    // Dropping the receiver stops observing without breaking edits
+    drop(events);
+    editor.insert("?");
+    assert_eq!(editor.text(), "!?fresh");
+}
+
+#[test]
+fn test_selection_copy_cut_and_size() {
+    let mut editor = Editor::from_text("foo bar\nfoo baz\nqux");
+    assert_eq!(editor.selection_label(), None);
+    assert_eq!(editor.selected_text(), "");
+    assert_eq!(editor.cut(), None);
+
+    // A selection ending at a line start doesn't count that line
+    editor.set_selection(Selection::new(Point::new(0, 4), Point::new(2, 0)));
+    assert_eq!(editor.selected_text(), "bar\nfoo baz\n");
+    assert_eq!(
+        editor.selection_label().as_deref(),
+        Some("2 lines, 12 bytes selected")
+    );
+
+    // Several selections copy one per line and cut as one undo step
+    editor.set_selection(Selection::new(Point::new(0, 0), Point::new(0, 3)));
+    assert_eq!(editor.select_all_occurrences(), 2);
+    assert_eq!(
+        editor.selection_label().as_deref(),
+        Some("2 selections (2 lines, 6 bytes) selected")
+    );
+    assert_eq!(editor.cut().as_deref(), Some("foo\nfoo"));
+    assert_eq!(editor.text(), " bar\n baz\nqux");
+    assert_eq!(editor.selection_label(), None);
+    editor.undo();
+    assert_eq!(editor.text(), "foo bar\nfoo baz\nqux");
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use zed_text_editor::formatter::{FormatError, Sandbox};
+
+#[test]
+fn test_sandbox_invocation_env_and_working_dir() {
+    std::env::set_var("SANDBOX_TEST_SECRET", "hunter2");
+    let sandbox = Sandbox {
+        inherit_env: vec!["PATH".to_string(), "SANDBOX_TEST_MISSING".to_string()],
+        env: BTreeMap::from([("RUST_LOG".to_string(), "debug info".to_string())]),
+        nice: None,
+        ..Sandbox::default()
+    };
+    let args = vec!["--edition".to_string(), "2021".to_string()];
+    let invocation = sandbox.invocation("rustfmt", &args, Some(Path::new("/src/my app/main.rs")));
+
+    // Only listed variables that are set, plus the extra ones
+    let names: Vec<&str> = invocation
+        .env
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(names, vec!["PATH", "RUST_LOG"]);
+    assert_eq!(
+        invocation.working_dir.as_deref(),
+        Some(Path::new("/src/my app"))
+    );
+
+    let logged = invocation.to_string();
+    assert!(logged.starts_with("cd '/src/my app' && env -i PATH="));
+    assert!(logged.ends_with(" RUST_LOG='debug info' rustfmt --edition 2021"));
+    assert!(!logged.contains("hunter2"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sandbox_runs_with_clean_env_and_timeout() {
+    std::env::set_var("SANDBOX_TEST_LEAK", "1");
+    let sandbox = Sandbox::default();
+    let script = |code: &str| vec!["-c".to_string(), code.to_string()];
+
+    let output = sandbox
+        .invocation("sh", &script("cat; echo ${SANDBOX_TEST_LEAK:-clean}"), None)
+        .run("text\n")
+        .unwrap();
+    assert_eq!(output, "text\nclean\n");
+
+    let failed = sandbox
+        .invocation("sh", &script("echo oops >&2; exit 1"), None)
+        .run("");
+    assert!(
+        matches!(failed, Err(FormatError::ExecutionFailed(message)) if message.contains("oops"))
+    );
+
+    let slow = Sandbox {
+        timeout_seconds: 1,
+        ..Sandbox::default()
+    };
+    let result = slow.invocation("sleep", &["5".to_string()], None).run("");
+    assert!(matches!(result, Err(FormatError::TimedOut(_))));
+
+    let missing = sandbox
+        .invocation("no-such-formatter-binary", &[], None)
+        .run("");
+    assert!(matches!(missing, Err(FormatError::NotFound(_))));
+}
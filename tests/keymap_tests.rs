@@ -0,0 +1,331 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fs;
+use std::time::{Duration, Instant};
+use zed_text_editor::editor::{SortOptions, Transform};
+use zed_text_editor::keymap::{
+    Abandoned, CommandHistory, EditAction, KeyAction, KeyEvent, KeySequence, Modifiers,
+    PendingKeys, SEQUENCE_TIMEOUT,
+};
+use zed_text_editor::ui::key_event_from_crossterm;
+use zed_text_editor::{Chord, Command, Keymap};
+
+fn temp_keymap(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("zed-keymap-{}-{}.toml", name, std::process::id()))
+}
+
+#[test]
+fn test_chord_parse_and_display() {
+    let chord: Chord = "ctrl+shift+K".parse().unwrap();
+    assert_eq!(chord, Chord::new("K").ctrl().shift());
+    assert_eq!(chord.to_string(), "Ctrl+Shift+K");
+
+    let chord: Chord = "Shift + Alt + OpenBracket".parse().unwrap();
+    assert_eq!(chord.to_string(), "Alt+Shift+OpenBracket");
+    assert_eq!("F12".parse::<Chord>().unwrap(), Chord::new("F12"));
+
+    assert!("Ctrl+".parse::<Chord>().is_err());
+    assert!("Hyper+K".parse::<Chord>().is_err());
+}
+
+#[test]
+fn test_default_keymap_has_no_conflicts() {
+    let keymap = Keymap::defaults();
+    assert!(keymap.conflicts().is_empty());
+    assert_eq!(
+        keymap.command_for(&Chord::new("K").ctrl()),
+        Some(Command::DeleteToLineEnd)
+    );
+    assert_eq!(
+        keymap.command_for(&Chord::new("K").ctrl().shift()),
+        Some(Command::DeleteLine)
+    );
+    assert_eq!(keymap.command_for(&Chord::new("K")), None);
+
+    for command in Command::ALL {
+        assert_eq!(Command::from_id(command.id()), Some(command));
+    }
+}
+
+#[test]
+fn test_rebinding_reports_conflicts() {
+    let mut keymap = Keymap::defaults();
+    keymap.bind(Command::DuplicateLine, Chord::new("Z").ctrl());
+
+    assert_eq!(
+        keymap.conflicts(),
+        vec![(
+            KeySequence::from(Chord::new("Z").ctrl()),
+            vec![Command::Undo, Command::DuplicateLine]
+        )]
+    );
+    assert_eq!(
+        keymap.conflicts_with(Command::DuplicateLine),
+        vec![Command::Undo]
+    );
+    // The old chord is gone
+    assert_eq!(keymap.command_for(&Chord::new("D").ctrl().shift()), None);
+
+    keymap.reset(Command::DuplicateLine);
+    assert!(keymap.conflicts().is_empty());
+    assert_eq!(
+        keymap.sequences_for(Command::DuplicateLine),
+        vec![&KeySequence::from(Chord::new("D").ctrl().shift())]
+    );
+}
+
+#[test]
+fn test_keymap_save_and_load() {
+    let path = temp_keymap("roundtrip");
+    let _ = fs::remove_file(&path);
+    assert_eq!(Keymap::load(&path).unwrap(), Keymap::defaults());
+
+    let mut keymap = Keymap::defaults();
+    keymap.bind(Command::TransposeChars, Chord::new("T").ctrl().alt());
+    keymap.unbind(Command::ToggleProfiler);
+    keymap.save(&path).unwrap();
+
+    let loaded = Keymap::load(&path).unwrap();
+    assert_eq!(
+        loaded.command_for(&Chord::new("T").ctrl().alt()),
+        Some(Command::TransposeChars)
+    );
+    assert!(loaded.sequences_for(Command::ToggleProfiler).is_empty());
+
+    // Commands missing from the file keep their defaults
+    fs::write(&path, "undo = [\"Ctrl+U\"]\n").unwrap();
+    let loaded = Keymap::load(&path).unwrap();
+    assert_eq!(
+        loaded.sequences_for(Command::Undo),
+        vec![&KeySequence::from(Chord::new("U").ctrl())]
+    );
+    assert_eq!(
+        loaded.sequences_for(Command::Redo),
+        vec![&KeySequence::from(Chord::new("Y").ctrl())]
+    );
+
+    fs::write(&path, "no_such_command = []\n").unwrap();
+    assert!(Keymap::load(&path).is_err());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_key_sequences() {
+    let sequence: KeySequence = "Space f  s".parse().unwrap();
+    assert_eq!(sequence.to_string(), "Space F S");
+    assert!("".parse::<KeySequence>().is_err());
+    assert!("Space Hyper+K".parse::<KeySequence>().is_err());
+
+    let mut keymap = Keymap::defaults();
+    keymap.bind(Command::Save, sequence.clone());
+    keymap.bind(Command::Open, "Space F".parse::<KeySequence>().unwrap());
+    // A prefix of a longer binding isn't a conflict
+    assert!(keymap.conflicts().is_empty());
+
+    let space = KeyEvent::from_char(' ', Modifiers::default());
+    let f = KeyEvent::from_char('f', Modifiers::default());
+    let s = KeyEvent::from_char('s', Modifiers::default());
+    let x = KeyEvent::from_char('x', Modifiers::default());
+    let start = Instant::now();
+    let mut pending = PendingKeys::new();
+
+    assert_eq!(
+        pending.press(&keymap, &space, start).action,
+        KeyAction::Wait
+    );
+    assert_eq!(pending.press(&keymap, &f, start).action, KeyAction::Wait);
+    let hints = pending.hints(&keymap);
+    assert_eq!(hints, vec![("S".parse().unwrap(), Command::Save)]);
+    let dispatch = pending.press(&keymap, &s, start);
+    assert_eq!(dispatch.abandoned, None);
+    assert_eq!(dispatch.action, KeyAction::Run(Command::Save));
+    assert!(!pending.is_pending());
+    assert!(pending.hints(&keymap).is_empty());
+
+    // A key that breaks the sequence replays what was typed, then counts on
+    // its own
+    pending.press(&keymap, &space, start);
+    let dispatch = pending.press(&keymap, &x, start);
+    assert_eq!(
+        dispatch.abandoned,
+        Some(Abandoned::Replay(vec![space.clone()]))
+    );
+    assert_eq!(dispatch.action, KeyAction::Unbound);
+
+    // ... or runs the shorter binding it already spelled out
+    pending.press(&keymap, &space, start);
+    pending.press(&keymap, &f, start);
+    let dispatch = pending.press(&keymap, &x, start);
+    assert_eq!(dispatch.abandoned, Some(Abandoned::Run(Command::Open)));
+    assert_eq!(dispatch.action, KeyAction::Unbound);
+
+    // Waiting too long gives up on the sequence
+    pending.press(&keymap, &space, start);
+    assert_eq!(
+        pending.expire(&keymap, start + Duration::from_millis(10)),
+        None
+    );
+    assert_eq!(
+        pending.expire(&keymap, start + SEQUENCE_TIMEOUT),
+        Some(Abandoned::Replay(vec![space.clone()]))
+    );
+    assert!(!pending.is_pending());
+
+    // Single chords still run straight away
+    let ctrl_z = KeyEvent::new(
+        "z",
+        Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        },
+        None,
+    );
+    assert_eq!(
+        pending.press(&keymap, &ctrl_z, start).action,
+        KeyAction::Run(Command::Undo)
+    );
+
+    let path = temp_keymap("sequences");
+    keymap.save(&path).unwrap();
+    let loaded = Keymap::load(&path).unwrap();
+    assert_eq!(loaded.sequences_for(Command::Save), vec![&sequence]);
+    assert_eq!(
+        loaded.command_for_sequence(sequence.chords()),
+        Some(Command::Save)
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+fn crossterm_key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    key_event_from_crossterm(&crossterm::event::KeyEvent::new(code, modifiers)).unwrap()
+}
+
+#[test]
+fn test_key_event_normalization() {
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(KeyEvent::new("k", ctrl, None).key, "K");
+    assert_eq!(KeyEvent::new(",", ctrl, None).key, "Comma");
+    assert_eq!(KeyEvent::new("ArrowUp", ctrl, None).key, "Up");
+    assert_eq!(KeyEvent::new("Numpad7", ctrl, None).key, "7");
+    assert_eq!(KeyEvent::new("NumpadEnter", ctrl, None).key, "Enter");
+    assert_eq!(KeyEvent::new("NumpadSubtract", ctrl, None).key, "Minus");
+
+    // Cmd acts as Ctrl for keymap lookups
+    let cmd = Modifiers {
+        cmd: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(
+        KeyEvent::new("S", cmd, None).chord(),
+        Some(Chord::new("S").ctrl())
+    );
+    assert_eq!("Cmd+s".parse::<Chord>().unwrap(), Chord::new("S").ctrl());
+}
+
+#[test]
+fn test_altgr_text_is_typed_not_dispatched() {
+    let altgr = Modifiers {
+        ctrl: true,
+        alt: true,
+        ..Modifiers::default()
+    };
+    let at = KeyEvent::new("Q", altgr, Some('@'));
+    assert!(at.is_altgr_text());
+    assert_eq!(at.chord(), None);
+    assert_eq!(at.typed_text(), Some('@'));
+
+    // A real Ctrl+Alt shortcut still dispatches and types nothing
+    let shortcut = KeyEvent::new("Q", altgr, Some('q'));
+    assert_eq!(shortcut.chord(), Some(Chord::new("Q").ctrl().alt()));
+    assert_eq!(shortcut.typed_text(), None);
+}
+
+#[test]
+fn test_crossterm_and_egui_keys_share_chords() {
+    let keymap = Keymap::defaults();
+    let command = |key: KeyEvent| key.chord().and_then(|chord| keymap.command_for(&chord));
+
+    assert_eq!(
+        command(crossterm_key(KeyCode::Char('k'), KeyModifiers::CONTROL)),
+        Some(Command::DeleteToLineEnd)
+    );
+    // Terminals report Shift+K as an uppercase char
+    assert_eq!(
+        command(crossterm_key(KeyCode::Char('K'), KeyModifiers::CONTROL)),
+        Some(Command::DeleteLine)
+    );
+    // ...and Alt+Shift+[ as '{'
+    assert_eq!(
+        command(crossterm_key(KeyCode::Char('{'), KeyModifiers::ALT)),
+        Some(Command::SelectAroundBrackets)
+    );
+    assert_eq!(
+        command(crossterm_key(KeyCode::Up, KeyModifiers::ALT)),
+        Some(Command::ExpandSelection)
+    );
+    assert_eq!(
+        command(crossterm_key(
+            KeyCode::Up,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        )),
+        Some(Command::MoveLineUp)
+    );
+    assert_eq!(
+        command(crossterm_key(KeyCode::F(12), KeyModifiers::NONE)),
+        Some(Command::ToggleProfiler)
+    );
+
+    let typed = crossterm_key(KeyCode::Char('a'), KeyModifiers::NONE);
+    assert_eq!(command(typed.clone()), None);
+    assert_eq!(typed.typed_text(), Some('a'));
+}
+
+#[test]
+fn test_command_history_tracks_recent_and_last_edit() {
+    let mut history = CommandHistory::new();
+    assert_eq!(history.last_edit(), None);
+
+    history.record(Command::DuplicateLine);
+    history.record(Command::OpenSettings);
+    history.record(Command::DuplicateLine);
+    history.record(Command::RepeatLastCommand);
+
+    // Non-edit commands are recent but don't replace the repeatable edit
+    assert_eq!(
+        history.recent(),
+        &[Command::DuplicateLine, Command::OpenSettings]
+    );
+    assert_eq!(
+        history.last_edit(),
+        Some(EditAction::Command(Command::DuplicateLine))
+    );
+
+    let options = SortOptions {
+        unique: true,
+        ..SortOptions::default()
+    };
+    history.record_edit(EditAction::SortLines(options));
+    history.record(Command::JumpToMatchingBracket);
+    assert_eq!(history.last_edit(), Some(EditAction::SortLines(options)));
+
+    history.record_edit(EditAction::Transform(Transform::Uppercase));
+    assert_eq!(
+        history.last_edit(),
+        Some(EditAction::Transform(Transform::Uppercase))
+    );
+
+    let ordered = history.recent_first(&Command::ALL);
+    assert_eq!(
+        &ordered[..3],
+        &[
+            Command::JumpToMatchingBracket,
+            Command::DuplicateLine,
+            Command::OpenSettings
+        ]
+    );
+    assert_eq!(ordered.len(), Command::ALL.len());
+    assert_eq!(ordered[3], Command::Undo);
+}
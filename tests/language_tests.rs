@@ -0,0 +1,356 @@
+use std::path::Path;
+use zed_text_editor::syntax::{FileAssociations, LanguageId, LanguageRegistry};
+use zed_text_editor::{Editor, IndentCalculator, Settings};
+
+#[test]
+fn test_association_glob_patterns() {
+    let mut associations = FileAssociations::new();
+    associations.set("*.conf", "ini");
+    associations.set("Dockerfile*", "dockerfile");
+    associations.set("scripts/*.txt", "python");
+    associations.set("build?.log", "log");
+
+    assert_eq!(
+        associations.language_for(Path::new("/etc/nginx.conf")),
+        Some("ini")
+    );
+    assert_eq!(
+        associations.language_for(Path::new("Dockerfile.dev")),
+        Some("dockerfile")
+    );
+    assert_eq!(
+        associations.language_for(Path::new("scripts/run.txt")),
+        Some("python")
+    );
+    assert_eq!(
+        associations.language_for(Path::new("build1.log")),
+        Some("log")
+    );
+    assert_eq!(associations.language_for(Path::new("build12.log")), None);
+    assert_eq!(associations.language_for(Path::new("notes.txt")), None);
+}
+
+#[test]
+fn test_association_set_replaces_and_takes_priority() {
+    let mut associations = FileAssociations::new();
+    associations.set("*.txt", "python");
+    associations.set("notes*", "rust");
+    associations.set("*.txt", "javascript");
+
+    assert_eq!(associations.entries().len(), 2);
+    assert_eq!(
+        associations.language_for(Path::new("a.txt")),
+        Some("javascript")
+    );
+
+    assert!(associations.remove("*.txt"));
+    assert!(!associations.remove("*.txt"));
+    assert_eq!(
+        associations.language_for(Path::new("notes.txt")),
+        Some("rust")
+    );
+}
+
+#[test]
+fn test_registry_consults_associations_before_extension() {
+    let mut registry = LanguageRegistry::new();
+    assert_eq!(
+        registry.detect_language(Path::new("main.rs")).map(|l| l.id),
+        Some(LanguageId::Rust)
+    );
+
+    let mut associations = FileAssociations::new();
+    associations.set("*.rs", "Python");
+    associations.set("*.conf", "ini");
+    registry.set_associations(associations);
+
+    let detected = registry.detect_language(Path::new("main.rs")).map(|l| l.id);
+    assert_eq!(detected, Some(LanguageId::Python));
+    // Associated with a language we don't support - plain text
+    assert!(registry.detect_language(Path::new("app.conf")).is_none());
+}
+
+#[test]
+fn test_detect_from_shebang() {
+    let registry = LanguageRegistry::new();
+    let id = |line: &str| registry.detect_from_shebang(line).map(|l| l.id);
+
+    assert_eq!(id("#!/usr/bin/python3"), Some(LanguageId::Python));
+    assert_eq!(id("#!/usr/bin/env node"), Some(LanguageId::JavaScript));
+    assert_eq!(id("#!/usr/bin/env -S python -u"), Some(LanguageId::Python));
+    assert_eq!(id("#!/bin/sh"), None);
+    assert_eq!(id("print('hi')"), None);
+}
+
+#[test]
+fn test_editor_language_uses_associations_and_shebang() {
+    let mut editor = Editor::from_text("#!/usr/bin/env python\nprint('hi')");
+    assert_eq!(editor.language().map(|l| l.id), Some(LanguageId::Python));
+
+    editor.set_file_path(Some("build.conf".into()));
+    assert_eq!(editor.language().map(|l| l.id), Some(LanguageId::Python));
+
+    let mut associations = FileAssociations::new();
+    associations.set("*.conf", "rust");
+    editor.set_file_associations(associations);
+    assert_eq!(editor.language().map(|l| l.id), Some(LanguageId::Rust));
+}
+
+#[test]
+fn test_settings_roundtrip() {
+    let dir = std::env::temp_dir().join(format!("zed-settings-test-{}", std::process::id()));
+    let path = dir.join("settings.json");
+
+    // Missing file gives defaults
+    assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+
+    let mut settings = Settings::default();
+    settings.file_associations.set("Dockerfile*", "dockerfile");
+    settings.save(&path).unwrap();
+
+    assert_eq!(Settings::load(&path).unwrap(), settings);
+
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(Settings::load(&path).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_settings_editor_section_defaults() {
+    use zed_text_editor::settings::EditorSettings;
+
+    // Files written before the editor section existed still load
+    let settings: Settings = serde_json::from_str(r#"{"file_associations": []}"#).unwrap();
+    assert_eq!(settings.editor, EditorSettings::default());
+
+    let settings: Settings =
+        serde_json::from_str(r#"{"editor": {"font_size": 18.0, "hot_exit": false}}"#).unwrap();
+    assert_eq!(settings.editor.font_size, 18.0);
+    assert!(!settings.editor.hot_exit);
+    assert!(settings.editor.cursor_blink);
+}
+
+#[test]
+fn test_settings_search_matches_labels_and_keywords() {
+    use zed_text_editor::gui::settings_panel::matches_query;
+
+    assert!(matches_query("", "Font size", "text zoom"));
+    assert!(matches_query("FONT", "Font size", "text zoom"));
+    assert!(matches_query("zoom size", "Font size", "text zoom"));
+    assert!(!matches_query("font blink", "Font size", "text zoom"));
+}
+
+#[test]
+fn test_indent_calculator_uses_hard_tabs() {
+    let mut calculator = IndentCalculator::new();
+    assert_eq!(calculator.calculate_indent("fn main() {", 0, None), "    ");
+
+    calculator.set_indent(2, true);
+    assert_eq!(calculator.calculate_indent("\tif x {", 0, None), "\t\t");
+    assert_eq!(calculator.indent_unit(), "\t");
+
+    calculator.set_indent(2, false);
+    assert_eq!(calculator.calculate_indent("  if x {", 0, None), "    ");
+}
+
+#[test]
+fn test_organize_imports_groups_sorts_and_dedupes() {
+    use zed_text_editor::syntax::imports::organize_imports;
+
+    let registry = LanguageRegistry::new();
+    let parse = |id: LanguageId, text: &str| {
+        let config = registry.get_language(id).unwrap();
+        registry.create_parser(config).parse(text, None).unwrap()
+    };
+
+    let text =
+        "use std::io;\nuse crate::a;\n\nuse b::c;\nuse std::fmt;\nuse b::c;\n\nfn main() {}\n";
+    let edits = organize_imports(&parse(LanguageId::Rust, text), text, LanguageId::Rust);
+    assert_eq!(
+        edits,
+        vec![(
+            0..61,
+            "use std::fmt;\nuse std::io;\n\nuse b::c;\n\nuse crate::a;".to_string()
+        )]
+    );
+
+    let text =
+        "import requests\nfrom . import util\nimport os\nfrom __future__ import annotations\n";
+    let edits = organize_imports(&parse(LanguageId::Python, text), text, LanguageId::Python);
+    assert_eq!(
+        edits[0].1,
+        "from __future__ import annotations\n\nimport os\n\nimport requests\n\nfrom . import util"
+    );
+
+    let text = "import b from './b';\nimport React from 'react';\nimport a from './a';\n";
+    let edits = organize_imports(
+        &parse(LanguageId::JavaScript, text),
+        text,
+        LanguageId::JavaScript,
+    );
+    assert_eq!(
+        edits[0].1,
+        "import React from 'react';\n\nimport a from './a';\nimport b from './b';"
+    );
+
+    // A comment splits blocks; organized imports produce no edits
+    let text = "import os\nimport sys\n# local\nimport app\n";
+    assert!(
+        organize_imports(&parse(LanguageId::Python, text), text, LanguageId::Python).is_empty()
+    );
+}
+
+#[test]
+fn test_modelines_set_indent_language_and_wrap() {
+    use zed_text_editor::settings::Modeline;
+
+    let vim = Modeline::parse("# vim: set ts=8 sw=2 et ft=python nowrap: trailing").unwrap();
+    assert_eq!(vim.tab_size, Some(2));
+    assert_eq!(vim.hard_tabs, Some(false));
+    assert_eq!(vim.language.as_deref(), Some("python"));
+    assert_eq!(vim.soft_wrap, Some(false));
+
+    let emacs = Modeline::parse("// -*- mode: js; tab-width: 3; indent-tabs-mode: t -*-").unwrap();
+    assert_eq!(emacs.tab_size, Some(3));
+    assert_eq!(emacs.hard_tabs, Some(true));
+    assert_eq!(emacs.language.as_deref(), Some("js"));
+    assert_eq!(
+        Modeline::parse(";; -*- python-mode -*-")
+            .unwrap()
+            .language
+            .as_deref(),
+        Some("python")
+    );
+
+    // "vim:" must start a word, and a coding cookie alone sets nothing
+    assert_eq!(Modeline::parse("let envim: ts=2"), None);
+    assert_eq!(Modeline::parse("# -*- coding: utf-8 -*-"), None);
+
+    // Only the first and last five lines are searched
+    let mut lines = vec!["x"; 12];
+    lines[6] = "# vim: ts=2";
+    assert_eq!(Modeline::find(&lines), None);
+    lines[8] = "# vim: ts=2";
+    assert_eq!(Modeline::find(&lines).unwrap().tab_size, Some(2));
+
+    let mut editor = Editor::from_text("print('hi')\n# vim: ft=py\n");
+    editor.set_file_path(Some("notes.txt".into()));
+    assert!(editor.language().is_none());
+    let modeline = editor.modeline().unwrap();
+    editor.set_language_override(modeline.language);
+    assert_eq!(editor.language().map(|l| l.id), Some(LanguageId::Python));
+}
+
+#[test]
+fn test_editorconfig_sections_and_nearer_files_win() {
+    use zed_text_editor::editor::LineEnding;
+    use zed_text_editor::settings::EditorConfig;
+
+    let text = "root = true\n\n[*]\nend_of_line = lf\nindent_style = space\nindent_size = 4\n\n\
+                # Later sections win\n[*.{js,ts}]\nindent_size = 2\n\n[Makefile]\nindent_style = tab\n\
+                indent_size = tab\ntab_width = 8\n\n[/docs/*.md]\nend_of_line = CRLF\n";
+    let (js, root) = EditorConfig::parse(text, "src/app.ts");
+    assert!(root);
+    assert_eq!(js.tab_size, Some(2));
+    assert_eq!(js.hard_tabs, Some(false));
+    assert_eq!(js.line_ending, Some(LineEnding::Lf));
+    let (make, _) = EditorConfig::parse(text, "Makefile");
+    assert_eq!((make.tab_size, make.hard_tabs), (Some(8), Some(true)));
+    assert_eq!(
+        EditorConfig::parse(text, "docs/guide.md").0.line_ending,
+        Some(LineEnding::CrLf)
+    );
+    assert_eq!(
+        EditorConfig::parse(text, "src/docs/guide.md").0.line_ending,
+        Some(LineEnding::Lf)
+    );
+    assert_eq!(
+        EditorConfig::parse("[*]\nindent_size = 3", "a.rs"),
+        (
+            EditorConfig {
+                tab_size: Some(3),
+                ..EditorConfig::default()
+            },
+            false
+        )
+    );
+
+    // Nearer files win, and a root file ends the search
+    let dir = std::env::temp_dir().join(format!("zed-editorconfig-{}", std::process::id()));
+    let project = dir.join("project");
+    std::fs::create_dir_all(project.join("sub")).unwrap();
+    std::fs::write(dir.join(".editorconfig"), "[*]\nindent_style = tab\n").unwrap();
+    std::fs::write(
+        project.join(".editorconfig"),
+        "root = true\n[*]\nindent_size = 4\nend_of_line = crlf\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.join("sub/.editorconfig"),
+        "[*.py]\nindent_size = 2\n",
+    )
+    .unwrap();
+    let config = EditorConfig::for_path(&project.join("sub/new.py"));
+    assert_eq!(
+        config,
+        EditorConfig {
+            tab_size: Some(2),
+            hard_tabs: None,
+            line_ending: Some(LineEnding::CrLf),
+        }
+    );
+    assert_eq!(
+        EditorConfig::for_path(&project.join("sub/new.rs")).tab_size,
+        Some(4)
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_new_files_start_from_templates_in_the_project_line_ending() {
+    use zed_text_editor::editor::LineEnding;
+
+    let mut settings = Settings::default();
+    settings
+        .editor
+        .file_templates
+        .insert("Rust".to_string(), "fn main() {\n    $0\n}\n".to_string());
+    let template = settings.editor.file_template("rust").unwrap();
+    assert!(settings.editor.file_template("Python").is_none());
+
+    let editor = Editor::from_template(template, LineEnding::CrLf);
+    assert_eq!(editor.text(), "fn main() {\r\n    \r\n}\r\n");
+    assert_eq!(editor.cursor(), zed_text_editor::Point::new(1, 4));
+    assert!(!editor.can_undo());
+
+    let editor = Editor::from_template("no cursor\r\n", LineEnding::Lf);
+    assert_eq!(editor.text(), "no cursor\n");
+    assert_eq!(editor.cursor(), zed_text_editor::Point::new(0, 0));
+
+    // Saving converts what was typed since to the project's ending
+    let mut editor = Editor::from_text("a\r\nb\nc");
+    assert!(editor.convert_line_endings(LineEnding::CrLf));
+    assert_eq!(editor.text(), "a\r\nb\r\nc");
+    assert!(!editor.convert_line_endings(LineEnding::CrLf));
+    editor.undo();
+    assert_eq!(editor.text(), "a\r\nb\nc");
+}
+
+#[test]
+fn test_long_lines_stay_unhighlighted() {
+    use zed_text_editor::syntax::InstantHighlighter;
+
+    let long = format!("let x = \"{}\";\n", "a".repeat(200));
+    let text = format!("let a = 1;\n{}let b = 2;\n", long);
+    let highlighter = InstantHighlighter::new();
+
+    let all = highlighter.highlight_lines(&text, "rust", usize::MAX);
+    let capped = highlighter.highlight_lines(&text, "rust", 100);
+    let long_line = 11..11 + long.len();
+    assert!(all.iter().any(|h| long_line.contains(&h.start)));
+    assert!(!capped.iter().any(|h| long_line.contains(&h.start)));
+    // The lines around it keep their highlights, at their own offsets
+    assert_eq!(capped.len(), 4);
+    assert_eq!(&text[capped[2].start..capped[2].end], "let");
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use zed_text_editor::notifications::{Level, NotificationAction, Notifications, HISTORY_LIMIT};
+
+#[test]
+fn test_toasts_time_out_into_history() {
+    let mut notifications = Notifications::new();
+    notifications.info("Saved");
+    notifications
+        .error("Could not save main.rs")
+        .action(NotificationAction::RetrySave);
+    let created = notifications.history()[1].created;
+    assert_eq!(notifications.history()[1].actions[0].label(), "Retry");
+
+    // Info toasts go first; errors stay up longest
+    let toasts = |after: u64| {
+        notifications
+            .toasts(created + Duration::from_secs(after))
+            .map(|n| n.level)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(toasts(0), vec![Level::Info, Level::Error]);
+    assert_eq!(toasts(4), vec![Level::Error]);
+    assert_eq!(toasts(11), vec![]);
+
+    // Dismissing hides a toast but keeps it in the history
+    let id = notifications.history()[1].id;
+    notifications.dismiss(id);
+    notifications.dismiss_info();
+    assert_eq!(notifications.toasts(created).count(), 0);
+    assert_eq!(notifications.history().len(), 2);
+
+    for n in 0..HISTORY_LIMIT {
+        notifications.warn(format!("warning {}", n));
+    }
+    assert_eq!(notifications.history().len(), HISTORY_LIMIT);
+    assert_eq!(notifications.history()[0].message, "warning 0");
+}
@@ -0,0 +1,43 @@
+use std::io::ErrorKind;
+use zed_text_editor::io::read_file_with_progress;
+use zed_text_editor::progress::Progress;
+
+#[test]
+fn test_progress_tasks_report_cancel_and_finish() {
+    let progress = Progress::new();
+    let load = progress.start("Loading big.log");
+    let format = progress.start("Formatting main.rs");
+    load.set_fraction(0.25);
+
+    let tasks = progress.tasks();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].name, "Loading big.log");
+    assert_eq!(tasks[0].fraction, Some(0.25));
+    assert_eq!(tasks[1].fraction, None);
+
+    // The UI cancels through the registry; the worker sees it on its handle
+    progress.cancel(format.id());
+    assert!(format.cancel_handle().is_cancelled());
+    assert!(!load.is_cancelled());
+    assert!(progress.tasks()[1].cancelled);
+
+    // Dropping a handle finishes its task
+    drop(format);
+    assert_eq!(progress.tasks().len(), 1);
+    drop(load);
+    assert!(!progress.is_busy());
+
+    // Reading a file reports the fraction read and stops once cancelled
+    let path = std::env::temp_dir().join(format!("progress-{}.txt", std::process::id()));
+    std::fs::write(&path, "héllo\n".repeat(100_000)).unwrap();
+    let task = progress.start("Loading");
+    let text = read_file_with_progress(&path, &task).unwrap();
+    assert_eq!(text.len(), "héllo\n".len() * 100_000);
+    assert_eq!(progress.tasks()[0].fraction, Some(1.0));
+    progress.cancel(task.id());
+    let Err(error) = read_file_with_progress(&path, &task) else {
+        panic!("a cancelled load should stop");
+    };
+    assert_eq!(error.kind(), ErrorKind::Interrupted);
+    std::fs::remove_file(&path).ok();
+}
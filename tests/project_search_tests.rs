@@ -0,0 +1,72 @@
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+use zed_text_editor::gui::project_search_panel::SEARCH_DEBOUNCE;
+use zed_text_editor::gui::ProjectSearchPanel;
+use zed_text_editor::io::search_project;
+use zed_text_editor::progress::Progress;
+
+#[test]
+fn test_new_query_cancels_running_search() {
+    let root = std::env::temp_dir().join(format!("project-search-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target")).unwrap();
+    std::fs::write(root.join("src/main.rs"), "fn main() {\n    run();\n}\n").unwrap();
+    std::fs::write(root.join("notes.txt"), "run the tests\n").unwrap();
+    std::fs::write(root.join("target/built.rs"), "run();\n").unwrap();
+
+    // Build directories are skipped and matches come in path order
+    let progress = Progress::new();
+    let task = progress.start("Searching");
+    let matches = search_project(&root, "run", &task).unwrap();
+    let found: Vec<(String, usize)> = matches
+        .iter()
+        .map(|m| {
+            (
+                m.path.strip_prefix(&root).unwrap().display().to_string(),
+                m.line,
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [("notes.txt".to_string(), 0), ("src/main.rs".to_string(), 1)]
+    );
+    assert_eq!(matches[1].text, "run();");
+    progress.cancel(task.id());
+    let error = search_project(&root, "run", &task).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Interrupted);
+    let first_task = task.id();
+    drop(task);
+
+    // Queries typed in quick succession only start one search, after the debounce
+    let mut panel = ProjectSearchPanel::new();
+    panel.set_root(root.clone());
+    let start = Instant::now();
+    panel.set_query("ru", &progress, start);
+    panel.poll(&progress, start + SEARCH_DEBOUNCE / 2);
+    panel.set_query("run", &progress, start + SEARCH_DEBOUNCE / 2);
+    panel.poll(&progress, start + SEARCH_DEBOUNCE);
+    assert!(!panel.is_searching());
+    assert!(panel.is_busy());
+    panel.poll(&progress, start + SEARCH_DEBOUNCE * 2);
+    assert!(panel.is_searching());
+    assert_eq!(panel.search_task(), Some(first_task + 1));
+
+    // Editing the query cancels the search in flight
+    let task = panel.search_task().unwrap();
+    panel.set_query("main", &progress, start + SEARCH_DEBOUNCE * 2);
+    assert!(!panel.is_searching());
+    assert_eq!(panel.search_task(), None);
+    assert!(progress.tasks().iter().all(|t| t.id != task || t.cancelled));
+
+    let later = start + SEARCH_DEBOUNCE * 4;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    panel.poll(&progress, later);
+    while panel.is_busy() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+        panel.poll(&progress, later);
+    }
+    assert_eq!(panel.results().len(), 1);
+    assert_eq!(panel.results()[0].text, "fn main() {");
+    std::fs::remove_dir_all(&root).ok();
+}
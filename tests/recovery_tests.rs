@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+use zed_text_editor::io::{RecoveryEntry, RecoveryJournal};
+use zed_text_editor::Editor;
+
+fn journal_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zed-recovery-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_recovery_roundtrip() {
+    let journal = RecoveryJournal::new(journal_dir("roundtrip"));
+    let entries = vec![
+        RecoveryEntry {
+            path: Some(PathBuf::from("/tmp/notes.txt")),
+            text: "first line\nsecond line\n".to_string(),
+        },
+        RecoveryEntry {
+            path: None,
+            text: "untitled".to_string(),
+        },
+    ];
+
+    journal.save_all(&entries).unwrap();
+    assert_eq!(journal.load_all().unwrap(), entries);
+
+    journal.clear().unwrap();
+    assert!(journal.load_all().unwrap().is_empty());
+}
+
+#[test]
+fn test_recovery_missing_dir_is_empty() {
+    let journal = RecoveryJournal::new(journal_dir("missing"));
+    assert!(journal.load_all().unwrap().is_empty());
+    journal.clear().unwrap();
+}
+
+#[test]
+fn test_save_all_replaces_previous_entries() {
+    let journal = RecoveryJournal::new(journal_dir("replace"));
+    let entry = |text: &str| RecoveryEntry {
+        path: None,
+        text: text.to_string(),
+    };
+
+    journal.save_all(&[entry("a"), entry("b")]).unwrap();
+    journal.save_all(&[entry("c")]).unwrap();
+    assert_eq!(journal.load_all().unwrap(), vec![entry("c")]);
+}
+
+#[test]
+fn test_editor_dirty_tracking() {
+    let mut editor = Editor::from_text("hello");
+    assert!(!editor.is_dirty());
+
+    editor.insert("x");
+    assert!(editor.is_dirty());
+
+    editor.mark_saved();
+    assert!(!editor.is_dirty());
+
+    editor.mark_dirty();
+    assert!(editor.is_dirty());
+}
@@ -141,3 +141,352 @@ fn test_multiple_edits() {
     rope.delete(5, 6); // Remove space
     assert_eq!(rope.to_string(), "HelloWorld!");
 }
+
+/// Hands out one byte per read so every multi-byte char straddles reads
+struct ByteReader<'a>(&'a [u8]);
+
+impl std::io::Read for ByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some((&first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        buf[0] = first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+#[test]
+fn test_rope_from_reader() {
+    let text = "héllo wörld ✓\n".repeat(200);
+    let rope = Rope::from_reader(text.as_bytes()).unwrap();
+    assert_eq!(rope.to_string(), text);
+    assert_eq!(rope.line_count(), 200);
+
+    // Reads a byte at a time still make full-size chunks
+    let rope = Rope::from_reader(ByteReader(text.as_bytes())).unwrap();
+    assert_eq!(rope.to_string(), text);
+    assert_chunk_sizes(&rope);
+
+    // Lengths just past a chunk or a read don't end in a sliver of a chunk
+    for len in [1030, 2050, 66_565] {
+        let text = "x".repeat(len);
+        let rope = Rope::from_reader(text.as_bytes()).unwrap();
+        assert_eq!(rope.len(), len);
+        assert_chunk_sizes(&rope);
+        let rope = Rope::from_reader(ByteReader(text.as_bytes())).unwrap();
+        assert_chunk_sizes(&rope);
+    }
+
+    // A char cut off by the end of the stream
+    let Err(err) = Rope::from_reader(ByteReader(b"ok \xe2\x9c")) else {
+        panic!("truncated UTF-8 should be rejected");
+    };
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("offset 3"));
+
+    let Err(err) = Rope::from_reader(&b"ok\xffok"[..]) else {
+        panic!("invalid UTF-8 should be rejected");
+    };
+    assert!(err.to_string().contains("offset 2"));
+}
+
+#[test]
+fn test_rope_find_across_chunk_boundaries() {
+    let rope = Rope::from_chunks(["ab", "ca", "b", "c", "é€", "x€"].map(String::from));
+    assert_eq!(rope.to_string(), "abcabcé€x€");
+
+    assert_eq!(rope.find("abc", 0), Some(0..3));
+    assert_eq!(rope.find("abc", 1), Some(3..6));
+    assert_eq!(rope.find("cab", 0), Some(2..5));
+    assert_eq!(rope.find("€x€", 0), Some(8..15));
+    assert_eq!(rope.find("abc", 4), None);
+    assert_eq!(rope.find("", 0), None);
+}
+
+#[test]
+fn test_rope_find_all_matches_regex_find_iter() {
+    let text = "fn main() {\n    let héllo = 1;\n}\n\nfn other() {}\n";
+    let chunks: Vec<String> = text.chars().map(String::from).collect();
+    let rope = Rope::from_chunks(chunks);
+
+    let patterns = [
+        r"fn \w+",
+        r"(?m)^\}$",
+        r"(?i)HÉLLO",
+        r"l*",
+        r"\n\n",
+        r"\blet\b",
+    ];
+    for pattern in patterns {
+        let regex = regex::Regex::new(pattern).unwrap();
+        let expected: Vec<_> = regex.find_iter(text).map(|found| found.range()).collect();
+        assert_eq!(rope.find_all(&regex), expected, "pattern {:?}", pattern);
+    }
+}
+
+#[test]
+fn test_edits_share_untouched_chunks() {
+    let text: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+    let original = Rope::from_text(&text);
+
+    let mut edited = original.clone();
+    let middle = edited.line_to_byte(10_000);
+    edited.insert(middle, "inserted\n");
+    edited.delete(10, 20);
+
+    let before: std::collections::HashSet<*const u8> = original
+        .chunks()
+        .map(|chunk| chunk.as_str().as_ptr())
+        .collect();
+    let copied = edited
+        .chunks()
+        .filter(|chunk| !before.contains(&chunk.as_str().as_ptr()))
+        .count();
+    // Each edit re-chunks only the chunk it lands in
+    assert!(
+        copied <= 4,
+        "{} of {} chunks copied",
+        copied,
+        edited.chunk_count()
+    );
+    assert_eq!(original.to_string(), text);
+    let mut expected = text.clone();
+    expected.insert_str(middle, "inserted\n");
+    expected.replace_range(10..20, "");
+    assert_eq!(edited.to_string(), expected);
+}
+
+/// Every chunk is at most `CHUNK_SIZE` and, in a rope of several, at least
+/// `MIN_CHUNK_SIZE`
+fn assert_chunk_sizes(rope: &Rope) {
+    let sizes: Vec<usize> = rope.chunks().map(|chunk| chunk.len()).collect();
+    let limits = if sizes.len() > 1 {
+        Rope::MIN_CHUNK_SIZE..=Rope::CHUNK_SIZE
+    } else {
+        0..=Rope::CHUNK_SIZE
+    };
+    assert!(
+        sizes.iter().all(|size| limits.contains(size)),
+        "chunk sizes {:?}",
+        sizes
+    );
+}
+
+#[test]
+fn test_edits_keep_chunk_sizes_in_range() {
+    let mut expected = "héllo wörld ✓\n".repeat(300);
+    let mut rope = Rope::from_text(&expected);
+    assert_chunk_sizes(&rope);
+
+    let mut seed = 17usize;
+    for step in 0..2000 {
+        seed = (seed * 1_103_515_245 + 12_345) % (1 << 31);
+        let at = (seed % (expected.len() + 1)..)
+            .find(|&i| expected.is_char_boundary(i))
+            .unwrap();
+        if step % 2 == 0 {
+            let text = ["x", "ü", "\n", "abc"][step % 4 / 2 + seed % 2 * 2];
+            rope.insert(at, text);
+            expected.insert_str(at, text);
+        } else {
+            let end = ((at + seed % 6).min(expected.len())..)
+                .find(|&i| expected.is_char_boundary(i))
+                .unwrap();
+            rope.delete(at, end);
+            expected.replace_range(at..end, "");
+        }
+        assert_chunk_sizes(&rope);
+    }
+    assert_eq!(rope.to_string(), expected);
+
+    // Deletes that leave a sliver of a chunk (or of two) merge it away
+    for (start, end) in [(100, 1000), (1200, 2000), (0, 700)] {
+        let start = (start..).find(|&i| expected.is_char_boundary(i)).unwrap();
+        let end = (end..).find(|&i| expected.is_char_boundary(i)).unwrap();
+        rope.delete(start, end);
+        expected.replace_range(start..end, "");
+        assert_chunk_sizes(&rope);
+    }
+    assert_eq!(rope.to_string(), expected);
+
+    // Deleting down to a little text leaves one small chunk
+    rope.delete(10, rope.len());
+    assert_eq!(rope.chunk_count(), 1);
+
+    // Appending a large rope to a tiny one merges the chunks at the seam
+    let mut rope = Rope::from_text("ab");
+    rope.append(Rope::from_text(&"x".repeat(2000)));
+    assert_chunk_sizes(&rope);
+    assert_eq!(rope.len(), 2002);
+}
+
+#[test]
+fn test_compact_merges_small_and_splits_large_chunks() {
+    let text = "fn main() {}\n".repeat(400);
+    let mut pieces: Vec<String> = text.chars().take(500).map(String::from).collect();
+    pieces.push(text[500..4000].to_string());
+    pieces.push(text[4000..].to_string());
+    let mut rope = Rope::from_chunks(pieces);
+    assert!(rope.chunk_count() > 500);
+
+    rope.compact();
+    assert_eq!(rope.to_string(), text);
+    assert_chunk_sizes(&rope);
+
+    // An already compact rope keeps its chunks
+    let before: Vec<*const u8> = rope.chunks().map(|chunk| chunk.as_str().as_ptr()).collect();
+    rope.compact();
+    let after: Vec<*const u8> = rope.chunks().map(|chunk| chunk.as_str().as_ptr()).collect();
+    assert_eq!(before, after);
+
+    let mut small = Rope::from_chunks(vec!["a".to_string(), "b".to_string()]);
+    small.compact();
+    assert_eq!(small.chunk_count(), 1);
+    assert_eq!(small.to_string(), "ab");
+}
+
+#[test]
+fn test_offsets_inside_chars_round_up() {
+    // 1 to 4 byte chars, so chunk cuts and random offsets land inside them
+    let mut expected = "aé€😀\n".repeat(500);
+    let mut rope = Rope::from_text(&expected);
+    let ceil = |text: &str, offset: usize| {
+        (offset.min(text.len())..=text.len())
+            .find(|&i| text.is_char_boundary(i))
+            .unwrap()
+    };
+
+    let mut seed = 5usize;
+    for step in 0..2000 {
+        seed = (seed * 1_103_515_245 + 12_345) % (1 << 31);
+        // Up to a few bytes past the end as well
+        let at = seed % (expected.len() + 4);
+        let end = at + seed % 9;
+        let (start, stop) = (ceil(&expected, at), ceil(&expected, end));
+        assert_eq!(rope.ceil_char_boundary(at), start);
+
+        assert_eq!(rope.slice_bytes(at, end), expected[start..stop]);
+        assert_eq!(rope.slice(at..end).to_string(), expected[start..stop]);
+        let (left, right) = rope.split_at(at);
+        assert_eq!(left.to_string(), expected[..start]);
+        assert_eq!(right.len(), expected.len() - start);
+
+        if step % 2 == 0 {
+            let text = ["ü", "x😀", "\n"][seed % 3];
+            rope.insert(at, text);
+            expected.insert_str(start, text);
+        } else {
+            rope.delete(at, end);
+            expected.replace_range(start..stop, "");
+        }
+        assert_eq!(rope.len(), expected.len());
+    }
+    assert_eq!(rope.to_string(), expected);
+    assert_eq!(rope.len_chars(), expected.chars().count());
+}
+
+#[test]
+fn test_line_col_to_byte_matches_lines_across_chunks() {
+    // Short lines and ones several chunks long, with multi-byte chars
+    let line = |i: usize| "aé€😀".repeat(if i % 3 == 0 { 400 } else { i });
+    let text: Vec<String> = (0..12).map(line).collect();
+    let text = text.join("\n");
+    let rope = Rope::from_text(&text);
+
+    let mut line_start = 0;
+    for (row, line) in text.split('\n').enumerate() {
+        let chars = line.chars().count();
+        for column in (0..chars).step_by(37).chain([chars, chars + 5]) {
+            let offset = line
+                .char_indices()
+                .nth(column)
+                .map_or(line.len(), |(i, _)| i);
+            assert_eq!(
+                rope.line_col_to_byte(row, column),
+                line_start + offset,
+                "row {} column {}",
+                row,
+                column
+            );
+            if column <= chars {
+                assert_eq!(rope.byte_to_line_col(line_start + offset), (row, column));
+            }
+        }
+        line_start += line.len() + 1;
+    }
+    assert_eq!(rope.line_col_to_byte(20, 3), rope.len());
+}
+
+#[test]
+fn test_chunks_in_streams_a_range_across_chunks() {
+    let text = "aé€😀\n".repeat(2000);
+    let rope = Rope::from_text(&text);
+    for (start, end) in [
+        (0, text.len()),
+        (5, 3000),
+        (1500, 1501),
+        (7000, 7000),
+        (9000, 2),
+    ] {
+        let pieces: Vec<&str> = rope.chunks_in(start, end).collect();
+        assert_eq!(pieces.concat(), rope.slice_bytes(start, end));
+        assert!(pieces.iter().all(|piece| !piece.is_empty()));
+    }
+    // Only the chunks holding the range
+    assert!(rope.chunks_in(5, 3000).count() <= 3000 / Rope::MIN_CHUNK_SIZE + 2);
+}
+
+fn longest_line(text: &str) -> (usize, usize) {
+    text.split('\n')
+        .map(|line| line.chars().count())
+        .enumerate()
+        .fold((0, 0), |longest, (row, chars)| {
+            if chars > longest.1 {
+                (row, chars)
+            } else {
+                longest
+            }
+        })
+}
+
+#[test]
+fn test_longest_line_tracks_edits_across_chunks() {
+    assert_eq!(Rope::new().longest_line(), (0, 0));
+
+    let mut expected = "short\nmedium ü line\n".repeat(200);
+    let mut rope = Rope::from_text(&expected);
+    assert_eq!(rope.longest_line(), (1, 13));
+
+    // A line joining the end of one chunk to the start of the next
+    let long = "é".repeat(3000);
+    rope.insert(700, &long);
+    expected.insert_str(700, &long);
+    assert!(rope.chunk_count() > 3);
+    assert_eq!(rope.longest_line(), longest_line(&expected));
+
+    let mut seed = 5usize;
+    for step in 0..1000 {
+        seed = (seed * 1_103_515_245 + 12_345) % (1 << 31);
+        let at = (seed % (expected.len() + 1)..)
+            .find(|&i| expected.is_char_boundary(i))
+            .unwrap();
+        if step % 2 == 0 {
+            let text = ["\n", "abc", "ü\nx", "wide 😀 text"][seed % 4];
+            rope.insert(at, text);
+            expected.insert_str(at, text);
+        } else {
+            let end = ((at + seed % 40).min(expected.len())..)
+                .find(|&i| expected.is_char_boundary(i))
+                .unwrap();
+            rope.delete(at, end);
+            expected.replace_range(at..end, "");
+        }
+        assert_eq!(
+            rope.longest_line(),
+            longest_line(&expected),
+            "step {}",
+            step
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+
+use zed_text_editor::buffer::BufferSnapshot;
+use zed_text_editor::rope::RopeSnapshot;
+use zed_text_editor::{Editor, Rope};
+
+fn chunks(rope: &Rope) -> Vec<String> {
+    let mut chunks = Vec::new();
+    rope.for_each_chunk(|chunk| chunks.push(chunk.to_string()));
+    chunks
+}
+
+#[test]
+fn test_rope_round_trip_keeps_chunk_boundaries() {
+    let text: String = (0..300).map(|i| format!("line {} ✓\n", i)).collect();
+    let rope = Rope::from_text(&text);
+    assert!(rope.chunk_count() > 1);
+
+    let json = serde_json::to_string(&rope).unwrap();
+    let restored: Rope = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.to_string(), text);
+    assert_eq!(restored.summary(), rope.summary());
+    assert_eq!(chunks(&restored), chunks(&rope));
+}
+
+#[test]
+fn test_rope_snapshot_rejects_bad_summary() {
+    let mut snapshot = RopeSnapshot::from(&Rope::from_text("a\nb\n"));
+    assert_eq!(snapshot.summary.lines, 2);
+
+    snapshot.summary.lines = 5;
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert!(serde_json::from_str::<Rope>(&json).is_err());
+}
+
+#[test]
+fn test_editor_snapshot_round_trip() {
+    let mut editor = Editor::from_text("fn main() {}\n");
+    editor.insert("// hi\n");
+    let snapshot = editor.snapshot();
+    assert_eq!(snapshot.version(), editor.version());
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let restored: BufferSnapshot = serde_json::from_str(&json).unwrap();
+    let restored_editor = Editor::from_snapshot(&restored);
+
+    assert_eq!(restored_editor.text(), editor.text());
+    assert_eq!(restored_editor.version(), editor.version());
+    assert!(restored_editor.is_dirty());
+    assert!(!restored_editor.can_undo());
+}
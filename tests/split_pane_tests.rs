@@ -0,0 +1,17 @@
+use zed_text_editor::gui::SplitPane;
+
+#[test]
+fn test_locked_split_scrolls_with_main_pane() {
+    let mut split = SplitPane::new(14.0);
+
+    // Unlocked panes scroll independently
+    assert_eq!(split.sync_scroll(0.0, 300.0), (None, None));
+    assert_eq!(split.sync_scroll(50.0, 300.0), (None, None));
+
+    // Locked, either pane drags the other by the same distance
+    split.toggle_scroll_lock();
+    assert_eq!(split.sync_scroll(80.0, 300.0), (None, Some(330.0)));
+    // The follower's settled offset isn't taken as a scroll of its own
+    assert_eq!(split.sync_scroll(80.0, 320.0), (None, None));
+    assert_eq!(split.sync_scroll(80.0, 220.0), (Some(0.0), None));
+}
@@ -36,3 +36,115 @@ fn test_push_multiple_items() {
 
     assert_eq!(tree.summary().value, 60);
 }
+
+#[test]
+fn test_splice_replaces_overlapping_items() {
+    let mut tree = SumTree::from_items((1..=20).map(|_| TestItem(10)).collect());
+
+    // 15..35 overlaps the items at 10, 20 and 30
+    tree.splice(
+        15..35,
+        |count| count.value,
+        |start, items| {
+            assert_eq!((start, items.len()), (10, 3));
+            vec![TestItem(1)]
+        },
+    );
+    assert_eq!(tree.summary().value, 171);
+    assert_eq!(tree.iter().count(), 18);
+    assert_eq!(tree.iter().nth(1), Some(TestItem(1)));
+
+    // An empty range at the end selects the last item
+    tree.splice(
+        171..171,
+        |count| count.value,
+        |start, items| {
+            assert_eq!((start, items), (161, vec![TestItem(10)]));
+            vec![]
+        },
+    );
+    assert_eq!(tree.summary().value, 161);
+    assert_eq!(tree.iter().count(), 17);
+}
+
+#[test]
+fn test_push_and_append_stay_balanced() {
+    let mut tree = SumTree::new();
+    for n in 0..1000 {
+        tree.push(TestItem(n));
+    }
+    // 1000 items fit in four levels of 6-12 children
+    assert!(tree.height() <= 4, "height {}", tree.height());
+    assert_eq!(tree.summary().value, (0..1000).sum::<usize>());
+
+    // Appending trees of different heights keeps the items in order
+    let mut small = SumTree::from_items(vec![TestItem(1000), TestItem(1001)]);
+    small.append(SumTree::new());
+    tree.append(small);
+    let mut large = SumTree::from_items((1002..5000).map(TestItem).collect());
+    large.extend((5000..5010).map(TestItem));
+    let mut short = SumTree::from_items(vec![TestItem(999)]);
+    short.append(large);
+    tree.append(short);
+
+    let items: Vec<usize> = tree.iter().map(|item| item.0).collect();
+    let expected: Vec<usize> = (0..1000)
+        .chain([1000, 1001, 999])
+        .chain(1002..5010)
+        .collect();
+    assert_eq!(items, expected);
+    assert!(tree.height() <= 5, "height {}", tree.height());
+}
+
+#[test]
+fn test_splice_shares_untouched_nodes() {
+    let mut tree = SumTree::from_items((0..5000).map(|_| TestItem(10)).collect());
+    let original = tree.clone();
+    let mut model = vec![10; 5000];
+
+    for step in 0..500 {
+        let base = tree.clone();
+        let at = (step * 7919) % tree.summary().value;
+        tree.splice(
+            at..at + 1,
+            |count| count.value,
+            |_, mut items| {
+                match step % 3 {
+                    0 => items.extend([TestItem(1), TestItem(2)]),
+                    1 => items.clear(),
+                    _ => items[0] = TestItem(items[0].0 + 1),
+                }
+                items
+            },
+        );
+        let index = model
+            .iter()
+            .scan(0, |end, &value| {
+                *end += value;
+                Some(*end)
+            })
+            .position(|end| end > at)
+            .unwrap();
+        match step % 3 {
+            0 => model.splice(index + 1..index + 1, [1, 2]).for_each(drop),
+            1 => drop(model.remove(index)),
+            _ => model[index] += 1,
+        }
+
+        // Only the path down to the edited leaf (and a sibling it may be
+        // merged with) is new
+        assert!(
+            tree.unshared_nodes(&base) <= 2 * (tree.height() + 1),
+            "step {}: {} new nodes",
+            step,
+            tree.unshared_nodes(&base)
+        );
+    }
+    let items: Vec<usize> = tree.iter().map(|item| item.0).collect();
+    assert_eq!(items, model);
+    assert!(tree.height() <= 5, "height {}", tree.height());
+
+    // Earlier versions keep their own items
+    assert_eq!(original.iter().count(), 5000);
+    assert_eq!(original.summary().value, 50_000);
+}
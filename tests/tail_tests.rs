@@ -0,0 +1,92 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use zed_text_editor::io::FileTail;
+use zed_text_editor::ui::log_view::find_match;
+use zed_text_editor::ui::LogLevel;
+
+fn temp_log(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("zed-tail-{}-{}.log", name, std::process::id()))
+}
+
+fn append(path: &std::path::Path, text: &str) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+#[test]
+fn test_tail_follows_appends() {
+    let path = temp_log("appends");
+    fs::write(&path, "first\r\nsecond\n").unwrap();
+
+    let mut tail = FileTail::new(&path);
+    let update = tail.poll().unwrap();
+    assert!(!update.truncated);
+    assert_eq!(update.lines, vec!["first", "second"]);
+
+    // Nothing new
+    assert!(tail.poll().unwrap().lines.is_empty());
+
+    // Partial line is held back until its newline arrives
+    append(&path, "thi");
+    assert!(tail.poll().unwrap().lines.is_empty());
+    append(&path, "rd\nfourth\n");
+    assert_eq!(tail.poll().unwrap().lines, vec!["third", "fourth"]);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_tail_detects_truncation() {
+    let path = temp_log("truncate");
+    fs::write(&path, "old line one\nold line two\n").unwrap();
+
+    let mut tail = FileTail::new(&path);
+    tail.poll().unwrap();
+
+    fs::write(&path, "new\n").unwrap();
+    let update = tail.poll().unwrap();
+    assert!(update.truncated);
+    assert_eq!(update.lines, vec!["new"]);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_log_level_detection() {
+    assert_eq!(
+        LogLevel::detect("2024-01-01 12:00:00 ERROR db: connection lost"),
+        Some(LogLevel::Error)
+    );
+    assert_eq!(
+        LogLevel::detect("[warn] disk almost full"),
+        Some(LogLevel::Warn)
+    );
+    assert_eq!(
+        LogLevel::detect("INFO server started"),
+        Some(LogLevel::Info)
+    );
+    assert_eq!(
+        LogLevel::detect("level=debug msg=hi"),
+        Some(LogLevel::Debug)
+    );
+    // Level words deep in the message don't count
+    assert_eq!(
+        LogLevel::detect("request handled in ms without any error"),
+        None
+    );
+}
+
+#[test]
+fn test_find_match_wraps_both_ways() {
+    let lines: Vec<String> = ["alpha", "Beta", "gamma", "beta again"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    assert_eq!(find_match(&lines, "beta", 0, true), Some(1));
+    assert_eq!(find_match(&lines, "beta", 1, true), Some(3));
+    assert_eq!(find_match(&lines, "beta", 3, true), Some(1));
+    assert_eq!(find_match(&lines, "beta", 1, false), Some(3));
+    assert_eq!(find_match(&lines, "delta", 0, true), None);
+    assert_eq!(find_match(&lines, "", 0, true), None);
+}